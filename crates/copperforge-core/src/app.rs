@@ -89,10 +89,46 @@ pub struct DemoLensApp {
     pub ruler_dragging: bool,
     pub ruler_drag_start: Option<nalgebra::Point2<f64>>,
     
+    // Measurement tool, feature snapping and the points of a measurement in progress
+    pub measure_tool: ui::measurement_panel::MeasureTool,
+    pub snap_to_features: bool,
+    pub measure_points: Vec<nalgebra::Point2<f64>>,
+    pub clearance_start: Option<ecs::FeatureHit>,
+    /// Design review annotations, saved with the current project
+    pub review: ui::ReviewState,
+    /// Layer presets saved by the user, kept in the project config
+    pub layer_presets: Vec<project::LayerPreset>,
+    /// Name of the preset last applied, cleared when a layer is changed by hand
+    pub active_layer_preset: Option<String>,
+    pub layer_preset_name: String,
+    
     // Latched measurement (persists after measurement mode is exited)
     pub latched_measurement_start: Option<nalgebra::Point2<f64>>,
     pub latched_measurement_end: Option<nalgebra::Point2<f64>>,
     
+    // Netlist test point overlay
+    pub show_netlist_overlay: bool,
+    
+    // Net search text for X2 net highlighting
+    pub net_search: String,
+    
+    // Image export settings
+    pub export_state: ui::ExportPanelState,
+    
+    // Realistic view mode and its cached render
+    pub realistic_view: bool,
+    pub realistic_preview: Option<ui::realistic_view::RealisticPreview>,
+    
+    // Revision comparison view, its cached image and the difference navigated to
+    pub revision_diff_view: bool,
+    pub revision_diff_preview: Option<ui::revision_panel::RevisionDiffPreview>,
+    pub diff_focus: Option<ecs::DiffFocus>,
+    
+    // Viewer point to centre the view on at the next frame
+    pub pending_view_center: Option<nalgebra::Point2<f64>>,
+    
+    // Screen rect of the viewer at the last frame, for exporting the current view
+    pub viewer_rect: Option<Rect>,
     
     // BOM panel state
     pub bom_state: Option<ui::BomPanelState>,
@@ -165,7 +201,20 @@ impl DemoLensApp {
         // Run ECS systems to update entity states
         ecs::run_ecs_systems(&mut self.ecs_world, &self.display_manager, self.rotation_degrees);
         
+        // Revision comparison shows the active layer's diff instead of the layers
+        if self.revision_diff_view {
+            ui::revision_panel::render_revision_diff(self, painter);
+            return;
+        }
+        
+        // Realistic mode draws a cached composite instead of the flat layer colors
+        if self.realistic_view && !self.display_manager.quadrant_view_enabled {
+            ui::realistic_view::render_realistic_view(self, painter);
+            return;
+        }
+        
         // Use the new ECS render system
+        let render_start = std::time::Instant::now();
         ecs::execute_render_system(
             &mut self.ecs_world,
             painter,
@@ -173,6 +222,12 @@ impl DemoLensApp {
             &self.display_manager,
             true, // Use enhanced rendering with quadrant support
         );
+        
+        let frame_ms = painter.ctx().input(|i| i.unstable_dt) * 1000.0;
+        if let Some(mut stats) = self.ecs_world.get_resource_mut::<ecs::RenderStats>() {
+            stats.render_ms = render_start.elapsed().as_secs_f32() * 1000.0;
+            stats.frame_ms = frame_ms;
+        }
     }
 
     pub fn new() -> Self {
@@ -223,8 +278,26 @@ impl DemoLensApp {
             ruler_end: None,
             ruler_dragging: false,
             ruler_drag_start: None,
+            measure_tool: ui::measurement_panel::MeasureTool::default(),
+            snap_to_features: true,
+            measure_points: Vec::new(),
+            clearance_start: None,
+            review: ui::ReviewState::default(),
+            layer_presets: Vec::new(),
+            active_layer_preset: None,
+            layer_preset_name: String::new(),
             latched_measurement_start: None,
             latched_measurement_end: None,
+            show_netlist_overlay: true,
+            net_search: String::new(),
+            export_state: ui::ExportPanelState::default(),
+            realistic_view: false,
+            realistic_preview: None,
+            revision_diff_view: false,
+            revision_diff_preview: None,
+            diff_focus: None,
+            pending_view_center: None,
+            viewer_rect: None,
             bom_state: None,
             pending_bom_components: None,
             cross_probe_slot: None,
@@ -238,6 +311,7 @@ impl DemoLensApp {
             app.user_timezone = project_config.user_timezone.clone();
             app.use_24_hour_clock = project_config.use_24_hour_clock;
             app.global_units_mils = project_config.global_units_mils;
+            app.layer_presets = project_config.layer_presets.clone();
             
             // Sync units with ECS resource
             if let Some(mut units_resource) = app.ecs_world.get_resource_mut::<ecs::UnitsResource>() {
@@ -496,12 +570,13 @@ impl DemoLensApp {
         None
     }
     
-    fn save_settings(&self) {
+    pub(crate) fn save_settings(&self) {
         let mut config = self.project_manager.config.clone();
         config.state = self.project_manager.state.clone(); // Save current project state!
         config.user_timezone = self.user_timezone.clone();
         config.use_24_hour_clock = self.use_24_hour_clock;
         config.global_units_mils = self.global_units_mils;
+        config.layer_presets = self.layer_presets.clone();
         
         if let Err(e) = config.save_to_file(&self.config_path) {
             eprintln!("Failed to save settings: {}", e);
@@ -589,6 +664,9 @@ impl eframe::App for DemoLensApp {
         };
         
         if let Some(component) = pending_component {
+            // Highlight the component's pads from the Gerber X2 attributes
+            ecs::highlight_component_system(&mut self.ecs_world, Some(component.reference.clone()));
+            
             // Get the current viewport
             let viewport = ctx.available_rect();
             
@@ -672,6 +750,14 @@ impl eframe::App for DemoLensApp {
                 );
                 }
             
+            // L key - cycle layer presets (Shift+L backwards)
+            if i.key_pressed(egui::Key::L) {
+                let logger_state = self.logger_state.clone();
+                let log_colors = self.log_colors.clone();
+                let logger = ReactiveEventLogger::with_colors(&logger_state, &log_colors);
+                ui::layer_controls::cycle_layer_preset(self, !i.modifiers.shift, &logger);
+            }
+            
             // A key - align view to grid
             if i.key_pressed(egui::Key::A) {
                 display::align_to_grid(&mut self.view_state, &self.grid_settings);
@@ -694,6 +780,8 @@ impl eframe::App for DemoLensApp {
                     self.ruler_start = None;
                     self.ruler_end = None;
                     self.ruler_dragging = false;
+                    self.measure_points.clear();
+                    self.clearance_start = None;
                     
                     let logger = ReactiveEventLogger::with_colors(&self.logger_state, &self.log_colors);
                     logger.log_info("Ruler mode deactivated (M key) - measurement latched");
@@ -732,6 +820,8 @@ impl eframe::App for DemoLensApp {
                 self.ruler_start = None;
                 self.ruler_end = None;
                 self.ruler_dragging = false;
+                self.measure_points.clear();
+                self.clearance_start = None;
                 
                 let logger = ReactiveEventLogger::with_colors(&self.logger_state, &self.log_colors);
                 logger.log_info("Ruler mode cancelled (ESC key) - measurement latched");
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use crate::drc_operations::types::Position;
 // LayerType import removed with LayerManager functions
 
 /// Serializable mirroring settings
@@ -142,6 +143,34 @@ impl DisplayManager {
         }
     }
     
+    /// Convert a board position (mm) to viewer coordinates, applying rotation, mirroring and offsets
+    pub fn board_to_viewer(&self, position: Position, rotation_degrees: f32) -> nalgebra::Point2<f64> {
+        let mut transformed_pos = position;
+        
+        // Apply rotation
+        if rotation_degrees != 0.0 {
+            let rotation_radians = rotation_degrees.to_radians();
+            let (sin_theta, cos_theta) = (rotation_radians.sin(), rotation_radians.cos());
+            let rotated_x = transformed_pos.x * cos_theta as f64 - transformed_pos.y * sin_theta as f64;
+            let rotated_y = transformed_pos.x * sin_theta as f64 + transformed_pos.y * cos_theta as f64;
+            transformed_pos = Position::new(rotated_x, rotated_y);
+        }
+        
+        // Apply mirroring
+        if self.mirroring.x {
+            transformed_pos = transformed_pos.invert_x();
+        }
+        if self.mirroring.y {
+            transformed_pos = transformed_pos.invert_y();
+        }
+        
+        // Apply offsets
+        let origin = nalgebra::Vector2::from(self.center_offset.clone()) - nalgebra::Vector2::from(self.design_offset.clone());
+        transformed_pos = transformed_pos + origin.to_position();
+        
+        transformed_pos.to_point2()
+    }
+    
     /// Toggle quadrant view mode
     pub fn toggle_quadrant_view(&mut self) {
         self.quadrant_view_enabled = !self.quadrant_view_enabled;
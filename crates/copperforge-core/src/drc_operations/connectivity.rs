@@ -0,0 +1,226 @@
+use bevy_ecs::prelude::*;
+use gerber_viewer::BoundingBox;
+
+use crate::ecs::{
+    LayerType, LayerInfo, LayerGeometryData, DrillFileInfo, DrillHoles,
+    CopperIsland, ExtractedNet, ConnectivityExtraction,
+};
+use crate::geometry::{extract_connectivity, DrillData, LayerGeometry, DEFAULT_TOLERANCE};
+
+// Connectivity systems
+// Copper layer geometry and plated drill files are turned into island and net
+// entities, which shorts/opens and isolated copper checks build on. The
+// extraction is kept until copper geometry or a drill file is added, removed
+// or replaced.
+
+/// System to remove all island and net entities from a previous extraction
+pub fn clear_connectivity_system(world: &mut World) {
+    let entities_to_remove: Vec<Entity> = {
+        let mut islands = world.query_filtered::<Entity, With<CopperIsland>>();
+        let mut nets = world.query_filtered::<Entity, With<ExtractedNet>>();
+        islands.iter(world)
+            .chain(nets.iter(world))
+            .collect()
+    };
+
+    for entity in entities_to_remove {
+        world.despawn(entity);
+    }
+    world.remove_resource::<ConnectivityExtraction>();
+}
+
+/// Check whether the extracted islands and nets still match the copper geometry and drills
+pub fn connectivity_is_current(world: &mut World) -> bool {
+    let Some(extraction) = world.get_resource::<ConnectivityExtraction>().cloned() else {
+        return false;
+    };
+    let this_run = world.change_tick();
+
+    let mut layers = Vec::new();
+    let mut query = world.query::<(Entity, &LayerInfo, Ref<LayerGeometryData>)>();
+    for (entity, info, geometry) in query.iter(world) {
+        if !info.layer_type.is_copper() {
+            continue;
+        }
+        if geometry.last_changed().is_newer_than(extraction.tick, this_run) {
+            return false;
+        }
+        layers.push(entity);
+    }
+    layers.sort();
+    if layers != extraction.layers {
+        return false;
+    }
+
+    let mut drills = Vec::new();
+    let mut query = world.query::<(Entity, Ref<DrillFileInfo>, Ref<DrillHoles>)>();
+    for (entity, info, holes) in query.iter(world) {
+        if info.last_changed().is_newer_than(extraction.tick, this_run)
+            || holes.last_changed().is_newer_than(extraction.tick, this_run)
+        {
+            return false;
+        }
+        drills.push(entity);
+    }
+    drills.sort();
+    drills == extraction.drills
+}
+
+/// System to extract copper connectivity unless the last extraction is still current
+/// Returns (island_count, net_count)
+pub fn update_connectivity_system(world: &mut World) -> Result<(usize, usize), String> {
+    if !connectivity_is_current(world) {
+        return extract_connectivity_system(world);
+    }
+    let islands = world.query::<&CopperIsland>().iter(world).count();
+    let nets = world.query::<&ExtractedNet>().iter(world).count();
+    Ok((islands, nets))
+}
+
+/// System to extract copper connectivity from the copper layer entities
+/// Returns (island_count, net_count)
+pub fn extract_connectivity_system(world: &mut World) -> Result<(usize, usize), String> {
+    clear_connectivity_system(world);
+
+    let mut copper_layers: Vec<(Entity, LayerType, LayerGeometry)> = {
+        let mut query = world.query::<(Entity, &LayerInfo, &LayerGeometryData)>();
+        query.iter(world)
+            .filter(|(_, info, _)| info.layer_type.is_copper())
+            .map(|(entity, info, geometry)| (entity, info.layer_type, geometry.0.clone()))
+            .collect()
+    };
+    if copper_layers.is_empty() {
+        return Err("No copper layers with geometry loaded".to_string());
+    }
+    // Stable order (top to bottom) so net ids do not depend on entity order
+    copper_layers.sort_by_key(|(_, layer_type, _)| match layer_type {
+        LayerType::Copper(number) => *number,
+        _ => u8::MAX,
+    });
+
+    // Every drill file is recorded, a plated flag change also invalidates the extraction
+    let (mut drill_entities, drills): (Vec<Entity>, Vec<DrillData>) = {
+        let mut query = world.query::<(Entity, &DrillFileInfo, &DrillHoles)>();
+        let all: Vec<_> = query.iter(world).collect();
+        let entities = all.iter().map(|(entity, _, _)| *entity).collect();
+        let plated = all.iter()
+            .filter(|(_, info, _)| info.plated)
+            .map(|(_, _, holes)| holes.0.clone())
+            .collect();
+        (entities, plated)
+    };
+
+    let layer_refs: Vec<&LayerGeometry> = copper_layers.iter().map(|(_, _, geometry)| geometry).collect();
+    let drill_refs: Vec<&DrillData> = drills.iter().collect();
+    let connectivity = extract_connectivity(&layer_refs, &drill_refs, DEFAULT_TOLERANCE);
+    let dead = connectivity.dead_islands(&layer_refs);
+
+    // Spawn islands first so nets can reference them
+    let island_entities: Vec<Entity> = connectivity.islands.iter().enumerate()
+        .map(|(index, island)| {
+            let (layer_entity, layer_type, _) = &copper_layers[island.layer];
+            world.spawn(CopperIsland {
+                layer_entity: *layer_entity,
+                layer_type: *layer_type,
+                net_id: connectivity.island_nets[index],
+                features: island.features.clone(),
+                bounds: BoundingBox { min: island.min, max: island.max },
+                area: island.area,
+                dead: dead.contains(&index),
+            }).id()
+        })
+        .collect();
+
+    for (net_id, net) in connectivity.nets.iter().enumerate() {
+        let mut layers: Vec<LayerType> = Vec::new();
+        for &island in &net.islands {
+            let layer_type = copper_layers[connectivity.islands[island].layer].1;
+            if !layers.contains(&layer_type) {
+                layers.push(layer_type);
+            }
+        }
+        world.spawn(ExtractedNet {
+            net_id,
+            islands: net.islands.iter().map(|i| island_entities[*i]).collect(),
+            layers,
+            hole_count: net.holes.len(),
+        });
+    }
+
+    if !connectivity.unconnected_holes.is_empty() {
+        println!("Connectivity: {} plated holes touch no copper", connectivity.unconnected_holes.len());
+    }
+
+    // Later geometry changes get a newer tick than the one recorded here
+    let mut layers: Vec<Entity> = copper_layers.iter().map(|(entity, _, _)| *entity).collect();
+    layers.sort();
+    drill_entities.sort();
+    let tick = world.change_tick();
+    world.increment_change_tick();
+    world.insert_resource(ConnectivityExtraction { tick, layers, drills: drill_entities });
+
+    Ok((connectivity.islands.len(), connectivity.nets.len()))
+}
+
+/// Get all extracted nets, ordered by net id
+pub fn get_extracted_nets(world: &mut World) -> Vec<ExtractedNet> {
+    let mut query = world.query::<&ExtractedNet>();
+    let mut nets: Vec<ExtractedNet> = query.iter(world).cloned().collect();
+    nets.sort_by_key(|net| net.net_id);
+    nets
+}
+
+/// Get all copper islands belonging to a net
+pub fn get_net_islands(world: &mut World, net_id: usize) -> Vec<CopperIsland> {
+    let mut query = world.query::<&CopperIsland>();
+    query.iter(world)
+        .filter(|island| island.net_id == net_id)
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+
+    const TRACK: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\nD10*\nX0Y0D02*\nX5000000Y0D01*\nM02*\n";
+
+    #[test]
+    fn test_extraction_goes_stale() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let top = spawn_layer(&mut world, LayerType::Copper(1), TRACK);
+        assert!(!connectivity_is_current(&mut world));
+
+        assert_eq!(update_connectivity_system(&mut world), Ok((1, 1)));
+        assert!(connectivity_is_current(&mut world));
+        assert_eq!(update_connectivity_system(&mut world), Ok((1, 1)));
+
+        // Replacing the geometry of a copper layer invalidates the extraction
+        let geometry = crate::geometry::parse_gerber(
+            "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\nD10*\nX0Y0D02*\nX5000000Y0D01*\nX9000000Y0D02*\nX9500000Y0D01*\nM02*\n",
+        ).unwrap();
+        world.entity_mut(top).insert(LayerGeometryData(geometry));
+        assert!(!connectivity_is_current(&mut world));
+        assert_eq!(update_connectivity_system(&mut world), Ok((2, 2)));
+
+        // So does a new copper layer
+        spawn_layer(&mut world, LayerType::Copper(2), TRACK);
+        assert!(!connectivity_is_current(&mut world));
+        update_connectivity_system(&mut world).unwrap();
+
+        // And a drill file being loaded, changed or removed
+        let drill = crate::geometry::parse_excellon("M48\nMETRIC\nT1C0.400\n%\nT1\nX5.0Y0.0\nM30\n").unwrap();
+        let drill_entity = crate::ecs::create_drill_entity(&mut world, "board-PTH.drl".to_string(), drill, None);
+        assert!(!connectivity_is_current(&mut world));
+        update_connectivity_system(&mut world).unwrap();
+        assert!(connectivity_is_current(&mut world));
+
+        world.get_mut::<DrillFileInfo>(drill_entity).unwrap().plated = false;
+        assert!(!connectivity_is_current(&mut world));
+        update_connectivity_system(&mut world).unwrap();
+
+        world.despawn(drill_entity);
+        assert!(!connectivity_is_current(&mut world));
+    }
+}
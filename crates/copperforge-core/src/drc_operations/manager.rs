@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
-use super::types::{DrcRules, DrcViolation, TraceQualityIssue, CornerOverlayShape};
+use super::types::{DrcRules, DrcViolation, TraceQualityIssue, CornerOverlayShape, DeadCopperIsland};
 use super::types::GerberPrimitive;
+use super::rules::{DrcRule, DrcRuleRegistry};
+use bevy_ecs::world::World;
 
 /// Manager for all DRC (Design Rule Check) related functionality
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +26,14 @@ pub struct DrcManager {
     /// Corner overlay shapes for visualization
     #[serde(skip)] // Skip serialization as CornerOverlayShape contains non-serializable Position
     pub corner_overlay_shapes: Vec<CornerOverlayShape>,
+    
+    /// Copper islands with no pad connection (for visualization)
+    #[serde(skip)] // Skip serialization as DeadCopperIsland contains non-serializable Position
+    pub dead_copper: Vec<DeadCopperIsland>,
+    
+    /// Pluggable rules run in addition to the built-in DRC systems
+    #[serde(skip)] // Rules are registered in code, not persisted
+    pub rule_registry: DrcRuleRegistry,
 }
 
 impl DrcManager {
@@ -36,6 +46,8 @@ impl DrcManager {
             trace_quality_issues: Vec::new(),
             rounded_corner_primitives: Vec::new(),
             corner_overlay_shapes: Vec::new(),
+            dead_copper: Vec::new(),
+            rule_registry: DrcRuleRegistry::new(),
         }
     }
     
@@ -45,6 +57,7 @@ impl DrcManager {
         self.trace_quality_issues.clear();
         self.corner_overlay_shapes.clear();
         self.rounded_corner_primitives.clear();
+        self.dead_copper.clear();
     }
     
     /// Add a new DRC violation
@@ -106,6 +119,26 @@ impl DrcManager {
     pub fn update_rules(&mut self, rules: DrcRules) {
         self.rules = rules;
     }
+    
+    /// Register a pluggable DRC rule
+    pub fn register_rule<R: DrcRule + 'static>(&mut self, rule: R) -> Result<(), String> {
+        self.rule_registry.register(rule)
+    }
+    
+    /// Run the built-in DRC systems and all enabled registered rules on the world
+    /// Results are stored here and also left in the world as violation entities.
+    /// Without a board outline neither the built-in checks nor the registered rules run.
+    pub fn run_drc(&mut self, world: &mut World) -> Vec<DrcViolation> {
+        world.insert_resource(self.rules.clone());
+        if super::systems::run_drc_systems(world) {
+            super::systems::run_registered_rules_system(world, &self.rule_registry);
+        }
+        
+        self.trace_quality_issues = super::systems::get_trace_quality_issues(world);
+        self.dead_copper = super::systems::get_dead_copper(world);
+        self.violations = super::systems::get_drc_violations(world);
+        self.violations.clone()
+    }
 }
 
 impl Default for DrcManager {
@@ -1,6 +1,15 @@
 pub mod types;
 pub mod manager;
+pub mod systems;
+pub mod rules;
+pub mod connectivity;
 
 // Re-export the main types for easy access
-pub use types::{TraceQualityType, DrcSimple, run_simple_drc_check};
-pub use manager::DrcManager;
\ No newline at end of file
+pub use types::{TraceQualityType, DrcSimple};
+pub use manager::DrcManager;
+pub use systems::{run_simple_drc_check, run_drc_systems, clear_drc_results_system, get_drc_violations, get_trace_quality_issues, get_dead_copper};
+pub use rules::{
+    DrcRule, DrcRuleRegistry, DrcRuleEntry, DrcRuleContext, BoardGeometry, BoardLayer,
+    DrcParameterSpec, DrcParameterValue, DrcParameters,
+};
+pub use connectivity::{extract_connectivity_system, update_connectivity_system, connectivity_is_current, clear_connectivity_system, get_extracted_nets, get_net_islands};
@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use bevy_ecs::prelude::*;
+use gerber_viewer::{GerberLayer, BoundingBox};
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{LayerType, LayerInfo, GerberData, RawGerberData, RequiresDrc};
+use super::types::{DrcRules, DrcViolation};
+
+// Pluggable DRC rules
+// Built-in checks run as ECS systems (see `systems.rs`). Additional checks,
+// including ones shipped from other crates, implement `DrcRule` and are added
+// to the `DrcRuleRegistry` held by `DrcManager`:
+//
+//     struct LogoKeepout;
+//
+//     impl DrcRule for LogoKeepout {
+//         fn name(&self) -> &str { "Logo Keep-out" }
+//         fn required_layers(&self) -> Vec<LayerType> { vec![LayerType::Copper(1)] }
+//         fn run(&self, board: &BoardGeometry, context: &DrcRuleContext) -> Vec<DrcViolation> {
+//             // inspect board.layers and emit violations
+//             Vec::new()
+//         }
+//     }
+//
+//     app.drc_manager.register_rule(LogoKeepout)?;
+
+// ============================================================================
+// RULE PARAMETERS
+// ============================================================================
+
+/// Value of a rule parameter
+/// Lengths are always stored in mm, like `DrcRules`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DrcParameterValue {
+    Length(f32),
+    Number(f64),
+    Integer(i64),
+    Flag(bool),
+    Text(String),
+}
+
+impl DrcParameterValue {
+    /// Check whether two values are of the same kind
+    pub fn same_kind(&self, other: &DrcParameterValue) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// Schema entry describing one parameter of a rule
+#[derive(Debug, Clone)]
+pub struct DrcParameterSpec {
+    pub key: String,
+    pub label: String,
+    pub description: String,
+    pub default: DrcParameterValue,
+}
+
+impl DrcParameterSpec {
+    pub fn new(key: &str, label: &str, default: DrcParameterValue) -> Self {
+        Self {
+            key: key.to_string(),
+            label: label.to_string(),
+            description: String::new(),
+            default,
+        }
+    }
+
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+}
+
+/// Current parameter values of a registered rule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DrcParameters(pub HashMap<String, DrcParameterValue>);
+
+impl DrcParameters {
+    /// Build parameter values from a rule's defaults
+    pub fn from_specs(specs: &[DrcParameterSpec]) -> Self {
+        Self(specs.iter().map(|spec| (spec.key.clone(), spec.default.clone())).collect())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&DrcParameterValue> {
+        self.0.get(key)
+    }
+
+    pub fn length(&self, key: &str) -> Option<f32> {
+        match self.0.get(key) {
+            Some(DrcParameterValue::Length(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn number(&self, key: &str) -> Option<f64> {
+        match self.0.get(key) {
+            Some(DrcParameterValue::Number(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self, key: &str) -> Option<i64> {
+        match self.0.get(key) {
+            Some(DrcParameterValue::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn flag(&self, key: &str) -> Option<bool> {
+        match self.0.get(key) {
+            Some(DrcParameterValue::Flag(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, key: &str) -> Option<&str> {
+        match self.0.get(key) {
+            Some(DrcParameterValue::Text(value)) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+}
+
+// ============================================================================
+// BOARD GEOMETRY
+// ============================================================================
+
+/// One loaded layer as seen by a DRC rule
+#[derive(Clone)]
+pub struct BoardLayer {
+    pub entity: Entity,
+    pub layer_type: LayerType,
+    pub name: String,
+    pub gerber: GerberLayer,
+    pub raw_gerber_data: Option<String>,
+    pub bounds: BoundingBox,
+    pub requires_drc: bool,
+}
+
+/// Snapshot of the board taken from the ECS world for rule evaluation
+#[derive(Clone, Default)]
+pub struct BoardGeometry {
+    pub layers: Vec<BoardLayer>,
+    pub outline: Option<BoundingBox>,
+}
+
+impl BoardGeometry {
+    /// Collect all layer entities from the world
+    pub fn from_world(world: &mut World) -> Self {
+        let mut query = world.query::<(Entity, &LayerInfo, &GerberData, Option<&RawGerberData>, Has<RequiresDrc>)>();
+
+        let mut layers: Vec<BoardLayer> = query.iter(world)
+            .map(|(entity, info, data, raw, requires_drc)| BoardLayer {
+                entity,
+                layer_type: info.layer_type,
+                name: info.layer_type.display_name().to_string(),
+                gerber: data.0.clone(),
+                raw_gerber_data: raw.map(|raw| raw.0.clone()),
+                bounds: data.0.bounding_box().clone(),
+                requires_drc,
+            })
+            .collect();
+        layers.sort_by_key(|layer| layer.layer_type.display_name().to_string());
+
+        let outline = layers.iter()
+            .find(|layer| layer.layer_type == LayerType::MechanicalOutline)
+            .map(|layer| layer.bounds.clone());
+
+        Self { layers, outline }
+    }
+
+    /// Get a layer by type
+    pub fn layer(&self, layer_type: LayerType) -> Option<&BoardLayer> {
+        self.layers.iter().find(|layer| layer.layer_type == layer_type)
+    }
+
+    /// Layers marked with `RequiresDrc` (all copper layers)
+    pub fn drc_layers(&self) -> impl Iterator<Item = &BoardLayer> {
+        self.layers.iter().filter(|layer| layer.requires_drc)
+    }
+
+    /// Check whether every given layer type is loaded
+    pub fn has_layers(&self, layer_types: &[LayerType]) -> bool {
+        layer_types.iter().all(|layer_type| self.layer(*layer_type).is_some())
+    }
+}
+
+/// Everything a rule gets besides the board itself
+pub struct DrcRuleContext<'a> {
+    /// Global design rules from the DRC panel
+    pub rules: &'a DrcRules,
+    /// This rule's own parameter values
+    pub parameters: &'a DrcParameters,
+}
+
+// ============================================================================
+// RULE TRAIT
+// ============================================================================
+
+/// A design rule check that can be registered with `DrcManager`
+pub trait DrcRule: Send + Sync {
+    /// Unique rule name, also used as `DrcViolation::rule_name`
+    fn name(&self) -> &str;
+
+    /// Short description shown in the DRC panel
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Parameters this rule accepts, with their defaults
+    fn parameters(&self) -> Vec<DrcParameterSpec> {
+        Vec::new()
+    }
+
+    /// Layers that must be loaded for the rule to run
+    fn required_layers(&self) -> Vec<LayerType> {
+        Vec::new()
+    }
+
+    /// Run the rule over the board and return any violations
+    /// Violations should set `layer` to the layer's display name so they can be
+    /// linked back to the layer entity
+    fn run(&self, board: &BoardGeometry, context: &DrcRuleContext) -> Vec<DrcViolation>;
+}
+
+// ============================================================================
+// REGISTRY
+// ============================================================================
+
+/// A rule together with its enabled state and parameter values
+#[derive(Clone)]
+pub struct DrcRuleEntry {
+    pub rule: Arc<dyn DrcRule>,
+    pub enabled: bool,
+    pub parameters: DrcParameters,
+}
+
+/// Registry of pluggable DRC rules
+#[derive(Clone, Default)]
+pub struct DrcRuleRegistry {
+    entries: Vec<DrcRuleEntry>,
+}
+
+impl std::fmt::Debug for DrcRuleRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|entry| entry.rule.name()))
+            .finish()
+    }
+}
+
+impl DrcRuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a rule, enabled and with default parameters
+    pub fn register<R: DrcRule + 'static>(&mut self, rule: R) -> Result<(), String> {
+        self.register_arc(Arc::new(rule))
+    }
+
+    /// Register a shared rule instance
+    pub fn register_arc(&mut self, rule: Arc<dyn DrcRule>) -> Result<(), String> {
+        if self.get(rule.name()).is_some() {
+            return Err(format!("DRC rule '{}' is already registered", rule.name()));
+        }
+
+        let parameters = DrcParameters::from_specs(&rule.parameters());
+        self.entries.push(DrcRuleEntry {
+            rule,
+            enabled: true,
+            parameters,
+        });
+        Ok(())
+    }
+
+    /// Remove a rule by name, returns true if it was registered
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.rule.name() != name);
+        self.entries.len() != before
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DrcRuleEntry> {
+        self.entries.iter().find(|entry| entry.rule.name() == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut DrcRuleEntry> {
+        self.entries.iter_mut().find(|entry| entry.rule.name() == name)
+    }
+
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let entry = self.get_mut(name)
+            .ok_or_else(|| format!("DRC rule '{}' not found", name))?;
+        entry.enabled = enabled;
+        Ok(())
+    }
+
+    /// Set a parameter value, checked against the rule's schema
+    pub fn set_parameter(&mut self, name: &str, key: &str, value: DrcParameterValue) -> Result<(), String> {
+        let entry = self.get_mut(name)
+            .ok_or_else(|| format!("DRC rule '{}' not found", name))?;
+
+        let spec = entry.rule.parameters().into_iter()
+            .find(|spec| spec.key == key)
+            .ok_or_else(|| format!("DRC rule '{}' has no parameter '{}'", name, key))?;
+
+        if !spec.default.same_kind(&value) {
+            return Err(format!("Parameter '{}' of '{}' expects {:?}-style values", key, name, spec.default));
+        }
+
+        entry.parameters.0.insert(key.to_string(), value);
+        Ok(())
+    }
+
+    /// Reset a rule's parameters to their defaults
+    pub fn reset_parameters(&mut self, name: &str) {
+        if let Some(entry) = self.get_mut(name) {
+            entry.parameters = DrcParameters::from_specs(&entry.rule.parameters());
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DrcRuleEntry> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut DrcRuleEntry> {
+        self.entries.iter_mut()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MinCount;
+
+    impl DrcRule for MinCount {
+        fn name(&self) -> &str {
+            "Min Count"
+        }
+
+        fn parameters(&self) -> Vec<DrcParameterSpec> {
+            vec![DrcParameterSpec::new("count", "Count", DrcParameterValue::Integer(2))]
+        }
+
+        fn run(&self, _board: &BoardGeometry, context: &DrcRuleContext) -> Vec<DrcViolation> {
+            let count = context.parameters.integer("count").unwrap_or(0);
+            (0..count).map(|i| DrcViolation {
+                rule_name: self.name().to_string(),
+                description: "Test violation".to_string(),
+                layer: "Top Copper".to_string(),
+                measured_value: 0.0,
+                required_value: 0.0,
+                x: i as f32,
+                y: 0.0,
+            }).collect()
+        }
+    }
+
+    #[test]
+    fn test_register_rejects_duplicates() {
+        let mut registry = DrcRuleRegistry::new();
+        assert!(registry.register(MinCount).is_ok());
+        assert!(registry.register(MinCount).is_err());
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.get("Min Count").unwrap().parameters.integer("count"), Some(2));
+    }
+
+    #[test]
+    fn test_set_parameter_checks_schema() {
+        let mut registry = DrcRuleRegistry::new();
+        registry.register(MinCount).unwrap();
+
+        assert!(registry.set_parameter("Min Count", "count", DrcParameterValue::Integer(5)).is_ok());
+        assert!(registry.set_parameter("Min Count", "count", DrcParameterValue::Length(0.1)).is_err());
+        assert!(registry.set_parameter("Min Count", "missing", DrcParameterValue::Integer(1)).is_err());
+        assert_eq!(registry.get("Min Count").unwrap().parameters.integer("count"), Some(5));
+
+        registry.reset_parameters("Min Count");
+        assert_eq!(registry.get("Min Count").unwrap().parameters.integer("count"), Some(2));
+    }
+
+    #[test]
+    fn test_registered_rules_spawn_violations() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let mut registry = DrcRuleRegistry::new();
+        registry.register(MinCount).unwrap();
+
+        let count = crate::drc_operations::systems::run_registered_rules_system(&mut world, &registry);
+        assert_eq!(count, 2);
+        assert_eq!(crate::drc_operations::systems::get_drc_violations(&mut world).len(), 2);
+
+        registry.set_enabled("Min Count", false).unwrap();
+        crate::drc_operations::systems::clear_drc_results_system(&mut world);
+        let count = crate::drc_operations::systems::run_registered_rules_system(&mut world, &registry);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_registered_rules_need_outline() {
+        // Like the built-in checks, registered rules are skipped without a board outline
+        let mut world = crate::ecs::setup_ecs_world();
+        let mut manager = crate::drc_operations::DrcManager::new();
+        manager.register_rule(MinCount).unwrap();
+
+        assert!(manager.run_drc(&mut world).is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+use bevy_ecs::prelude::*;
+use gerber_viewer::BoundingBox;
+
+use crate::ecs::{
+    LayerType, LayerInfo, GerberData, RequiresDrc,
+    DrcViolationData, TraceQualityData, DeadCopperData, DrcSourceLayer,
+    LayerGeometryData, CopperIsland,
+};
+use crate::geometry::DEFAULT_TOLERANCE;
+use super::rules::{BoardGeometry, DrcRuleContext, DrcRuleRegistry};
+use super::types::{
+    DrcSimple, DrcRules, DrcViolation, TraceQualityIssue, TraceQualityType,
+    DeadCopperIsland, Position,
+};
+
+// DRC systems
+// Each rule runs as its own system over layer entities marked with `RequiresDrc`
+// and spawns violation / quality-issue entities back into the world.
+
+// ============================================================================
+// HELPERS
+// ============================================================================
+
+/// Get the PCB boundary from the mechanical outline layer entity
+pub fn get_pcb_boundary(world: &mut World) -> Option<BoundingBox> {
+    let mut query = world.query::<(&LayerInfo, &GerberData)>();
+    query.iter(world)
+        .find(|(info, _)| info.layer_type == LayerType::MechanicalOutline)
+        .map(|(_, data)| data.0.bounding_box().clone())
+}
+
+/// Spawn a violation entity linked to the layer it came from
+fn spawn_violation(world: &mut World, source: DrcSourceLayer, violation: DrcViolation) -> Entity {
+    world.spawn((DrcViolationData(violation), source)).id()
+}
+
+/// Spawn a trace quality issue entity linked to the layer it came from
+fn spawn_quality_issue(world: &mut World, source: DrcSourceLayer, issue: TraceQualityIssue) -> Entity {
+    world.spawn((TraceQualityData(issue), source)).id()
+}
+
+// ============================================================================
+// DRC SYSTEMS
+// ============================================================================
+
+/// System to remove all DRC result entities from a previous run
+pub fn clear_drc_results_system(world: &mut World) {
+    let entities_to_remove: Vec<Entity> = {
+        let mut violations = world.query_filtered::<Entity, With<DrcViolationData>>();
+        let mut issues = world.query_filtered::<Entity, With<TraceQualityData>>();
+        let mut dead_copper = world.query_filtered::<Entity, With<DeadCopperData>>();
+        violations.iter(world)
+            .chain(issues.iter(world))
+            .chain(dead_copper.iter(world))
+            .collect()
+    };
+
+    for entity in entities_to_remove {
+        world.despawn(entity);
+    }
+}
+
+/// System to check trace widths from the parsed primitives of each DRC layer
+/// Returns the number of violations spawned
+pub fn trace_width_drc_system(world: &mut World) -> usize {
+    let drc_rules = world.get_resource::<DrcRules>().cloned().unwrap_or_default();
+    let pcb_bounds = get_pcb_boundary(world);
+
+    let drc = DrcSimple {
+        min_trace_width: drc_rules.min_trace_width,
+        lines_only: true,  // Only check Line primitives to avoid copper pour false positives
+        min_trace_length: 1.0,  // Only lines >= 1mm are considered traces (not pad connections)
+        ..DrcSimple::default()
+    };
+
+    let mut found = Vec::new();
+    {
+        let mut query = world.query_filtered::<(Entity, &LayerInfo, &GerberData), With<RequiresDrc>>();
+        for (entity, info, data) in query.iter(world) {
+            println!("Running primitive-based trace detection on {}", info.layer_type.display_name());
+
+            let source = DrcSourceLayer { entity, layer_type: info.layer_type };
+            for violation in drc.run_trace_width_drc_with_bounds(&data.0, pcb_bounds.as_ref()) {
+                found.push((source, DrcViolation {
+                    rule_name: "Primitive Trace Width".to_string(),
+                    description: format!("Trace width {:.3}mm below minimum", violation.measured_width),
+                    layer: info.layer_type.display_name().to_string(),
+                    measured_value: violation.measured_width,
+                    required_value: drc_rules.min_trace_width,
+                    x: violation.trace.center_x,
+                    y: violation.trace.center_y,
+                }));
+            }
+        }
+    }
+
+    let count = found.len();
+    for (source, violation) in found {
+        spawn_violation(world, source, violation);
+    }
+    count
+}
+
+/// System to analyze trace quality (corners, jogs, etc.) on each DRC layer
+/// Returns the number of issues spawned
+pub fn trace_quality_system(world: &mut World) -> usize {
+    let drc_rules = world.get_resource::<DrcRules>().cloned().unwrap_or_default();
+    let drc = DrcSimple {
+        min_trace_width: drc_rules.min_trace_width,
+        ..DrcSimple::default()
+    };
+
+    let mut found = Vec::new();
+    {
+        let mut query = world.query_filtered::<(Entity, &LayerInfo, &GerberData), With<RequiresDrc>>();
+        for (entity, info, data) in query.iter(world) {
+            let quality_issues = drc.analyze_trace_quality(&data.0);
+            println!("Found {} trace quality issues on {}", quality_issues.len(), info.layer_type.display_name());
+
+            // Log corner issues specifically
+            for issue in &quality_issues {
+                if matches!(issue.issue_type, TraceQualityType::SharpCorner) {
+                    println!("Corner issue at ({:.2}, {:.2}): {}", issue.location.0, issue.location.1, issue.description);
+                }
+            }
+
+            let source = DrcSourceLayer { entity, layer_type: info.layer_type };
+            found.extend(quality_issues.into_iter().map(|issue| (source, issue)));
+        }
+    }
+
+    let count = found.len();
+    for (source, issue) in found {
+        spawn_quality_issue(world, source, issue);
+    }
+    count
+}
+
+/// System to report copper islands whose net reaches no component pad or plated hole
+/// Uses the extracted connectivity (re-extracting only when stale) and checks the
+/// islands on DRC layers. Spawns a dead copper entity per island and a violation
+/// for islands smaller than `min_island_area`. Returns the number of dead islands.
+pub fn dead_copper_system(world: &mut World) -> usize {
+    let drc_rules = world.get_resource::<DrcRules>().cloned().unwrap_or_default();
+    if let Err(e) = crate::drc_operations::update_connectivity_system(world) {
+        println!("Skipping dead copper check: {}", e);
+        return 0;
+    }
+
+    let islands: Vec<CopperIsland> = {
+        let mut query = world.query::<&CopperIsland>();
+        query.iter(world)
+            .filter(|island| island.dead)
+            .cloned()
+            .collect()
+    };
+
+    let mut found = Vec::new();
+    for island in islands.iter().filter(|island| world.get::<RequiresDrc>(island.layer_entity).is_some()) {
+        let Some(geometry) = world.get::<LayerGeometryData>(island.layer_entity) else {
+            continue;
+        };
+        let outlines: Vec<Vec<Position>> = island.features.iter()
+            .filter_map(|&index| geometry.0.features.get(index))
+            .flat_map(|feature| geometry.0.feature_polygons(feature, DEFAULT_TOLERANCE))
+            .filter(|polygon| polygon.dark)
+            .map(|polygon| polygon.points.iter().map(|p| Position::new(p.x, p.y)).collect())
+            .collect();
+        let area = island.area as f32;
+        let location = (
+            ((island.bounds.min.x + island.bounds.max.x) / 2.0) as f32,
+            ((island.bounds.min.y + island.bounds.max.y) / 2.0) as f32,
+        );
+        let source = DrcSourceLayer { entity: island.layer_entity, layer_type: island.layer_type };
+        found.push((source, DeadCopperIsland {
+            layer: island.layer_type.display_name(),
+            area,
+            location,
+            outlines,
+            below_threshold: area < drc_rules.min_island_area,
+        }));
+    }
+
+    let count = found.len();
+    for (source, dead_copper) in found {
+        if dead_copper.below_threshold {
+            spawn_violation(world, source, DrcViolation {
+                rule_name: "Dead Copper".to_string(),
+                description: format!("Isolated copper island of {:.3} mm² with no pad connection", dead_copper.area),
+                layer: dead_copper.layer.clone(),
+                measured_value: dead_copper.area,
+                required_value: drc_rules.min_island_area,
+                x: dead_copper.location.0,
+                y: dead_copper.location.1,
+            });
+        }
+        world.spawn((DeadCopperData(dead_copper), source));
+    }
+    count
+}
+
+/// System to run the enabled rules of a `DrcRuleRegistry` over the board
+/// Returns the number of violations spawned
+pub fn run_registered_rules_system(world: &mut World, registry: &DrcRuleRegistry) -> usize {
+    if registry.iter().all(|entry| !entry.enabled) {
+        return 0;
+    }
+
+    let drc_rules = world.get_resource::<DrcRules>().cloned().unwrap_or_default();
+    let board = BoardGeometry::from_world(world);
+    let mut found = Vec::new();
+
+    for entry in registry.iter().filter(|entry| entry.enabled) {
+        let required = entry.rule.required_layers();
+        if !board.has_layers(&required) {
+            println!("Skipping DRC rule '{}': required layers not loaded", entry.rule.name());
+            continue;
+        }
+
+        let context = DrcRuleContext {
+            rules: &drc_rules,
+            parameters: &entry.parameters,
+        };
+        let violations = entry.rule.run(&board, &context);
+        println!("DRC rule '{}' found {} violations", entry.rule.name(), violations.len());
+
+        for violation in violations {
+            // Link back to the layer entity by display name when possible
+            let source = board.layers.iter()
+                .find(|layer| layer.name == violation.layer)
+                .map(|layer| DrcSourceLayer { entity: layer.entity, layer_type: layer.layer_type });
+            found.push((source, violation));
+        }
+    }
+
+    let count = found.len();
+    for (source, violation) in found {
+        match source {
+            Some(source) => { spawn_violation(world, source, violation); }
+            None => { world.spawn(DrcViolationData(violation)); }
+        }
+    }
+    count
+}
+
+/// Run all DRC systems in order, replacing the results of any previous run
+/// Rules are taken from the `DrcRules` resource, or the defaults without one.
+/// Returns false when no board outline is loaded and nothing was checked.
+pub fn run_drc_systems(world: &mut World) -> bool {
+    clear_drc_results_system(world);
+
+    // Rules are only meaningful inside the board outline
+    match get_pcb_boundary(world) {
+        Some(boundary) => {
+            println!("DRC boundary check: PCB area is {:.1} x {:.1} mm", boundary.width(), boundary.height());
+        }
+        None => {
+            println!("Warning: No mechanical outline found - cannot determine PCB boundary for DRC");
+            return false;
+        }
+    }
+
+    trace_width_drc_system(world);
+    trace_quality_system(world);
+    dead_copper_system(world);
+    true
+}
+
+// ============================================================================
+// RESULT QUERIES
+// ============================================================================
+
+/// Collect all dead copper islands currently in the world
+pub fn get_dead_copper(world: &mut World) -> Vec<DeadCopperIsland> {
+    let mut query = world.query::<&DeadCopperData>();
+    query.iter(world).map(|data| data.0.clone()).collect()
+}
+
+/// Collect all DRC violations currently in the world
+pub fn get_drc_violations(world: &mut World) -> Vec<DrcViolation> {
+    let mut query = world.query::<&DrcViolationData>();
+    query.iter(world).map(|data| data.0.clone()).collect()
+}
+
+/// Collect all trace quality issues currently in the world
+pub fn get_trace_quality_issues(world: &mut World) -> Vec<TraceQualityIssue> {
+    let mut query = world.query::<&TraceQualityData>();
+    query.iter(world).map(|data| data.0.clone()).collect()
+}
+
+/// Run DRC on the layer entities in the world with the given rules
+/// Returns the violations found and refreshes the given trace quality issue list
+pub fn run_simple_drc_check(
+    world: &mut World,
+    drc_rules: &DrcRules,
+    trace_quality_issues: &mut Vec<TraceQualityIssue>
+) -> Vec<DrcViolation> {
+    world.insert_resource(drc_rules.clone());
+    run_drc_systems(world);
+
+    *trace_quality_issues = get_trace_quality_issues(world);
+    get_drc_violations(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_violation() -> DrcViolation {
+        DrcViolation {
+            rule_name: "Test".to_string(),
+            description: "Test violation".to_string(),
+            layer: "Top Copper".to_string(),
+            measured_value: 0.1,
+            required_value: 0.15,
+            x: 1.0,
+            y: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_clear_drc_results() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let layer = world.spawn_empty().id();
+        let source = DrcSourceLayer { entity: layer, layer_type: LayerType::Copper(1) };
+
+        spawn_violation(&mut world, source, test_violation());
+        assert_eq!(get_drc_violations(&mut world).len(), 1);
+
+        clear_drc_results_system(&mut world);
+        assert!(get_drc_violations(&mut world).is_empty());
+
+        // Layer entities are left alone
+        assert!(world.get_entity(layer).is_some());
+    }
+
+    #[test]
+    fn test_no_outline_skips_drc() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let mut issues = Vec::new();
+
+        let violations = run_simple_drc_check(&mut world, &DrcRules::default(), &mut issues);
+        assert!(violations.is_empty());
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn test_one_trace_width_violation_per_trace() {
+        // Layers loaded with their raw content still get the primitive check only
+        let mut world = crate::ecs::setup_ecs_world();
+        crate::ecs::test_support::spawn_layer(&mut world, LayerType::MechanicalOutline,
+            "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.100000*%\nD10*\nX0Y0D02*\nX20000000Y0D01*\n\
+            X20000000Y20000000D01*\nX0Y20000000D01*\nX0Y0D01*\nM02*\n");
+        crate::ecs::test_support::spawn_layer(&mut world, LayerType::Copper(1),
+            "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.050000*%\nD10*\nX5000000Y5000000D02*\nX15000000Y5000000D01*\nM02*\n");
+
+        let mut issues = Vec::new();
+        let violations = run_simple_drc_check(&mut world, &DrcRules::default(), &mut issues);
+        let trace_width: Vec<&DrcViolation> = violations.iter()
+            .filter(|v| v.rule_name.contains("Trace Width"))
+            .collect();
+        assert_eq!(trace_width.len(), 1);
+        assert_eq!(trace_width[0].rule_name, "Primitive Trace Width");
+        assert!((trace_width[0].measured_value - 0.05).abs() < 1e-6);
+        assert_eq!(trace_width[0].layer, LayerType::Copper(1).display_name());
+    }
+
+    #[test]
+    fn test_dead_copper_on_drc_layers() {
+        // A track with no pad anywhere on its net
+        let mut world = crate::ecs::setup_ecs_world();
+        let layer = crate::ecs::test_support::spawn_layer(&mut world, LayerType::Copper(1),
+            "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\nD10*\nX0Y0D02*\nX5000000Y0D01*\nM02*\n");
+
+        assert_eq!(dead_copper_system(&mut world), 1);
+        assert_eq!(get_dead_copper(&mut world).len(), 1);
+
+        clear_drc_results_system(&mut world);
+        world.entity_mut(layer).remove::<RequiresDrc>();
+        assert_eq!(dead_copper_system(&mut world), 0);
+    }
+}
@@ -65,7 +65,6 @@ pub enum GerberPrimitive {
         radius: f64,
     },
 }
-use std::collections::HashMap;
 use std::io::BufReader;
 use gerber_viewer::gerber_parser::parse;
 
@@ -144,17 +143,35 @@ pub struct CornerOverlayShape {
     pub trace_width: f32,
 }
 
+/// Copper island without any pad or plated hole connection (dead copper)
+#[derive(Debug, Clone)]
+pub struct DeadCopperIsland {
+    pub layer: String,
+    pub area: f32,               // mm²
+    pub location: (f32, f32),    // mm, centre of the island bounds
+    pub outlines: Vec<Vec<Position>>,
+    /// Smaller than the minimum island area rule
+    pub below_threshold: bool,
+}
+
 /// DRC Rules structure with unit conversion support
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// Also a world resource read by the DRC systems
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, bevy_ecs::prelude::Resource)]
 pub struct DrcRules {
     pub min_trace_width: f32,      // mm
     pub min_via_diameter: f32,     // mm  
     pub min_drill_diameter: f32,   // mm
     pub min_spacing: f32,          // mm
     pub min_annular_ring: f32,     // mm
+    #[serde(default = "default_min_island_area")]
+    pub min_island_area: f32,      // mm², smaller dead copper islands are violations
     pub use_mils: bool,            // true = display in mils, false = mm
 }
 
+fn default_min_island_area() -> f32 {
+    1.0
+}
+
 impl Default for DrcRules {
     fn default() -> Self {
         Self {
@@ -163,6 +180,7 @@ impl Default for DrcRules {
             min_drill_diameter: 0.2,  // 0.2mm = ~8 mil
             min_spacing: 0.15,        // 0.15mm = ~6 mil
             min_annular_ring: 0.1,    // 0.1mm = ~4 mil
+            min_island_area: default_min_island_area(),
             use_mils: false,          // Default to mm
         }
     }
@@ -1105,118 +1123,3 @@ pub fn check_trace_width_in_gerber_data(
     
     violations
 }
-
-/// Main DRC check function - runs all configured DRC checks
-pub fn run_simple_drc_check(
-    layers: &HashMap<crate::ecs::LayerType, crate::ui::drc_panel::LayerInfo>,
-    drc_rules: &DrcRules,
-    trace_quality_issues: &mut Vec<TraceQualityIssue>
-) -> Vec<DrcViolation> {
-    use crate::ecs::LayerType;
-    
-    let mut violations = Vec::new();
-    
-    // Clear previous quality issues
-    trace_quality_issues.clear();
-    
-    // Get PCB boundary from mechanical outline layer
-    let pcb_boundary = if let Some(outline_info) = layers.get(&LayerType::MechanicalOutline) {
-        outline_info.gerber_layer.as_ref().map(|layer| layer.bounding_box())
-    } else {
-        None
-    };
-    
-    if pcb_boundary.is_none() {
-        println!("Warning: No mechanical outline found - cannot determine PCB boundary for DRC");
-        return violations;
-    }
-    
-    let boundary = pcb_boundary.unwrap();
-    println!("DRC boundary check: PCB area is {:.1} x {:.1} mm", boundary.width(), boundary.height());
-    
-    // Check each copper layer for trace width violations
-    for (layer_type, layer_info) in layers {
-        // Only check copper layers
-        if !layer_type.is_copper() {
-            continue;
-        }
-        
-        // Use primitive-based DRC analysis
-        if let Some(gerber_layer) = &layer_info.gerber_layer {
-            println!("Running primitive-based trace detection on {}", layer_type.display_name());
-            
-            let drc = DrcSimple {
-                min_trace_width: drc_rules.min_trace_width,
-                lines_only: true,  // Only check Line primitives to avoid copper pour false positives
-                min_trace_length: 1.0,  // Only lines >= 1mm are considered traces (not pad connections)
-                ..DrcSimple::default()
-            };
-            
-            // Get mechanical outline bounds for filtering
-            let pcb_bounds = layers.get(&LayerType::MechanicalOutline)
-                .and_then(|outline| outline.gerber_layer.as_ref())
-                .map(|layer| layer.bounding_box());
-                
-            let primitive_violations = drc.run_trace_width_drc_with_bounds(gerber_layer, pcb_bounds);
-            
-            // Also analyze trace quality (corners, jogs, etc.)
-            let quality_issues = drc.analyze_trace_quality(gerber_layer);
-            println!("Found {} trace quality issues on {}", quality_issues.len(), layer_type.display_name());
-            
-            // Log corner issues specifically
-            for issue in &quality_issues {
-                if matches!(issue.issue_type, TraceQualityType::SharpCorner) {
-                    println!("Corner issue at ({:.2}, {:.2}): {}", issue.location.0, issue.location.1, issue.description);
-                }
-            }
-            
-            // Store quality issues for this layer (extend the existing vector)
-            trace_quality_issues.extend(quality_issues);
-            
-            if let Some(bounds) = &pcb_bounds {
-                println!("PCB bounds: ({:.2}, {:.2}) to ({:.2}, {:.2})", 
-                    bounds.min.x, bounds.min.y, bounds.max.x, bounds.max.y);
-            }
-            
-            // Convert to DrcViolation format
-            for (i, violation) in primitive_violations.iter().enumerate() {
-                if i < 3 { // Debug first few violations
-                    println!("Violation {}: trace at ({:.2}, {:.2}), width {:.3}mm", 
-                        i, violation.trace.center_x, violation.trace.center_y, violation.trace.width);
-                }
-            }
-            
-            // Convert to DrcViolation format
-            for violation in primitive_violations {
-                violations.push(DrcViolation {
-                    rule_name: "Primitive Trace Width".to_string(),
-                    description: format!("Trace width {:.3}mm below minimum", violation.measured_width),
-                    layer: layer_type.display_name().to_string(),
-                    measured_value: violation.measured_width,
-                    required_value: drc_rules.min_trace_width,
-                    x: violation.trace.center_x,
-                    y: violation.trace.center_y,
-                });
-            }
-        }
-        
-        // Also check using raw gerber data analysis
-        if let Some(raw_data) = &layer_info.raw_gerber_data {
-            let raw_violations = check_trace_width_in_gerber_data(
-                raw_data, 
-                &layer_type.display_name(), 
-                drc_rules.min_trace_width,
-                &boundary
-            );
-            
-            // Cluster violations to reduce duplicates
-            let clustered = cluster_violations_per_trace(&raw_violations);
-            println!("Raw gerber analysis found {} violations ({} traces) on {}", 
-                raw_violations.len(), clustered.len(), layer_type.display_name());
-            
-            violations.extend(clustered);
-        }
-    }
-    
-    violations
-}
\ No newline at end of file
@@ -14,6 +14,18 @@ use super::LayerType; // Import LayerType from types module
 #[derive(Component)]
 pub struct GerberData(pub GerberLayer);
 
+// Original gerber file content (used by DRC and export systems)
+#[derive(Component, Clone)]
+pub struct RawGerberData(pub String);
+
+// Flattened geometry parsed from the raw gerber content (all values in mm)
+#[derive(Component, Clone, Debug)]
+pub struct LayerGeometryData(pub crate::geometry::LayerGeometry);
+
+// Comparison of the layer against the same layer in the reference revision
+#[derive(Component, Clone, Debug)]
+pub struct RevisionDiff(pub Option<crate::geometry::LayerDiff>);
+
 // Layer identification
 #[derive(Component, Clone, Debug)]
 pub struct LayerInfo {
@@ -92,6 +104,88 @@ pub struct BoundingBoxCache {
 #[derive(Component)]
 pub struct RequiresDrc;
 
+// DRC violation entity data (spawned by DRC systems)
+#[derive(Component, Clone, Debug)]
+pub struct DrcViolationData(pub crate::drc_operations::types::DrcViolation);
+
+// Trace quality issue entity data (spawned by DRC systems)
+#[derive(Component, Clone, Debug)]
+pub struct TraceQualityData(pub crate::drc_operations::types::TraceQualityIssue);
+
+// Dead copper island entity data (spawned by DRC systems)
+#[derive(Component, Clone, Debug)]
+pub struct DeadCopperData(pub crate::drc_operations::types::DeadCopperIsland);
+
+// Layer entity a DRC result was produced from
+#[derive(Component, Clone, Copy, Debug)]
+pub struct DrcSourceLayer {
+    pub entity: Entity,
+    pub layer_type: LayerType,
+}
+
+// Drill file entity (Excellon)
+#[derive(Component, Clone, Debug)]
+pub struct DrillFileInfo {
+    pub name: String,
+    pub file_path: Option<PathBuf>,
+    pub plated: bool,
+}
+
+// Parsed drill hits and slots of a drill file entity
+#[derive(Component, Clone, Debug)]
+pub struct DrillHoles(pub crate::geometry::DrillData);
+
+// Connected copper on one layer (spawned by the connectivity system)
+#[derive(Component, Clone, Debug)]
+pub struct CopperIsland {
+    pub layer_entity: Entity,
+    pub layer_type: LayerType,
+    pub net_id: usize,
+    /// Indices into the layer's LayerGeometryData features
+    pub features: Vec<usize>,
+    pub bounds: BoundingBox,
+    pub area: f64,
+    /// No island of its net holds a component pad
+    pub dead: bool,
+}
+
+// Islands joined through plated holes (spawned by the connectivity system)
+#[derive(Component, Clone, Debug)]
+pub struct ExtractedNet {
+    pub net_id: usize,
+    pub islands: Vec<Entity>,
+    pub layers: Vec<LayerType>,
+    pub hole_count: usize,
+}
+
+// Test point loaded from an IPC-D-356 netlist (index keeps the file order)
+#[derive(Component, Clone, Debug)]
+pub struct NetlistTestPoint {
+    pub index: usize,
+    pub point: crate::netlist::TestPoint,
+}
+
+// Marker for test points involved in an open, short or missing copper
+#[derive(Component)]
+pub struct NetlistProblem;
+
+// Placed component read from a board file (IPC-2581) rather than from KiCad
+#[derive(Component, Clone, Debug)]
+pub struct BoardComponent(pub crate::project_manager::bom::BomComponent);
+
+// Feature of this layer picked in the viewer (at most one in the world)
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelectedFeature {
+    pub index: usize,
+}
+
+// Outlines (file coordinates) of this layer's features on the highlighted net or component
+#[derive(Component, Clone, Debug, Default)]
+pub struct HighlightedFeatures {
+    pub net: Vec<crate::geometry::Polygon>,
+    pub component: Vec<crate::geometry::Polygon>,
+}
+
 // Marker for selected layers
 #[derive(Component)]
 pub struct Selected;
\ No newline at end of file
@@ -13,13 +13,13 @@ pub fn create_gerber_layer_entity(
     world: &mut World,
     layer_type: LayerType,
     gerber_layer: GerberLayer,
-    _raw_gerber_data: Option<String>,
+    raw_gerber_data: Option<String>,
     file_path: Option<PathBuf>,
     visible: bool,
 ) -> Entity {
     let bounds = gerber_layer.bounding_box().clone();
     
-    world.spawn((
+    let entity_id = world.spawn((
         GerberData(gerber_layer),
         LayerInfo {
             layer_type,
@@ -38,7 +38,27 @@ pub fn create_gerber_layer_entity(
             z_order: layer_type_to_z_order(&layer_type),
         },
         BoundingBoxCache { bounds },
-    )).id()
+    )).id();
+    
+    if let Some(raw_gerber_data) = raw_gerber_data {
+        // Flattened geometry for connectivity and other analysis systems
+        match crate::geometry::parse_gerber(&raw_gerber_data) {
+            Ok(geometry) => {
+                world.entity_mut(entity_id).insert(LayerGeometryData(geometry));
+            }
+            Err(e) => {
+                println!("Could not build geometry for {}: {}", layer_type.display_name(), e);
+            }
+        }
+        world.entity_mut(entity_id).insert(RawGerberData(raw_gerber_data));
+    }
+    
+    // Copper layers always take part in DRC, regardless of how they were loaded
+    if layer_type.is_copper() {
+        world.entity_mut(entity_id).insert(RequiresDrc);
+    }
+    
+    entity_id
 }
 
 /* DEPRECATED: LayerManager migration function (no longer needed)
@@ -90,6 +110,27 @@ pub fn create_mechanical_outline_entity(
     )
 }
 
+/// Factory for creating a drill file entity
+/// Plated state comes from the file attributes, falling back to the file name (PTH/NPTH)
+pub fn create_drill_entity(
+    world: &mut World,
+    name: String,
+    drill_data: crate::geometry::DrillData,
+    file_path: Option<PathBuf>,
+) -> Entity {
+    let plated = drill_data.plated
+        .unwrap_or_else(|| !name.to_uppercase().contains("NPTH"));
+    
+    world.spawn((
+        DrillFileInfo {
+            name,
+            file_path,
+            plated,
+        },
+        DrillHoles(drill_data),
+    )).id()
+}
+
 /// Factory for creating any layer entity (unified)
 pub fn create_layer_entity(
     world: &mut World,
@@ -111,8 +152,7 @@ pub fn create_layer_entity(
     // Add layer-specific components based on type
     match layer_type {
         LayerType::Copper(_) => {
-            // Add DRC requirement for copper layers
-            world.entity_mut(entity_id).insert(RequiresDrc);
+            // DRC requirement is attached by create_gerber_layer_entity
         }
         LayerType::Silkscreen(_) |
         LayerType::Soldermask(_) |
@@ -133,7 +173,7 @@ pub fn create_layer_entity(
 
 
 /// Utility function to determine z-order for layer rendering
-fn layer_type_to_z_order(layer_type: &LayerType) -> i32 {
+pub fn layer_type_to_z_order(layer_type: &LayerType) -> i32 {
     match layer_type {
         LayerType::Paste(Side::Top) => 90,
         LayerType::Silkscreen(Side::Top) => 80,
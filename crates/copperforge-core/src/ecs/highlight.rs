@@ -0,0 +1,174 @@
+use std::collections::{BTreeMap, BTreeSet};
+use bevy_ecs::prelude::*;
+
+use crate::display::DisplayManager;
+use crate::geometry::{Polygon, DEFAULT_TOLERANCE};
+use super::components::*;
+use super::resources::AttributeHighlight;
+
+// Net and component highlighting
+// Uses the X2 object attributes KiCad writes into Gerbers (.N net, .C component,
+// .P pin), so it works from the files alone, without a KiCad connection.
+// Matching features get a HighlightedFeatures component on their layer entity.
+
+/// Net found in the loaded Gerber attributes
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetSummary {
+    pub name: String,
+    pub features: usize,
+    /// Pins on the net as "R1-2", sorted
+    pub pins: Vec<String>,
+}
+
+/// System to highlight a net across all copper layers (None clears it)
+pub fn highlight_net_system(world: &mut World, net: Option<String>) {
+    {
+        let mut highlight = world.get_resource_or_insert_with(AttributeHighlight::default);
+        if highlight.net == net {
+            return;
+        }
+        highlight.net = net;
+    }
+    refresh_highlight_system(world);
+}
+
+/// System to highlight the features of a component by reference designator (None clears it)
+pub fn highlight_component_system(world: &mut World, reference: Option<String>) {
+    {
+        let mut highlight = world.get_resource_or_insert_with(AttributeHighlight::default);
+        if highlight.component == reference {
+            return;
+        }
+        highlight.component = reference;
+    }
+    refresh_highlight_system(world);
+}
+
+/// System to rebuild the HighlightedFeatures components from the AttributeHighlight resource
+pub fn refresh_highlight_system(world: &mut World) {
+    let highlight = world.get_resource::<AttributeHighlight>().cloned().unwrap_or_default();
+
+    let highlighted: Vec<Entity> = world.query_filtered::<Entity, With<HighlightedFeatures>>()
+        .iter(world)
+        .collect();
+    for entity in highlighted {
+        world.entity_mut(entity).remove::<HighlightedFeatures>();
+    }
+
+    if highlight.net.is_none() && highlight.component.is_none() {
+        return;
+    }
+
+    let updates: Vec<(Entity, HighlightedFeatures)> = {
+        let mut query = world.query::<(Entity, &LayerInfo, &LayerGeometryData)>();
+        query.iter(world)
+            .filter_map(|(entity, info, geometry)| {
+                let mut features = HighlightedFeatures::default();
+                for feature in &geometry.0.features {
+                    let outlines = || geometry.0.feature_polygons(feature, DEFAULT_TOLERANCE);
+                    if info.layer_type.is_copper() && highlight.net.as_deref().is_some_and(|net| feature.in_net(net)) {
+                        features.net.extend(outlines());
+                    }
+                    if highlight.component.is_some() && feature.component() == highlight.component.as_deref() {
+                        features.component.extend(outlines());
+                    }
+                }
+                let empty = features.net.is_empty() && features.component.is_empty();
+                (!empty).then_some((entity, features))
+            })
+            .collect()
+    };
+    for (entity, features) in updates {
+        world.entity_mut(entity).insert(features);
+    }
+}
+
+/// Highlight outlines in viewer coordinates as (net, component), for drawn layers only
+pub fn highlighted_feature_outlines(world: &mut World, display_manager: &DisplayManager) -> (Vec<Polygon>, Vec<Polygon>) {
+    let mut net = Vec::new();
+    let mut component = Vec::new();
+    let mut query = world.query::<(&LayerInfo, &Transform, &ImageTransform, &Visibility, &HighlightedFeatures)>();
+    for (info, transform, image_transform, visibility, features) in query.iter(world) {
+        if !super::inspection::is_drawn(&info.layer_type, visibility, display_manager) {
+            continue;
+        }
+        let view_transform = super::layer_view_transform(transform, image_transform, &info.layer_type, display_manager);
+        let to_view = |polygon: &Polygon| Polygon::new(
+            polygon.points.iter().map(|point| view_transform.apply(*point)).collect(),
+            polygon.dark,
+        );
+        net.extend(features.net.iter().map(to_view));
+        component.extend(features.component.iter().map(to_view));
+    }
+    (net, component)
+}
+
+/// System to list the nets named in the copper layers' object attributes, sorted by name
+pub fn list_nets_system(world: &mut World) -> Vec<NetSummary> {
+    let mut nets: BTreeMap<String, (usize, BTreeSet<String>)> = BTreeMap::new();
+    let mut query = world.query::<(&LayerInfo, &LayerGeometryData)>();
+    for (info, geometry) in query.iter(world) {
+        if !info.layer_type.is_copper() {
+            continue;
+        }
+        for feature in &geometry.0.features {
+            let Some(names) = feature.attribute(".N") else {
+                continue;
+            };
+            for name in names.iter().filter(|name| !name.is_empty()) {
+                let entry = nets.entry(name.clone()).or_default();
+                entry.0 += 1;
+                if let Some((reference, pin)) = feature.pin() {
+                    entry.1.insert(format!("{}-{}", reference, pin));
+                }
+            }
+        }
+    }
+
+    nets.into_iter()
+        .map(|(name, (features, pins))| NetSummary {
+            name,
+            features,
+            pins: pins.into_iter().collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+    use crate::ecs::LayerType;
+
+    const COPPER: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,1.000000X1.000000*%\n%ADD11C,0.250000*%\n\
+        D10*\n%TO.P,R1,1*%\n%TO.N,GND*%\nX0Y0D03*\n%TD*%\n\
+        %TO.P,R1,2*%\n%TO.N,VCC*%\nX2000000Y0D03*\n%TD*%\n\
+        D11*\n%TO.N,GND*%\nX0Y0D02*\nX0Y5000000D01*\n%TD*%\nM02*\n";
+
+    #[test]
+    fn test_net_and_component_highlight() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+        spawn_layer(&mut world, LayerType::Copper(1), COPPER);
+        spawn_layer(&mut world, LayerType::Soldermask(crate::ecs::Side::Top), COPPER);
+
+        let nets = list_nets_system(&mut world);
+        assert_eq!(nets.len(), 2);
+        assert_eq!((nets[0].name.as_str(), nets[0].features), ("GND", 2));
+        assert_eq!(nets[0].pins, ["R1-1"]);
+
+        // Nets are highlighted on copper only
+        highlight_net_system(&mut world, Some("GND".to_string()));
+        let (net, component) = highlighted_feature_outlines(&mut world, &display_manager);
+        assert_eq!((net.len(), component.len()), (2, 0));
+
+        // Component pads are highlighted on every layer that carries them
+        highlight_component_system(&mut world, Some("R1".to_string()));
+        let (_, component) = highlighted_feature_outlines(&mut world, &display_manager);
+        assert_eq!(component.len(), 4);
+
+        highlight_net_system(&mut world, None);
+        highlight_component_system(&mut world, None);
+        assert_eq!(world.query::<&HighlightedFeatures>().iter(&world).count(), 0);
+    }
+}
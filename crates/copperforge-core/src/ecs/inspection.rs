@@ -0,0 +1,164 @@
+use bevy_ecs::prelude::*;
+use nalgebra::Point2;
+
+use crate::display::{DisplayManager, VectorOffset};
+use crate::geometry::{Aperture, GerberFeature, LayerTransform, Polygon, DEFAULT_TOLERANCE};
+use super::components::*;
+use super::LayerType;
+
+// Feature inspection
+// Hit-tests the flattened geometry of the visible layers at a viewer point and
+// keeps the picked feature as a SelectedFeature component on its layer entity,
+// so any panel can query the current selection.
+
+/// Feature found under a viewer point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureHit {
+    pub entity: Entity,
+    pub layer_type: LayerType,
+    pub index: usize,
+}
+
+/// Selected feature with the layer it belongs to
+#[derive(Debug, Clone)]
+pub struct SelectedFeatureInfo {
+    pub entity: Entity,
+    pub layer: LayerInfo,
+    pub index: usize,
+    pub feature: GerberFeature,
+    pub aperture: Option<Aperture>,
+}
+
+/// Mapping from a layer's file coordinates to viewer coordinates, as drawn by the enhanced render system
+pub fn layer_view_transform(
+    transform: &Transform,
+    image_transform: &ImageTransform,
+    layer_type: &LayerType,
+    display_manager: &DisplayManager,
+) -> LayerTransform {
+    let quadrant_offset = if display_manager.quadrant_view_enabled {
+        display_manager.get_quadrant_offset(layer_type)
+    } else {
+        VectorOffset { x: 0.0, y: 0.0 }
+    };
+    let gerber_transform = super::create_gerber_transform_with_offset_composed(transform, image_transform, display_manager, quadrant_offset);
+    LayerTransform::from_fn(|point| gerber_transform.apply_to_position(point))
+}
+
+/// Whether the render system draws the layer (quadrant view leaves out outline and paste)
+pub(crate) fn is_drawn(layer_type: &LayerType, visibility: &Visibility, display_manager: &DisplayManager) -> bool {
+    visibility.visible
+        && !(display_manager.quadrant_view_enabled
+            && matches!(layer_type, LayerType::MechanicalOutline | LayerType::Paste(_)))
+}
+
+/// System to find the topmost feature at a viewer point across the drawn layers
+/// `radius` is the pick tolerance in viewer units (mm).
+pub fn pick_feature_system(
+    world: &mut World,
+    point: Point2<f64>,
+    radius: f64,
+    display_manager: &DisplayManager,
+) -> Option<FeatureHit> {
+    let mut query = world.query::<(Entity, &LayerInfo, &LayerGeometryData, &Transform, &ImageTransform, &Visibility, &RenderProperties)>();
+    let mut layers: Vec<_> = query.iter(world)
+        .filter(|(_, info, _, _, _, visibility, _)| is_drawn(&info.layer_type, visibility, display_manager))
+        .collect();
+
+    // Highest z-order is drawn last, so it is on top
+    layers.sort_by_key(|(.., render_props)| std::cmp::Reverse(render_props.z_order));
+
+    layers.into_iter().find_map(|(entity, info, geometry, transform, image_transform, _, _)| {
+        let view_transform = layer_view_transform(transform, image_transform, &info.layer_type, display_manager);
+        let file_point = view_transform.inverse()?.apply(point);
+        let index = geometry.0.feature_at(file_point, radius / view_transform.scale())?;
+        Some(FeatureHit { entity, layer_type: info.layer_type, index })
+    })
+}
+
+/// System to make a picked feature the only selected one (None clears the selection)
+pub fn select_feature_system(world: &mut World, hit: Option<FeatureHit>) {
+    let selected: Vec<Entity> = world.query_filtered::<Entity, With<SelectedFeature>>()
+        .iter(world)
+        .collect();
+    for entity in selected {
+        world.entity_mut(entity).remove::<SelectedFeature>();
+    }
+
+    if let Some(hit) = hit {
+        if let Some(mut entity) = world.get_entity_mut(hit.entity) {
+            entity.insert(SelectedFeature { index: hit.index });
+        }
+    }
+}
+
+/// Get the selected feature, if any
+pub fn get_selected_feature(world: &mut World) -> Option<SelectedFeatureInfo> {
+    let mut query = world.query::<(Entity, &LayerInfo, &LayerGeometryData, &SelectedFeature)>();
+    query.iter(world).find_map(|(entity, info, geometry, selected)| {
+        let feature = geometry.0.features.get(selected.index)?.clone();
+        let aperture = feature.aperture.and_then(|d| geometry.0.apertures.get(&d)).cloned();
+        Some(SelectedFeatureInfo {
+            entity,
+            layer: info.clone(),
+            index: selected.index,
+            feature,
+            aperture,
+        })
+    })
+}
+
+/// Outline of the selected feature in viewer coordinates, empty while its layer is not drawn
+pub fn selected_feature_outlines(world: &mut World, display_manager: &DisplayManager) -> Vec<Polygon> {
+    let mut query = world.query::<(&LayerInfo, &LayerGeometryData, &Transform, &ImageTransform, &Visibility, &SelectedFeature)>();
+    let Some((info, geometry, transform, image_transform, _, selected)) = query.iter(world)
+        .find(|(info, _, _, _, visibility, _)| is_drawn(&info.layer_type, visibility, display_manager))
+    else {
+        return Vec::new();
+    };
+    let Some(feature) = geometry.0.features.get(selected.index) else {
+        return Vec::new();
+    };
+
+    let view_transform = layer_view_transform(transform, image_transform, &info.layer_type, display_manager);
+    geometry.0.feature_polygons(feature, DEFAULT_TOLERANCE).into_iter()
+        .map(|polygon| Polygon::new(
+            polygon.points.iter().map(|point| view_transform.apply(*point)).collect(),
+            polygon.dark,
+        ))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+
+    const TRACK: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.300000*%\n\
+        %TO.N,GND*%\nD10*\nX0Y0D02*\nX10000000Y0D01*\n%TD*%\nM02*\n";
+
+    #[test]
+    fn test_pick_and_select() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+        let top = spawn_layer(&mut world, LayerType::Copper(1), TRACK);
+        let bottom = spawn_layer(&mut world, LayerType::Copper(2), TRACK);
+
+        let hit = pick_feature_system(&mut world, Point2::new(5.0, 0.1), 0.0, &display_manager).unwrap();
+        assert_eq!((hit.entity, hit.index), (top, 0));
+        assert!(pick_feature_system(&mut world, Point2::new(5.0, 1.0), 0.1, &display_manager).is_none());
+
+        world.get_mut::<Visibility>(top).unwrap().visible = false;
+        let hit = pick_feature_system(&mut world, Point2::new(5.0, 0.1), 0.0, &display_manager).unwrap();
+        assert_eq!(hit.entity, bottom);
+
+        select_feature_system(&mut world, Some(hit));
+        let selected = get_selected_feature(&mut world).unwrap();
+        assert_eq!(selected.layer.layer_type, LayerType::Copper(2));
+        assert_eq!(selected.feature.attribute(".N"), Some(&["GND".to_string()][..]));
+        assert!(!selected_feature_outlines(&mut world, &display_manager).is_empty());
+
+        select_feature_system(&mut world, None);
+        assert!(get_selected_feature(&mut world).is_none());
+    }
+}
@@ -0,0 +1,101 @@
+use bevy_ecs::prelude::*;
+use egui::Color32;
+
+use crate::project::{LayerPreset, LayerPresetEntry};
+use super::components::*;
+use super::{layer_type_to_z_order, LayerType};
+
+// Layer presets on the layer entities
+// Captures the visibility, opacity and color of every loaded layer into a
+// preset and applies a preset back. The view side is left to the caller,
+// which owns the display manager.
+
+/// Copper layer numbers of the loaded layers, ascending
+pub fn loaded_copper_layers(world: &mut World) -> Vec<u8> {
+    let mut query = world.query::<&LayerInfo>();
+    let mut copper: Vec<u8> = query.iter(world)
+        .filter_map(|info| match info.layer_type {
+            LayerType::Copper(n) => Some(n),
+            _ => None,
+        })
+        .collect();
+    copper.sort_unstable();
+    copper.dedup();
+    copper
+}
+
+/// System to capture the current layer settings as a named preset
+pub fn capture_layer_preset_system(world: &mut World, name: &str, showing_top: bool) -> LayerPreset {
+    let mut query = world.query::<(&LayerInfo, &Visibility, &RenderProperties)>();
+    let mut layers: Vec<LayerPresetEntry> = query.iter(world)
+        .map(|(info, visibility, properties)| LayerPresetEntry {
+            layer_type: info.layer_type,
+            visible: visibility.visible,
+            opacity: visibility.opacity,
+            color: Some(properties.color.to_array()),
+        })
+        .collect();
+    layers.sort_by_key(|entry| layer_type_to_z_order(&entry.layer_type));
+    LayerPreset { name: name.to_string(), showing_top: Some(showing_top), layers }
+}
+
+/// System to apply a preset to the loaded layers, returning how many are left visible
+pub fn apply_layer_preset_system(world: &mut World, preset: &LayerPreset) -> usize {
+    let mut visible = 0;
+    let mut query = world.query::<(&LayerInfo, &mut Visibility, &mut RenderProperties)>();
+    for (info, mut visibility, mut properties) in query.iter_mut(world) {
+        match preset.entry(info.layer_type) {
+            Some(entry) => {
+                visibility.visible = entry.visible;
+                visibility.opacity = entry.opacity.clamp(0.0, 1.0);
+                if let Some([r, g, b, a]) = entry.color {
+                    properties.color = Color32::from_rgba_premultiplied(r, g, b, a);
+                }
+            }
+            None => visibility.visible = false,
+        }
+        if visibility.visible {
+            visible += 1;
+        }
+    }
+    visible
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+
+    const PAD: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,1.000000*%\nD10*\nX0Y0D03*\nM02*\n";
+
+    #[test]
+    fn test_capture_and_apply_preset() {
+        let mut world = crate::ecs::setup_ecs_world();
+        for layer_type in [LayerType::Copper(1), LayerType::Copper(2), LayerType::MechanicalOutline] {
+            spawn_layer(&mut world, layer_type, PAD);
+        }
+        assert_eq!(loaded_copper_layers(&mut world), vec![1, 2]);
+
+        crate::ecs::set_layer_visibility(&mut world, LayerType::Copper(2), false);
+        crate::ecs::set_layer_opacity(&mut world, LayerType::Copper(1), 0.5);
+        crate::ecs::update_layer_render_properties(&mut world, LayerType::Copper(1), Color32::RED);
+        let saved = capture_layer_preset_system(&mut world, "Review", false);
+        assert_eq!(saved.layers.len(), 3);
+        assert_eq!(saved.showing_top, Some(false));
+
+        // A preset listing only the outline hides the copper
+        let outline_only = LayerPreset {
+            name: "Outline".to_string(),
+            showing_top: None,
+            layers: vec![LayerPresetEntry { layer_type: LayerType::MechanicalOutline, visible: true, opacity: 1.0, color: None }],
+        };
+        assert_eq!(apply_layer_preset_system(&mut world, &outline_only), 1);
+        assert!(!crate::ecs::get_layer_visibility(&mut world, LayerType::Copper(1)));
+
+        assert_eq!(apply_layer_preset_system(&mut world, &saved), 2);
+        assert!(crate::ecs::get_layer_visibility(&mut world, LayerType::Copper(1)));
+        assert!(!crate::ecs::get_layer_visibility(&mut world, LayerType::Copper(2)));
+        let properties = crate::ecs::get_layer_render_properties(&mut world, LayerType::Copper(1)).unwrap();
+        assert_eq!(properties.color, Color32::RED);
+    }
+}
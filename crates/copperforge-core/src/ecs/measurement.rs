@@ -0,0 +1,271 @@
+use bevy_ecs::prelude::*;
+use nalgebra::Point2;
+
+use crate::display::DisplayManager;
+use crate::geometry::{
+    best_snap, shapes, snap_to_drills, snap_to_layer, LayerTransform, SnapKind, SnapPoint, DEFAULT_TOLERANCE,
+};
+use super::components::*;
+use super::inspection::{is_drawn, FeatureHit};
+use super::resources::Measurements;
+use super::units::{mm_to_nm, UnitsResource};
+use super::LayerType;
+
+// Measurements
+// Snapping of measurement points to the geometry of the drawn layers and drill
+// files, edge-to-edge clearance between two features, and the list of
+// measurements taken this session. All points are in viewer coordinates (mm).
+
+/// Measurement point snapped to feature geometry
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapTarget {
+    pub point: Point2<f64>,
+    pub kind: SnapKind,
+    /// Layer the point was found on (None for drill files)
+    pub layer_type: Option<LayerType>,
+}
+
+impl SnapTarget {
+    pub fn label(&self) -> String {
+        match (self.layer_type, self.kind) {
+            (Some(LayerType::MechanicalOutline), SnapKind::Endpoint | SnapKind::Vertex) => "Outline vertex".to_string(),
+            (Some(layer_type), kind) => format!("{} ({})", kind.display_name(), layer_type.display_name()),
+            (None, kind) => kind.display_name().to_string(),
+        }
+    }
+}
+
+/// Closest edge points of two features
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clearance {
+    pub from: Point2<f64>,
+    pub to: Point2<f64>,
+    /// Edge-to-edge distance, 0 when the features overlap
+    pub distance: f64,
+}
+
+/// A measurement kept in the session list
+#[derive(Debug, Clone, PartialEq)]
+pub enum Measurement {
+    Distance { start: Point2<f64>, end: Point2<f64> },
+    Clearance { clearance: Clearance, description: String },
+    /// Angle at `vertex` between the rays to `first` and `second`
+    Angle { first: Point2<f64>, vertex: Point2<f64>, second: Point2<f64> },
+    Area { points: Vec<Point2<f64>> },
+}
+
+impl Measurement {
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Measurement::Distance { .. } => "Distance",
+            Measurement::Clearance { .. } => "Clearance",
+            Measurement::Angle { .. } => "Angle",
+            Measurement::Area { .. } => "Area",
+        }
+    }
+
+    /// Length in mm, angle in degrees or area in mm²
+    pub fn value(&self) -> f64 {
+        match self {
+            Measurement::Distance { start, end } => (end - start).norm(),
+            Measurement::Clearance { clearance, .. } => clearance.distance,
+            Measurement::Angle { first, vertex, second } => angle_between(*first, *vertex, *second),
+            Measurement::Area { points } => shapes::signed_area(points).abs(),
+        }
+    }
+
+    /// Points to draw, in order
+    pub fn points(&self) -> Vec<Point2<f64>> {
+        match self {
+            Measurement::Distance { start, end } => vec![*start, *end],
+            Measurement::Clearance { clearance, .. } => vec![clearance.from, clearance.to],
+            Measurement::Angle { first, vertex, second } => vec![*first, *vertex, *second],
+            Measurement::Area { points } => points.clone(),
+        }
+    }
+}
+
+/// Angle at `vertex` between the rays to `first` and `second` (degrees, 0 - 180)
+pub fn angle_between(first: Point2<f64>, vertex: Point2<f64>, second: Point2<f64>) -> f64 {
+    let (a, b) = (first - vertex, second - vertex);
+    if a.norm() == 0.0 || b.norm() == 0.0 {
+        return 0.0;
+    }
+    a.perp(&b).atan2(a.dot(&b)).abs().to_degrees()
+}
+
+/// Measured value in the display units
+pub fn format_measurement(measurement: &Measurement, units: &UnitsResource) -> String {
+    let value = measurement.value();
+    match measurement {
+        Measurement::Angle { .. } => format!("{:.2}°", value),
+        Measurement::Area { .. } if units.is_mils() => format!("{:.1} mils²", value / (0.0254 * 0.0254)),
+        Measurement::Area { .. } => format!("{:.4} mm²", value),
+        _ => units.format_value(mm_to_nm(value as f32)),
+    }
+}
+
+/// View transform the drill files are drawn with (they share the top copper coordinates)
+fn drill_view_transform(world: &mut World, display_manager: &DisplayManager) -> LayerTransform {
+    let mut query = world.query::<(&LayerInfo, &Transform, &ImageTransform)>();
+    query.iter(world)
+        .find(|(info, ..)| info.layer_type == LayerType::Copper(1))
+        .map(|(info, transform, image_transform)| {
+            super::layer_view_transform(transform, image_transform, &info.layer_type, display_manager)
+        })
+        .unwrap_or_default()
+}
+
+/// System to snap a viewer point to the nearest feature point of the drawn layers or the drill files
+/// `radius` is the snap distance in viewer units (mm).
+pub fn snap_point_system(
+    world: &mut World,
+    point: Point2<f64>,
+    radius: f64,
+    display_manager: &DisplayManager,
+) -> Option<SnapTarget> {
+    let mut best: Option<(SnapPoint, Option<LayerType>)> = None;
+    let mut consider = |snap: Option<SnapPoint>, view_transform: &LayerTransform, layer_type: Option<LayerType>| {
+        let Some(snap) = snap else {
+            return;
+        };
+        // Compare candidates in viewer coordinates
+        let view_point = view_transform.apply(snap.point);
+        let snap = SnapPoint { point: view_point, kind: snap.kind, distance: (view_point - point).norm() };
+        if best_snap(best.map(|(current, _)| current), Some(snap)) == Some(snap) {
+            best = Some((snap, layer_type));
+        }
+    };
+
+    {
+        let mut query = world.query::<(&LayerInfo, &LayerGeometryData, &Transform, &ImageTransform, &Visibility)>();
+        for (info, geometry, transform, image_transform, visibility) in query.iter(world) {
+            if !is_drawn(&info.layer_type, visibility, display_manager) {
+                continue;
+            }
+            let view_transform = super::layer_view_transform(transform, image_transform, &info.layer_type, display_manager);
+            let Some(inverse) = view_transform.inverse() else {
+                continue;
+            };
+            let snap = snap_to_layer(&geometry.0, inverse.apply(point), radius / view_transform.scale());
+            consider(snap, &view_transform, Some(info.layer_type));
+        }
+    }
+
+    let drill_transform = drill_view_transform(world, display_manager);
+    if let Some(inverse) = drill_transform.inverse() {
+        let file_point = inverse.apply(point);
+        let file_radius = radius / drill_transform.scale();
+        let mut query = world.query::<&DrillHoles>();
+        for holes in query.iter(world) {
+            consider(snap_to_drills(&holes.0, file_point, file_radius), &drill_transform, None);
+        }
+    }
+
+    best.map(|(snap, layer_type)| SnapTarget { point: snap.point, kind: snap.kind, layer_type })
+}
+
+/// System to measure the edge-to-edge clearance between two picked features
+pub fn feature_clearance_system(
+    world: &mut World,
+    first: &FeatureHit,
+    second: &FeatureHit,
+    display_manager: &DisplayManager,
+) -> Option<Clearance> {
+    let mut outlines = |hit: &FeatureHit| -> Option<Vec<Vec<Point2<f64>>>> {
+        let mut query = world.query::<(&LayerInfo, &LayerGeometryData, &Transform, &ImageTransform)>();
+        let (info, geometry, transform, image_transform) = query.get(world, hit.entity).ok()?;
+        let feature = geometry.0.features.get(hit.index)?;
+        let view_transform = super::layer_view_transform(transform, image_transform, &info.layer_type, display_manager);
+        Some(geometry.0.feature_polygons(feature, DEFAULT_TOLERANCE).into_iter()
+            .filter(|polygon| polygon.dark && polygon.points.len() >= 2)
+            .map(|polygon| polygon.points.iter().map(|p| view_transform.apply(*p)).collect())
+            .collect())
+    };
+    let a = outlines(first)?;
+    let b = outlines(second)?;
+
+    let mut best: Option<Clearance> = None;
+    for pa in &a {
+        for pb in &b {
+            let Some((from, to, distance)) = shapes::closest_points(pa, pb) else {
+                continue;
+            };
+            let distance = if pa.len() >= 3 && pb.len() >= 3 && shapes::polygons_intersect(pa, pb) { 0.0 } else { distance };
+            if best.is_none_or(|current| distance < current.distance) {
+                best = Some(Clearance { from, to, distance });
+            }
+        }
+    }
+    best
+}
+
+/// System to add a measurement to the session list
+pub fn add_measurement_system(world: &mut World, measurement: Measurement) {
+    world.get_resource_or_insert_with(Measurements::default).0.push(measurement);
+}
+
+/// System to remove a measurement by its position in the list
+pub fn remove_measurement_system(world: &mut World, index: usize) {
+    if let Some(mut measurements) = world.get_resource_mut::<Measurements>() {
+        if index < measurements.0.len() {
+            measurements.0.remove(index);
+        }
+    }
+}
+
+pub fn clear_measurements_system(world: &mut World) {
+    if let Some(mut measurements) = world.get_resource_mut::<Measurements>() {
+        measurements.0.clear();
+    }
+}
+
+pub fn get_measurements(world: &World) -> Vec<Measurement> {
+    world.get_resource::<Measurements>()
+        .map(|measurements| measurements.0.clone())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+
+    // Two 1 mm square pads 3 mm apart, centre to centre
+    const PADS: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,1.000000X1.000000*%\n\
+        D10*\nX0Y0D03*\nX3000000Y0D03*\nM02*\n";
+
+    #[test]
+    fn test_snap_clearance_and_list() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+        let entity = spawn_layer(&mut world, LayerType::Copper(1), PADS);
+
+        let snap = snap_point_system(&mut world, Point2::new(3.2, 0.1), 0.5, &display_manager).unwrap();
+        assert_eq!(snap.kind, SnapKind::Center);
+        assert!((snap.point - Point2::new(3.0, 0.0)).norm() < 1e-9);
+        assert!(snap_point_system(&mut world, Point2::new(1.5, 5.0), 0.5, &display_manager).is_none());
+
+        let first = FeatureHit { entity, layer_type: LayerType::Copper(1), index: 0 };
+        let second = FeatureHit { entity, layer_type: LayerType::Copper(1), index: 1 };
+        let clearance = feature_clearance_system(&mut world, &first, &second, &display_manager).unwrap();
+        assert!((clearance.distance - 2.0).abs() < 1e-9);
+        assert!((clearance.from.x - 0.5).abs() < 1e-9 && (clearance.to.x - 2.5).abs() < 1e-9);
+
+        let angle = angle_between(Point2::new(1.0, 0.0), Point2::origin(), Point2::new(0.0, 2.0));
+        assert!((angle - 90.0).abs() < 1e-9);
+
+        add_measurement_system(&mut world, Measurement::Clearance { clearance, description: String::new() });
+        add_measurement_system(&mut world, Measurement::Area {
+            points: vec![Point2::origin(), Point2::new(2.0, 0.0), Point2::new(2.0, 3.0), Point2::new(0.0, 3.0)],
+        });
+        let measurements = get_measurements(&world);
+        assert_eq!(measurements.len(), 2);
+        assert!((measurements[1].value() - 6.0).abs() < 1e-9);
+
+        remove_measurement_system(&mut world, 0);
+        assert_eq!(get_measurements(&world)[0].kind_name(), "Area");
+        clear_measurements_system(&mut world);
+        assert!(get_measurements(&world).is_empty());
+    }
+}
@@ -0,0 +1,366 @@
+use std::ops::Range;
+use bevy_ecs::component::Tick;
+use bevy_ecs::prelude::*;
+use egui::epaint::{Mesh, Vertex, WHITE_UV};
+use egui::{Color32, Painter, Pos2, Rect, Shape};
+use gerber_viewer::ViewState;
+use nalgebra::Point2;
+
+use crate::display::DisplayManager;
+use crate::geometry::{LayerGeometry, LayerTransform, Polarity, Polygon, DEFAULT_TOLERANCE};
+use super::components::*;
+use super::resources::RenderStats;
+
+// Cached layer meshes
+// Triangulates a layer's flattened geometry once, in viewer coordinates, so a
+// frame only maps the vertices of the features inside the viewport to screen.
+// A cache is rebuilt when the layer transform, paint color or geometry change.
+// Layers with clear polarity features keep using gerber_viewer, since a mesh
+// cannot erase what was drawn before it.
+
+/// Grid cells per axis used for viewport culling
+const GRID_CELLS: usize = 64;
+
+/// What a layer mesh was built from
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshKey {
+    pub transform: LayerTransform,
+    pub color: Color32,
+    /// Change tick of the layer's LayerGeometryData, newer whenever it is replaced
+    pub geometry: Tick,
+}
+
+impl MeshKey {
+    pub fn new(transform: LayerTransform, color: Color32, geometry: Tick) -> Self {
+        Self { transform, color, geometry }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FeatureSpan {
+    vertices: Range<usize>,
+    indices: Range<usize>,
+    min: [f32; 2],
+    max: [f32; 2],
+}
+
+impl FeatureSpan {
+    fn overlaps(&self, min: [f32; 2], max: [f32; 2]) -> bool {
+        self.min[0] <= max[0] && self.max[0] >= min[0] && self.min[1] <= max[1] && self.max[1] >= min[1]
+    }
+}
+
+/// Uniform grid over the layer bounds listing the features touching each cell
+#[derive(Debug, Clone, Default)]
+struct SpatialGrid {
+    min: [f32; 2],
+    cell: [f32; 2],
+    cells: Vec<Vec<u32>>,
+}
+
+impl SpatialGrid {
+    fn new(features: &[FeatureSpan]) -> Self {
+        let Some(first) = features.first() else {
+            return Self::default();
+        };
+        let (mut min, mut max) = (first.min, first.max);
+        for span in features {
+            for axis in 0..2 {
+                min[axis] = min[axis].min(span.min[axis]);
+                max[axis] = max[axis].max(span.max[axis]);
+            }
+        }
+        let cell = [
+            ((max[0] - min[0]) / GRID_CELLS as f32).max(1e-6),
+            ((max[1] - min[1]) / GRID_CELLS as f32).max(1e-6),
+        ];
+        let mut grid = Self { min, cell, cells: vec![Vec::new(); GRID_CELLS * GRID_CELLS] };
+        for (index, span) in features.iter().enumerate() {
+            let (columns, rows) = grid.cell_range(span.min, span.max);
+            for row in rows {
+                for column in columns.clone() {
+                    grid.cells[row * GRID_CELLS + column].push(index as u32);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Cells covering a box, clamped to the grid
+    fn cell_range(&self, min: [f32; 2], max: [f32; 2]) -> (Range<usize>, Range<usize>) {
+        let axis = |axis: usize| {
+            let cell = |value: f32| (((value - self.min[axis]) / self.cell[axis]).floor().max(0.0) as usize).min(GRID_CELLS - 1);
+            cell(min[axis])..cell(max[axis]) + 1
+        };
+        (axis(0), axis(1))
+    }
+
+    /// Indices of the features in the cells a box touches, each once, in drawing order
+    fn query(&self, min: [f32; 2], max: [f32; 2], feature_count: usize) -> Vec<usize> {
+        if self.cells.is_empty() {
+            return Vec::new();
+        }
+        let mut seen = vec![false; feature_count];
+        let (columns, rows) = self.cell_range(min, max);
+        for row in rows {
+            for column in columns.clone() {
+                for &index in &self.cells[row * GRID_CELLS + column] {
+                    seen[index as usize] = true;
+                }
+            }
+        }
+        (0..feature_count).filter(|index| seen[*index]).collect()
+    }
+}
+
+// Triangulated geometry of a layer in viewer coordinates
+#[derive(Component, Debug, Clone)]
+pub struct LayerMeshCache {
+    pub key: MeshKey,
+    // False when the layer has to be painted by gerber_viewer (clear polarity features)
+    pub supported: bool,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    features: Vec<FeatureSpan>,
+    grid: SpatialGrid,
+}
+
+impl LayerMeshCache {
+    pub fn build(geometry: &LayerGeometry, key: MeshKey) -> Self {
+        let mut cache = Self {
+            supported: true,
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            features: Vec::new(),
+            grid: SpatialGrid::default(),
+            key,
+        };
+
+        for feature in &geometry.features {
+            if feature.polarity == Polarity::Clear {
+                cache.supported = false;
+                break;
+            }
+            let vertices_start = cache.vertices.len();
+            let indices_start = cache.indices.len();
+            let polygons = geometry.feature_polygons(feature, DEFAULT_TOLERANCE);
+            if !cache.triangulate(&polygons) {
+                cache.supported = false;
+                break;
+            }
+            if cache.vertices.len() == vertices_start {
+                continue;
+            }
+
+            let mut min = [f32::MAX; 2];
+            let mut max = [f32::MIN; 2];
+            for vertex in &cache.vertices[vertices_start..] {
+                min = [min[0].min(vertex.pos.x), min[1].min(vertex.pos.y)];
+                max = [max[0].max(vertex.pos.x), max[1].max(vertex.pos.y)];
+            }
+            cache.features.push(FeatureSpan {
+                vertices: vertices_start..cache.vertices.len(),
+                indices: indices_start..cache.indices.len(),
+                min,
+                max,
+            });
+        }
+
+        if cache.supported {
+            cache.grid = SpatialGrid::new(&cache.features);
+        } else {
+            cache.vertices = Vec::new();
+            cache.indices = Vec::new();
+            cache.features = Vec::new();
+        }
+        cache
+    }
+
+    /// Triangulate the dark polygons of a feature, using the clear polygons inside them as holes
+    /// Returns false when a clear polygon is not a hole of a single dark polygon.
+    fn triangulate(&mut self, polygons: &[Polygon]) -> bool {
+        let is_area = |polygon: &&Polygon| polygon.points.len() >= 3;
+        let holes: Vec<&Polygon> = polygons.iter().filter(is_area).filter(|p| !p.dark).collect();
+        let mut used_holes = 0;
+
+        for outer in polygons.iter().filter(is_area).filter(|p| p.dark) {
+            let mut data = Vec::new();
+            let mut hole_indices = Vec::new();
+            push_contour(&mut data, &outer.points);
+            for hole in holes.iter().filter(|hole| hole.points.iter().all(|point| outer.contains(*point))) {
+                hole_indices.push(data.len() / 2);
+                push_contour(&mut data, &hole.points);
+                used_holes += 1;
+            }
+
+            let Ok(triangles) = earcutr::earcut(&data, &hole_indices, 2) else {
+                continue;
+            };
+            let base = self.vertices.len() as u32;
+            for point in data.chunks_exact(2) {
+                let view = self.key.transform.apply(Point2::new(point[0], point[1]));
+                self.vertices.push(Vertex {
+                    pos: Pos2::new(view.x as f32, view.y as f32),
+                    uv: WHITE_UV,
+                    color: self.key.color,
+                });
+            }
+            self.indices.extend(triangles.into_iter().map(|index| base + index as u32));
+        }
+        used_holes == holes.len()
+    }
+
+    pub fn feature_count(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Features whose bounds intersect a box in viewer coordinates
+    fn visible_features(&self, min: [f32; 2], max: [f32; 2]) -> impl Iterator<Item = &FeatureSpan> {
+        self.grid.query(min, max, self.features.len())
+            .into_iter()
+            .map(|index| &self.features[index])
+            .filter(move |span| span.overlaps(min, max))
+    }
+
+    /// Paint the features intersecting the painter's clip rect, returning (features, triangles) drawn
+    pub fn paint(&self, painter: &Painter, view_state: &ViewState) -> (usize, usize) {
+        let (min, max) = viewport_bounds(view_state, painter.clip_rect());
+        let mut mesh = Mesh::default();
+        let mut drawn = 0;
+        for span in self.visible_features(min, max) {
+            let base = mesh.vertices.len() as u32;
+            let start = span.vertices.start as u32;
+            mesh.vertices.extend(self.vertices[span.vertices.clone()].iter().map(|vertex| Vertex {
+                pos: view_state.gerber_to_screen_coords(Point2::new(vertex.pos.x as f64, vertex.pos.y as f64)),
+                ..*vertex
+            }));
+            mesh.indices.extend(self.indices[span.indices.clone()].iter().map(|index| index - start + base));
+            drawn += 1;
+        }
+        let triangles = mesh.indices.len() / 3;
+        if !mesh.is_empty() {
+            painter.add(Shape::mesh(mesh));
+        }
+        (drawn, triangles)
+    }
+}
+
+fn push_contour(data: &mut Vec<f64>, points: &[Point2<f64>]) {
+    // Contours may repeat their first point at the end
+    let closed = points.len() > 1 && points.first() == points.last();
+    let points = if closed { &points[..points.len() - 1] } else { points };
+    data.extend(points.iter().flat_map(|point| [point.x, point.y]));
+}
+
+/// Screen rect as (min, max) in viewer coordinates
+fn viewport_bounds(view_state: &ViewState, rect: Rect) -> ([f32; 2], [f32; 2]) {
+    let a = view_state.screen_to_gerber_coords(rect.min);
+    let b = view_state.screen_to_gerber_coords(rect.max);
+    (
+        [a.x.min(b.x) as f32, a.y.min(b.y) as f32],
+        [a.x.max(b.x) as f32, a.y.max(b.y) as f32],
+    )
+}
+
+/// System to rebuild the mesh caches whose transform, color or geometry changed
+/// `colors` holds the paint color of each drawn layer entity; returns the number of meshes rebuilt.
+pub fn update_layer_meshes_system(
+    world: &mut World,
+    colors: &[(Entity, Color32)],
+    display_manager: &DisplayManager,
+) -> usize {
+    let rebuilt: Vec<(Entity, LayerMeshCache)> = {
+        let mut query = world.query::<(&LayerInfo, Ref<LayerGeometryData>, &Transform, &ImageTransform, Option<&LayerMeshCache>)>();
+        colors.iter()
+            .filter_map(|(entity, color)| {
+                let (info, geometry, transform, image_transform, cache) = query.get(world, *entity).ok()?;
+                let view_transform = super::layer_view_transform(transform, image_transform, &info.layer_type, display_manager);
+                let key = MeshKey::new(view_transform, *color, geometry.last_changed());
+                if cache.is_some_and(|cache| cache.key == key) {
+                    return None;
+                }
+                Some((*entity, LayerMeshCache::build(&geometry.0, key)))
+            })
+            .collect()
+    };
+
+    let count = rebuilt.len();
+    for (entity, cache) in rebuilt {
+        world.entity_mut(entity).insert(cache);
+    }
+    if count > 0 {
+        // Geometry replaced after this point gets a tick other than the recorded one
+        world.increment_change_tick();
+        if let Some(mut stats) = world.get_resource_mut::<RenderStats>() {
+            stats.mesh_rebuilds += count;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+    use crate::ecs::LayerType;
+
+    // Two pads far apart, one with a round hole
+    const PADS: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,1.000000X1.000000*%\n%ADD11C,1.000000X0.400000*%\n\
+        D10*\nX0Y0D03*\nD11*\nX50000000Y0D03*\nM02*\n";
+
+    #[test]
+    fn test_mesh_cache_and_culling() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+        let entity = spawn_layer(&mut world, LayerType::Copper(1), PADS);
+
+        let colors = [(entity, Color32::RED)];
+        assert_eq!(update_layer_meshes_system(&mut world, &colors, &display_manager), 1);
+        // Nothing changed, nothing rebuilt
+        assert_eq!(update_layer_meshes_system(&mut world, &colors, &display_manager), 0);
+
+        let cache = world.get::<LayerMeshCache>(entity).unwrap();
+        assert!(cache.supported);
+        assert_eq!(cache.feature_count(), 2);
+        // The square pad is two triangles, the holed pad needs more
+        assert!(cache.triangle_count() > 2);
+
+        // Only the pad at the origin is in a box around it
+        let visible: Vec<_> = cache.visible_features([-1.0, -1.0], [1.0, 1.0]).collect();
+        assert_eq!(visible.len(), 1);
+        assert_eq!(visible[0].vertices.start, 0);
+        assert_eq!(cache.visible_features([-1.0, -1.0], [60.0, 1.0]).count(), 2);
+        assert_eq!(cache.visible_features([10.0, 10.0], [20.0, 20.0]).count(), 0);
+
+        // Color and transform changes invalidate the cache
+        assert_eq!(update_layer_meshes_system(&mut world, &[(entity, Color32::BLUE)], &display_manager), 1);
+        world.get_mut::<Transform>(entity).unwrap().position.x = 5.0;
+        assert_eq!(update_layer_meshes_system(&mut world, &[(entity, Color32::BLUE)], &display_manager), 1);
+        assert_eq!(world.resource::<RenderStats>().mesh_rebuilds, 3);
+    }
+
+    #[test]
+    fn test_replaced_geometry_rebuilds_mesh() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+        let entity = spawn_layer(&mut world, LayerType::Copper(1), PADS);
+        let colors = [(entity, Color32::RED)];
+        assert_eq!(update_layer_meshes_system(&mut world, &colors, &display_manager), 1);
+
+        // Same feature count, so only change detection can tell it was reloaded
+        let mut geometry = world.get::<LayerGeometryData>(entity).unwrap().clone();
+        geometry.0.features.swap(0, 1);
+        world.entity_mut(entity).insert(geometry);
+        assert_eq!(update_layer_meshes_system(&mut world, &colors, &display_manager), 1);
+        assert_eq!(update_layer_meshes_system(&mut world, &colors, &display_manager), 0);
+
+        // Editing the geometry in place counts as well
+        world.get_mut::<LayerGeometryData>(entity).unwrap().0.features.pop();
+        assert_eq!(update_layer_meshes_system(&mut world, &colors, &display_manager), 1);
+        assert_eq!(world.get::<LayerMeshCache>(entity).unwrap().feature_count(), 1);
+    }
+}
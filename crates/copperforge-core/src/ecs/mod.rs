@@ -6,6 +6,15 @@ pub mod systems;
 pub mod factories;
 pub mod detection;
 pub mod units;
+pub mod inspection;
+pub mod highlight;
+pub mod mesh_cache;
+pub mod revision;
+pub mod measurement;
+pub mod layer_presets;
+pub mod stackup;
+#[cfg(test)]
+pub mod test_support;
 
 pub use types::*;
 pub use components::*;
@@ -14,6 +23,13 @@ pub use systems::*;
 pub use factories::*;
 pub use detection::*;
 pub use units::*;
+pub use inspection::*;
+pub use highlight::*;
+pub use mesh_cache::*;
+pub use revision::*;
+pub use measurement::*;
+pub use layer_presets::*;
+pub use stackup::*;
 
 use bevy_ecs::prelude::*;
 // All types now local to ECS module - no more layer_operations dependency
@@ -25,13 +41,19 @@ pub fn setup_ecs_world() -> World {
     // Initialize resources
     world.insert_resource(ViewStateResource::default());
     world.insert_resource(RenderConfig::default());
+    world.insert_resource(RenderStats::default());
     world.insert_resource(ActiveLayer(LayerType::Copper(1)));
     world.insert_resource(LayerAssignments::default());
+    world.insert_resource(BoardStackup::default());
+    world.insert_resource(AttributeHighlight::default());
+    world.insert_resource(ReferenceRevision::default());
+    world.insert_resource(Measurements::default());
     world.insert_resource(UnassignedGerbers::default());
     world.insert_resource(LayerDetectorResource::default());
     world.insert_resource(CoordinateUpdateTracker::default());
     world.insert_resource(UnitsResource::default());
     world.insert_resource(ZoomResource::default());
+    world.insert_resource(crate::drc_operations::types::DrcRules::default());
     
     world
 }
@@ -202,6 +224,24 @@ pub fn update_layer_render_properties(world: &mut World, layer_type: LayerType,
     false
 }
 
+// Set the opacity a layer is drawn with (0.0 - 1.0)
+pub fn set_layer_opacity(world: &mut World, layer_type: LayerType, opacity: f32) {
+    if let Some(entity) = get_layer_by_type(world, layer_type) {
+        if let Some(mut visibility) = world.get_mut::<Visibility>(entity) {
+            visibility.opacity = opacity.clamp(0.0, 1.0);
+        }
+    }
+}
+
+// Set the color used when the layer is active or selected (None for a lighter layer color)
+pub fn set_layer_highlight_color(world: &mut World, layer_type: LayerType, color: Option<egui::Color32>) {
+    if let Some(entity) = get_layer_by_type(world, layer_type) {
+        if let Some(mut render_props) = world.get_mut::<components::RenderProperties>(entity) {
+            render_props.highlight_color = color;
+        }
+    }
+}
+
 // Get unassigned gerbers (replaces LayerManager::unassigned_gerbers access)
 pub fn get_unassigned_gerbers(world: &World) -> Vec<UnassignedGerber> {
     world.get_resource::<UnassignedGerbers>()
@@ -1,6 +1,6 @@
 use bevy_ecs::prelude::*;
 use gerber_viewer::ViewState;
-use super::{LayerType, LayerDetector, UnassignedGerber};
+use super::{LayerType, LayerDetector, StackupLayer, UnassignedGerber};
 use std::collections::HashMap;
 
 // Simple view mode enum
@@ -104,12 +104,40 @@ impl ZoomResource {
     }
 }
 
+// How layer colors combine where layers overlap
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayerBlendMode {
+    // Upper layers cover lower ones (by opacity)
+    #[default]
+    Normal,
+    // Colors add up ("X-ray"), so every layer stays visible through the others
+    Additive,
+}
+
+impl LayerBlendMode {
+    pub fn all() -> [LayerBlendMode; 2] {
+        [LayerBlendMode::Normal, LayerBlendMode::Additive]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            LayerBlendMode::Normal => "Normal",
+            LayerBlendMode::Additive => "X-ray (additive)",
+        }
+    }
+}
+
 // Global rendering configuration
 #[derive(Resource, Clone)]
 pub struct RenderConfig {
     pub show_grid: bool,
     pub grid_spacing: f32,
     pub background_color: egui::Color32,
+    pub blend_mode: LayerBlendMode,
+    // Draw the active layer in its highlight color (selected layers always are)
+    pub highlight_active_layer: bool,
+    // Draw layers from cached, viewport-culled meshes instead of repainting every primitive
+    pub cached_meshes: bool,
 }
 
 impl Default for RenderConfig {
@@ -118,14 +146,64 @@ impl Default for RenderConfig {
             show_grid: true,
             grid_spacing: 1.0,
             background_color: egui::Color32::from_gray(20),
+            blend_mode: LayerBlendMode::Normal,
+            highlight_active_layer: false,
+            cached_meshes: true,
         }
     }
 }
 
+// Timing and workload of the last rendered frame
+#[derive(Resource, Default, Clone, Debug)]
+pub struct RenderStats {
+    // Time spent drawing the layers (ms)
+    pub render_ms: f32,
+    // Time between the last two frames (ms)
+    pub frame_ms: f32,
+    pub features_drawn: usize,
+    pub features_total: usize,
+    pub triangles: usize,
+    // Layer meshes rebuilt since startup
+    pub mesh_rebuilds: usize,
+}
+
 // Active layer resource (replaces LayerManager.active_layer)
 #[derive(Resource)]
 pub struct ActiveLayer(pub LayerType);
 
+// Physical layer stack of the loaded board, empty when unknown
+#[derive(Resource, Default, Clone, Debug)]
+pub struct BoardStackup(pub Vec<StackupLayer>);
+
+// Copper layers, drill files and change tick of the last connectivity extraction
+// Geometry or drills changed after `tick` make the extracted islands and nets stale.
+#[derive(Resource, Clone, Debug)]
+pub struct ConnectivityExtraction {
+    pub tick: bevy_ecs::component::Tick,
+    pub layers: Vec<Entity>,
+    pub drills: Vec<Entity>,
+}
+
+// Net and component highlighted from Gerber X2 object attributes
+#[derive(Resource, Default, Clone, Debug, PartialEq)]
+pub struct AttributeHighlight {
+    pub net: Option<String>,
+    pub component: Option<String>,
+}
+
+// Earlier revision of the Gerber set that the loaded layers are compared against
+#[derive(Resource, Default, Clone, Debug)]
+pub struct ReferenceRevision {
+    pub source: Option<std::path::PathBuf>,
+    pub layers: HashMap<LayerType, crate::geometry::LayerGeometry>,
+    // Difference the navigator is on, as (layer, region index)
+    pub cursor: Option<(LayerType, usize)>,
+}
+
+// Measurements taken this session, in viewer coordinates
+#[derive(Resource, Default, Clone, Debug)]
+pub struct Measurements(pub Vec<super::Measurement>);
+
 // Layer assignment tracking (replaces LayerManager.layer_assignments)
 #[derive(Resource, Default)]
 pub struct LayerAssignments(pub HashMap<String, LayerType>);
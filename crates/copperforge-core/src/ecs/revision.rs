@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+use std::path::Path;
+use bevy_ecs::prelude::*;
+use nalgebra::Point2;
+
+use crate::display::DisplayManager;
+use crate::geometry::{diff_layers, parse_gerber, DiffRegion, LayerGeometry};
+use super::components::*;
+use super::resources::{ActiveLayer, ReferenceRevision};
+use super::{layer_type_to_z_order, LayerType};
+
+// Revision comparison
+// A second Gerber set is kept as the reference revision, paired with the loaded
+// layers by LayerType. Each loaded layer gets a RevisionDiff component holding
+// the pixel comparison of the two revisions.
+
+/// Longest side of a layer's comparison grid (px)
+pub const REVISION_DIFF_PIXELS: u32 = 2048;
+
+/// How a layer differs between the reference and the current revision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevisionLayerStatus {
+    Unchanged,
+    Changed,
+    /// Only in the current revision
+    Added,
+    /// Only in the reference revision
+    Removed,
+}
+
+impl RevisionLayerStatus {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            RevisionLayerStatus::Unchanged => "Unchanged",
+            RevisionLayerStatus::Changed => "Changed",
+            RevisionLayerStatus::Added => "New layer",
+            RevisionLayerStatus::Removed => "Layer removed",
+        }
+    }
+}
+
+/// Comparison result of one layer
+#[derive(Debug, Clone)]
+pub struct LayerRevisionSummary {
+    pub layer_type: LayerType,
+    pub status: RevisionLayerStatus,
+    pub added_area: f64,
+    pub removed_area: f64,
+    pub regions: Vec<DiffRegion>,
+}
+
+/// Difference the navigator moved to
+#[derive(Debug, Clone)]
+pub struct DiffFocus {
+    pub layer_type: LayerType,
+    /// Position among all differences, and their count
+    pub position: usize,
+    pub count: usize,
+    pub region: DiffRegion,
+    /// Region centre in viewer coordinates
+    pub center: Point2<f64>,
+}
+
+/// System to load a Gerber directory as the reference revision and compare it with the loaded layers
+/// Returns the number of reference layers.
+pub fn load_reference_revision_system(world: &mut World, gerber_dir: &Path) -> Result<usize, String> {
+    let entries = std::fs::read_dir(gerber_dir)
+        .map_err(|e| format!("Failed to read directory: {}", e))?;
+
+    let mut paths: Vec<_> = entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|s| s.to_str()) == Some("gbr"))
+        .collect();
+    paths.sort();
+
+    let mut layers = HashMap::new();
+    for path in paths {
+        let filename = path.file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("")
+            .to_string();
+        let Some(layer_type) = super::detect_layer_type(world, &filename) else {
+            continue;
+        };
+        if layers.contains_key(&layer_type) {
+            continue;
+        }
+        let geometry = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_gerber(&content));
+        match geometry {
+            Ok(geometry) => {
+                layers.insert(layer_type, geometry);
+            }
+            Err(e) => println!("Skipping reference gerber {}: {}", filename, e),
+        }
+    }
+
+    if layers.is_empty() {
+        return Err("No gerber layers were recognized in the reference directory".to_string());
+    }
+    let count = layers.len();
+    world.insert_resource(ReferenceRevision {
+        source: Some(gerber_dir.to_path_buf()),
+        layers,
+        cursor: None,
+    });
+    remove_revision_diffs(world);
+    compare_revisions_system(world);
+    Ok(count)
+}
+
+/// System to drop the reference revision and its comparisons
+pub fn clear_reference_revision_system(world: &mut World) {
+    world.insert_resource(ReferenceRevision::default());
+    remove_revision_diffs(world);
+}
+
+fn remove_revision_diffs(world: &mut World) {
+    let entities: Vec<Entity> = world.query_filtered::<Entity, With<RevisionDiff>>()
+        .iter(world)
+        .collect();
+    for entity in entities {
+        world.entity_mut(entity).remove::<RevisionDiff>();
+    }
+}
+
+/// System to compare the layers that have no RevisionDiff yet (e.g. after a reload)
+/// Returns the number of layers compared.
+pub fn compare_revisions_system(world: &mut World) -> usize {
+    let Some(reference) = world.get_resource::<ReferenceRevision>() else {
+        return 0;
+    };
+    if reference.source.is_none() {
+        return 0;
+    }
+
+    let empty = LayerGeometry::default();
+    let diffs: Vec<(Entity, RevisionDiff)> = {
+        let mut query = world.query_filtered::<(Entity, &LayerInfo, &LayerGeometryData), Without<RevisionDiff>>();
+        let reference = world.resource::<ReferenceRevision>();
+        query.iter(world)
+            .map(|(entity, info, geometry)| {
+                let before = reference.layers.get(&info.layer_type).unwrap_or(&empty);
+                (entity, RevisionDiff(diff_layers(before, &geometry.0, REVISION_DIFF_PIXELS)))
+            })
+            .collect()
+    };
+
+    let count = diffs.len();
+    for (entity, diff) in diffs {
+        world.entity_mut(entity).insert(diff);
+    }
+    count
+}
+
+/// System to summarize the comparison per layer, in stackup order
+pub fn revision_summary_system(world: &mut World) -> Vec<LayerRevisionSummary> {
+    let reference_layers: Vec<LayerType> = match world.get_resource::<ReferenceRevision>() {
+        Some(reference) if reference.source.is_some() => reference.layers.keys().copied().collect(),
+        _ => return Vec::new(),
+    };
+
+    let mut summaries: Vec<LayerRevisionSummary> = world.query::<(&LayerInfo, &RevisionDiff)>()
+        .iter(world)
+        .map(|(info, diff)| {
+            let regions = diff.0.as_ref().map(|diff| diff.regions.clone()).unwrap_or_default();
+            let status = if !reference_layers.contains(&info.layer_type) {
+                RevisionLayerStatus::Added
+            } else if regions.is_empty() {
+                RevisionLayerStatus::Unchanged
+            } else {
+                RevisionLayerStatus::Changed
+            };
+            LayerRevisionSummary {
+                layer_type: info.layer_type,
+                status,
+                added_area: regions.iter().map(|region| region.added_area).sum(),
+                removed_area: regions.iter().map(|region| region.removed_area).sum(),
+                regions,
+            }
+        })
+        .collect();
+
+    // Reference layers with nothing loaded to compare against
+    for layer_type in reference_layers {
+        if summaries.iter().any(|summary| summary.layer_type == layer_type) {
+            continue;
+        }
+        summaries.push(LayerRevisionSummary {
+            layer_type,
+            status: RevisionLayerStatus::Removed,
+            added_area: 0.0,
+            removed_area: 0.0,
+            regions: Vec::new(),
+        });
+    }
+
+    summaries.sort_by_key(|summary| layer_type_to_z_order(&summary.layer_type));
+    summaries
+}
+
+/// System to move the navigator to the next (or previous) difference, wrapping around
+/// The layer of the difference becomes the active layer.
+pub fn step_difference_system(world: &mut World, forward: bool, display_manager: &DisplayManager) -> Option<DiffFocus> {
+    let differences: Vec<(LayerType, usize, DiffRegion)> = revision_summary_system(world)
+        .into_iter()
+        .flat_map(|summary| {
+            let layer_type = summary.layer_type;
+            summary.regions.into_iter().enumerate().map(move |(index, region)| (layer_type, index, region))
+        })
+        .collect();
+    if differences.is_empty() {
+        return None;
+    }
+
+    let cursor = world.resource::<ReferenceRevision>().cursor;
+    let current = cursor.and_then(|(layer, index)| {
+        differences.iter().position(|(layer_type, i, _)| *layer_type == layer && *i == index)
+    });
+    let position = match (current, forward) {
+        (None, true) => 0,
+        (None, false) => differences.len() - 1,
+        (Some(position), true) => (position + 1) % differences.len(),
+        (Some(position), false) => (position + differences.len() - 1) % differences.len(),
+    };
+    let (layer_type, index, region) = differences[position].clone();
+
+    world.resource_mut::<ReferenceRevision>().cursor = Some((layer_type, index));
+    world.insert_resource(ActiveLayer(layer_type));
+
+    // Centre in viewer coordinates, through the layer's transform
+    let mut query = world.query::<(&LayerInfo, &Transform, &ImageTransform)>();
+    let center = query.iter(world)
+        .find(|(info, ..)| info.layer_type == layer_type)
+        .map(|(info, transform, image_transform)| {
+            super::layer_view_transform(transform, image_transform, &info.layer_type, display_manager).apply(region.center())
+        })
+        .unwrap_or_else(|| region.center());
+
+    Some(DiffFocus {
+        layer_type,
+        position,
+        count: differences.len(),
+        region,
+        center,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+
+    fn pads(flashes: &[i64]) -> String {
+        let mut gerber = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,1.000000X1.000000*%\nD10*\n".to_string();
+        for x in flashes {
+            gerber.push_str(&format!("X{}Y0D03*\n", x * 1_000_000));
+        }
+        gerber.push_str("M02*\n");
+        gerber
+    }
+
+    #[test]
+    fn test_compare_and_navigate() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+
+        spawn_layer(&mut world, LayerType::Copper(1), &pads(&[0, 10]));
+
+        let dir = std::env::temp_dir().join(format!("copperforge_revision_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("board-F_Cu.gbr"), pads(&[0, 5])).unwrap();
+        std::fs::write(dir.join("board-F_Mask.gbr"), pads(&[0])).unwrap();
+        let loaded = load_reference_revision_system(&mut world, &dir);
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(loaded, Ok(2));
+
+        let summary = revision_summary_system(&mut world);
+        assert_eq!(summary.len(), 2);
+        let copper = summary.iter().find(|s| s.layer_type == LayerType::Copper(1)).unwrap();
+        assert_eq!(copper.status, RevisionLayerStatus::Changed);
+        assert_eq!(copper.regions.len(), 2);
+        assert!(summary.iter().any(|s| s.status == RevisionLayerStatus::Removed));
+
+        // Already compared layers are not compared again
+        assert_eq!(compare_revisions_system(&mut world), 0);
+
+        let first = step_difference_system(&mut world, true, &display_manager).unwrap();
+        let second = step_difference_system(&mut world, true, &display_manager).unwrap();
+        assert_eq!((first.position, second.position, first.count), (0, 1, 2));
+        let back = step_difference_system(&mut world, false, &display_manager).unwrap();
+        assert_eq!(back.position, 0);
+        assert_eq!(world.resource::<ActiveLayer>().0, LayerType::Copper(1));
+
+        clear_reference_revision_system(&mut world);
+        assert!(revision_summary_system(&mut world).is_empty());
+        assert!(step_difference_system(&mut world, true, &display_manager).is_none());
+    }
+}
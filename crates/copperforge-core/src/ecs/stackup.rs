@@ -0,0 +1,219 @@
+use bevy_ecs::prelude::*;
+use kicad_ecs::client::{StackupLayerData, StackupLayerType};
+use serde_json::Value;
+
+use super::resources::{ActiveLayer, BoardStackup};
+use super::{LayerType, Side, StackupLayer, StackupLayerKind};
+
+// Board stackup sources
+// Fills the BoardStackup resource from a Gerber job file (.gbrjob) or from the
+// stackup KiCad reports over IPC. Copper is numbered in stack order, and mask,
+// legend and paste layers above the first copper layer are on the top side.
+
+/// Set the viewer layer of each stackup layer from its kind and position
+fn assign_layer_types(layers: &mut [StackupLayer]) {
+    let mut copper = 0u8;
+    for layer in layers.iter_mut() {
+        let side = if copper == 0 { Side::Top } else { Side::Bottom };
+        layer.layer_type = match layer.kind {
+            StackupLayerKind::Copper => {
+                copper += 1;
+                Some(LayerType::Copper(copper))
+            }
+            StackupLayerKind::Dielectric => None,
+            StackupLayerKind::Soldermask => Some(LayerType::Soldermask(side)),
+            StackupLayerKind::Silkscreen => Some(LayerType::Silkscreen(side)),
+            StackupLayerKind::Paste => Some(LayerType::Paste(side)),
+        };
+    }
+}
+
+/// Read the MaterialStackup of a Gerber job file
+pub fn parse_gbrjob_stackup(text: &str) -> Result<Vec<StackupLayer>, String> {
+    let job: Value = serde_json::from_str(text).map_err(|e| format!("Invalid job file: {}", e))?;
+    let entries = job.get("MaterialStackup")
+        .and_then(Value::as_array)
+        .ok_or("Job file has no MaterialStackup")?;
+
+    let mut layers: Vec<StackupLayer> = entries.iter()
+        .filter_map(|entry| {
+            let kind = match entry.get("Type")?.as_str()? {
+                "Copper" => StackupLayerKind::Copper,
+                "Dielectric" => StackupLayerKind::Dielectric,
+                "SolderMask" => StackupLayerKind::Soldermask,
+                "Legend" => StackupLayerKind::Silkscreen,
+                "SolderPaste" => StackupLayerKind::Paste,
+                _ => return None,
+            };
+            Some(StackupLayer {
+                name: entry.get("Name").and_then(Value::as_str).unwrap_or("").to_string(),
+                kind,
+                layer_type: None,
+                thickness: entry.get("Thickness").and_then(Value::as_f64).unwrap_or(0.0),
+                material: entry.get("Material").and_then(Value::as_str).map(str::to_string),
+                dielectric_constant: entry.get("DielectricConstant").and_then(Value::as_f64),
+            })
+        })
+        .collect();
+    if layers.is_empty() {
+        return Err("Job file stackup has no layers".to_string());
+    }
+    assign_layer_types(&mut layers);
+    Ok(layers)
+}
+
+/// Convert the stackup reported by KiCad
+pub fn stackup_from_kicad(layers: &[StackupLayerData]) -> Vec<StackupLayer> {
+    let mut stackup: Vec<StackupLayer> = layers.iter()
+        .filter_map(|layer| {
+            let kind = match layer.layer_type {
+                StackupLayerType::Copper => StackupLayerKind::Copper,
+                StackupLayerType::Dielectric => StackupLayerKind::Dielectric,
+                StackupLayerType::Soldermask => StackupLayerKind::Soldermask,
+                StackupLayerType::Silkscreen => StackupLayerKind::Silkscreen,
+                StackupLayerType::Solderpaste => StackupLayerKind::Paste,
+                StackupLayerType::Unknown => return None,
+            };
+            Some(StackupLayer {
+                name: layer.name.clone(),
+                kind,
+                layer_type: None,
+                thickness: layer.thickness,
+                material: layer.material.clone(),
+                dielectric_constant: layer.epsilon_r,
+            })
+        })
+        .collect();
+    assign_layer_types(&mut stackup);
+    stackup
+}
+
+/// Board thickness as the sum of the stackup layers (mm)
+pub fn stackup_thickness(layers: &[StackupLayer]) -> f64 {
+    layers.iter().map(|layer| layer.thickness).sum()
+}
+
+/// Fabrication notes describing a stackup, empty when nothing is known
+pub fn stackup_notes(layers: &[StackupLayer]) -> Vec<String> {
+    let mut notes = Vec::new();
+    let thickness = stackup_thickness(layers);
+    if thickness > 0.0 {
+        notes.push(format!("Finished thickness: {:.2} mm", thickness));
+    }
+
+    let mut materials: Vec<&str> = Vec::new();
+    let dielectrics = layers.iter().filter(|layer| layer.kind == StackupLayerKind::Dielectric);
+    for material in dielectrics.filter_map(|layer| layer.material.as_deref()) {
+        if !materials.contains(&material) {
+            materials.push(material);
+        }
+    }
+    if !materials.is_empty() {
+        notes.push(format!("Material: {}", materials.join(", ")));
+    }
+
+    // Outer copper is the first and last copper layer of the stack
+    let copper: Vec<f64> = layers.iter()
+        .filter(|layer| layer.kind == StackupLayerKind::Copper)
+        .map(|layer| layer.thickness)
+        .collect();
+    if let Some(&outer) = copper.first().filter(|&&t| t > 0.0) {
+        let mut note = format!("Copper thickness: {:.3} mm outer layers", outer);
+        if let Some(&inner) = copper.get(1).filter(|&&t| t > 0.0 && copper.len() > 2) {
+            note.push_str(&format!(", {:.3} mm inner layers", inner));
+        }
+        notes.push(note);
+    }
+
+    let sides = |kind: StackupLayerKind| -> Option<&'static str> {
+        let (top, bottom) = layers.iter()
+            .filter(|layer| layer.kind == kind)
+            .fold((false, false), |(top, bottom), layer| match layer.layer_type {
+                Some(LayerType::Soldermask(Side::Bottom) | LayerType::Silkscreen(Side::Bottom)) => (top, true),
+                _ => (true, bottom),
+            });
+        match (top, bottom) {
+            (true, true) => Some("both sides"),
+            (true, false) => Some("top"),
+            (false, true) => Some("bottom"),
+            (false, false) => None,
+        }
+    };
+    if let Some(sides) = sides(StackupLayerKind::Soldermask) {
+        notes.push(format!("Solder mask: {}", sides));
+    }
+    if let Some(sides) = sides(StackupLayerKind::Silkscreen) {
+        notes.push(format!("Silkscreen: {}", sides));
+    }
+    notes
+}
+
+/// System to replace the board stackup, returning the number of copper layers
+pub fn load_stackup_system(world: &mut World, layers: Vec<StackupLayer>) -> usize {
+    let copper = layers.iter().filter(|layer| layer.kind == StackupLayerKind::Copper).count();
+    world.insert_resource(BoardStackup(layers));
+    copper
+}
+
+/// System to make the copper layer at a stackup index the active layer
+/// Returns the new active layer, None for non-copper layers.
+pub fn select_stackup_layer_system(world: &mut World, index: usize) -> Option<LayerType> {
+    let layer_type = world.get_resource::<BoardStackup>()?
+        .0.get(index)
+        .filter(|layer| layer.kind == StackupLayerKind::Copper)?
+        .layer_type?;
+    world.insert_resource(ActiveLayer(layer_type));
+    Some(layer_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JOB: &str = r#"{
+        "Header": { "GenerationSoftware": { "Vendor": "KiCad", "Application": "Pcbnew" } },
+        "MaterialStackup": [
+            { "Type": "Legend", "Name": "Top Silk Screen" },
+            { "Type": "SolderPaste", "Name": "Top Solder Paste" },
+            { "Type": "SolderMask", "Thickness": 0.01, "Name": "Top Solder Mask" },
+            { "Type": "Copper", "Thickness": 0.035, "Name": "F.Cu" },
+            { "Type": "Dielectric", "Thickness": 0.2, "Material": "FR4", "Name": "F.Cu/In1.Cu", "DielectricConstant": 4.5 },
+            { "Type": "Copper", "Thickness": 0.035, "Name": "In1.Cu" },
+            { "Type": "Dielectric", "Thickness": 1.2, "Material": "FR4", "Name": "In1.Cu/B.Cu", "DielectricConstant": 4.6 },
+            { "Type": "Copper", "Thickness": 0.035, "Name": "B.Cu" },
+            { "Type": "SolderMask", "Thickness": 0.01, "Name": "Bottom Solder Mask" },
+            { "Type": "Legend", "Name": "Bottom Silk Screen" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_gbrjob_stackup_and_selection() {
+        let layers = parse_gbrjob_stackup(JOB).unwrap();
+        assert_eq!(layers.len(), 10);
+        assert_eq!(layers[0].layer_type, Some(LayerType::Silkscreen(Side::Top)));
+        assert_eq!(layers[5].layer_type, Some(LayerType::Copper(2)));
+        assert_eq!(layers[6].material.as_deref(), Some("FR4"));
+        assert_eq!(layers[6].dielectric_constant, Some(4.6));
+        assert_eq!(layers[8].layer_type, Some(LayerType::Soldermask(Side::Bottom)));
+        assert!((stackup_thickness(&layers) - 1.525).abs() < 1e-9);
+        assert!(parse_gbrjob_stackup("{}").is_err());
+
+        assert_eq!(stackup_notes(&layers), vec![
+            format!("Finished thickness: {:.2} mm", stackup_thickness(&layers)),
+            "Material: FR4".to_string(),
+            "Copper thickness: 0.035 mm outer layers, 0.035 mm inner layers".to_string(),
+            "Solder mask: both sides".to_string(),
+            "Silkscreen: both sides".to_string(),
+        ]);
+        assert!(stackup_notes(&[]).is_empty());
+
+        let mut world = crate::ecs::setup_ecs_world();
+        assert_eq!(load_stackup_system(&mut world, layers), 3);
+        assert_eq!(select_stackup_layer_system(&mut world, 7), Some(LayerType::Copper(3)));
+        assert_eq!(world.resource::<ActiveLayer>().0, LayerType::Copper(3));
+        // Dielectrics and masks leave the active layer alone
+        assert_eq!(select_stackup_layer_system(&mut world, 4), None);
+        assert_eq!(select_stackup_layer_system(&mut world, 2), None);
+        assert_eq!(world.resource::<ActiveLayer>().0, LayerType::Copper(3));
+    }
+}
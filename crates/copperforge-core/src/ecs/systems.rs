@@ -2,50 +2,25 @@ use bevy_ecs::prelude::*;
 use crate::ecs::components::*;
 use crate::ecs::resources::*;
 use gerber_viewer::{GerberRenderer, RenderConfiguration, GerberTransform, ViewState};
-use egui::Painter;
+use egui::{Color32, Painter};
 use crate::display::DisplayManager;
 use super::{LayerType, UnassignedGerber};
+use super::mesh_cache::{LayerMeshCache, update_layer_meshes_system};
 
 /// ECS-based rendering system for gerber layers
-/// This system queries all layer entities and renders them using gerber-viewer
+/// Draws every visible layer in place, ignoring quadrant view, through the same
+/// mesh cache and viewport culling as the enhanced system.
 pub fn render_layers_system(
     world: &mut World,
     painter: &Painter,
     view_state: ViewState,
     display_manager: &DisplayManager,
 ) {
-    let config = RenderConfiguration::default();
-    let renderer = GerberRenderer::default();
-    
-    // Query all layer entities including ImageTransform
-    let mut layer_query = world.query::<(&GerberData, &Transform, &ImageTransform, &Visibility, &RenderProperties, &LayerInfo)>();
-    let mut layers: Vec<_> = layer_query.iter(world).collect();
-    
-    // Sort layers by z-order for proper rendering depth
-    layers.sort_by_key(|(_, _, _, _, props, _)| props.z_order);
-    
-    // Render each visible layer
-    for (gerber_data, transform, image_transform, visibility, render_props, _layer_info) in layers {
-        if !visibility.visible {
-            continue;
-        }
-        
-        // Note: We rely solely on visibility.visible to determine if a layer should be shown
-        // This allows manual layer control overrides regardless of top/bottom view
-        
-        // Create GerberTransform from ECS Transform and ImageTransform
-        let gerber_transform = create_gerber_transform_composed(transform, image_transform, display_manager);
-        
-        // Render the layer
-        renderer.paint_layer(
-            painter,
-            view_state,
-            &gerber_data.0,
-            render_props.color,
-            &config,
-            &gerber_transform,
-        );
-    }
+    let in_place = DisplayManager {
+        quadrant_view_enabled: false,
+        ..display_manager.clone()
+    };
+    render_layers_system_enhanced(world, painter, view_state, &in_place);
 }
 
 /// Enhanced ECS-based rendering system with quadrant support
@@ -58,6 +33,7 @@ pub fn render_layers_system_enhanced(
 ) {
     let config = RenderConfiguration::default();
     let renderer = GerberRenderer::default();
+    let (render_config, active_layer) = layer_style_settings(world);
     
     // Get mechanical outline for quadrant view (do this first to avoid borrow issues)
     let mechanical_outline = if display_manager.quadrant_view_enabled {
@@ -66,15 +42,30 @@ pub fn render_layers_system_enhanced(
         None
     };
     
+    // Bring the mesh caches of the drawn layers up to date before borrowing them
+    if render_config.cached_meshes {
+        let colors: Vec<(Entity, Color32)> = world
+            .query::<(Entity, &Visibility, &RenderProperties, &LayerInfo, Has<Selected>)>()
+            .iter(world)
+            .filter(|(_, visibility, _, info, _)| super::inspection::is_drawn(&info.layer_type, visibility, display_manager))
+            .map(|(entity, visibility, render_props, info, selected)| {
+                let highlighted = selected || (render_config.highlight_active_layer && active_layer == Some(info.layer_type));
+                (entity, layer_paint_color(render_props, visibility, highlighted, render_config.blend_mode))
+            })
+            .collect();
+        update_layer_meshes_system(world, &colors, display_manager);
+    }
+    let mut stats = RenderStats::default();
+    
     // Query all layer entities including ImageTransform
-    let mut layer_query = world.query::<(&GerberData, &Transform, &ImageTransform, &Visibility, &RenderProperties, &LayerInfo)>();
+    let mut layer_query = world.query::<(&GerberData, &Transform, &ImageTransform, &Visibility, &RenderProperties, &LayerInfo, Has<Selected>, Option<&LayerMeshCache>, Option<&LayerGeometryData>)>();
     let mut layers: Vec<_> = layer_query.iter(world).collect();
     
     // Sort layers by z-order for proper rendering depth
-    layers.sort_by_key(|(_, _, _, _, props, _)| props.z_order);
+    layers.sort_by_key(|(_, _, _, _, props, ..)| props.z_order);
     
     // Render each visible layer
-    for (gerber_data, transform, image_transform, visibility, render_props, layer_info) in layers {
+    for (gerber_data, transform, image_transform, visibility, render_props, layer_info, selected, mesh_cache, geometry) in layers {
         if !visibility.visible {
             continue;
         }
@@ -102,15 +93,29 @@ pub fn render_layers_system_enhanced(
         // Create GerberTransform with quadrant offset and image transform
         let gerber_transform = create_gerber_transform_with_offset_composed(transform, image_transform, display_manager, quadrant_offset.clone());
         
-        // Render main layer
-        renderer.paint_layer(
-            painter,
-            view_state,
-            &gerber_data.0,
-            render_props.color,
-            &config,
-            &gerber_transform,
-        );
+        let highlighted = selected || (render_config.highlight_active_layer && active_layer == Some(layer_info.layer_type));
+        let color = layer_paint_color(render_props, visibility, highlighted, render_config.blend_mode);
+        
+        // Render main layer, from its cached mesh when it has an up to date one
+        let cached = mesh_cache.filter(|cache| render_config.cached_meshes && cache.supported && cache.key.color == color);
+        if let Some(cache) = cached {
+            let (features, triangles) = cache.paint(painter, &view_state);
+            stats.features_drawn += features;
+            stats.features_total += cache.feature_count();
+            stats.triangles += triangles;
+        } else {
+            renderer.paint_layer(
+                painter,
+                view_state,
+                &gerber_data.0,
+                color,
+                &config,
+                &gerber_transform,
+            );
+            let features = geometry.map_or(0, |geometry| geometry.0.features.len());
+            stats.features_drawn += features;
+            stats.features_total += features;
+        }
         
         // Render mechanical outline in quadrant view
         if display_manager.quadrant_view_enabled {
@@ -127,13 +132,52 @@ pub fn render_layers_system_enhanced(
                     painter,
                     view_state,
                     mechanical_gerber,
-                    *mechanical_color,
+                    blend_color(*mechanical_color, render_config.blend_mode),
                     &config,
                     &mechanical_transform,
                 );
             }
         }
     }
+    
+    if let Some(mut render_stats) = world.get_resource_mut::<RenderStats>() {
+        render_stats.features_drawn = stats.features_drawn;
+        render_stats.features_total = stats.features_total;
+        render_stats.triangles = stats.triangles;
+    }
+}
+
+/// Blend mode and active layer used to style the layers
+fn layer_style_settings(world: &World) -> (RenderConfig, Option<LayerType>) {
+    let render_config = world.get_resource::<RenderConfig>().cloned().unwrap_or_default();
+    let active_layer = world.get_resource::<ActiveLayer>().map(|active| active.0);
+    (render_config, active_layer)
+}
+
+/// Color a layer is painted with: its highlight color when active or selected,
+/// scaled by the layer opacity and made additive in X-ray mode
+pub fn layer_paint_color(
+    render_props: &RenderProperties,
+    visibility: &Visibility,
+    highlighted: bool,
+    blend_mode: LayerBlendMode,
+) -> Color32 {
+    let base = if highlighted {
+        // Without an explicit highlight color, a lighter shade of the layer color
+        render_props.highlight_color
+            .unwrap_or_else(|| render_props.color.lerp_to_gamma(Color32::WHITE, 0.4))
+    } else {
+        render_props.color
+    };
+    blend_color(base.gamma_multiply(visibility.opacity.clamp(0.0, 1.0)), blend_mode)
+}
+
+fn blend_color(color: Color32, blend_mode: LayerBlendMode) -> Color32 {
+    match blend_mode {
+        LayerBlendMode::Normal => color,
+        // Zero alpha in premultiplied color makes egui add it to what is below
+        LayerBlendMode::Additive => color.additive(),
+    }
 }
 
 /// Helper function to create GerberTransform from ECS Transform
@@ -169,7 +213,7 @@ fn create_gerber_transform_with_offset(
 }
 
 /// Helper function to create composed GerberTransform from ECS Transform and ImageTransform
-fn create_gerber_transform_composed(
+pub fn create_gerber_transform_composed(
     transform: &Transform, 
     image_transform: &ImageTransform, 
     _display_manager: &DisplayManager
@@ -198,7 +242,7 @@ fn create_gerber_transform_composed(
 }
 
 /// Helper function to create composed GerberTransform with quadrant offset
-fn create_gerber_transform_with_offset_composed(
+pub fn create_gerber_transform_with_offset_composed(
     transform: &Transform,
     image_transform: &ImageTransform,
     _display_manager: &DisplayManager,
@@ -541,6 +585,39 @@ pub fn clear_all_layers_system(world: &mut World) {
         world.despawn(entity);
     }
     
+    // Remove all drill file entities
+    let drills_to_remove: Vec<Entity> = {
+        let mut query = world.query_filtered::<Entity, With<DrillFileInfo>>();
+        query.iter(world).collect()
+    };
+    
+    for entity in drills_to_remove {
+        world.despawn(entity);
+    }
+    
+    // Components and stackup of an imported board
+    let components_to_remove: Vec<Entity> = {
+        let mut query = world.query_filtered::<Entity, With<BoardComponent>>();
+        query.iter(world).collect()
+    };
+    
+    for entity in components_to_remove {
+        world.despawn(entity);
+    }
+    
+    if let Some(mut stackup) = world.get_resource_mut::<BoardStackup>() {
+        stackup.0.clear();
+    }
+    
+    if let Some(mut highlight) = world.get_resource_mut::<AttributeHighlight>() {
+        *highlight = AttributeHighlight::default();
+    }
+    
+    // DRC and connectivity results refer to the removed layer entities
+    crate::drc_operations::clear_drc_results_system(world);
+    crate::drc_operations::clear_connectivity_system(world);
+    crate::netlist::clear_netlist_system(world);
+    
     // Clear unassigned gerbers
     if let Some(mut unassigned_res) = world.get_resource_mut::<UnassignedGerbers>() {
         unassigned_res.0.clear();
@@ -580,6 +657,27 @@ pub fn load_gerbers_from_directory_system(
     
     for entry in entries.flatten() {
         let path = entry.path();
+        
+        // Drill files become their own entities (used for connectivity)
+        if path.extension().and_then(|s| s.to_str()) == Some("drl") {
+            let filename = path.file_name()
+                .and_then(|n| n.to_str())
+                .unwrap_or("")
+                .to_string();
+            
+            if let Ok(drill_content) = std::fs::read_to_string(&path) {
+                match crate::geometry::parse_excellon(&drill_content) {
+                    Ok(drill_data) => {
+                        crate::ecs::create_drill_entity(world, filename, drill_data, Some(path.clone()));
+                    }
+                    Err(e) => {
+                        println!("Skipping drill file {}: {}", filename, e);
+                    }
+                }
+            }
+            continue;
+        }
+        
         if path.extension().and_then(|s| s.to_str()) == Some("gbr") {
             let filename = path.file_name()
                 .and_then(|n| n.to_str())
@@ -659,4 +757,29 @@ pub fn load_gerbers_from_directory_system(
     }
     
     Ok((loaded_count, unassigned_count))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_paint_color() {
+        let props = RenderProperties {
+            color: Color32::from_rgb(200, 100, 0),
+            highlight_color: None,
+            z_order: 0,
+        };
+        let half = Visibility { visible: true, opacity: 0.5 };
+        let full = Visibility { visible: true, opacity: 1.0 };
+
+        assert_eq!(layer_paint_color(&props, &full, false, LayerBlendMode::Normal), props.color);
+        assert_eq!(layer_paint_color(&props, &half, false, LayerBlendMode::Normal).a(), 128);
+        assert_eq!(layer_paint_color(&props, &full, false, LayerBlendMode::Additive).a(), 0);
+
+        let lighter = layer_paint_color(&props, &full, true, LayerBlendMode::Normal);
+        assert!(lighter.b() > props.color.b());
+        let custom = RenderProperties { highlight_color: Some(Color32::YELLOW), ..props };
+        assert_eq!(layer_paint_color(&custom, &full, true, LayerBlendMode::Normal), Color32::YELLOW);
+    }
+}
@@ -0,0 +1,17 @@
+use std::io::BufReader;
+
+use bevy_ecs::prelude::*;
+use gerber_viewer::gerber_parser::parse;
+use gerber_viewer::GerberLayer;
+
+use super::LayerType;
+
+// Test fixtures
+// Layer entities built from inline Gerber source, shared by the system tests.
+
+/// Spawn a visible layer entity with its raw content and flattened geometry
+pub fn spawn_layer(world: &mut World, layer_type: LayerType, src: &str) -> Entity {
+    let doc = parse(BufReader::new(src.as_bytes())).unwrap();
+    let gerber_layer = GerberLayer::new(doc.into_commands());
+    super::create_gerber_layer_entity(world, layer_type, gerber_layer, Some(src.to_string()), None, true)
+}
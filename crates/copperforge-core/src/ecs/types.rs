@@ -186,6 +186,29 @@ impl LayerType {
     }
 }
 
+/// Material class of a layer in the board stackup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StackupLayerKind {
+    Copper,
+    Dielectric,
+    Soldermask,
+    Silkscreen,
+    Paste,
+}
+
+/// One layer of the physical board stackup, top to bottom
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackupLayer {
+    pub name: String,
+    pub kind: StackupLayerKind,
+    /// Viewer layer drawn for this stackup layer (None for dielectrics)
+    pub layer_type: Option<LayerType>,
+    /// Thickness in mm, 0.0 when unknown
+    pub thickness: f64,
+    pub material: Option<String>,
+    pub dielectric_constant: Option<f64>,
+}
+
 /// Helper function to convert HSV to RGB
 fn hsv_to_rgb(h: f32, s: f32, v: f32) -> (f32, f32, f32) {
     let c = v * s;
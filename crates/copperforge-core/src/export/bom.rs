@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use rust_xlsxwriter::{Format, Workbook};
+
+use crate::project_manager::bom::BomComponent;
+use super::placement::{csv_field, natural_cmp};
+
+// Grouped BOM export
+// Components with the same value, footprint and DNP state become one line with
+// a quantity and reference list. Components excluded from the BOM are dropped.
+
+/// Output format for BOM files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BomExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+    Json,
+}
+
+impl BomExportFormat {
+    pub fn all() -> [BomExportFormat; 3] {
+        [BomExportFormat::Csv, BomExportFormat::Xlsx, BomExportFormat::Json]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            BomExportFormat::Csv => "CSV",
+            BomExportFormat::Xlsx => "XLSX",
+            BomExportFormat::Json => "JSON",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            BomExportFormat::Csv => "csv",
+            BomExportFormat::Xlsx => "xlsx",
+            BomExportFormat::Json => "json",
+        }
+    }
+
+    /// Format for a file extension, if known
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::all().into_iter().find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+}
+
+/// A column of the exported BOM
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BomColumn {
+    Item,
+    Quantity,
+    References,
+    Value,
+    Footprint,
+    Description,
+    Dnp,
+    /// Custom field looked up by name on the components
+    Field(String),
+}
+
+impl BomColumn {
+    /// Built-in columns, in their default order
+    pub fn standard() -> Vec<BomColumn> {
+        vec![
+            BomColumn::Item,
+            BomColumn::Quantity,
+            BomColumn::References,
+            BomColumn::Value,
+            BomColumn::Footprint,
+            BomColumn::Description,
+            BomColumn::Dnp,
+        ]
+    }
+
+    pub fn header(&self) -> &str {
+        match self {
+            BomColumn::Item => "Item",
+            BomColumn::Quantity => "Qty",
+            BomColumn::References => "References",
+            BomColumn::Value => "Value",
+            BomColumn::Footprint => "Footprint",
+            BomColumn::Description => "Description",
+            BomColumn::Dnp => "DNP",
+            BomColumn::Field(name) => name,
+        }
+    }
+}
+
+/// Settings for a BOM export
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomExportOptions {
+    pub format: BomExportFormat,
+    pub columns: Vec<BomColumn>,
+    /// List DNP parts as their own lines instead of leaving them out
+    pub include_dnp: bool,
+}
+
+impl Default for BomExportOptions {
+    fn default() -> Self {
+        Self {
+            format: BomExportFormat::default(),
+            columns: BomColumn::standard(),
+            include_dnp: false,
+        }
+    }
+}
+
+/// One grouped BOM line
+#[derive(Debug, Clone, PartialEq)]
+pub struct BomLine {
+    pub item: usize,
+    pub references: Vec<String>,
+    pub value: String,
+    pub footprint: String,
+    pub description: String,
+    pub dnp: bool,
+    /// First non-empty value of each custom field in the group
+    /// Fields the components were grouped by have the same value on every part.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl BomLine {
+    pub fn quantity(&self) -> usize {
+        self.references.len()
+    }
+
+    /// Cell text for a column
+    pub fn cell(&self, column: &BomColumn) -> String {
+        match column {
+            BomColumn::Item => self.item.to_string(),
+            BomColumn::Quantity => self.quantity().to_string(),
+            BomColumn::References => self.references.join(", "),
+            BomColumn::Value => self.value.clone(),
+            BomColumn::Footprint => self.footprint.clone(),
+            BomColumn::Description => self.description.clone(),
+            BomColumn::Dnp => if self.dnp { "DNP".to_string() } else { String::new() },
+            BomColumn::Field(name) => self.fields.get(name).cloned().unwrap_or_default(),
+        }
+    }
+}
+
+/// Group components into BOM lines, ordered by their first reference
+/// Parts only share a line when they also agree on every field in `group_fields`.
+pub fn group_components(components: &[BomComponent], include_dnp: bool, group_fields: &[String]) -> Vec<BomLine> {
+    let mut groups: Vec<BomLine> = Vec::new();
+    let mut index: BTreeMap<(String, String, bool, Vec<String>), usize> = BTreeMap::new();
+
+    for component in components {
+        if component.exclude_from_bom || (component.do_not_populate && !include_dnp) {
+            continue;
+        }
+        let field_values = group_fields.iter()
+            .map(|name| component.fields.get(name).cloned().unwrap_or_default())
+            .collect();
+        let key = (component.value.clone(), component.footprint.clone(), component.do_not_populate, field_values);
+        let line = match index.get(&key) {
+            Some(&i) => &mut groups[i],
+            None => {
+                index.insert(key, groups.len());
+                groups.push(BomLine {
+                    item: 0,
+                    references: Vec::new(),
+                    value: component.value.clone(),
+                    footprint: component.footprint.clone(),
+                    description: component.description.clone(),
+                    dnp: component.do_not_populate,
+                    fields: BTreeMap::new(),
+                });
+                groups.last_mut().unwrap()
+            }
+        };
+        line.references.push(component.reference.clone());
+        if line.description.is_empty() {
+            line.description = component.description.clone();
+        }
+        for (name, value) in &component.fields {
+            if !value.is_empty() {
+                line.fields.entry(name.clone()).or_insert_with(|| value.clone());
+            }
+        }
+    }
+
+    for line in &mut groups {
+        line.references.sort_by(|a, b| natural_cmp(a, b));
+    }
+    // Fitted parts first, then by reference
+    groups.sort_by(|a, b| a.dnp.cmp(&b.dnp).then_with(|| natural_cmp(&a.references[0], &b.references[0])));
+    for (i, line) in groups.iter_mut().enumerate() {
+        line.item = i + 1;
+    }
+    groups
+}
+
+/// Custom field names among the columns, in column order
+pub fn field_columns(columns: &[BomColumn]) -> Vec<String> {
+    columns.iter()
+        .filter_map(|column| match column {
+            BomColumn::Field(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Names of all custom fields present on the components
+pub fn custom_field_names(components: &[BomComponent]) -> Vec<String> {
+    let mut names: Vec<String> = components.iter()
+        .flat_map(|component| component.fields.keys().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+pub fn write_bom_csv(lines: &[BomLine], columns: &[BomColumn]) -> String {
+    let mut out = String::new();
+    let header: Vec<String> = columns.iter().map(|column| csv_field(column.header())).collect();
+    let _ = writeln!(out, "{}", header.join(","));
+    for line in lines {
+        let cells: Vec<String> = columns.iter().map(|column| csv_field(&line.cell(column))).collect();
+        let _ = writeln!(out, "{}", cells.join(","));
+    }
+    out
+}
+
+pub fn write_bom_json(lines: &[BomLine], columns: &[BomColumn]) -> Result<String, String> {
+    let rows: Vec<serde_json::Value> = lines.iter()
+        .map(|line| {
+            let mut row = serde_json::Map::new();
+            for column in columns {
+                let value = match column {
+                    BomColumn::Item => serde_json::Value::from(line.item),
+                    BomColumn::Quantity => serde_json::Value::from(line.quantity()),
+                    BomColumn::References => serde_json::Value::from(line.references.clone()),
+                    BomColumn::Dnp => serde_json::Value::from(line.dnp),
+                    _ => serde_json::Value::from(line.cell(column)),
+                };
+                row.insert(column.header().to_string(), value);
+            }
+            serde_json::Value::Object(row)
+        })
+        .collect();
+    serde_json::to_string_pretty(&rows).map_err(|e| format!("Failed to write JSON: {}", e))
+}
+
+pub fn write_bom_xlsx(lines: &[BomLine], columns: &[BomColumn]) -> Result<Vec<u8>, String> {
+    let xlsx_error = |e: rust_xlsxwriter::XlsxError| format!("Failed to write XLSX: {}", e);
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("BOM").map_err(xlsx_error)?;
+
+    let bold = Format::new().set_bold();
+    for (col, column) in columns.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, column.header(), &bold).map_err(xlsx_error)?;
+    }
+    for (row, line) in lines.iter().enumerate() {
+        let row = row as u32 + 1;
+        for (col, column) in columns.iter().enumerate() {
+            let col = col as u16;
+            match column {
+                BomColumn::Item => sheet.write_number(row, col, line.item as f64),
+                BomColumn::Quantity => sheet.write_number(row, col, line.quantity() as f64),
+                _ => sheet.write_string(row, col, line.cell(column)),
+            }
+            .map_err(xlsx_error)?;
+        }
+    }
+    sheet.set_freeze_panes(1, 0).map_err(xlsx_error)?;
+    sheet.autofit();
+
+    workbook.save_to_buffer().map_err(xlsx_error)
+}
+
+/// Group and write a BOM file without the GUI, returning the number of lines
+pub fn export_bom(components: &[BomComponent], output_path: &Path, options: &BomExportOptions) -> Result<usize, String> {
+    if options.columns.is_empty() {
+        return Err("No BOM columns selected".to_string());
+    }
+    let lines = group_components(components, options.include_dnp, &field_columns(&options.columns));
+    let content = match options.format {
+        BomExportFormat::Csv => write_bom_csv(&lines, &options.columns).into_bytes(),
+        BomExportFormat::Json => write_bom_json(&lines, &options.columns)?.into_bytes(),
+        BomExportFormat::Xlsx => write_bom_xlsx(&lines, &options.columns)?,
+    };
+    std::fs::write(output_path, content).map_err(|e| format!("Failed to save BOM: {}", e))?;
+    Ok(lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(reference: &str, value: &str, footprint: &str) -> BomComponent {
+        let mut component = BomComponent::new(reference.to_string());
+        component.value = value.to_string();
+        component.footprint = footprint.to_string();
+        component
+    }
+
+    fn sample() -> Vec<BomComponent> {
+        let mut dnp = component("R3", "10k", "R_0603");
+        dnp.do_not_populate = true;
+        let mut excluded = component("H1", "MountingHole", "MountingHole_3.2mm");
+        excluded.exclude_from_bom = true;
+        let mut cap = component("C1", "100n", "C_0402");
+        cap.fields.insert("MPN".to_string(), "GRM155R71C104KA88D".to_string());
+        vec![
+            component("R10", "10k", "R_0603"),
+            cap,
+            component("R2", "10k", "R_0603"),
+            dnp,
+            excluded,
+        ]
+    }
+
+    #[test]
+    fn test_group_components() {
+        let lines = group_components(&sample(), false, &[]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].references, ["C1"]);
+        assert_eq!(lines[1].references, ["R2", "R10"]);
+        assert_eq!(lines[1].item, 2);
+
+        let with_dnp = group_components(&sample(), true, &[]);
+        assert_eq!(with_dnp.len(), 3);
+        assert!(with_dnp[2].dnp);
+        assert_eq!(with_dnp[2].references, ["R3"]);
+    }
+
+    #[test]
+    fn test_group_by_custom_fields() {
+        // Same value and footprint, but different parts
+        let mut r1 = component("R1", "10k", "R_0603");
+        r1.fields.insert("MPN".to_string(), "RC0603FR-0710KL".to_string());
+        let mut r2 = component("R2", "10k", "R_0603");
+        r2.fields.insert("MPN".to_string(), "ERJ-3EKF1002V".to_string());
+        let mut r3 = r1.clone();
+        r3.reference = "R3".to_string();
+        let components = vec![r1, r2, r3];
+
+        let lines = group_components(&components, false, &["MPN".to_string()]);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].references, ["R1", "R3"]);
+        assert_eq!(lines[0].fields["MPN"], "RC0603FR-0710KL");
+        assert_eq!(lines[1].references, ["R2"]);
+        assert_eq!(lines[1].fields["MPN"], "ERJ-3EKF1002V");
+
+        let columns = vec![BomColumn::References, BomColumn::Field("MPN".to_string())];
+        assert_eq!(field_columns(&columns), ["MPN"]);
+        assert_eq!(group_components(&components, false, &[]).len(), 1);
+    }
+
+    #[test]
+    fn test_write_csv_and_json() {
+        let lines = group_components(&sample(), false, &[]);
+        let columns = vec![
+            BomColumn::Quantity,
+            BomColumn::References,
+            BomColumn::Value,
+            BomColumn::Field("MPN".to_string()),
+        ];
+
+        let csv = write_bom_csv(&lines, &columns);
+        assert_eq!(csv, "Qty,References,Value,MPN\n1,C1,100n,GRM155R71C104KA88D\n2,\"R2, R10\",10k,\n");
+
+        let json: serde_json::Value = serde_json::from_str(&write_bom_json(&lines, &columns).unwrap()).unwrap();
+        assert_eq!(json[1]["Qty"], 2);
+        assert_eq!(json[1]["References"][1], "R10");
+        assert_eq!(json[0]["MPN"], "GRM155R71C104KA88D");
+    }
+
+    #[test]
+    fn test_write_xlsx() {
+        let lines = group_components(&sample(), false, &[]);
+        let bytes = write_bom_xlsx(&lines, &BomColumn::standard()).unwrap();
+        // XLSX files are zip archives
+        assert!(bytes.starts_with(b"PK"));
+    }
+}
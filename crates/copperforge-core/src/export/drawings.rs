@@ -0,0 +1,695 @@
+use std::path::Path;
+use bevy_ecs::prelude::*;
+use nalgebra::Point2;
+use printpdf::path::{PaintMode, WindingOrder};
+use printpdf::{
+    BuiltinFont, Color, IndirectFontRef, Line, Mm, PdfDocument, PdfLayerReference, Point,
+    Polygon as PdfPolygon, Rgb,
+};
+
+use crate::DemoLensApp;
+use crate::ecs::{stackup_notes, BoardStackup, DrillHoles, LayerGeometryData, LayerInfo, LayerType, Side};
+use crate::geometry::{shapes, DrillData, LayerGeometry, Polarity, Polygon, DEFAULT_TOLERANCE};
+use crate::project_manager::annotations::{Annotation, AnnotationKind};
+use crate::project_manager::bom::BomComponent;
+use crate::project_manager::database::ProjectMetadata;
+use super::loaded_bom_components;
+
+// Fabrication and assembly drawings (PDF)
+// One landscape page per drawing with a frame and title block: a fab drawing
+// with outline dimensions, drill table and stackup notes, then top and bottom
+// assembly drawings with silkscreen and reference designators, and optionally
+// a review sheet with the project's annotations.
+
+const MARGIN: f64 = 10.0;
+const TITLE_BLOCK_WIDTH: f64 = 120.0;
+const TITLE_BLOCK_HEIGHT: f64 = 30.0;
+const PT_PER_MM: f64 = 72.0 / 25.4;
+
+/// Page sizes, always used in landscape
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    A3,
+    Letter,
+    Tabloid,
+}
+
+impl PageSize {
+    /// Landscape (width, height) in mm
+    pub fn dimensions(&self) -> (f64, f64) {
+        match self {
+            PageSize::A4 => (297.0, 210.0),
+            PageSize::A3 => (420.0, 297.0),
+            PageSize::Letter => (279.4, 215.9),
+            PageSize::Tabloid => (431.8, 279.4),
+        }
+    }
+
+    pub fn all() -> [PageSize; 4] {
+        [PageSize::A4, PageSize::A3, PageSize::Letter, PageSize::Tabloid]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PageSize::A4 => "A4",
+            PageSize::A3 => "A3",
+            PageSize::Letter => "Letter",
+            PageSize::Tabloid => "Tabloid",
+        }
+    }
+}
+
+/// Title block fields printed on every page
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TitleBlock {
+    pub project: String,
+    pub revision: String,
+    pub date: String,
+    pub company: String,
+}
+
+impl TitleBlock {
+    pub fn from_metadata(metadata: &ProjectMetadata) -> Self {
+        Self {
+            project: metadata.name.clone(),
+            revision: metadata.version.clone(),
+            date: metadata.last_modified.format("%Y-%m-%d").to_string(),
+            company: String::new(),
+        }
+    }
+}
+
+/// What to put in the drawing PDF
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawingOptions {
+    pub page_size: PageSize,
+    pub title: TitleBlock,
+    /// Fabrication notes, one per line; the board stackup's notes are used when empty
+    pub fab_notes: Vec<String>,
+    pub fabrication: bool,
+    pub assembly_top: bool,
+    pub assembly_bottom: bool,
+    /// Sheet with the review annotations and their comments
+    pub review: bool,
+}
+
+impl Default for DrawingOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::default(),
+            title: TitleBlock::default(),
+            fab_notes: Vec::new(),
+            fabrication: true,
+            assembly_top: true,
+            assembly_bottom: true,
+            review: false,
+        }
+    }
+}
+
+/// Board data the drawings are made from
+#[derive(Debug, Clone, Default)]
+pub struct DrawingSet {
+    pub outline: Option<LayerGeometry>,
+    pub silkscreen_top: Option<LayerGeometry>,
+    pub silkscreen_bottom: Option<LayerGeometry>,
+    pub drills: Vec<DrillData>,
+    pub copper_layers: usize,
+    pub components: Vec<BomComponent>,
+    pub annotations: Vec<Annotation>,
+    /// Fabrication notes derived from the loaded board stackup
+    pub stackup_notes: Vec<String>,
+}
+
+impl DrawingSet {
+    /// Collect layer geometry and drill files from the ECS world
+    pub fn from_world(world: &mut World, components: Vec<BomComponent>) -> Self {
+        let mut set = DrawingSet { components, ..Default::default() };
+
+        let mut layers = world.query::<(&LayerInfo, &LayerGeometryData)>();
+        for (info, geometry) in layers.iter(world) {
+            match info.layer_type {
+                LayerType::MechanicalOutline => set.outline = Some(geometry.0.clone()),
+                LayerType::Silkscreen(Side::Top) => set.silkscreen_top = Some(geometry.0.clone()),
+                LayerType::Silkscreen(Side::Bottom) => set.silkscreen_bottom = Some(geometry.0.clone()),
+                LayerType::Copper(_) => set.copper_layers += 1,
+                _ => {}
+            }
+        }
+
+        let mut drills = world.query::<&DrillHoles>();
+        set.drills = drills.iter(world).map(|holes| holes.0.clone()).collect();
+        set.stackup_notes = world.get_resource::<BoardStackup>()
+            .map(|stackup| stackup_notes(&stackup.0))
+            .unwrap_or_default();
+        set
+    }
+
+    /// Board extents, from the outline or else any loaded geometry
+    pub fn board_bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        if let Some(bounds) = self.outline.as_ref().and_then(|outline| outline.bounds()) {
+            return Some(bounds);
+        }
+        let mut corners = Vec::new();
+        for geometry in [&self.silkscreen_top, &self.silkscreen_bottom].into_iter().flatten() {
+            if let Some((min, max)) = geometry.bounds() {
+                corners.extend([min, max]);
+            }
+        }
+        for drill in &self.drills {
+            if let Some((min, max)) = drill.bounds() {
+                corners.extend([min, max]);
+            }
+        }
+        shapes::points_bounds(&corners)
+    }
+
+    /// Drill table rows: (diameter mm, count, plated), plated first then by size
+    pub fn drill_table(&self) -> Vec<(f64, usize, bool)> {
+        let mut rows: Vec<(f64, usize, bool)> = Vec::new();
+        for drill in &self.drills {
+            let plated = drill.plated != Some(false);
+            for (tool, count) in drill.hole_counts() {
+                let diameter = drill.tools.get(&tool).copied().unwrap_or(0.0);
+                match rows.iter_mut().find(|(d, _, p)| (*d - diameter).abs() < 1e-4 && *p == plated) {
+                    Some(row) => row.1 += count,
+                    None => rows.push((diameter, count, plated)),
+                }
+            }
+        }
+        rows.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.total_cmp(&b.0)));
+        rows
+    }
+}
+
+/// Placement of the board on a page: page = offset + scale * board (mirrored for bottom views)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageMapping {
+    pub scale: f64,
+    pub offset: (f64, f64),
+    pub mirror: bool,
+    /// Board X the mirror is applied about
+    pub center_x: f64,
+}
+
+impl PageMapping {
+    /// Fit board bounds into an area (x, y, width, height) of the page, centred
+    pub fn fit(min: Point2<f64>, max: Point2<f64>, area: (f64, f64, f64, f64), mirror: bool) -> Self {
+        let (x, y, width, height) = area;
+        let board_width = (max.x - min.x).max(1e-6);
+        let board_height = (max.y - min.y).max(1e-6);
+        let scale = (width / board_width).min(height / board_height);
+        let offset = (
+            x + (width - board_width * scale) / 2.0 - min.x * scale,
+            y + (height - board_height * scale) / 2.0 - min.y * scale,
+        );
+        Self { scale, offset, mirror, center_x: (min.x + max.x) / 2.0 }
+    }
+
+    pub fn apply(&self, point: Point2<f64>) -> (f64, f64) {
+        let x = if self.mirror { 2.0 * self.center_x - point.x } else { point.x };
+        (self.offset.0 + x * self.scale, self.offset.1 + point.y * self.scale)
+    }
+}
+
+fn rgb(r: f32, g: f32, b: f32) -> Color {
+    Color::Rgb(Rgb::new(r, g, b, None))
+}
+
+fn pdf_point(x: f64, y: f64) -> (Point, bool) {
+    (Point::new(Mm(x as f32), Mm(y as f32)), false)
+}
+
+fn draw_polyline(layer: &PdfLayerReference, points: &[(f64, f64)], closed: bool) {
+    layer.add_line(Line {
+        points: points.iter().map(|&(x, y)| pdf_point(x, y)).collect(),
+        is_closed: closed,
+    });
+}
+
+fn draw_rect(layer: &PdfLayerReference, x: f64, y: f64, width: f64, height: f64) {
+    draw_polyline(layer, &[(x, y), (x + width, y), (x + width, y + height), (x, y + height)], true);
+}
+
+/// Approximate Helvetica text width in mm
+fn text_width(text: &str, size_pt: f64) -> f64 {
+    text.chars().count() as f64 * size_pt * 0.55 / PT_PER_MM
+}
+
+/// Draw layer geometry filled through a mapping; clear features are painted white
+fn draw_geometry(layer: &PdfLayerReference, geometry: &LayerGeometry, mapping: &PageMapping, color: Color) {
+    for feature in &geometry.features {
+        let rings: Vec<Vec<(Point, bool)>> = geometry.feature_polygons(feature, DEFAULT_TOLERANCE).into_iter()
+            .filter(|polygon| polygon.points.len() >= 3)
+            .map(|polygon| {
+                let polygon: Polygon = polygon.oriented();
+                polygon.points.iter()
+                    .map(|p| {
+                        let (x, y) = mapping.apply(*p);
+                        pdf_point(x, y)
+                    })
+                    .collect()
+            })
+            .collect();
+        if rings.is_empty() {
+            continue;
+        }
+        layer.set_fill_color(match feature.polarity {
+            Polarity::Dark => color.clone(),
+            Polarity::Clear => rgb(1.0, 1.0, 1.0),
+        });
+        layer.add_polygon(PdfPolygon {
+            rings,
+            mode: PaintMode::Fill,
+            winding_order: WindingOrder::NonZero,
+        });
+    }
+}
+
+struct PageWriter<'a> {
+    layer: PdfLayerReference,
+    font: &'a IndirectFontRef,
+    bold: &'a IndirectFontRef,
+    page_width: f64,
+    page_height: f64,
+}
+
+impl PageWriter<'_> {
+    fn text(&self, text: &str, size_pt: f64, x: f64, y: f64) {
+        self.layer.use_text(text, size_pt as f32, Mm(x as f32), Mm(y as f32), self.font);
+    }
+
+    fn bold_text(&self, text: &str, size_pt: f64, x: f64, y: f64) {
+        self.layer.use_text(text, size_pt as f32, Mm(x as f32), Mm(y as f32), self.bold);
+    }
+
+    fn stroke(&self, color: Color, width_mm: f64) {
+        self.layer.set_outline_color(color);
+        self.layer.set_outline_thickness((width_mm * PT_PER_MM) as f32);
+    }
+
+    /// Drawing area inside the frame, above the title block, as (x, y, width, height)
+    fn drawing_area(&self) -> (f64, f64, f64, f64) {
+        let x = MARGIN + 5.0;
+        let y = MARGIN + TITLE_BLOCK_HEIGHT + 5.0;
+        (x, y, self.page_width - x - MARGIN - 5.0, self.page_height - y - MARGIN - 5.0)
+    }
+
+    /// Frame and title block
+    fn frame(&self, title: &TitleBlock, sheet_title: &str, sheet: usize, sheets: usize, scale: f64) {
+        self.stroke(rgb(0.0, 0.0, 0.0), 0.5);
+        draw_rect(&self.layer, MARGIN, MARGIN, self.page_width - 2.0 * MARGIN, self.page_height - 2.0 * MARGIN);
+
+        let x = self.page_width - MARGIN - TITLE_BLOCK_WIDTH;
+        let y = MARGIN;
+        self.stroke(rgb(0.0, 0.0, 0.0), 0.35);
+        draw_rect(&self.layer, x, y, TITLE_BLOCK_WIDTH, TITLE_BLOCK_HEIGHT);
+        draw_polyline(&self.layer, &[(x, y + 20.0), (x + TITLE_BLOCK_WIDTH, y + 20.0)], false);
+        draw_polyline(&self.layer, &[(x, y + 10.0), (x + TITLE_BLOCK_WIDTH, y + 10.0)], false);
+        draw_polyline(&self.layer, &[(x + 60.0, y), (x + 60.0, y + 20.0)], false);
+        draw_polyline(&self.layer, &[(x + 90.0, y), (x + 90.0, y + 10.0)], false);
+
+        self.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+        let project = if title.project.is_empty() { "Untitled" } else { title.project.as_str() };
+        self.bold_text(project, 12.0, x + 2.0, y + 24.0);
+        if !title.company.is_empty() {
+            let width = text_width(&title.company, 8.0);
+            self.text(&title.company, 8.0, x + TITLE_BLOCK_WIDTH - 2.0 - width, y + 24.5);
+        }
+        self.text(sheet_title, 10.0, x + 2.0, y + 13.5);
+        self.text(&format!("Rev: {}", title.revision), 8.0, x + 62.0, y + 13.5);
+        self.text(&format!("Date: {}", title.date), 8.0, x + 2.0, y + 3.5);
+        self.text(&format!("Scale: {:.2}:1", scale), 8.0, x + 62.0, y + 3.5);
+        self.text(&format!("Sheet {} / {}", sheet, sheets), 8.0, x + 92.0, y + 3.5);
+    }
+
+    /// Horizontal dimension line below and vertical one left of the board
+    fn dimensions(&self, min: Point2<f64>, max: Point2<f64>, mapping: &PageMapping) {
+        let (left, bottom) = mapping.apply(min);
+        let (right, top) = mapping.apply(max);
+        let (left, right) = (left.min(right), left.max(right));
+        let arrow = 1.5;
+        self.stroke(rgb(0.0, 0.0, 0.0), 0.2);
+        self.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+
+        // Width
+        let y = bottom - 6.0;
+        draw_polyline(&self.layer, &[(left, bottom - 1.0), (left, y - 1.5)], false);
+        draw_polyline(&self.layer, &[(right, bottom - 1.0), (right, y - 1.5)], false);
+        draw_polyline(&self.layer, &[(left, y), (right, y)], false);
+        draw_polyline(&self.layer, &[(left + arrow, y + arrow / 2.0), (left, y), (left + arrow, y - arrow / 2.0)], false);
+        draw_polyline(&self.layer, &[(right - arrow, y + arrow / 2.0), (right, y), (right - arrow, y - arrow / 2.0)], false);
+        let label = format!("{:.2} mm", max.x - min.x);
+        self.text(&label, 8.0, (left + right) / 2.0 - text_width(&label, 8.0) / 2.0, y + 1.0);
+
+        // Height
+        let x = left - 6.0;
+        draw_polyline(&self.layer, &[(left - 1.0, bottom), (x - 1.5, bottom)], false);
+        draw_polyline(&self.layer, &[(left - 1.0, top), (x - 1.5, top)], false);
+        draw_polyline(&self.layer, &[(x, bottom), (x, top)], false);
+        draw_polyline(&self.layer, &[(x - arrow / 2.0, bottom + arrow), (x, bottom), (x + arrow / 2.0, bottom + arrow)], false);
+        draw_polyline(&self.layer, &[(x - arrow / 2.0, top - arrow), (x, top), (x + arrow / 2.0, top - arrow)], false);
+        let label = format!("{:.2} mm", max.y - min.y);
+        self.text(&label, 8.0, x - 1.0 - text_width(&label, 8.0), (bottom + top) / 2.0);
+    }
+}
+
+/// Generate the drawing PDF, returning the file content
+pub fn generate_drawings_pdf(set: &DrawingSet, options: &DrawingOptions) -> Result<Vec<u8>, String> {
+    let (min, max) = set.board_bounds().ok_or("No board outline or geometry loaded")?;
+
+    let mut sheets: Vec<(&str, bool)> = Vec::new();
+    if options.fabrication {
+        sheets.push(("Fabrication Drawing", false));
+    }
+    if options.assembly_top {
+        sheets.push(("Assembly Drawing - Top", false));
+    }
+    if options.assembly_bottom {
+        sheets.push(("Assembly Drawing - Bottom", true));
+    }
+    if options.review {
+        sheets.push(("Review Comments", false));
+    }
+    if sheets.is_empty() {
+        return Err("No drawings selected".to_string());
+    }
+
+    let (page_width, page_height) = options.page_size.dimensions();
+    let document_title = format!("{} drawings", options.title.project);
+    let (doc, first_page, first_layer) = PdfDocument::new(
+        document_title,
+        Mm(page_width as f32),
+        Mm(page_height as f32),
+        sheets[0].0,
+    );
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica).map_err(|e| format!("Failed to add font: {}", e))?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold).map_err(|e| format!("Failed to add font: {}", e))?;
+
+    let sheet_count = sheets.len();
+    for (index, (sheet_title, bottom)) in sheets.into_iter().enumerate() {
+        let layer = if index == 0 {
+            doc.get_page(first_page).get_layer(first_layer)
+        } else {
+            let (page, layer) = doc.add_page(Mm(page_width as f32), Mm(page_height as f32), sheet_title);
+            doc.get_page(page).get_layer(layer)
+        };
+        let writer = PageWriter { layer, font: &font, bold: &bold, page_width, page_height };
+
+        let scale = if sheet_title.starts_with("Fabrication") {
+            fabrication_page(&writer, set, options, min, max)
+        } else if sheet_title.starts_with("Review") {
+            review_page(&writer, set, min, max)
+        } else {
+            assembly_page(&writer, set, min, max, bottom)
+        };
+        writer.frame(&options.title, sheet_title, index + 1, sheet_count, scale);
+    }
+
+    doc.save_to_bytes().map_err(|e| format!("Failed to write PDF: {}", e))
+}
+
+/// Outline with dimensions and holes on the left, drill table and notes on the right
+fn fabrication_page(writer: &PageWriter, set: &DrawingSet, options: &DrawingOptions, min: Point2<f64>, max: Point2<f64>) -> f64 {
+    let (x, y, width, height) = writer.drawing_area();
+    let table_width = 80.0;
+    // Leave room for the dimension lines
+    let board_area = (x + 15.0, y + 12.0, width - table_width - 25.0, height - 17.0);
+    let mapping = PageMapping::fit(min, max, board_area, false);
+
+    if let Some(outline) = &set.outline {
+        draw_geometry(&writer.layer, outline, &mapping, rgb(0.0, 0.0, 0.0));
+    }
+
+    // Holes as circles, plated ones in blue
+    for drill in &set.drills {
+        let color = if drill.plated == Some(false) { rgb(0.8, 0.1, 0.1) } else { rgb(0.1, 0.2, 0.8) };
+        writer.stroke(color, 0.15);
+        for hit in &drill.hits {
+            let (cx, cy) = mapping.apply(hit.position);
+            let radius = (hit.diameter / 2.0 * mapping.scale).max(0.3);
+            let points: Vec<(f64, f64)> = shapes::circle_points(Point2::new(cx, cy), radius, 0.02).into_iter()
+                .map(|p| (p.x, p.y))
+                .collect();
+            draw_polyline(&writer.layer, &points, true);
+        }
+        for slot in &drill.slots {
+            let start = mapping.apply(slot.start);
+            let end = mapping.apply(slot.end);
+            draw_polyline(&writer.layer, &[start, end], false);
+        }
+    }
+
+    writer.dimensions(min, max, &mapping);
+
+    // Drill table
+    let table_x = x + width - table_width;
+    let mut row_y = y + height - 6.0;
+    writer.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+    writer.bold_text("DRILL TABLE", 10.0, table_x, row_y);
+    row_y -= 6.0;
+    writer.bold_text("Diameter", 8.0, table_x, row_y);
+    writer.bold_text("Count", 8.0, table_x + 30.0, row_y);
+    writer.bold_text("Type", 8.0, table_x + 50.0, row_y);
+    writer.stroke(rgb(0.0, 0.0, 0.0), 0.2);
+    draw_polyline(&writer.layer, &[(table_x, row_y - 1.5), (table_x + table_width, row_y - 1.5)], false);
+
+    let rows = set.drill_table();
+    let total: usize = rows.iter().map(|(_, count, _)| count).sum();
+    for (diameter, count, plated) in rows {
+        row_y -= 4.5;
+        writer.text(&format!("{:.3} mm", diameter), 8.0, table_x, row_y);
+        writer.text(&count.to_string(), 8.0, table_x + 30.0, row_y);
+        writer.text(if plated { "PTH" } else { "NPTH" }, 8.0, table_x + 50.0, row_y);
+    }
+    row_y -= 4.5;
+    draw_polyline(&writer.layer, &[(table_x, row_y + 3.0), (table_x + table_width, row_y + 3.0)], false);
+    writer.bold_text(&format!("Total holes: {}", total), 8.0, table_x, row_y);
+
+    // Notes
+    row_y -= 10.0;
+    writer.bold_text("NOTES", 10.0, table_x, row_y);
+    for (number, note) in fabrication_notes(set, options, min, max).iter().enumerate() {
+        row_y -= 4.5;
+        writer.text(&format!("{}. {}", number + 1, note), 8.0, table_x, row_y);
+    }
+
+    mapping.scale
+}
+
+/// Numbered notes of the fab drawing, using the stackup's notes unless the user wrote their own
+fn fabrication_notes(set: &DrawingSet, options: &DrawingOptions, min: Point2<f64>, max: Point2<f64>) -> Vec<String> {
+    let mut notes = vec![
+        format!("Board size: {:.2} x {:.2} mm", max.x - min.x, max.y - min.y),
+        format!("Copper layers: {}", set.copper_layers),
+    ];
+    let user_notes: Vec<String> = options.fab_notes.iter().filter(|note| !note.trim().is_empty()).cloned().collect();
+    if user_notes.is_empty() {
+        notes.extend(set.stackup_notes.iter().cloned());
+    } else {
+        notes.extend(user_notes);
+    }
+    notes
+}
+
+/// Component centre in board coordinates (Y up); BOM locations are Y-down like KiCad
+fn component_position(component: &BomComponent) -> Point2<f64> {
+    Point2::new(component.x_location, -component.y_location)
+}
+
+/// Silkscreen, outline and reference designators of one side
+fn assembly_page(writer: &PageWriter, set: &DrawingSet, min: Point2<f64>, max: Point2<f64>, bottom: bool) -> f64 {
+    let area = writer.drawing_area();
+    // Bottom side is viewed from below, so mirror it
+    let mapping = PageMapping::fit(min, max, area, bottom);
+
+    let silkscreen = if bottom { &set.silkscreen_bottom } else { &set.silkscreen_top };
+    if let Some(silkscreen) = silkscreen {
+        draw_geometry(&writer.layer, silkscreen, &mapping, rgb(0.6, 0.6, 0.6));
+    }
+    if let Some(outline) = &set.outline {
+        draw_geometry(&writer.layer, outline, &mapping, rgb(0.0, 0.0, 0.0));
+    }
+
+    // Reference designators at the component centres
+    let size_pt = (1.2 * mapping.scale * PT_PER_MM).clamp(3.0, 8.0);
+    writer.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+    for component in set.components.iter().filter(|c| c.is_bottom() == bottom) {
+        let (cx, cy) = mapping.apply(component_position(component));
+        let width = text_width(&component.reference, size_pt);
+        writer.text(&component.reference, size_pt, cx - width / 2.0, cy - size_pt / PT_PER_MM / 3.0);
+    }
+
+    mapping.scale
+}
+
+/// Outline with the numbered annotations on the left, their comments on the right
+fn review_page(writer: &PageWriter, set: &DrawingSet, min: Point2<f64>, max: Point2<f64>) -> f64 {
+    let (x, y, width, height) = writer.drawing_area();
+    let list_width = 100.0;
+    let mapping = PageMapping::fit(min, max, (x, y, width - list_width - 10.0, height), false);
+
+    if let Some(silkscreen) = &set.silkscreen_top {
+        draw_geometry(&writer.layer, silkscreen, &mapping, rgb(0.8, 0.8, 0.8));
+    }
+    if let Some(outline) = &set.outline {
+        draw_geometry(&writer.layer, outline, &mapping, rgb(0.0, 0.0, 0.0));
+    }
+
+    // Open annotations in red, resolved ones in green
+    for annotation in &set.annotations {
+        let color = if annotation.is_open() { rgb(0.85, 0.1, 0.1) } else { rgb(0.1, 0.6, 0.2) };
+        writer.stroke(color.clone(), 0.3);
+        let start = mapping.apply(Point2::new(annotation.position.0, annotation.position.1));
+        let end = annotation.end.map(|(ex, ey)| mapping.apply(Point2::new(ex, ey)));
+        match (annotation.kind, end) {
+            (AnnotationKind::Rectangle, Some(end)) => {
+                draw_rect(&writer.layer, start.0.min(end.0), start.1.min(end.1), (end.0 - start.0).abs(), (end.1 - start.1).abs());
+            }
+            (AnnotationKind::Arrow, Some(end)) => {
+                draw_polyline(&writer.layer, &[start, end], false);
+                let angle = (end.1 - start.1).atan2(end.0 - start.0);
+                let head = |offset: f64| (end.0 - 2.0 * (angle + offset).cos(), end.1 - 2.0 * (angle + offset).sin());
+                draw_polyline(&writer.layer, &[head(0.4), end, head(-0.4)], false);
+            }
+            _ => {
+                let points: Vec<(f64, f64)> = shapes::circle_points(Point2::new(start.0, start.1), 1.2, 0.02).into_iter()
+                    .map(|p| (p.x, p.y))
+                    .collect();
+                draw_polyline(&writer.layer, &points, true);
+            }
+        }
+        writer.layer.set_fill_color(color);
+        writer.bold_text(&annotation.id.to_string(), 7.0, start.0 + 1.5, start.1 + 1.5);
+    }
+
+    // Comment list, as many as fit
+    let list_x = x + width - list_width;
+    let mut row_y = y + height - 6.0;
+    writer.layer.set_fill_color(rgb(0.0, 0.0, 0.0));
+    writer.bold_text("REVIEW COMMENTS", 10.0, list_x, row_y);
+    let open = set.annotations.iter().filter(|annotation| annotation.is_open()).count();
+    row_y -= 5.0;
+    writer.text(&format!("{} open, {} resolved", open, set.annotations.len() - open), 8.0, list_x, row_y);
+    writer.stroke(rgb(0.0, 0.0, 0.0), 0.2);
+    draw_polyline(&writer.layer, &[(list_x, row_y - 1.5), (list_x + list_width, row_y - 1.5)], false);
+
+    let max_chars = (list_width / text_width("m", 7.0)) as usize;
+    for (index, annotation) in set.annotations.iter().enumerate() {
+        if row_y - 9.0 < y {
+            writer.text(&format!("... {} more", set.annotations.len() - index), 7.0, list_x, row_y - 4.5);
+            break;
+        }
+        row_y -= 4.5;
+        writer.bold_text(
+            &format!("#{} {} - {} - {}", annotation.id, annotation.status.display_name(), annotation.layer.display_name(), annotation.author),
+            7.0, list_x, row_y,
+        );
+        row_y -= 3.5;
+        let text: String = annotation.text.lines().next().unwrap_or_default().chars().take(max_chars).collect();
+        writer.text(&text, 7.0, list_x + 2.0, row_y);
+    }
+
+    mapping.scale
+}
+
+/// Build the drawings from the loaded layers and BOM and write them to a PDF file
+/// Returns the number of components placed on the assembly drawings.
+pub fn export_drawings_pdf(app: &mut DemoLensApp, output_path: &Path, options: &DrawingOptions) -> Result<usize, String> {
+    let components = loaded_bom_components(app);
+
+    let mut set = DrawingSet::from_world(&mut app.ecs_world, components);
+    if options.review {
+        set.annotations = app.review.annotations.clone();
+    }
+    let bytes = generate_drawings_pdf(&set, options)?;
+    std::fs::write(output_path, bytes).map_err(|e| format!("Failed to save PDF: {}", e))?;
+    Ok(set.components.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_mapping() {
+        let mapping = PageMapping::fit(Point2::new(0.0, 0.0), Point2::new(100.0, 50.0), (10.0, 10.0, 200.0, 200.0), false);
+        assert!((mapping.scale - 2.0).abs() < 1e-9);
+        assert_eq!(mapping.apply(Point2::new(0.0, 0.0)), (10.0, 60.0));
+        assert_eq!(mapping.apply(Point2::new(100.0, 50.0)), (210.0, 160.0));
+
+        let mirrored = PageMapping::fit(Point2::new(0.0, 0.0), Point2::new(100.0, 50.0), (10.0, 10.0, 200.0, 200.0), true);
+        assert_eq!(mirrored.apply(Point2::new(0.0, 0.0)), (210.0, 60.0));
+    }
+
+    #[test]
+    fn test_reference_over_component() {
+        // Board 0..50 x 0..30, part near the top left corner at KiCad (10, -25)
+        let mut component = BomComponent::new("U1".to_string());
+        component.x_location = 10.0;
+        component.y_location = -25.0;
+        let mapping = PageMapping::fit(Point2::new(0.0, 0.0), Point2::new(50.0, 30.0), (0.0, 0.0, 100.0, 60.0), false);
+
+        let (x, y) = mapping.apply(component_position(&component));
+        assert!((x - 20.0).abs() < 1e-9);
+        // Page Y is up too, so the part stays near the top of the drawing
+        assert!((y - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_drill_table_merges_tools() {
+        let drill = crate::geometry::parse_excellon(
+            "M48\nMETRIC\nT1C0.300\nT2C0.800\nT3C0.300\n%\nT1\nX1.0Y1.0\nX2.0Y1.0\nT2\nX3.0Y1.0\nT3\nX4.0Y1.0\nM30\n"
+        ).unwrap();
+        let set = DrawingSet { drills: vec![drill], ..Default::default() };
+        assert_eq!(set.drill_table(), vec![(0.3, 3, true), (0.8, 1, true)]);
+    }
+
+    #[test]
+    fn test_generate_pdf() {
+        let outline = crate::geometry::parse_gerber(
+            "%FSLAX26Y26*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nX0Y0D02*\nX50000000Y0D01*\nX50000000Y30000000D01*\nX0Y30000000D01*\nX0Y0D01*\nM02*\n"
+        ).unwrap();
+        let mut component = BomComponent::new("R1".to_string());
+        component.x_location = 10.0;
+        component.y_location = -10.0;
+        component.layer = "F.Cu".to_string();
+        let set = DrawingSet { outline: Some(outline), components: vec![component], ..Default::default() };
+
+        let bytes = generate_drawings_pdf(&set, &DrawingOptions::default()).unwrap();
+        assert!(bytes.starts_with(b"%PDF"));
+
+        let annotation = Annotation {
+            id: 1,
+            kind: AnnotationKind::Arrow,
+            layer: LayerType::Silkscreen(Side::Top),
+            position: (5.0, 5.0),
+            end: Some((10.0, 10.0)),
+            text: "Move R1 label off the pad".to_string(),
+            author: "reviewer".to_string(),
+            status: Default::default(),
+            created_at: chrono::Utc::now(),
+        };
+        let set = DrawingSet { annotations: vec![annotation], ..set };
+        let options = DrawingOptions { review: true, ..Default::default() };
+        assert!(generate_drawings_pdf(&set, &options).unwrap().starts_with(b"%PDF"));
+        assert!(generate_drawings_pdf(&DrawingSet::default(), &DrawingOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_fabrication_notes() {
+        let (min, max) = (Point2::new(0.0, 0.0), Point2::new(50.0, 30.0));
+        let set = DrawingSet { copper_layers: 2, ..Default::default() };
+        // Nothing is made up without a stackup
+        assert_eq!(fabrication_notes(&set, &DrawingOptions::default(), min, max).len(), 2);
+
+        let set = DrawingSet { stackup_notes: vec!["Material: FR4".to_string()], ..set };
+        assert_eq!(fabrication_notes(&set, &DrawingOptions::default(), min, max)[2], "Material: FR4");
+
+        // Notes written by the user replace the stackup's
+        let options = DrawingOptions { fab_notes: vec!["Surface finish: ENIG".to_string(), " ".to_string()], ..Default::default() };
+        assert_eq!(fabrication_notes(&set, &options, min, max)[2..], ["Surface finish: ENIG".to_string()]);
+    }
+}
@@ -0,0 +1,519 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use bevy_ecs::prelude::*;
+use nalgebra::Point2;
+
+use crate::DemoLensApp;
+use crate::ecs::{DrillHoles, LayerGeometryData, LayerInfo, LayerType};
+use crate::geometry::{
+    outline_contours, shapes, ApertureShape, DrillData, FeatureKind, LayerGeometry, OutlineContour, OutlineSegment,
+    Polarity, DEFAULT_TOLERANCE,
+};
+use crate::geometry::outline::JOIN_TOLERANCE;
+use super::layer_file_stem;
+
+// DXF export for mechanical CAD
+// Writes AutoCAD R12 ASCII, which every MCAD tool reads. The board outline is
+// chained into polylines with arc bulges, other layers become outlines or
+// centre lines and drills become circles. One DXF layer per PCB layer. Clear
+// polarity features are left out.
+//
+// Values are mm, but R12 has no header variable for drawing units ($INSUNITS
+// and $MEASUREMENT are R14+), so the file carries none and must be imported
+// as millimetres.
+
+/// How the features of non-outline layers are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DxfFeatureMode {
+    /// Closed polylines around the copper (or ink) of each feature
+    #[default]
+    Outline,
+    /// Tracks and arcs as their centre lines, flashes as outlines
+    Centerline,
+}
+
+/// Settings for a DXF export
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxfOptions {
+    /// Layers written in addition to the board outline
+    pub layers: Vec<LayerType>,
+    pub include_drills: bool,
+    pub mode: DxfFeatureMode,
+    /// Mirror left to right about the board centre, for bottom side views
+    pub mirror: bool,
+}
+
+impl Default for DxfOptions {
+    fn default() -> Self {
+        Self {
+            layers: Vec::new(),
+            include_drills: true,
+            mode: DxfFeatureMode::default(),
+            mirror: false,
+        }
+    }
+}
+
+/// Geometry going into a DXF file, raw gerber coordinates
+#[derive(Debug, Clone, Default)]
+pub struct DxfSource {
+    pub outline: Option<LayerGeometry>,
+    pub layers: Vec<(LayerType, LayerGeometry)>,
+    pub drills: Vec<DrillData>,
+}
+
+impl DxfSource {
+    /// Collect the outline, the requested layers and the drill files
+    pub fn from_world(world: &mut World, options: &DxfOptions) -> Self {
+        let mut source = DxfSource::default();
+
+        let mut layers = world.query::<(&LayerInfo, &LayerGeometryData)>();
+        for (info, geometry) in layers.iter(world) {
+            if info.layer_type == LayerType::MechanicalOutline {
+                source.outline = Some(geometry.0.clone());
+            } else if options.layers.contains(&info.layer_type) {
+                source.layers.push((info.layer_type, geometry.0.clone()));
+            }
+        }
+        source.layers.sort_by_key(|(layer_type, _)| options.layers.iter().position(|t| t == layer_type));
+
+        if options.include_drills {
+            let mut drills = world.query::<&DrillHoles>();
+            source.drills = drills.iter(world).map(|holes| holes.0.clone()).collect();
+        }
+        source
+    }
+
+    /// Extents of everything in the source
+    pub fn bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        let mut corners = Vec::new();
+        for geometry in self.outline.iter().chain(self.layers.iter().map(|(_, geometry)| geometry)) {
+            if let Some((min, max)) = geometry.bounds() {
+                corners.extend([min, max]);
+            }
+        }
+        for drill in &self.drills {
+            if let Some((min, max)) = drill.bounds() {
+                corners.extend([min, max]);
+            }
+        }
+        shapes::points_bounds(&corners)
+    }
+}
+
+/// Minimal R12 DXF writer: a layer table and 2D entities
+#[derive(Debug, Clone, Default)]
+pub struct DxfWriter {
+    layers: Vec<(String, u8)>,
+    entities: String,
+    count: usize,
+}
+
+/// One group code / value pair
+fn pair(out: &mut String, code: u16, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "{:>3}\n{}", code, value);
+}
+
+/// Coordinate value, without producing "-0.000000"
+fn coord(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    if text.trim_start_matches('-').trim_matches(['0', '.']).is_empty() { "0.000000".to_string() } else { text }
+}
+
+impl DxfWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declare a layer with an AutoCAD color index; entities may only use declared layers
+    pub fn add_layer(&mut self, name: &str, color: u8) {
+        if !self.layers.iter().any(|(existing, _)| existing == name) {
+            self.layers.push((name.to_string(), color));
+        }
+    }
+
+    pub fn entity_count(&self) -> usize {
+        self.count
+    }
+
+    fn entity(&mut self, kind: &str, layer: &str) {
+        pair(&mut self.entities, 0, kind);
+        pair(&mut self.entities, 8, layer);
+        self.count += 1;
+    }
+
+    fn point(&mut self, code: u16, point: Point2<f64>) {
+        pair(&mut self.entities, code, coord(point.x));
+        pair(&mut self.entities, code + 10, coord(point.y));
+        pair(&mut self.entities, code + 20, coord(0.0));
+    }
+
+    pub fn line(&mut self, layer: &str, start: Point2<f64>, end: Point2<f64>) {
+        self.entity("LINE", layer);
+        self.point(10, start);
+        self.point(11, end);
+    }
+
+    pub fn circle(&mut self, layer: &str, center: Point2<f64>, radius: f64) {
+        self.entity("CIRCLE", layer);
+        self.point(10, center);
+        pair(&mut self.entities, 40, coord(radius));
+    }
+
+    /// Counter-clockwise arc between two angles in degrees
+    pub fn arc(&mut self, layer: &str, center: Point2<f64>, radius: f64, start_angle: f64, end_angle: f64) {
+        self.entity("ARC", layer);
+        self.point(10, center);
+        pair(&mut self.entities, 40, coord(radius));
+        pair(&mut self.entities, 50, coord(start_angle.rem_euclid(360.0)));
+        pair(&mut self.entities, 51, coord(end_angle.rem_euclid(360.0)));
+    }
+
+    /// Polyline through (vertex, bulge) pairs; a bulge bends the span to the next vertex
+    /// into an arc, tan(sweep / 4), positive counter-clockwise.
+    pub fn polyline(&mut self, layer: &str, vertices: &[(Point2<f64>, f64)], closed: bool) {
+        if vertices.len() < 2 {
+            return;
+        }
+        self.entity("POLYLINE", layer);
+        pair(&mut self.entities, 66, 1);
+        self.point(10, Point2::origin());
+        pair(&mut self.entities, 70, if closed { 1 } else { 0 });
+        for (vertex, bulge) in vertices {
+            pair(&mut self.entities, 0, "VERTEX");
+            pair(&mut self.entities, 8, layer);
+            self.point(10, *vertex);
+            if bulge.abs() > 1e-12 {
+                pair(&mut self.entities, 42, coord(*bulge));
+            }
+        }
+        pair(&mut self.entities, 0, "SEQEND");
+        pair(&mut self.entities, 8, layer);
+    }
+
+    /// Complete DXF file text
+    pub fn finish(self) -> String {
+        let mut out = String::new();
+        pair(&mut out, 0, "SECTION");
+        pair(&mut out, 2, "HEADER");
+        pair(&mut out, 9, "$ACADVER");
+        pair(&mut out, 1, "AC1009");
+        pair(&mut out, 0, "ENDSEC");
+
+        pair(&mut out, 0, "SECTION");
+        pair(&mut out, 2, "TABLES");
+        pair(&mut out, 0, "TABLE");
+        pair(&mut out, 2, "LTYPE");
+        pair(&mut out, 70, 1);
+        pair(&mut out, 0, "LTYPE");
+        pair(&mut out, 2, "CONTINUOUS");
+        pair(&mut out, 70, 0);
+        pair(&mut out, 3, "Solid line");
+        pair(&mut out, 72, 65);
+        pair(&mut out, 73, 0);
+        pair(&mut out, 40, coord(0.0));
+        pair(&mut out, 0, "ENDTAB");
+        pair(&mut out, 0, "TABLE");
+        pair(&mut out, 2, "LAYER");
+        pair(&mut out, 70, self.layers.len());
+        for (name, color) in &self.layers {
+            pair(&mut out, 0, "LAYER");
+            pair(&mut out, 2, name);
+            pair(&mut out, 70, 0);
+            pair(&mut out, 62, color);
+            pair(&mut out, 6, "CONTINUOUS");
+        }
+        pair(&mut out, 0, "ENDTAB");
+        pair(&mut out, 0, "ENDSEC");
+
+        pair(&mut out, 0, "SECTION");
+        pair(&mut out, 2, "ENTITIES");
+        out.push_str(&self.entities);
+        pair(&mut out, 0, "ENDSEC");
+        pair(&mut out, 0, "EOF");
+        out
+    }
+}
+
+/// DXF layer name of a PCB layer, e.g. "TOP_COPPER_L1"
+/// R12 only allows letters, digits, '$', '-' and '_' in names.
+pub fn dxf_layer_name(layer_type: &LayerType) -> String {
+    layer_file_stem(layer_type)
+        .to_uppercase()
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '$' || c == '-'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+/// AutoCAD color index used for a PCB layer
+fn dxf_layer_color(layer_type: &LayerType) -> u8 {
+    match layer_type {
+        LayerType::Copper(1) => 1,
+        LayerType::Copper(_) => 5,
+        LayerType::Silkscreen(_) => 7,
+        LayerType::Soldermask(_) => 3,
+        LayerType::Paste(_) => 8,
+        LayerType::MechanicalOutline => 2,
+    }
+}
+
+/// What was written to a DXF file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DxfReport {
+    pub entities: usize,
+    /// Outline contours whose ends do not meet
+    pub open_contours: usize,
+}
+
+/// Mapping applied to every point: identity, or a mirror about x = axis
+#[derive(Debug, Clone, Copy)]
+struct Placement {
+    mirror_axis: Option<f64>,
+}
+
+impl Placement {
+    fn point(&self, point: Point2<f64>) -> Point2<f64> {
+        match self.mirror_axis {
+            Some(axis) => Point2::new(2.0 * axis - point.x, point.y),
+            None => point,
+        }
+    }
+
+    /// Mirroring reverses the turning direction of arcs
+    fn clockwise(&self, clockwise: bool) -> bool {
+        clockwise != self.mirror_axis.is_some()
+    }
+}
+
+fn arc_bulge(start: Point2<f64>, end: Point2<f64>, center: Point2<f64>, clockwise: bool) -> f64 {
+    (shapes::arc_sweep(start, end, center, clockwise) / 4.0).tan()
+}
+
+fn write_contour(writer: &mut DxfWriter, layer: &str, contour: &OutlineContour, place: Placement) {
+    // A lone full circle is a CIRCLE entity
+    if let [OutlineSegment::Arc { start, end, center, .. }] = contour.segments.as_slice()
+        && (start - end).norm() <= 1e-9
+    {
+        writer.circle(layer, place.point(*center), (start - center).norm());
+        return;
+    }
+
+    let mut vertices: Vec<(Point2<f64>, f64)> = contour.segments.iter()
+        .map(|segment| match segment {
+            OutlineSegment::Line { start, .. } => (place.point(*start), 0.0),
+            OutlineSegment::Arc { start, end, center, clockwise } => (
+                place.point(*start),
+                arc_bulge(place.point(*start), place.point(*end), place.point(*center), place.clockwise(*clockwise)),
+            ),
+        })
+        .collect();
+    if !contour.closed
+        && let Some(last) = contour.segments.last()
+    {
+        vertices.push((place.point(last.end()), 0.0));
+    }
+    writer.polyline(layer, &vertices, contour.closed);
+}
+
+fn write_layer(writer: &mut DxfWriter, layer: &str, geometry: &LayerGeometry, mode: DxfFeatureMode, place: Placement) {
+    for feature in geometry.features.iter().filter(|feature| feature.polarity == Polarity::Dark) {
+        let aperture = feature.aperture.and_then(|d| geometry.apertures.get(&d));
+        let circle_diameter = match aperture.map(|a| &a.shape) {
+            Some(ApertureShape::Circle { diameter, hole: None }) => Some(diameter * feature.aperture_transform.scale),
+            _ => None,
+        };
+
+        match (&feature.kind, mode) {
+            (FeatureKind::Flash { position }, _) if circle_diameter.is_some() => {
+                writer.circle(layer, place.point(*position), circle_diameter.unwrap_or(0.0) / 2.0);
+            }
+            (FeatureKind::Line { start, end }, DxfFeatureMode::Centerline) => {
+                writer.line(layer, place.point(*start), place.point(*end));
+            }
+            (FeatureKind::Arc { start, end, center, clockwise }, DxfFeatureMode::Centerline) => {
+                let (start, end, center) = (place.point(*start), place.point(*end), place.point(*center));
+                let radius = (start - center).norm();
+                if (start - end).norm() <= 1e-9 {
+                    writer.circle(layer, center, radius);
+                    continue;
+                }
+                let angle = |p: Point2<f64>| (p.y - center.y).atan2(p.x - center.x).to_degrees();
+                // DXF arcs always run counter-clockwise
+                if place.clockwise(*clockwise) {
+                    writer.arc(layer, center, radius, angle(end), angle(start));
+                } else {
+                    writer.arc(layer, center, radius, angle(start), angle(end));
+                }
+            }
+            _ => {
+                for polygon in geometry.feature_polygons(feature, DEFAULT_TOLERANCE) {
+                    if polygon.points.len() < 3 {
+                        continue;
+                    }
+                    let vertices: Vec<(Point2<f64>, f64)> = polygon.points.iter().map(|p| (place.point(*p), 0.0)).collect();
+                    writer.polyline(layer, &vertices, true);
+                }
+            }
+        }
+    }
+}
+
+fn write_drills(writer: &mut DxfWriter, drills: &[DrillData], place: Placement) {
+    for drill in drills {
+        let layer = if drill.plated == Some(false) { "DRILL_NPTH" } else { "DRILL_PTH" };
+        writer.add_layer(layer, if drill.plated == Some(false) { 6 } else { 4 });
+        for hit in &drill.hits {
+            writer.circle(layer, place.point(hit.position), hit.diameter / 2.0);
+        }
+        for slot in &drill.slots {
+            let (start, end) = (place.point(slot.start), place.point(slot.end));
+            let radius = slot.diameter / 2.0;
+            let direction = end - start;
+            if direction.norm() <= 1e-9 {
+                writer.circle(layer, start, radius);
+                continue;
+            }
+            let normal = nalgebra::Vector2::new(-direction.y, direction.x).normalize() * radius;
+            // Counter-clockwise stadium, half circles have a bulge of 1
+            writer.polyline(layer, &[
+                (start - normal, 0.0),
+                (end - normal, 1.0),
+                (end + normal, 0.0),
+                (start + normal, 1.0),
+            ], true);
+        }
+    }
+}
+
+/// Write the source geometry into a DXF document
+pub fn build_dxf(source: &DxfSource, options: &DxfOptions) -> Result<(DxfWriter, DxfReport), String> {
+    let (min, max) = source.bounds().ok_or("No outline, layer or drill geometry to export")?;
+    let place = Placement { mirror_axis: options.mirror.then_some((min.x + max.x) / 2.0) };
+
+    let mut writer = DxfWriter::new();
+    let mut open_contours = 0;
+    if let Some(outline) = &source.outline {
+        let layer = dxf_layer_name(&LayerType::MechanicalOutline);
+        writer.add_layer(&layer, dxf_layer_color(&LayerType::MechanicalOutline));
+        for contour in outline_contours(outline, JOIN_TOLERANCE) {
+            if !contour.closed {
+                open_contours += 1;
+            }
+            write_contour(&mut writer, &layer, &contour, place);
+        }
+    }
+    for (layer_type, geometry) in &source.layers {
+        let layer = dxf_layer_name(layer_type);
+        writer.add_layer(&layer, dxf_layer_color(layer_type));
+        write_layer(&mut writer, &layer, geometry, options.mode, place);
+    }
+    write_drills(&mut writer, &source.drills, place);
+
+    let report = DxfReport { entities: writer.entity_count(), open_contours };
+    Ok((writer, report))
+}
+
+/// Export the outline, the selected layers and drills as a DXF file
+pub fn export_dxf(app: &mut DemoLensApp, output_path: &Path, options: &DxfOptions) -> Result<DxfReport, String> {
+    let source = DxfSource::from_world(&mut app.ecs_world, options);
+    if source.outline.is_none() && source.layers.is_empty() {
+        return Err("No mechanical outline or selected layers loaded".to_string());
+    }
+    let (writer, report) = build_dxf(&source, options)?;
+    std::fs::write(output_path, writer.finish()).map_err(|e| format!("Failed to save DXF: {}", e))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{parse_excellon, parse_gerber};
+
+    /// Entities of a DXF file as (type, [(code, value)])
+    fn entities(dxf: &str) -> Vec<(String, Vec<(u16, String)>)> {
+        let lines: Vec<&str> = dxf.lines().collect();
+        let pairs: Vec<(u16, String)> = lines.chunks(2)
+            .map(|chunk| (chunk[0].trim().parse().unwrap(), chunk[1].to_string()))
+            .collect();
+        let start = pairs.iter().position(|(code, value)| *code == 2 && value == "ENTITIES").unwrap() + 1;
+        let mut result: Vec<(String, Vec<(u16, String)>)> = Vec::new();
+        for (code, value) in &pairs[start..] {
+            if *code == 0 {
+                result.push((value.clone(), Vec::new()));
+            } else if let Some(last) = result.last_mut() {
+                last.1.push((*code, value.clone()));
+            }
+        }
+        result
+    }
+
+    fn value(fields: &[(u16, String)], code: u16) -> f64 {
+        fields.iter().find(|(c, _)| *c == code).unwrap().1.parse().unwrap()
+    }
+
+    fn outline() -> LayerGeometry {
+        // 10 x 5 board whose right edge is a half circle bulging out to x = 12.5
+        parse_gerber("%FSLAX26Y26*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nG75*\n\
+            X0Y0D02*\nX10000000Y0D01*\nG03X10000000Y5000000I0J2500000D01*\nG01X0Y5000000D01*\nX0Y0D01*\nM02*\n").unwrap()
+    }
+
+    #[test]
+    fn test_outline_polyline() {
+        let source = DxfSource { outline: Some(outline()), ..Default::default() };
+        let (writer, report) = build_dxf(&source, &DxfOptions::default()).unwrap();
+        assert_eq!(report, DxfReport { entities: 1, open_contours: 0 });
+
+        let dxf = writer.finish();
+        assert!(dxf.contains("MECHANICAL_OUTLINE"));
+        assert!(dxf.ends_with("  0\nEOF\n"));
+        // R12 header without the R14+ unit variables
+        assert!(dxf.contains("$ACADVER\n  1\nAC1009\n"));
+        assert!(!dxf.contains("$INSUNITS"));
+        let entities = entities(&dxf);
+        assert_eq!(entities[0].0, "POLYLINE");
+        assert_eq!(value(&entities[0].1, 70), 1.0);
+
+        let vertices: Vec<&(String, Vec<(u16, String)>)> = entities.iter().filter(|(kind, _)| kind == "VERTEX").collect();
+        assert_eq!(vertices.len(), 4);
+        // The half circle starts at the second vertex, counter-clockwise
+        assert_eq!((value(&vertices[1].1, 10), value(&vertices[1].1, 20)), (10.0, 0.0));
+        assert!((value(&vertices[1].1, 42) - 1.0).abs() < 1e-6);
+        assert!(!vertices[0].1.iter().any(|(code, _)| *code == 42));
+    }
+
+    #[test]
+    fn test_mirrored_outline() {
+        let source = DxfSource { outline: Some(outline()), ..Default::default() };
+        let options = DxfOptions { mirror: true, ..Default::default() };
+        let dxf = build_dxf(&source, &options).unwrap().0.finish();
+        let vertices: Vec<(String, Vec<(u16, String)>)> = entities(&dxf).into_iter().filter(|(kind, _)| kind == "VERTEX").collect();
+
+        // Bounds span x -0.05..12.55, so the mirror axis is x = 6.25
+        assert!((value(&vertices[1].1, 10) - 2.5).abs() < 1e-6);
+        // Mirrored, the arc turns clockwise
+        assert!((value(&vertices[1].1, 42) + 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_drills_and_layers() {
+        let drill = parse_excellon("M48\nMETRIC\nT1C3.2\n%\nT1\nX5.0Y2.5\nT1\nG00X1.0Y1.0\nM15\nG01X3.0Y1.0\nM16\nM30\n").unwrap();
+        let silk = parse_gerber("%FSLAX26Y26*%\n%MOMM*%\n%ADD10C,0.15*%\nD10*\nX0Y0D02*\nX2000000Y0D01*\nM02*\n").unwrap();
+        let source = DxfSource {
+            outline: None,
+            layers: vec![(LayerType::Silkscreen(crate::ecs::Side::Top), silk)],
+            drills: vec![drill],
+        };
+        let options = DxfOptions { mode: DxfFeatureMode::Centerline, ..Default::default() };
+        let dxf = build_dxf(&source, &options).unwrap().0.finish();
+        assert!(dxf.contains("TOP_SILKSCREEN"));
+
+        let entities = entities(&dxf);
+        let line = entities.iter().find(|(kind, _)| kind == "LINE").unwrap();
+        assert_eq!(value(&line.1, 11), 2.0);
+        let circle = entities.iter().find(|(kind, _)| kind == "CIRCLE").unwrap();
+        assert_eq!((value(&circle.1, 10), value(&circle.1, 20), value(&circle.1, 40)), (5.0, 2.5, 1.6));
+        // Slot drawn as a closed stadium
+        assert!(entities.iter().any(|(kind, fields)| kind == "POLYLINE" && value(fields, 70) == 1.0));
+    }
+}
@@ -0,0 +1,620 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use nalgebra::Point2;
+
+use crate::DemoLensApp;
+use crate::ecs::{ImageTransform, LayerGeometryData, LayerInfo, LayerType, Transform};
+use crate::geometry::{
+    shapes, Aperture, ApertureShape, ApertureTransform, FeatureKind, GerberFeature, LayerGeometry, LayerTransform,
+    Polarity, Units, DEFAULT_TOLERANCE,
+};
+use super::layer_file_stem;
+
+// RS-274X (Gerber X2) writer
+// Serializes parsed layers back to Gerber with a layer transform baked into
+// the coordinates. Apertures keep their definitions; their rotation, mirroring
+// and scale are expressed with %LM/%LR/%LS, so pads stay pads at the fab.
+// Several layers can be merged into one file, with D-codes renumbered and
+// clashing macro names made unique.
+
+/// File attributes not carried over, they describe the original file
+const REPLACED_ATTRIBUTES: [&str; 3] = [".GenerationSoftware", ".CreationDate", ".MD5"];
+
+/// Object attributes carried over: net, component and pin
+const OBJECT_ATTRIBUTES: [&str; 3] = [".N", ".C", ".P"];
+
+/// Result of writing one Gerber file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GerberReport {
+    pub features: usize,
+    pub apertures: usize,
+    pub warnings: Vec<String>,
+}
+
+/// Decimal without trailing zeros, e.g. "0.25" or "90"
+pub(crate) fn decimal(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".to_string() } else { text.to_string() }
+}
+
+/// Standard aperture parameters rewritten from the mm shape, in output units
+pub(crate) fn standard_parameters(shape: &ApertureShape, units: Units) -> Option<String> {
+    let unit = |mm: f64| decimal(mm / units.to_mm());
+    let with_hole = |mut params: Vec<String>, hole: &Option<f64>| {
+        if let Some(hole) = hole {
+            params.push(unit(*hole));
+        }
+        params.join("X")
+    };
+    match shape {
+        ApertureShape::Circle { diameter, hole } => Some(with_hole(vec![unit(*diameter)], hole)),
+        ApertureShape::Rectangle { width, height, hole } | ApertureShape::Obround { width, height, hole } => {
+            Some(with_hole(vec![unit(*width), unit(*height)], hole))
+        }
+        ApertureShape::Polygon { diameter, vertices, rotation, hole } => {
+            Some(with_hole(vec![unit(*diameter), vertices.to_string(), decimal(*rotation)], hole))
+        }
+        ApertureShape::Macro { .. } => None,
+    }
+}
+
+/// Template, parameters and function of an output aperture
+type Definition = (String, String, Option<String>);
+
+/// Aperture dictionary and macros of the output file
+#[derive(Default)]
+struct Dictionary {
+    /// Macro name -> body
+    macros: BTreeMap<String, String>,
+    /// Output D-code -> (first layer using it, definition)
+    apertures: BTreeMap<i32, (usize, Definition)>,
+    /// (layer index, source D-code) -> (output D-code, extra scale for unit conversion)
+    mapping: HashMap<(usize, i32), (i32, f64)>,
+}
+
+impl Dictionary {
+    /// Register a macro body, renaming it when another layer used the name differently
+    fn add_macro(&mut self, name: &str, source: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut n = 2;
+        loop {
+            match self.macros.get(&candidate) {
+                Some(existing) if existing == source => return candidate,
+                Some(_) => {
+                    candidate = format!("{}_{}", name, n);
+                    n += 1;
+                }
+                None => {
+                    self.macros.insert(candidate.clone(), source.to_string());
+                    return candidate;
+                }
+            }
+        }
+    }
+
+    /// Register an aperture, keeping its D-code unless another definition already has it
+    /// Identical definitions are shared between layers, never folded within one.
+    fn add_aperture(&mut self, layer: usize, geometry: &LayerGeometry, aperture: &Aperture, units: Units) -> Result<(), String> {
+        let (template, parameters, extra_scale) = match &aperture.shape {
+            ApertureShape::Macro { name, .. } => {
+                let source = geometry.macros.get(name)
+                    .ok_or_else(|| format!("Aperture D{} uses undefined macro '{}'", aperture.d_code, name))?;
+                let name = self.add_macro(name, &source.source);
+                // Macro bodies stay in their own units, %LS converts them
+                (name, aperture.raw_parameters.clone(), geometry.units.to_mm() / units.to_mm())
+            }
+            _ if geometry.units == units => (aperture.template.clone(), aperture.raw_parameters.clone(), 1.0),
+            shape => (aperture.template.clone(), standard_parameters(shape, units).unwrap_or_default(), 1.0),
+        };
+
+        let definition: Definition = (template, parameters, aperture.function.clone());
+        let existing = self.apertures.iter()
+            .find(|(_, (owner, other))| *owner != layer && *other == definition)
+            .map(|(code, _)| *code);
+        let code = match existing {
+            Some(code) => code,
+            None => {
+                let code = if self.apertures.contains_key(&aperture.d_code) {
+                    self.apertures.keys().next_back().map_or(10, |last| (last + 1).max(10))
+                } else {
+                    aperture.d_code
+                };
+                self.apertures.insert(code, (layer, definition));
+                code
+            }
+        };
+        self.mapping.insert((layer, aperture.d_code), (code, extra_scale));
+        Ok(())
+    }
+}
+
+/// Graphics state of the writer, to only emit what changes
+struct BodyWriter {
+    out: String,
+    units: Units,
+    /// Largest absolute coordinate written, in output units
+    extent: f64,
+    polarity: Option<Polarity>,
+    aperture_transform: ApertureTransform,
+    aperture: Option<i32>,
+    interpolation: Option<&'static str>,
+    position: Option<(i64, i64)>,
+    /// Object attributes (%TO) currently set
+    attributes: Vec<(String, Vec<String>)>,
+}
+
+impl BodyWriter {
+    fn new(units: Units) -> Self {
+        Self {
+            out: String::new(),
+            units,
+            extent: 0.0,
+            polarity: None,
+            aperture_transform: ApertureTransform::default(),
+            aperture: None,
+            interpolation: None,
+            position: None,
+            attributes: Vec::new(),
+        }
+    }
+
+    /// Coordinate in output units with six decimals, as an integer
+    fn value(&mut self, mm: f64) -> i64 {
+        let value = mm / self.units.to_mm();
+        self.extent = self.extent.max(value.abs());
+        (value * 1e6).round() as i64
+    }
+
+    fn polarity(&mut self, polarity: Polarity) {
+        if self.polarity != Some(polarity) {
+            let code = match polarity {
+                Polarity::Dark => "D",
+                Polarity::Clear => "C",
+            };
+            let _ = writeln!(self.out, "%LP{}*%", code);
+            self.polarity = Some(polarity);
+        }
+    }
+
+    fn aperture_transform(&mut self, transform: ApertureTransform) {
+        let current = self.aperture_transform;
+        if (current.mirror_x, current.mirror_y) != (transform.mirror_x, transform.mirror_y) {
+            let mirror = match (transform.mirror_x, transform.mirror_y) {
+                (false, false) => "N",
+                (true, false) => "X",
+                (false, true) => "Y",
+                (true, true) => "XY",
+            };
+            let _ = writeln!(self.out, "%LM{}*%", mirror);
+        }
+        if decimal(current.rotation) != decimal(transform.rotation) {
+            let _ = writeln!(self.out, "%LR{}*%", decimal(transform.rotation));
+        }
+        if decimal(current.scale) != decimal(transform.scale) {
+            let _ = writeln!(self.out, "%LS{}*%", decimal(transform.scale));
+        }
+        self.aperture_transform = transform;
+    }
+
+    fn object_attributes(&mut self, attributes: &[(String, Vec<String>)]) {
+        let wanted: Vec<(String, Vec<String>)> = attributes.iter()
+            .filter(|(name, _)| OBJECT_ATTRIBUTES.contains(&name.as_str()))
+            .cloned()
+            .collect();
+        if wanted == self.attributes {
+            return;
+        }
+        if wanted.is_empty() {
+            let _ = writeln!(self.out, "%TD*%");
+        } else {
+            for (name, _) in &self.attributes {
+                if !wanted.iter().any(|(key, _)| key == name) {
+                    let _ = writeln!(self.out, "%TD{}*%", name);
+                }
+            }
+            for attribute in &wanted {
+                if !self.attributes.contains(attribute) {
+                    let (name, values) = attribute;
+                    let _ = writeln!(self.out, "%TO{}*%", std::iter::once(name).chain(values).cloned().collect::<Vec<_>>().join(","));
+                }
+            }
+        }
+        self.attributes = wanted;
+    }
+
+    fn select(&mut self, code: i32) {
+        if self.aperture != Some(code) {
+            let _ = writeln!(self.out, "D{}*", code);
+            self.aperture = Some(code);
+        }
+    }
+
+    fn interpolation(&mut self, mode: &'static str) {
+        if self.interpolation != Some(mode) {
+            let _ = writeln!(self.out, "{}*", mode);
+            self.interpolation = Some(mode);
+        }
+    }
+
+    fn move_to(&mut self, point: Point2<f64>) {
+        let target = (self.value(point.x), self.value(point.y));
+        if self.position != Some(target) {
+            let _ = writeln!(self.out, "X{}Y{}D02*", target.0, target.1);
+            self.position = Some(target);
+        }
+    }
+
+    fn line_to(&mut self, point: Point2<f64>) {
+        self.interpolation("G01");
+        let target = (self.value(point.x), self.value(point.y));
+        let _ = writeln!(self.out, "X{}Y{}D01*", target.0, target.1);
+        self.position = Some(target);
+    }
+
+    fn arc_to(&mut self, start: Point2<f64>, end: Point2<f64>, center: Point2<f64>, clockwise: bool) {
+        self.interpolation(if clockwise { "G02" } else { "G03" });
+        let target = (self.value(end.x), self.value(end.y));
+        let i = self.value(center.x - start.x);
+        let j = self.value(center.y - start.y);
+        let _ = writeln!(self.out, "X{}Y{}I{}J{}D01*", target.0, target.1, i, j);
+        self.position = Some(target);
+    }
+
+    fn flash(&mut self, point: Point2<f64>) {
+        let target = (self.value(point.x), self.value(point.y));
+        let _ = writeln!(self.out, "X{}Y{}D03*", target.0, target.1);
+        self.position = Some(target);
+    }
+
+    fn feature(&mut self, feature: &GerberFeature, transform: &LayerTransform, aperture: Option<(i32, f64)>) {
+        self.polarity(feature.polarity);
+        self.object_attributes(&feature.attributes);
+
+        if let FeatureKind::Region { points } = &feature.kind {
+            if let Some(function) = &feature.function {
+                let _ = writeln!(self.out, "%TA.AperFunction,{}*%", function);
+            }
+            let _ = writeln!(self.out, "G36*");
+            let points: Vec<Point2<f64>> = points.iter().map(|p| transform.apply(*p)).collect();
+            self.position = None;
+            self.move_to(points[0]);
+            for point in points.iter().skip(1).chain(std::iter::once(&points[0])) {
+                self.line_to(*point);
+            }
+            let _ = writeln!(self.out, "G37*");
+            // Only the aperture function, object attributes stay set
+            if feature.function.is_some() {
+                let _ = writeln!(self.out, "%TD.AperFunction*%");
+            }
+            return;
+        }
+
+        let Some((code, extra_scale)) = aperture else {
+            return;
+        };
+        let mut aperture_transform = transform.compose(&feature.aperture_transform);
+        aperture_transform.scale *= extra_scale;
+        self.aperture_transform(aperture_transform);
+        self.select(code);
+
+        match &feature.kind {
+            FeatureKind::Flash { position } => self.flash(transform.apply(*position)),
+            FeatureKind::Line { start, end } => {
+                self.move_to(transform.apply(*start));
+                self.line_to(transform.apply(*end));
+            }
+            FeatureKind::Arc { start, end, center, clockwise } if transform.is_conformal() => {
+                let clockwise = *clockwise != transform.is_mirrored();
+                let (start, end, center) = (transform.apply(*start), transform.apply(*end), transform.apply(*center));
+                self.move_to(start);
+                self.arc_to(start, end, center, clockwise);
+            }
+            FeatureKind::Arc { start, end, center, clockwise } => {
+                // A stretched arc is no longer circular, write it as line segments
+                let points = shapes::arc_points(*start, *end, *center, *clockwise, DEFAULT_TOLERANCE);
+                self.move_to(transform.apply(points[0]));
+                for point in points.iter().skip(1) {
+                    self.line_to(transform.apply(*point));
+                }
+            }
+            FeatureKind::Region { .. } => {}
+        }
+    }
+}
+
+/// Write layers as one RS-274X file, each through its own transform
+/// Units are kept when all layers share them, otherwise the file is in mm.
+pub fn write_gerber(layers: &[(&LayerGeometry, LayerTransform)]) -> Result<(String, GerberReport), String> {
+    if layers.is_empty() {
+        return Err("No layers to write".to_string());
+    }
+    let units = if layers.iter().all(|(geometry, _)| geometry.units == layers[0].0.units) {
+        layers[0].0.units
+    } else {
+        Units::Millimeters
+    };
+    let mut report = GerberReport::default();
+
+    let mut dictionary = Dictionary::default();
+    for (index, (geometry, _)) in layers.iter().enumerate() {
+        for aperture in geometry.apertures.values() {
+            dictionary.add_aperture(index, geometry, aperture, units)?;
+        }
+    }
+
+    let mut body = BodyWriter::new(units);
+    for (index, (geometry, transform)) in layers.iter().enumerate() {
+        if !transform.is_conformal() {
+            report.warnings.push("Layer transform stretches the image, aperture shapes are written unstretched".to_string());
+        }
+        for feature in &geometry.features {
+            let aperture = feature.aperture.and_then(|code| dictionary.mapping.get(&(index, code)).copied());
+            if feature.aperture.is_some() && aperture.is_none() {
+                let message = format!("Skipped features using undefined aperture D{}", feature.aperture.unwrap_or(0));
+                if !report.warnings.contains(&message) {
+                    report.warnings.push(message);
+                }
+                continue;
+            }
+            if matches!(&feature.kind, FeatureKind::Region { points } if points.len() < 3) {
+                continue;
+            }
+            body.feature(feature, transform, aperture);
+            report.features += 1;
+        }
+    }
+
+    let integer_digits = match units {
+        Units::Millimeters => 4,
+        Units::Inches => 2,
+    }.max(body.extent.trunc().to_string().len());
+    if integer_digits > 6 {
+        return Err(format!("Coordinates up to {} do not fit the Gerber format", decimal(body.extent)));
+    }
+
+    let mut out = String::new();
+    // Attributes shared by every layer describe the merged file too
+    for (name, values) in &layers[0].0.file_attributes {
+        let shared = layers.iter().all(|(geometry, _)| geometry.file_attribute(name) == Some(values.as_slice()));
+        if shared && !REPLACED_ATTRIBUTES.contains(&name.as_str()) {
+            let _ = writeln!(out, "%TF{}*%", std::iter::once(name).chain(values).cloned().collect::<Vec<_>>().join(","));
+        }
+    }
+    let _ = writeln!(out, "%TF.GenerationSoftware,CopperForge,,{}*%", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "%FSLAX{0}6Y{0}6*%", integer_digits);
+    let _ = writeln!(out, "%MO{}*%", match units {
+        Units::Millimeters => "MM",
+        Units::Inches => "IN",
+    });
+
+    for (name, source) in &dictionary.macros {
+        let _ = writeln!(out, "%AM{}*\n{}*%", name, source.trim_end_matches(['*', '\n', '\r']));
+    }
+    for (code, (_, (template, parameters, function))) in &dictionary.apertures {
+        if let Some(function) = function {
+            let _ = writeln!(out, "%TA.AperFunction,{}*%", function);
+        }
+        if parameters.is_empty() {
+            let _ = writeln!(out, "%ADD{}{}*%", code, template);
+        } else {
+            let _ = writeln!(out, "%ADD{}{},{}*%", code, template, parameters);
+        }
+        if function.is_some() {
+            let _ = writeln!(out, "%TD*%");
+        }
+    }
+    report.apertures = dictionary.apertures.len();
+
+    let _ = writeln!(out, "G75*");
+    out.push_str(&body.out);
+    let _ = writeln!(out, "M02*");
+    Ok((out, report))
+}
+
+/// Settings for a Gerber export
+#[derive(Debug, Clone, PartialEq)]
+pub struct GerberExportOptions {
+    pub layers: Vec<LayerType>,
+    /// Bake the viewer rotation and mirroring into the output
+    pub apply_view_transform: bool,
+    /// Move the origin picked in the viewer to 0,0
+    pub relative_to_user_origin: bool,
+    /// One file with all layers instead of one file per layer
+    pub merge: bool,
+}
+
+impl Default for GerberExportOptions {
+    fn default() -> Self {
+        Self {
+            layers: Vec::new(),
+            apply_view_transform: true,
+            relative_to_user_origin: false,
+            merge: false,
+        }
+    }
+}
+
+/// Geometry and transform of each requested layer, in the requested order
+fn collect_layers(app: &mut DemoLensApp, options: &GerberExportOptions) -> Vec<(LayerType, LayerGeometry, LayerTransform)> {
+    let user_origin = Point2::new(app.display_manager.design_offset.x, app.display_manager.design_offset.y);
+    let mut query = app.ecs_world.query::<(&LayerInfo, &LayerGeometryData, &Transform, &ImageTransform)>();
+    let mut layers: Vec<(LayerType, LayerGeometry, LayerTransform)> = query.iter(&app.ecs_world)
+        .filter(|(info, _, _, _)| options.layers.contains(&info.layer_type))
+        .map(|(info, geometry, transform, image_transform)| {
+            let mut layer_transform = if options.apply_view_transform {
+                // Quadrant view spreads layers apart on screen only
+                let mut transform = transform.clone();
+                transform.position = crate::display::VectorOffset { x: 0.0, y: 0.0 };
+                let gerber_transform = crate::ecs::create_gerber_transform_composed(&transform, image_transform, &app.display_manager);
+                LayerTransform::from_fn(|point| gerber_transform.apply_to_position(point))
+            } else {
+                LayerTransform::default()
+            };
+            if options.relative_to_user_origin {
+                layer_transform.offset -= layer_transform.apply(user_origin).coords;
+            }
+            (info.layer_type, geometry.0.clone(), layer_transform)
+        })
+        .collect();
+    layers.sort_by_key(|(layer_type, _, _)| options.layers.iter().position(|t| t == layer_type));
+    layers
+}
+
+/// Export the chosen layers as Gerber
+/// `output` is the file when merging, otherwise the directory for one file per layer.
+pub fn export_gerbers(app: &mut DemoLensApp, output: &Path, options: &GerberExportOptions) -> Result<(Vec<PathBuf>, GerberReport), String> {
+    let layers = collect_layers(app, options);
+    if layers.is_empty() {
+        return Err("None of the chosen layers are loaded".to_string());
+    }
+
+    if options.merge {
+        let inputs: Vec<(&LayerGeometry, LayerTransform)> = layers.iter()
+            .map(|(_, geometry, transform)| (geometry, *transform))
+            .collect();
+        let (content, report) = write_gerber(&inputs)?;
+        std::fs::write(output, content)
+            .map_err(|e| format!("Failed to write {}: {}", output.display(), e))?;
+        return Ok((vec![output.to_path_buf()], report));
+    }
+
+    let mut files = Vec::new();
+    let mut total = GerberReport::default();
+    for (layer_type, geometry, transform) in &layers {
+        let (content, report) = write_gerber(&[(geometry, *transform)])?;
+        let path = output.join(format!("{}.gbr", layer_file_stem(layer_type)));
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        total.features += report.features;
+        total.apertures += report.apertures;
+        for warning in report.warnings {
+            if !total.warnings.contains(&warning) {
+                total.warnings.push(warning);
+            }
+        }
+        files.push(path);
+    }
+    Ok((files, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+
+    const PADS: &str = "%TF.FileFunction,Copper,L1,Top*%\n%TF.GenerationSoftware,KiCad,Pcbnew,8.0*%\n\
+        %FSLAX46Y46*%\n%MOMM*%\n\
+        %AMRoundRect*\n0 comment*\n21,1,$1,$2,0,0,0*%\n\
+        %TA.AperFunction,SMDPad,CuDef*%\n%ADD10R,2.000000X1.000000*%\n%TD*%\n\
+        %ADD11C,0.250000*%\n%ADD12RoundRect,1.5X0.5*%\n\
+        D10*\nX5000000Y0D03*\nD11*\nX0Y0D02*\nX5000000Y0D01*\n\
+        G75*\nG03X0Y5000000I-5000000J0D01*\nD12*\nX1000000Y1000000D03*\n\
+        G36*\nX0Y0D02*\nG01*\nX1000000Y0D01*\nX1000000Y1000000D01*\nX0Y0D01*\nG37*\nM02*\n";
+
+    fn aperture_bounds(geometry: &LayerGeometry, feature: &GerberFeature) -> (Point2<f64>, Point2<f64>) {
+        let FeatureKind::Flash { position } = feature.kind else { panic!("not a flash") };
+        let aperture = &geometry.apertures[&feature.aperture.unwrap()];
+        let points: Vec<Point2<f64>> = aperture.polygons(0.001).iter()
+            .flat_map(|polygon| polygon.points.clone())
+            .map(|p| feature.aperture_transform.apply(p) + position.coords)
+            .collect();
+        shapes::points_bounds(&points).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_identity() {
+        let original = parse_gerber(PADS).unwrap();
+        let (content, report) = write_gerber(&[(&original, LayerTransform::default())]).unwrap();
+        assert_eq!(report.features, original.features.len());
+        assert!(content.contains("%TF.FileFunction,Copper,L1,Top*%"));
+        assert!(!content.contains("KiCad"));
+
+        let written = parse_gerber(&content).unwrap();
+        assert!(written.warnings.is_empty(), "{:?}", written.warnings);
+        assert_eq!(written.apertures, original.apertures);
+        assert_eq!(written.features.len(), original.features.len());
+        for (a, b) in written.features.iter().zip(&original.features) {
+            assert_eq!(a, b);
+        }
+    }
+
+    #[test]
+    fn test_object_attributes_round_trip() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\n%ADD11R,1.000000X1.000000*%\n\
+            D11*\n%TO.P,U1,3,GPIO*%\n%TO.N,/SDA*%\n%TO.C,U1*%\n%TO.CRot,90*%\nX0Y0D03*\n%TD.P*%\n\
+            D10*\nX0Y0D02*\nX5000000Y0D01*\n%TD*%\nX9000000Y0D01*\n\
+            %TO.N,GND*%\n%TA.AperFunction,Conductor*%\nG36*\nX0Y0D02*\nX1000000Y0D01*\nX1000000Y1000000D01*\nX0Y0D01*\nG37*\n\
+            %TD.AperFunction*%\nX9000000Y9000000D02*\nX9500000Y9000000D01*\nM02*\n";
+        let original = parse_gerber(content).unwrap();
+        let (written, _) = write_gerber(&[(&original, LayerTransform::default())]).unwrap();
+        assert!(!written.contains(".CRot"));
+        let written = parse_gerber(&written).unwrap();
+
+        let kept = |attributes: &[(String, Vec<String>)]| -> Vec<(String, Vec<String>)> {
+            let mut kept: Vec<_> = attributes.iter()
+                .filter(|(name, _)| OBJECT_ATTRIBUTES.contains(&name.as_str()))
+                .cloned()
+                .collect();
+            kept.sort();
+            kept
+        };
+        assert_eq!(written.features.len(), original.features.len());
+        for (a, b) in written.features.iter().zip(&original.features) {
+            assert_eq!(kept(&a.attributes), kept(&b.attributes));
+            assert_eq!(a.function, b.function);
+        }
+        assert_eq!(written.features[0].pin(), Some(("U1", "3")));
+        assert_eq!(written.features[1].net(), Some("/SDA"));
+        assert!(written.features[2].attributes.is_empty());
+        assert_eq!(written.features[4].net(), Some("GND"));
+    }
+
+    #[test]
+    fn test_rotated_and_mirrored() {
+        let original = parse_gerber(PADS).unwrap();
+        // Rotate 90° counter-clockwise, then mirror left to right
+        let transform = LayerTransform::from_fn(|p| Point2::new(p.y, p.x));
+        let (content, _) = write_gerber(&[(&original, transform)]).unwrap();
+        let written = parse_gerber(&content).unwrap();
+
+        let flashes = |geometry: &LayerGeometry| geometry.features.iter()
+            .filter(|f| matches!(f.kind, FeatureKind::Flash { .. }))
+            .map(|f| aperture_bounds(geometry, f))
+            .collect::<Vec<_>>();
+        for ((min, max), (original_min, original_max)) in flashes(&written).into_iter().zip(flashes(&original)) {
+            let expected = shapes::points_bounds(&[transform.apply(original_min), transform.apply(original_max)]).unwrap();
+            assert!((min - expected.0).norm() < 1e-6 && (max - expected.1).norm() < 1e-6);
+        }
+
+        // The arc keeps its sweep: mirrored images turn counter-clockwise arcs clockwise
+        let arc = written.features.iter().find_map(|f| match f.kind {
+            FeatureKind::Arc { start, end, clockwise, .. } => Some((start, end, clockwise)),
+            _ => None,
+        }).unwrap();
+        assert!((arc.0 - Point2::new(0.0, 5.0)).norm() < 1e-6);
+        assert!((arc.1 - Point2::new(5.0, 0.0)).norm() < 1e-6);
+        assert!(arc.2);
+    }
+
+    #[test]
+    fn test_merge_renumbers_and_converts_units() {
+        let copper = parse_gerber(PADS).unwrap();
+        let inch = "%FSLAX26Y26*%\n%MOIN*%\n%ADD10C,0.010000*%\nD10*\nX1000000Y0D03*\nM02*\n";
+        let other = parse_gerber(inch).unwrap();
+
+        let (content, report) = write_gerber(&[(&copper, LayerTransform::default()), (&other, LayerTransform::default())]).unwrap();
+        assert_eq!(report.apertures, 4);
+        assert!(!content.contains("FileFunction"));
+        let merged = parse_gerber(&content).unwrap();
+        assert_eq!(merged.units, Units::Millimeters);
+
+        let last = merged.features.last().unwrap();
+        let FeatureKind::Flash { position } = last.kind else { panic!("not a flash") };
+        assert!((position - Point2::new(25.4, 0.0)).norm() < 1e-9);
+        let aperture = &merged.apertures[&last.aperture.unwrap()];
+        assert_ne!(aperture.d_code, 10);
+        assert_eq!(aperture.shape, ApertureShape::Circle { diameter: 0.254, hole: None });
+    }
+}
@@ -1,17 +1,209 @@
-use std::path::PathBuf;
+pub mod svg;
+pub mod drawings;
+pub mod placement;
+pub mod bom;
+pub mod package;
+pub mod realistic;
+pub mod dxf;
+pub mod gerber;
+pub mod review;
+pub mod view;
+
+pub use svg::{SvgExporter, SvgOptions};
+pub use drawings::{export_drawings_pdf, DrawingOptions, PageSize, TitleBlock};
+pub use bom::{export_bom, BomColumn, BomExportFormat, BomExportOptions};
+pub use package::{build_fab_package, FabHouse, PackageOptions, PackageReport};
+pub use dxf::{export_dxf, DxfFeatureMode, DxfOptions, DxfReport};
+pub use gerber::{export_gerbers, write_gerber, GerberExportOptions, GerberReport};
+pub use review::{export_review_html, generate_review_html};
+pub use view::{render_view_image, render_view_svg, ViewExportOptions, ViewOverlays, ViewRegion};
+pub use realistic::{export_realistic_png, RealisticBoard, RealisticOptions};
+pub use placement::{export_placement, PlacementFormat, PlacementOptions, PlacementOrigin, PlacementUnit, RotationOffset};
+
+use std::path::{Path, PathBuf};
+use bevy_ecs::prelude::*;
 use image::{ImageBuffer, Rgba, RgbaImage};
 use eframe::emath::{Rect, Vec2};
-use egui::Pos2;
+use egui::{Color32, Pos2};
 use gerber_viewer::{ViewState, BoundingBox, GerberTransform};
 use crate::{DemoLensApp, ecs::LayerType};
-use crate::display::VectorOffset;
+use crate::display::{DisplayManager, VectorOffset};
+use crate::ecs::{BoardComponent, LayerInfo, LayerGeometryData, Visibility, RenderProperties, Transform, ImageTransform};
+use crate::geometry::{rasterize_layer, Antialias, CoverageMask, LayerGeometry, RasterView};
+use crate::project_manager::bom::BomComponent;
 use nalgebra::{Vector2, Point2};
 
+/// Settings for rasterized (PNG) exports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterOptions {
+    pub dpi: f64,
+    pub antialias: Antialias,
+    pub background: Color32,
+    /// Blank border around the board (mm)
+    pub margin: f64,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            dpi: 600.0,
+            antialias: Antialias::Low,
+            background: Color32::BLACK,
+            margin: 1.0,
+        }
+    }
+}
+
+/// A visible layer prepared for export, with the transform used on screen
+#[derive(Clone)]
+pub struct ExportLayer {
+    pub layer_type: LayerType,
+    pub geometry: LayerGeometry,
+    pub color: Color32,
+    pub opacity: f32,
+    pub transform: GerberTransform,
+}
+
+impl ExportLayer {
+    /// Bounds of the layer after its transform, as (min, max)
+    pub fn bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        let (min, max) = self.geometry.bounds()?;
+        let corners = [min, Point2::new(max.x, min.y), max, Point2::new(min.x, max.y)]
+            .map(|corner| self.transform.apply_to_position(corner));
+        crate::geometry::shapes::points_bounds(&corners)
+    }
+}
+
+/// Collect the visible layers in z-order, as they are drawn in the viewer
+pub fn collect_export_layers(world: &mut World, display_manager: &DisplayManager) -> Vec<ExportLayer> {
+    let mut query = world.query::<(&LayerInfo, &LayerGeometryData, &Visibility, &RenderProperties, &Transform, &ImageTransform)>();
+    let mut layers: Vec<(i32, ExportLayer)> = query.iter(world)
+        .filter(|(_, _, visibility, _, _, _)| visibility.visible)
+        .map(|(info, geometry, visibility, props, transform, image_transform)| (props.z_order, ExportLayer {
+            layer_type: info.layer_type,
+            geometry: geometry.0.clone(),
+            color: props.color,
+            opacity: visibility.opacity,
+            transform: crate::ecs::create_gerber_transform_composed(transform, image_transform, display_manager),
+        }))
+        .collect();
+    layers.sort_by_key(|(z_order, _)| *z_order);
+    layers.into_iter().map(|(_, layer)| layer).collect()
+}
+
+/// Combined bounds of the given layers
+pub fn export_layers_bounds(layers: &[ExportLayer]) -> Option<(Point2<f64>, Point2<f64>)> {
+    let corners: Vec<Point2<f64>> = layers.iter()
+        .filter_map(|layer| layer.bounds())
+        .flat_map(|(min, max)| [min, max])
+        .collect();
+    crate::geometry::shapes::points_bounds(&corners)
+}
+
+/// File name used for a single layer export, e.g. "top_copper"
+pub fn layer_file_stem(layer_type: &LayerType) -> String {
+    layer_type.display_name().replace(" ", "_").to_lowercase()
+}
+
+/// BOM components from the live BOM panel, the current project, or else an imported board
+pub fn loaded_bom_components(app: &DemoLensApp) -> Vec<BomComponent> {
+    app.bom_state.as_ref()
+        .map(|state| state.components.lock().unwrap().clone())
+        .filter(|components| !components.is_empty())
+        .or_else(|| app.project_manager_state.as_ref()
+            .and_then(|state| state.current_project.as_ref())
+            .map(|project| project.bom_components.clone())
+            .filter(|components| !components.is_empty()))
+        .unwrap_or_else(|| app.ecs_world.iter_entities()
+            .filter_map(|entity| entity.get::<BoardComponent>().map(|component| component.0.clone()))
+            .collect())
+}
+
 #[allow(dead_code)]
 pub struct PngExporter;
 
 #[allow(dead_code)]
 impl PngExporter {
+    /// Rasterize layers bottom to top into one image covering all of them
+    pub fn render_layers(layers: &[ExportLayer], options: &RasterOptions) -> Result<RgbaImage, String> {
+        let (min, max) = export_layers_bounds(layers).ok_or("No layer geometry to export")?;
+        let view = Self::raster_view(min, max, options)?;
+        let mut img = Self::background_image(&view, options);
+        for layer in layers {
+            let mask = rasterize_layer(&layer.geometry, &view, options.antialias, |p| layer.transform.apply_to_position(p));
+            Self::blend_mask(&mut img, &mask, layer.color, layer.opacity);
+        }
+        Ok(img)
+    }
+
+    /// Pixel view for board bounds, refusing images too large to allocate
+    fn raster_view(min: Point2<f64>, max: Point2<f64>, options: &RasterOptions) -> Result<RasterView, String> {
+        let view = RasterView::from_bounds(min, max, options.dpi, options.margin);
+        if view.width as u64 * view.height as u64 > 400_000_000 {
+            return Err(format!("Image of {}x{} pixels is too large, lower the DPI", view.width, view.height));
+        }
+        Ok(view)
+    }
+
+    fn background_image(view: &RasterView, options: &RasterOptions) -> RgbaImage {
+        let background = options.background;
+        ImageBuffer::from_pixel(
+            view.width,
+            view.height,
+            Rgba([background.r(), background.g(), background.b(), background.a()]),
+        )
+    }
+
+    /// Draw a coverage mask over the image in a color
+    fn blend_mask(img: &mut RgbaImage, mask: &CoverageMask, color: Color32, opacity: f32) {
+        let alpha = opacity.clamp(0.0, 1.0) * color.a() as f32 / 255.0;
+        let rgb = [color.r() as f32, color.g() as f32, color.b() as f32];
+        for (pixel, &coverage) in img.pixels_mut().zip(mask.data.iter()) {
+            let a = coverage * alpha;
+            if a <= 0.0 {
+                continue;
+            }
+            for channel in 0..3 {
+                pixel.0[channel] = (pixel.0[channel] as f32 * (1.0 - a) + rgb[channel] * a).round() as u8;
+            }
+            pixel.0[3] = (pixel.0[3] as f32 + (255.0 - pixel.0[3] as f32) * a).round() as u8;
+        }
+    }
+
+    /// Export all visible layers as one composite PNG
+    pub fn export_composite_png(app: &mut DemoLensApp, output_path: &Path, options: &RasterOptions) -> Result<(), String> {
+        let layers = collect_export_layers(&mut app.ecs_world, &app.display_manager);
+        if layers.is_empty() {
+            return Err("No visible layers to export".to_string());
+        }
+        let img = Self::render_layers(&layers, options)?;
+        println!("Exporting composite of {} layers to {:?} ({}x{} px at {} DPI)",
+                 layers.len(), output_path, img.width(), img.height(), options.dpi);
+        img.save(output_path).map_err(|e| format!("Failed to save PNG: {}", e))
+    }
+
+    /// Export each visible layer as its own PNG, all with the same board bounds
+    pub fn export_layer_pngs(app: &mut DemoLensApp, output_dir: &Path, options: &RasterOptions) -> Result<Vec<PathBuf>, String> {
+        std::fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+        let layers = collect_export_layers(&mut app.ecs_world, &app.display_manager);
+        let (min, max) = export_layers_bounds(&layers).ok_or("No visible layers to export")?;
+
+        // Shared bounds keep the images aligned with each other
+        let view = Self::raster_view(min, max, options)?;
+        let mut exported_files = Vec::new();
+        for layer in &layers {
+            let mut img = Self::background_image(&view, options);
+            let mask = rasterize_layer(&layer.geometry, &view, options.antialias, |p| layer.transform.apply_to_position(p));
+            Self::blend_mask(&mut img, &mask, layer.color, 1.0);
+
+            let output_path = output_dir.join(format!("{}.png", layer_file_stem(&layer.layer_type)));
+            img.save(&output_path).map_err(|e| format!("Failed to save PNG: {}", e))?;
+            exported_files.push(output_path);
+        }
+        Ok(exported_files)
+    }
+
     /// Export each layer in quadrant view as a separate PNG file
     pub fn export_quadrant_layers(app: &mut DemoLensApp, output_dir: &PathBuf, width: u32, height: u32) -> Result<Vec<PathBuf>, String> {
         if !app.display_manager.quadrant_view_enabled {
@@ -21,39 +213,41 @@ impl PngExporter {
         std::fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
         
         // Get mechanical outline layer using ECS - this defines the consistent bounding box for all exports
-        let (mechanical_outline_gerber, master_bbox) = {
+        let master_bbox = {
             let mechanical_outline_data = crate::ecs::get_layer_data(&mut app.ecs_world, LayerType::MechanicalOutline)
                 .ok_or("Mechanical outline layer is required for consistent PNG export boundaries")?;
             let gerber_layer = mechanical_outline_data.2.0.clone();
-            let bbox = Self::calculate_master_bounding_box(app, &gerber_layer)?;
-            (gerber_layer, bbox)
+            Self::calculate_master_bounding_box(app, &gerber_layer)?
         };
         
         let mut exported_files = Vec::new();
         
-        // Collect visible layers data first to avoid borrowing conflicts
+        // Collect visible layer geometry first to avoid borrowing conflicts
+        let mut mechanical_outline = None;
         let mut layers_to_export = Vec::new();
-        for layer_type in LayerType::all() {
-            if let Some((_entity, _layer_info, gerber_data, visibility)) = crate::ecs::get_layer_data(&mut app.ecs_world, layer_type) {
-                if visibility.visible && layer_type != LayerType::MechanicalOutline {
-                    // Skip if layer shouldn't render for current view
-                    if layer_type.should_render(app.display_manager.showing_top) {
-                        layers_to_export.push((layer_type, gerber_data.0.clone()));
-                    }
+        {
+            let mut query = app.ecs_world.query::<(&LayerInfo, &LayerGeometryData, &Visibility)>();
+            for (layer_info, geometry, visibility) in query.iter(&app.ecs_world) {
+                let layer_type = layer_info.layer_type;
+                if layer_type == LayerType::MechanicalOutline {
+                    mechanical_outline = Some(geometry.0.clone());
+                } else if visibility.visible && layer_type.should_render(app.display_manager.showing_top) {
+                    layers_to_export.push((layer_type, geometry.0.clone()));
                 }
             }
         }
+        layers_to_export.sort_by_key(|(layer_type, _)| layer_file_stem(layer_type));
         
         // Now export each layer without borrowing conflicts
-        for (layer_type, gerber_layer) in layers_to_export {
-            let filename = format!("{}.png", layer_type.display_name().replace(" ", "_").to_lowercase());
+        for (layer_type, geometry) in layers_to_export {
+            let filename = format!("{}.png", layer_file_stem(&layer_type));
             let output_path = output_dir.join(&filename);
             
             Self::export_single_layer_with_bbox(
                 app,
-                &gerber_layer,
+                &geometry,
                 &layer_type,
-                Some(&mechanical_outline_gerber),
+                mechanical_outline.as_ref(),
                 &master_bbox,
                 &output_path,
                 width,
@@ -99,9 +293,9 @@ impl PngExporter {
     /// Export a single layer to PNG using the consistent master bounding box
     fn export_single_layer_with_bbox(
         app: &DemoLensApp,
-        gerber_layer: &gerber_viewer::GerberLayer,
+        geometry: &LayerGeometry,
         layer_type: &LayerType,
-        mechanical_outline: Option<&gerber_viewer::GerberLayer>,
+        mechanical_outline: Option<&LayerGeometry>,
         master_bbox: &BoundingBox,
         output_path: &PathBuf,
         width: u32,
@@ -137,13 +331,11 @@ impl PngExporter {
         // Render the gerber layer to the image buffer
         Self::render_gerber_to_image(
             app,
-            gerber_layer,
+            geometry,
             layer_type,
             mechanical_outline,
             &view_state,
             &mut img,
-            width,
-            height,
         )?;
         
         // Save the image
@@ -152,164 +344,40 @@ impl PngExporter {
         Ok(())
     }
     
-    /// Render gerber layer to image buffer using a simplified approach
+    /// Rasterize the layer geometry into the image buffer, with the outline drawn on top
     fn render_gerber_to_image(
         app: &DemoLensApp,
-        _gerber_layer: &gerber_viewer::GerberLayer,
+        geometry: &LayerGeometry,
         layer_type: &LayerType,
-        mechanical_outline: Option<&gerber_viewer::GerberLayer>,
-        _view_state: &ViewState,
+        mechanical_outline: Option<&LayerGeometry>,
+        view_state: &ViewState,
         img: &mut RgbaImage,
-        width: u32,
-        height: u32,
     ) -> Result<(), String> {
-        // Note: Layer positions are managed by the DisplayManager
-        
-        // Get quadrant offset for this layer type - this is the key positioning info
-        let quadrant_offset = app.display_manager.get_quadrant_offset(layer_type);
-        
-        println!("🎯 Exporting {} with quadrant offset: ({:.1}, {:.1})", 
-                layer_type.display_name(), quadrant_offset.x, quadrant_offset.y);
-        
-        let layer_color = layer_type.color();
-        let rgba = [layer_color.r(), layer_color.g(), layer_color.b(), layer_color.a()];
-        
-        // Draw the layer representation using the quadrant offset directly
-        Self::draw_layer_in_quadrant(img, layer_type, &quadrant_offset, &rgba, width, height);
-        
-        // Draw mechanical outline border at image edge for reference  
-        if let Some(_outline_layer) = mechanical_outline {
-            let outline_color = LayerType::MechanicalOutline.color();
-            let outline_rgba = [outline_color.r(), outline_color.g(), outline_color.b(), outline_color.a()];
-            
-            // Draw simple border around entire image
-            let margin = 20;
-            Self::draw_rectangle_border(img, margin, margin, width - margin, height - margin, &outline_rgba, 2);
-        }
-        
-        Ok(())
-    }
-    
-    /// Draw layer in its proper quadrant using simple, direct positioning
-    fn draw_layer_in_quadrant(
-        img: &mut RgbaImage,
-        layer_type: &LayerType,
-        quadrant_offset: &VectorOffset,
-        color: &[u8; 4],
-        width: u32,
-        height: u32,
-    ) {
-        println!("  🎨 Drawing {} in quadrant at offset ({:.1}, {:.1})", 
-                layer_type.display_name(), quadrant_offset.x, quadrant_offset.y);
-        
-        // Simple quadrant mapping - divide image into 4 sections
-        let half_width = width / 2;
-        let half_height = height / 2;
-        let quarter_width = width / 4;
-        let quarter_height = height / 4;
-        
-        // Determine quadrant based on offset signs (from DisplayManager::get_quadrant_offset)
-        let (quadrant_x, quadrant_y) = if quadrant_offset.x > 0.0 && quadrant_offset.y > 0.0 {
-            // Quadrant 1 (top-right): Copper
-            (half_width + quarter_width / 2, quarter_height / 2)
-        } else if quadrant_offset.x < 0.0 && quadrant_offset.y > 0.0 {
-            // Quadrant 2 (top-left): Silkscreen  
-            (quarter_width / 2, quarter_height / 2)
-        } else if quadrant_offset.x < 0.0 && quadrant_offset.y < 0.0 {
-            // Quadrant 3 (bottom-left): Soldermask
-            (quarter_width / 2, half_height + quarter_height / 2)
-        } else if quadrant_offset.x > 0.0 && quadrant_offset.y < 0.0 {
-            // Quadrant 4 (bottom-right): Paste
-            (half_width + quarter_width / 2, half_height + quarter_height / 2)
-        } else {
-            // Center (mechanical outline)
-            (half_width, half_height)
+        let view = RasterView {
+            scale: view_state.scale as f64,
+            translation: (view_state.translation.x as f64, view_state.translation.y as f64),
+            width: img.width(),
+            height: img.height(),
         };
         
-        // Draw layer representation in the calculated quadrant
-        let size = quarter_width.min(quarter_height) as u32;
-        let x1 = quadrant_x.saturating_sub(size / 2);
-        let y1 = quadrant_y.saturating_sub(size / 2);
-        let x2 = (quadrant_x + size / 2).min(width);
-        let y2 = (quadrant_y + size / 2).min(height);
+        // Every layer is drawn at the outline position so it lines up with the master bounding box
+        let transform = Self::layer_transform(app, &LayerType::MechanicalOutline);
+        let antialias = Antialias::Low;
         
-        println!("  📍 Quadrant position: ({}, {}) -> Rectangle ({}, {}) to ({}, {})", 
-                quadrant_x, quadrant_y, x1, y1, x2, y2);
+        let mask = rasterize_layer(geometry, &view, antialias, |p| transform.apply_to_position(p));
+        Self::blend_mask(img, &mask, layer_type.color(), 1.0);
         
-        // Draw based on layer type
-        match layer_type {
-            LayerType::Copper(_) => {
-                Self::fill_rectangle(img, x1, y1, x2, y2, color);
-            },
-            LayerType::Silkscreen(_) => {
-                // Draw silkscreen as text-like patterns
-                Self::draw_silkscreen_pattern(img, (x1, y1), (x2, y2), color);
-            },
-            LayerType::Soldermask(_) => {
-                // Draw soldermask as mostly filled with some openings
-                Self::fill_rectangle_with_openings(img, x1, y1, x2, y2, color);
-            },
-            LayerType::Paste(_) => {
-                // Draw paste as small squares/dots
-                Self::draw_paste_pattern(img, (x1, y1), (x2, y2), color);
-            },
-            LayerType::MechanicalOutline => {
-                // This is handled separately
-            }
+        // Draw mechanical outline for reference
+        if let Some(outline) = mechanical_outline {
+            let mask = rasterize_layer(outline, &view, antialias, |p| transform.apply_to_position(p));
+            Self::blend_mask(img, &mask, LayerType::MechanicalOutline.color(), 1.0);
         }
-    }
-    
-    /// Draw mechanical outline as border
-    fn draw_outline_representation(
-        img: &mut RgbaImage,
-        _bbox: &BoundingBox,
-        _offset: &VectorOffset,
-        _view_state: &ViewState,
-        color: &[u8; 4],
-        width: u32,
-        height: u32,
-    ) {
-        println!("  Drawing mechanical outline");
-        
-        // Draw outline as a border around the same area as the layers
-        let margin = 200;
-        let x1 = margin;
-        let y1 = margin;
-        let x2 = width - margin;
-        let y2 = height - margin;
-        
-        Self::draw_rectangle_border(img, x1, y1, x2, y2, color, 5);
-    }
-    
-    /// Convert gerber coordinates to screen coordinates
-    fn gerber_to_screen_coords(
-        gerber_x: f64,
-        gerber_y: f64,
-        view_state: &ViewState,
-        img_width: u32,
-        img_height: u32,
-    ) -> (u32, u32) {
-        let screen_x = view_state.translation.x + (gerber_x as f32 * view_state.scale);
-        let screen_y = view_state.translation.y - (gerber_y as f32 * view_state.scale); // Y is flipped
-        
-        let x = screen_x.clamp(0.0, img_width as f32 - 1.0) as u32;
-        let y = screen_y.clamp(0.0, img_height as f32 - 1.0) as u32;
         
-        // Debug output to see what coordinates we're getting
-        println!("  Gerber ({:.2}, {:.2}) -> Screen ({}, {})", gerber_x, gerber_y, x, y);
-        
-        (x, y)
+        Ok(())
     }
     
-    /// Calculate the transformed bounding box for a layer including all transformations
-    fn calculate_transformed_bounding_box(
-        app: &DemoLensApp,
-        gerber_layer: &gerber_viewer::GerberLayer,
-        layer_type: &LayerType,
-    ) -> Result<BoundingBox, String> {
-        // Get the original bounding box
-        let original_bbox = gerber_layer.bounding_box().clone();
-        
+    /// Transform of a layer as rendered in quadrant view
+    fn layer_transform(app: &DemoLensApp, layer_type: &LayerType) -> GerberTransform {
         // Get quadrant offset for this layer type
         let quadrant_offset = app.display_manager.get_quadrant_offset(layer_type);
         
@@ -323,13 +391,25 @@ impl PngExporter {
         let origin: Vector2<f64> = app.display_manager.center_offset.clone().into();
         let offset: Vector2<f64> = combined_offset.into();
         
-        let transform = GerberTransform {
+        GerberTransform {
             rotation: app.rotation_degrees.to_radians(),
             mirroring: app.display_manager.mirroring.clone().into(),
             origin: origin - offset,
             offset,
             scale: 1.0,
-        };
+        }
+    }
+    
+    /// Calculate the transformed bounding box for a layer including all transformations
+    fn calculate_transformed_bounding_box(
+        app: &DemoLensApp,
+        gerber_layer: &gerber_viewer::GerberLayer,
+        layer_type: &LayerType,
+    ) -> Result<BoundingBox, String> {
+        // Get the original bounding box
+        let original_bbox = gerber_layer.bounding_box().clone();
+        
+        let transform = Self::layer_transform(app, layer_type);
         
         // Transform all corners of the bounding box
         let corners = original_bbox.vertices();
@@ -387,141 +467,6 @@ impl PngExporter {
         }
     }
     
-    /// Fill a rectangle in the image
-    fn fill_rectangle(img: &mut RgbaImage, x1: u32, y1: u32, x2: u32, y2: u32, color: &[u8; 4]) {
-        let min_x = x1.min(x2);
-        let max_x = x1.max(x2);
-        let min_y = y1.min(y2);
-        let max_y = y1.max(y2);
-        
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                if let Some(pixel) = img.get_pixel_mut_checked(x, y) {
-                    *pixel = Rgba(*color);
-                }
-            }
-        }
-    }
-    
-    /// Draw rectangle border
-    fn draw_rectangle_border(img: &mut RgbaImage, x1: u32, y1: u32, x2: u32, y2: u32, color: &[u8; 4], thickness: u32) {
-        let min_x = x1.min(x2);
-        let max_x = x1.max(x2);
-        let min_y = y1.min(y2);
-        let max_y = y1.max(y2);
-        
-        // Draw border lines
-        for t in 0..thickness {
-            // Top and bottom borders
-            for x in min_x..=max_x {
-                if let Some(pixel) = img.get_pixel_mut_checked(x, min_y + t) {
-                    *pixel = Rgba(*color);
-                }
-                if max_y >= t {
-                    if let Some(pixel) = img.get_pixel_mut_checked(x, max_y - t) {
-                        *pixel = Rgba(*color);
-                    }
-                }
-            }
-            
-            // Left and right borders
-            for y in min_y..=max_y {
-                if let Some(pixel) = img.get_pixel_mut_checked(min_x + t, y) {
-                    *pixel = Rgba(*color);
-                }
-                if max_x >= t {
-                    if let Some(pixel) = img.get_pixel_mut_checked(max_x - t, y) {
-                        *pixel = Rgba(*color);
-                    }
-                }
-            }
-        }
-    }
-    
-    /// Draw trace pattern for copper layers
-    fn draw_trace_pattern(img: &mut RgbaImage, min_screen: (u32, u32), max_screen: (u32, u32), color: &[u8; 4]) {
-        let (min_x, min_y) = min_screen;
-        let (max_x, max_y) = max_screen;
-        
-        // Draw some horizontal and vertical lines to simulate traces
-        let step = 20;
-        
-        for i in (min_y..max_y).step_by(step) {
-            for x in min_x..max_x {
-                if let Some(pixel) = img.get_pixel_mut_checked(x, i) {
-                    *pixel = Rgba(*color);
-                }
-            }
-        }
-        
-        for i in (min_x..max_x).step_by(step) {
-            for y in min_y..max_y {
-                if let Some(pixel) = img.get_pixel_mut_checked(i, y) {
-                    *pixel = Rgba(*color);
-                }
-            }
-        }
-    }
-    
-    /// Draw silkscreen pattern
-    fn draw_silkscreen_pattern(img: &mut RgbaImage, min_screen: (u32, u32), max_screen: (u32, u32), color: &[u8; 4]) {
-        let (min_x, min_y) = min_screen;
-        let (max_x, max_y) = max_screen;
-        
-        // Draw some text-like patterns
-        for y in (min_y..max_y).step_by(10) {
-            for x in (min_x..max_x).step_by(15) {
-                // Draw small rectangles to simulate text
-                for dy in 0..3 {
-                    for dx in 0..8 {
-                        if let Some(pixel) = img.get_pixel_mut_checked(x + dx, y + dy) {
-                            *pixel = Rgba(*color);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
-    /// Draw soldermask with openings
-    fn fill_rectangle_with_openings(img: &mut RgbaImage, x1: u32, y1: u32, x2: u32, y2: u32, color: &[u8; 4]) {
-        let min_x = x1.min(x2);
-        let max_x = x1.max(x2);
-        let min_y = y1.min(y2);
-        let max_y = y1.max(y2);
-        
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                // Create some openings (skip every 25th pixel in a pattern)
-                if (x + y) % 25 != 0 {
-                    if let Some(pixel) = img.get_pixel_mut_checked(x, y) {
-                        *pixel = Rgba(*color);
-                    }
-                }
-            }
-        }
-    }
-    
-    /// Draw paste pattern as dots
-    fn draw_paste_pattern(img: &mut RgbaImage, min_screen: (u32, u32), max_screen: (u32, u32), color: &[u8; 4]) {
-        let (min_x, min_y) = min_screen;
-        let (max_x, max_y) = max_screen;
-        
-        // Draw dots in a grid pattern
-        for y in (min_y..max_y).step_by(8) {
-            for x in (min_x..max_x).step_by(8) {
-                // Draw 3x3 squares
-                for dy in 0..3 {
-                    for dx in 0..3 {
-                        if let Some(pixel) = img.get_pixel_mut_checked(x + dx, y + dy) {
-                            *pixel = Rgba(*color);
-                        }
-                    }
-                }
-            }
-        }
-    }
-    
     /// Calculate the appropriate view state for a single layer
     fn calculate_layer_view_state(
         app: &DemoLensApp,
@@ -553,11 +498,4 @@ impl PngExporter {
             ),
         }
     }
-    
-    /// Alternative approach: Export visible viewport area as PNG
-    pub fn export_current_view(_app: &DemoLensApp, _output_path: &PathBuf, _viewport: &Rect) -> Result<(), String> {
-        // This would require integration with egui's rendering system
-        // For now, we'll suggest using the built-in screenshot functionality
-        Err("Use your OS screenshot tool to capture the current view. Full PNG export will be implemented in a future version.".to_string())
-    }
 }
\ No newline at end of file
@@ -0,0 +1,515 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use nalgebra::Point2;
+use sha2::{Digest, Sha256};
+
+use crate::DemoLensApp;
+use crate::drc_operations::types::DrcViolation;
+use crate::ecs::{DrillFileInfo, LayerInfo, LayerType, RawGerberData, Side};
+use crate::project_manager::bom::BomComponent;
+use super::bom::{custom_field_names, group_components, write_bom_csv, BomColumn};
+use super::placement::{build_placements, write_placements, PlacementFormat, PlacementOptions};
+use super::loaded_bom_components;
+
+// Manufacturing package builder
+// Collects Gerbers, drills, centroid, BOM, a readme and the DRC report into one
+// zip, named after the fab's conventions, with a SHA256SUMS manifest.
+
+/// Fab house whose file naming and centroid layout the package follows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FabHouse {
+    /// KiCad style names (board-F_Cu.gbr)
+    #[default]
+    Generic,
+    Jlcpcb,
+    Pcbway,
+}
+
+impl FabHouse {
+    pub fn all() -> [FabHouse; 3] {
+        [FabHouse::Generic, FabHouse::Jlcpcb, FabHouse::Pcbway]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            FabHouse::Generic => "Generic",
+            FabHouse::Jlcpcb => "JLCPCB",
+            FabHouse::Pcbway => "PCBWay",
+        }
+    }
+
+    pub fn placement_format(&self) -> PlacementFormat {
+        match self {
+            FabHouse::Generic => PlacementFormat::Generic,
+            FabHouse::Jlcpcb => PlacementFormat::Jlcpcb,
+            FabHouse::Pcbway => PlacementFormat::Pcbway,
+        }
+    }
+
+    /// Gerber file name for a layer, `copper_count` is the number of copper layers
+    pub fn gerber_name(&self, base: &str, layer_type: LayerType, copper_count: u8) -> String {
+        match self {
+            FabHouse::Generic => {
+                let suffix = match layer_type {
+                    LayerType::Copper(1) => "F_Cu".to_string(),
+                    LayerType::Copper(n) if n == copper_count => "B_Cu".to_string(),
+                    LayerType::Copper(n) => format!("In{}_Cu", n - 1),
+                    LayerType::Silkscreen(Side::Top) => "F_Silkscreen".to_string(),
+                    LayerType::Silkscreen(Side::Bottom) => "B_Silkscreen".to_string(),
+                    LayerType::Soldermask(Side::Top) => "F_Mask".to_string(),
+                    LayerType::Soldermask(Side::Bottom) => "B_Mask".to_string(),
+                    LayerType::Paste(Side::Top) => "F_Paste".to_string(),
+                    LayerType::Paste(Side::Bottom) => "B_Paste".to_string(),
+                    LayerType::MechanicalOutline => "Edge_Cuts".to_string(),
+                };
+                format!("{}-{}.gbr", base, suffix)
+            }
+            // Protel extensions
+            FabHouse::Jlcpcb | FabHouse::Pcbway => {
+                let extension = match layer_type {
+                    LayerType::Copper(1) => "GTL".to_string(),
+                    LayerType::Copper(n) if n == copper_count => "GBL".to_string(),
+                    LayerType::Copper(n) => format!("G{}", n - 1),
+                    LayerType::Silkscreen(Side::Top) => "GTO".to_string(),
+                    LayerType::Silkscreen(Side::Bottom) => "GBO".to_string(),
+                    LayerType::Soldermask(Side::Top) => "GTS".to_string(),
+                    LayerType::Soldermask(Side::Bottom) => "GBS".to_string(),
+                    LayerType::Paste(Side::Top) => "GTP".to_string(),
+                    LayerType::Paste(Side::Bottom) => "GBP".to_string(),
+                    LayerType::MechanicalOutline if *self == FabHouse::Pcbway => "GM1".to_string(),
+                    LayerType::MechanicalOutline => "GKO".to_string(),
+                };
+                format!("{}.{}", base, extension)
+            }
+        }
+    }
+
+    /// Drill file name; `index` separates several files of the same plating
+    pub fn drill_name(&self, base: &str, plated: bool, index: usize) -> String {
+        let kind = if plated { "PTH" } else { "NPTH" };
+        let number = if index == 0 { String::new() } else { format!("-{}", index + 1) };
+        match self {
+            FabHouse::Generic => format!("{}-{}{}.drl", base, kind, number),
+            FabHouse::Jlcpcb | FabHouse::Pcbway => format!("{}-{}{}.DRL", base, kind, number),
+        }
+    }
+}
+
+/// Settings for building a package
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageOptions {
+    pub fab: FabHouse,
+    /// Revision used in the archive name, empty for the project version
+    pub revision: String,
+    /// Add centroid and BOM files when components are loaded
+    pub include_assembly: bool,
+    pub include_drc_report: bool,
+    /// Free text added to the readme
+    pub notes: String,
+}
+
+impl Default for PackageOptions {
+    fn default() -> Self {
+        Self {
+            fab: FabHouse::default(),
+            revision: String::new(),
+            include_assembly: true,
+            include_drc_report: true,
+            notes: String::new(),
+        }
+    }
+}
+
+/// A drill file to package
+#[derive(Debug, Clone)]
+pub struct DrillSource {
+    pub name: String,
+    pub plated: bool,
+    pub content: Vec<u8>,
+}
+
+/// Everything the package is built from
+#[derive(Debug, Clone, Default)]
+pub struct PackageSource {
+    pub project_name: String,
+    pub version: String,
+    /// Build date as YYYY-MM-DD
+    pub date: String,
+    /// Gerber layers with their raw file content
+    pub layers: Vec<(LayerType, String)>,
+    pub drills: Vec<DrillSource>,
+    pub components: Vec<BomComponent>,
+    pub drc_violations: Vec<DrcViolation>,
+    pub dead_copper: usize,
+}
+
+/// Problems found before building, errors stop the build
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageValidation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl PackageValidation {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// A file inside the package
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageFile {
+    pub path: String,
+    pub content: Vec<u8>,
+}
+
+/// Result of a package build
+#[derive(Debug, Clone)]
+pub struct PackageReport {
+    pub archive_path: PathBuf,
+    pub files: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Keep letters, digits, '-', '_' and '.' for file names
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name.trim().chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() { "board".to_string() } else { cleaned }
+}
+
+/// Archive name such as "motor_driver_v1.2_20251018.zip"
+pub fn package_file_name(project_name: &str, version: &str, date: &str) -> String {
+    let mut name = sanitize_file_name(project_name);
+    if !version.trim().is_empty() {
+        let _ = write!(name, "_v{}", sanitize_file_name(version.trim().trim_start_matches(['v', 'V'])));
+    }
+    let date: String = date.chars().filter(|c| c.is_ascii_digit()).collect();
+    if !date.is_empty() {
+        let _ = write!(name, "_{}", date);
+    }
+    name + ".zip"
+}
+
+/// Number of copper layers, from the highest copper layer number
+fn copper_count(layers: &[LayerType]) -> u8 {
+    layers.iter()
+        .filter_map(|layer| match layer {
+            LayerType::Copper(n) => Some(*n),
+            _ => None,
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Top to bottom position of a layer in the stackup
+fn stack_order(layer_type: &LayerType) -> u16 {
+    match layer_type {
+        LayerType::Paste(Side::Top) => 0,
+        LayerType::Silkscreen(Side::Top) => 1,
+        LayerType::Soldermask(Side::Top) => 2,
+        LayerType::Copper(n) => 10 + *n as u16,
+        LayerType::Soldermask(Side::Bottom) => 300,
+        LayerType::Silkscreen(Side::Bottom) => 301,
+        LayerType::Paste(Side::Bottom) => 302,
+        LayerType::MechanicalOutline => 400,
+    }
+}
+
+/// Check the layers a fab needs are present
+pub fn validate_source(source: &PackageSource, options: &PackageOptions) -> PackageValidation {
+    let mut validation = PackageValidation::default();
+    let layers: Vec<LayerType> = source.layers.iter().map(|(layer_type, _)| *layer_type).collect();
+
+    let copper = copper_count(&layers);
+    if copper == 0 {
+        validation.errors.push("No copper layers loaded".to_string());
+    } else {
+        for n in 1..=copper {
+            if !layers.contains(&LayerType::Copper(n)) {
+                validation.errors.push(format!("Copper layer L{} is missing", n));
+            }
+        }
+    }
+    if !layers.contains(&LayerType::MechanicalOutline) {
+        validation.errors.push("Board outline layer is missing".to_string());
+    }
+    if source.drills.is_empty() {
+        validation.warnings.push("No drill files loaded".to_string());
+    }
+    for side in [Side::Top, Side::Bottom] {
+        for layer_type in [LayerType::Soldermask(side), LayerType::Silkscreen(side)] {
+            if !layers.contains(&layer_type) {
+                validation.warnings.push(format!("{} is missing", layer_type.display_name()));
+            }
+        }
+    }
+    if options.include_assembly && source.components.is_empty() {
+        validation.warnings.push("No BOM components loaded, centroid and BOM are left out".to_string());
+    }
+    if options.include_drc_report && !source.drc_violations.is_empty() {
+        validation.warnings.push(format!("DRC reports {} violation(s)", source.drc_violations.len()));
+    }
+    validation
+}
+
+fn readme(source: &PackageSource, options: &PackageOptions, files: &[PackageFile]) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "Manufacturing package: {}", source.project_name);
+    let _ = writeln!(out, "Version: {}", source.version);
+    let _ = writeln!(out, "Date: {}", source.date);
+    let _ = writeln!(out, "Fab: {}", options.fab.display_name());
+    let layers: Vec<LayerType> = source.layers.iter().map(|(layer_type, _)| *layer_type).collect();
+    let _ = writeln!(out, "Copper layers: {}", copper_count(&layers));
+    let _ = writeln!(out);
+    let _ = writeln!(out, "Files:");
+    for file in files {
+        let _ = writeln!(out, "  {} ({} bytes)", file.path, file.content.len());
+    }
+    let _ = writeln!(out, "  SHA256SUMS (checksums of all files above)");
+    if !options.notes.trim().is_empty() {
+        let _ = writeln!(out);
+        let _ = writeln!(out, "Notes:");
+        for line in options.notes.lines() {
+            let _ = writeln!(out, "  {}", line);
+        }
+    }
+    out
+}
+
+fn drc_report(source: &PackageSource) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "DRC report: {} ({})", source.project_name, source.date);
+    let _ = writeln!(out, "Violations: {}", source.drc_violations.len());
+    let _ = writeln!(out, "Dead copper islands: {}", source.dead_copper);
+    if source.drc_violations.is_empty() {
+        let _ = writeln!(out, "No violations recorded. Run DRC before packaging to include its results.");
+    }
+    for (i, violation) in source.drc_violations.iter().enumerate() {
+        let _ = writeln!(out, "{:4}. {}", i + 1, violation.format_message());
+    }
+    out
+}
+
+/// "hash  path" lines as written by sha256sum
+pub fn sha256_manifest(files: &[PackageFile]) -> String {
+    let mut out = String::new();
+    for file in files {
+        let _ = writeln!(out, "{:x}  {}", Sha256::digest(&file.content), file.path);
+    }
+    out
+}
+
+/// Build the package file list: fab files, assembly files, reports, readme and manifest
+pub fn build_package_files(source: &PackageSource, options: &PackageOptions) -> Result<Vec<PackageFile>, String> {
+    let validation = validate_source(source, options);
+    if !validation.is_ok() {
+        return Err(validation.errors.join("; "));
+    }
+
+    let base = sanitize_file_name(&source.project_name);
+    let layers: Vec<LayerType> = source.layers.iter().map(|(layer_type, _)| *layer_type).collect();
+    let copper = copper_count(&layers);
+    let mut files = Vec::new();
+
+    let mut layers = source.layers.clone();
+    layers.sort_by_key(|(layer_type, _)| stack_order(layer_type));
+    for (layer_type, content) in layers {
+        files.push(PackageFile {
+            path: format!("gerbers/{}", options.fab.gerber_name(&base, layer_type, copper)),
+            content: content.into_bytes(),
+        });
+    }
+
+    let mut counts = [0usize; 2];
+    for drill in &source.drills {
+        let index = &mut counts[drill.plated as usize];
+        files.push(PackageFile {
+            path: format!("gerbers/{}", options.fab.drill_name(&base, drill.plated, *index)),
+            content: drill.content.clone(),
+        });
+        *index += 1;
+    }
+
+    if options.include_assembly && !source.components.is_empty() {
+        let format = options.fab.placement_format();
+        let placement_options = PlacementOptions {
+            format,
+            rotation_offsets: format.default_rotation_offsets(),
+            ..Default::default()
+        };
+        let placements = build_placements(&source.components, &placement_options, Point2::origin())?;
+        files.push(PackageFile {
+            path: format!("assembly/{}-pos.{}", base, format.extension()),
+            content: write_placements(&placements, &placement_options).into_bytes(),
+        });
+
+        // Keep parts with different custom fields (MPN, manufacturer) on separate lines
+        let fields = custom_field_names(&source.components);
+        let lines = group_components(&source.components, false, &fields);
+        let mut columns = BomColumn::standard();
+        columns.extend(fields.into_iter().map(BomColumn::Field));
+        files.push(PackageFile {
+            path: format!("assembly/{}-bom.csv", base),
+            content: write_bom_csv(&lines, &columns).into_bytes(),
+        });
+    }
+
+    if options.include_drc_report {
+        files.push(PackageFile { path: "drc-report.txt".to_string(), content: drc_report(source).into_bytes() });
+    }
+
+    let readme = readme(source, options, &files);
+    files.push(PackageFile { path: "README.txt".to_string(), content: readme.into_bytes() });
+    let manifest = sha256_manifest(&files);
+    files.push(PackageFile { path: "SHA256SUMS".to_string(), content: manifest.into_bytes() });
+    Ok(files)
+}
+
+/// Write files into a zip archive in memory
+pub fn write_zip(files: &[PackageFile]) -> Result<Vec<u8>, String> {
+    let zip_error = |e: zip::result::ZipError| format!("Failed to write zip: {}", e);
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for file in files {
+        zip.start_file(file.path.as_str(), options).map_err(zip_error)?;
+        zip.write_all(&file.content).map_err(|e| format!("Failed to write zip: {}", e))?;
+    }
+    Ok(zip.finish().map_err(zip_error)?.into_inner())
+}
+
+impl PackageSource {
+    /// Gather layers, drill files, BOM and DRC results from the app
+    pub fn from_app(app: &mut DemoLensApp, revision: &str) -> Result<Self, String> {
+        let metadata = app.project_manager_state.as_ref()
+            .and_then(|state| state.current_project.as_ref())
+            .map(|project| project.metadata.clone());
+        let project_name = metadata.as_ref()
+            .map(|metadata| metadata.name.clone())
+            .or_else(|| app.project_manager.get_pcb_path()
+                .and_then(|path| path.file_stem())
+                .map(|stem| stem.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "board".to_string());
+        let version = if revision.trim().is_empty() {
+            metadata.as_ref().map(|metadata| metadata.version.clone()).unwrap_or_default()
+        } else {
+            revision.trim().to_string()
+        };
+
+        let world = &mut app.ecs_world;
+        let mut query = world.query::<(&LayerInfo, &RawGerberData)>();
+        let layers = query.iter(world)
+            .map(|(info, raw)| (info.layer_type, raw.0.clone()))
+            .collect();
+
+        let mut drills = Vec::new();
+        let mut query = world.query::<&DrillFileInfo>();
+        for info in query.iter(world) {
+            let path = info.file_path.as_ref()
+                .ok_or_else(|| format!("Drill file {} has no path on disk", info.name))?;
+            let content = std::fs::read(path)
+                .map_err(|e| format!("Failed to read drill file {}: {}", path.display(), e))?;
+            drills.push(DrillSource { name: info.name.clone(), plated: info.plated, content });
+        }
+
+        Ok(Self {
+            project_name,
+            version,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            layers,
+            drills,
+            components: loaded_bom_components(app),
+            drc_violations: app.drc_manager.violations.clone(),
+            dead_copper: app.drc_manager.dead_copper.len(),
+        })
+    }
+}
+
+/// Validate, build and write the package zip into `output_dir`
+pub fn build_fab_package(app: &mut DemoLensApp, output_dir: &Path, options: &PackageOptions) -> Result<PackageReport, String> {
+    let source = PackageSource::from_app(app, &options.revision)?;
+    let validation = validate_source(&source, options);
+    let files = build_package_files(&source, options)?;
+    let archive = write_zip(&files)?;
+
+    std::fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+    let archive_path = output_dir.join(package_file_name(&source.project_name, &source.version, &source.date));
+    std::fs::write(&archive_path, archive).map_err(|e| format!("Failed to save package: {}", e))?;
+    println!("Wrote manufacturing package {:?} with {} files", archive_path, files.len());
+
+    Ok(PackageReport {
+        archive_path,
+        files: files.into_iter().map(|file| file.path).collect(),
+        warnings: validation.warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source() -> PackageSource {
+        PackageSource {
+            project_name: "Motor Driver".to_string(),
+            version: "1.2".to_string(),
+            date: "2025-10-18".to_string(),
+            layers: vec![
+                (LayerType::MechanicalOutline, "outline".to_string()),
+                (LayerType::Copper(1), "top".to_string()),
+                (LayerType::Copper(2), "bottom".to_string()),
+            ],
+            drills: vec![DrillSource { name: "board-PTH.drl".to_string(), plated: true, content: b"M48".to_vec() }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_naming_conventions() {
+        assert_eq!(FabHouse::Generic.gerber_name("b", LayerType::Copper(2), 4), "b-In1_Cu.gbr");
+        assert_eq!(FabHouse::Generic.gerber_name("b", LayerType::Copper(4), 4), "b-B_Cu.gbr");
+        assert_eq!(FabHouse::Jlcpcb.gerber_name("b", LayerType::Copper(3), 4), "b.G2");
+        assert_eq!(FabHouse::Jlcpcb.gerber_name("b", LayerType::MechanicalOutline, 2), "b.GKO");
+        assert_eq!(FabHouse::Pcbway.gerber_name("b", LayerType::Soldermask(Side::Bottom), 2), "b.GBS");
+        assert_eq!(FabHouse::Jlcpcb.drill_name("b", false, 1), "b-NPTH-2.DRL");
+        assert_eq!(package_file_name("Motor Driver", "v1.2", "2025-10-18"), "Motor_Driver_v1.2_20251018.zip");
+    }
+
+    #[test]
+    fn test_validation() {
+        let mut source = source();
+        assert!(validate_source(&source, &PackageOptions::default()).is_ok());
+
+        source.layers.retain(|(layer_type, _)| *layer_type != LayerType::Copper(1));
+        source.layers.retain(|(layer_type, _)| *layer_type != LayerType::MechanicalOutline);
+        let validation = validate_source(&source, &PackageOptions::default());
+        assert_eq!(validation.errors, ["Copper layer L1 is missing", "Board outline layer is missing"]);
+    }
+
+    #[test]
+    fn test_package_manifest_and_zip() {
+        let options = PackageOptions { fab: FabHouse::Jlcpcb, ..Default::default() };
+        let files = build_package_files(&source(), &options).unwrap();
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, [
+            "gerbers/Motor_Driver.GTL",
+            "gerbers/Motor_Driver.GBL",
+            "gerbers/Motor_Driver.GKO",
+            "gerbers/Motor_Driver-PTH.DRL",
+            "drc-report.txt",
+            "README.txt",
+            "SHA256SUMS",
+        ]);
+
+        let manifest = String::from_utf8(files.last().unwrap().content.clone()).unwrap();
+        // sha256("top"), one line per file before the manifest
+        assert!(manifest.starts_with("28720365c5e7476a011e4f43ac003ee5f16247a263b9d623aa85ed311d73bf39  gerbers/Motor_Driver.GTL\n"));
+        assert_eq!(manifest.lines().count(), 6);
+
+        let archive = write_zip(&files).unwrap();
+        let zip = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+        assert_eq!(zip.len(), files.len());
+    }
+}
@@ -0,0 +1,412 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use nalgebra::Point2;
+use regex::{Regex, RegexBuilder};
+
+use crate::DemoLensApp;
+use crate::project_manager::bom::BomComponent;
+use super::loaded_bom_components;
+
+// Pick-and-place (centroid) export
+// Positions come from the BOM components (KiCad board coordinates, Y down) and
+// are written Y up like the Gerbers, relative to the board or the user origin.
+// Each fab layout has its own columns and rotation offset table.
+
+/// Column layout of the placement file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlacementFormat {
+    #[default]
+    Generic,
+    Jlcpcb,
+    Pcbway,
+    Mycronic,
+}
+
+impl PlacementFormat {
+    pub fn all() -> [PlacementFormat; 4] {
+        [PlacementFormat::Generic, PlacementFormat::Jlcpcb, PlacementFormat::Pcbway, PlacementFormat::Mycronic]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PlacementFormat::Generic => "Generic CSV",
+            PlacementFormat::Jlcpcb => "JLCPCB",
+            PlacementFormat::Pcbway => "PCBWay",
+            PlacementFormat::Mycronic => "Mycronic",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PlacementFormat::Mycronic => "txt",
+            _ => "csv",
+        }
+    }
+
+    /// Rotation corrections the fab usually needs for KiCad footprints
+    pub fn default_rotation_offsets(&self) -> Vec<RotationOffset> {
+        let table: &[(&str, f64)] = match self {
+            PlacementFormat::Jlcpcb => &[
+                ("^SOT-223", 180.0),
+                ("^SOT-23", 180.0),
+                ("^SOT-353", 180.0),
+                ("^SOIC-", 270.0),
+                ("^SOP-", 270.0),
+                ("^SSOP-", 270.0),
+                ("^TSSOP-", 270.0),
+                ("^MSOP-", 270.0),
+                ("^QFN-", 270.0),
+                ("^LQFP-", 270.0),
+                ("^TQFP-", 270.0),
+            ],
+            PlacementFormat::Pcbway => &[
+                ("^SOT-23", 180.0),
+            ],
+            PlacementFormat::Generic | PlacementFormat::Mycronic => &[],
+        };
+        table.iter()
+            .map(|(pattern, degrees)| RotationOffset { pattern: pattern.to_string(), degrees: *degrees })
+            .collect()
+    }
+}
+
+/// Which point positions are measured from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlacementOrigin {
+    /// KiCad board coordinates as-is
+    #[default]
+    Board,
+    /// Origin picked in the viewer (set origin mode)
+    User,
+}
+
+/// Units for positions in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlacementUnit {
+    #[default]
+    Millimeters,
+    Mils,
+}
+
+impl PlacementUnit {
+    pub fn from_mm(&self, value: f64) -> f64 {
+        match self {
+            PlacementUnit::Millimeters => value,
+            PlacementUnit::Mils => value / 0.0254,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            PlacementUnit::Millimeters => "mm",
+            PlacementUnit::Mils => "mil",
+        }
+    }
+}
+
+/// Rotation added to footprints whose name matches `pattern` (case-insensitive regex)
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationOffset {
+    pub pattern: String,
+    pub degrees: f64,
+}
+
+/// Settings for a placement export
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacementOptions {
+    pub format: PlacementFormat,
+    pub origin: PlacementOrigin,
+    pub unit: PlacementUnit,
+    pub rotation_offsets: Vec<RotationOffset>,
+    /// Skip components without a footprint (mounting holes, logos)
+    pub skip_empty_footprints: bool,
+}
+
+impl Default for PlacementOptions {
+    fn default() -> Self {
+        Self {
+            format: PlacementFormat::default(),
+            origin: PlacementOrigin::default(),
+            unit: PlacementUnit::default(),
+            rotation_offsets: Vec::new(),
+            skip_empty_footprints: true,
+        }
+    }
+}
+
+/// One placement row, positions already relative to the origin (mm, Y up)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placement {
+    pub reference: String,
+    pub value: String,
+    pub footprint: String,
+    pub position: Point2<f64>,
+    pub rotation: f64,
+    pub bottom: bool,
+}
+
+/// Footprint name without the KiCad library prefix
+fn footprint_name(footprint: &str) -> &str {
+    footprint.rsplit_once(':').map(|(_, name)| name).unwrap_or(footprint)
+}
+
+/// Wrap to 0..360
+fn normalize_degrees(degrees: f64) -> f64 {
+    let degrees = degrees.rem_euclid(360.0);
+    if (degrees - 360.0).abs() < 1e-9 { 0.0 } else { degrees }
+}
+
+/// Compile the rotation offset patterns, reporting every invalid one
+pub fn compile_rotation_offsets(offsets: &[RotationOffset]) -> Result<Vec<(Regex, f64)>, String> {
+    let mut compiled = Vec::new();
+    let mut errors = Vec::new();
+    for offset in offsets {
+        match RegexBuilder::new(&offset.pattern).case_insensitive(true).build() {
+            Ok(regex) => compiled.push((regex, offset.degrees)),
+            Err(e) => errors.push(format!("Invalid rotation pattern '{}': {}", offset.pattern, e)),
+        }
+    }
+    if errors.is_empty() { Ok(compiled) } else { Err(errors.join("; ")) }
+}
+
+/// Rotation offset for a footprint, first matching rule wins
+pub fn rotation_offset(footprint: &str, offsets: &[(Regex, f64)]) -> f64 {
+    let name = footprint_name(footprint);
+    offsets.iter()
+        .find(|(regex, _)| regex.is_match(name))
+        .map(|(_, degrees)| *degrees)
+        .unwrap_or(0.0)
+}
+
+/// Build placement rows from BOM components, sorted by side then reference
+/// `user_origin` is in board coordinates with Y up (the viewer's design offset).
+pub fn build_placements(
+    components: &[BomComponent],
+    options: &PlacementOptions,
+    user_origin: Point2<f64>,
+) -> Result<Vec<Placement>, String> {
+    let origin = match options.origin {
+        PlacementOrigin::Board => Point2::origin(),
+        PlacementOrigin::User => user_origin,
+    };
+
+    let offsets = compile_rotation_offsets(&options.rotation_offsets)?;
+    let mut placements = Vec::new();
+    for component in components {
+        if options.skip_empty_footprints && component.footprint.trim().is_empty() {
+            continue;
+        }
+        let bottom = component.is_bottom();
+        // Bottom parts are seen mirrored, so the correction turns the other way
+        let offset = rotation_offset(&component.footprint, &offsets);
+        let rotation = if bottom { component.orientation - offset } else { component.orientation + offset };
+
+        placements.push(Placement {
+            reference: component.reference.clone(),
+            value: component.value.clone(),
+            footprint: footprint_name(&component.footprint).to_string(),
+            position: Point2::new(component.x_location - origin.x, -component.y_location - origin.y),
+            rotation: normalize_degrees(rotation),
+            bottom,
+        });
+    }
+
+    placements.sort_by(|a, b| a.bottom.cmp(&b.bottom).then_with(|| natural_cmp(&a.reference, &b.reference)));
+    Ok(placements)
+}
+
+/// Compare references so R2 sorts before R10
+pub(crate) fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    let split = |s: &str| {
+        let digits = s.trim_start_matches(|c: char| !c.is_ascii_digit());
+        let prefix = &s[..s.len() - digits.len()];
+        (prefix.to_string(), digits.parse::<u64>().unwrap_or(0), s.to_string())
+    };
+    split(a).cmp(&split(b))
+}
+
+/// Quote a CSV field when needed
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Write the placement file content in the chosen layout
+pub fn write_placements(placements: &[Placement], options: &PlacementOptions) -> String {
+    let unit = options.unit;
+    let coord = |value: f64| format!("{:.4}", unit.from_mm(value));
+    let mut out = String::new();
+
+    match options.format {
+        PlacementFormat::Generic => {
+            let _ = writeln!(out, "Ref,Val,Package,PosX,PosY,Rot,Side");
+            for p in placements {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{},{:.2},{}",
+                    csv_field(&p.reference),
+                    csv_field(&p.value),
+                    csv_field(&p.footprint),
+                    coord(p.position.x),
+                    coord(p.position.y),
+                    p.rotation,
+                    if p.bottom { "bottom" } else { "top" }
+                );
+            }
+        }
+        PlacementFormat::Jlcpcb => {
+            let _ = writeln!(out, "Designator,Val,Package,Mid X,Mid Y,Rotation,Layer");
+            for p in placements {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{}{},{}{},{:.2},{}",
+                    csv_field(&p.reference),
+                    csv_field(&p.value),
+                    csv_field(&p.footprint),
+                    coord(p.position.x), unit.suffix(),
+                    coord(p.position.y), unit.suffix(),
+                    p.rotation,
+                    if p.bottom { "Bottom" } else { "Top" }
+                );
+            }
+        }
+        PlacementFormat::Pcbway => {
+            let _ = writeln!(out, "Designator,Footprint,Mid X,Mid Y,Layer,Rotation,Comment");
+            for p in placements {
+                let _ = writeln!(
+                    out,
+                    "{},{},{}{},{}{},{},{:.2},{}",
+                    csv_field(&p.reference),
+                    csv_field(&p.footprint),
+                    coord(p.position.x), unit.suffix(),
+                    coord(p.position.y), unit.suffix(),
+                    if p.bottom { "B" } else { "T" },
+                    p.rotation,
+                    csv_field(&p.value)
+                );
+            }
+        }
+        PlacementFormat::Mycronic => {
+            // Whitespace separated columns with a comment header
+            let _ = writeln!(out, "# Units: {}", unit.suffix());
+            let _ = writeln!(out, "# Origin: {}", match options.origin {
+                PlacementOrigin::Board => "board",
+                PlacementOrigin::User => "user",
+            });
+            let _ = writeln!(out, "# RefDes\tX\tY\tAngle\tSide\tPackage\tValue");
+            for p in placements {
+                let _ = writeln!(
+                    out,
+                    "{}\t{}\t{}\t{:.2}\t{}\t{}\t{}",
+                    p.reference.replace(char::is_whitespace, "_"),
+                    coord(p.position.x),
+                    coord(p.position.y),
+                    p.rotation,
+                    if p.bottom { "B" } else { "T" },
+                    p.footprint.replace(char::is_whitespace, "_"),
+                    p.value.replace(char::is_whitespace, "_")
+                );
+            }
+        }
+    }
+    out
+}
+
+/// Export placements for the loaded BOM, returning the number of components written
+pub fn export_placement(app: &DemoLensApp, output_path: &Path, options: &PlacementOptions) -> Result<usize, String> {
+    let components = loaded_bom_components(app);
+    if components.is_empty() {
+        return Err("No BOM components loaded".to_string());
+    }
+    if options.origin == PlacementOrigin::User && !app.origin_has_been_set {
+        return Err("User origin selected but no origin has been set".to_string());
+    }
+
+    let user_origin = Point2::new(app.display_manager.design_offset.x, app.display_manager.design_offset.y);
+    let placements = build_placements(&components, options, user_origin)?;
+    let content = write_placements(&placements, options);
+    std::fs::write(output_path, content).map_err(|e| format!("Failed to save placement file: {}", e))?;
+    Ok(placements.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn component(reference: &str, footprint: &str, x: f64, y: f64, rotation: f64, layer: &str) -> BomComponent {
+        let mut component = BomComponent::new(reference.to_string());
+        component.value = "10k".to_string();
+        component.footprint = footprint.to_string();
+        component.x_location = x;
+        component.y_location = y;
+        component.orientation = rotation;
+        component.layer = layer.to_string();
+        component
+    }
+
+    #[test]
+    fn test_build_placements() {
+        let components = vec![
+            component("R10", "Resistor_SMD:R_0603", 10.0, -20.0, 90.0, "F.Cu"),
+            component("U1", "Package_TO_SOT_SMD:SOT-23", 5.0, -5.0, 0.0, "B.Cu"),
+            component("R2", "Resistor_SMD:R_0603", 1.0, -2.0, 0.0, "F.Cu"),
+            component("H1", "", 0.0, 0.0, 0.0, "F.Cu"),
+        ];
+        let options = PlacementOptions {
+            origin: PlacementOrigin::User,
+            rotation_offsets: PlacementFormat::Jlcpcb.default_rotation_offsets(),
+            ..Default::default()
+        };
+        let placements = build_placements(&components, &options, Point2::new(1.0, 1.0)).unwrap();
+
+        let references: Vec<&str> = placements.iter().map(|p| p.reference.as_str()).collect();
+        assert_eq!(references, ["R2", "R10", "U1"]);
+        assert_eq!(placements[1].position, Point2::new(9.0, 19.0));
+        assert_eq!(placements[1].footprint, "R_0603");
+        // Bottom SOT-23 gets the JLCPCB offset mirrored
+        assert!(placements[2].bottom);
+        assert_eq!(placements[2].rotation, 180.0);
+    }
+
+    #[test]
+    fn test_write_formats() {
+        let placements = vec![Placement {
+            reference: "C1".to_string(),
+            value: "100n, 50V".to_string(),
+            footprint: "C_0402".to_string(),
+            position: Point2::new(2.54, 1.0),
+            rotation: 270.0,
+            bottom: false,
+        }];
+
+        let generic = write_placements(&placements, &PlacementOptions::default());
+        assert_eq!(generic, "Ref,Val,Package,PosX,PosY,Rot,Side\nC1,\"100n, 50V\",C_0402,2.5400,1.0000,270.00,top\n");
+
+        let jlc = write_placements(&placements, &PlacementOptions {
+            format: PlacementFormat::Jlcpcb,
+            unit: PlacementUnit::Mils,
+            ..Default::default()
+        });
+        assert!(jlc.ends_with("C1,\"100n, 50V\",C_0402,100.0000mil,39.3701mil,270.00,Top\n"));
+
+        let mycronic = write_placements(&placements, &PlacementOptions { format: PlacementFormat::Mycronic, ..Default::default() });
+        assert!(mycronic.ends_with("C1\t2.5400\t1.0000\t270.00\tT\tC_0402\t100n,_50V\n"));
+    }
+
+    #[test]
+    fn test_invalid_rotation_pattern() {
+        let offsets = vec![
+            RotationOffset { pattern: "(".to_string(), degrees: 90.0 },
+            RotationOffset { pattern: "^SOT".to_string(), degrees: 180.0 },
+        ];
+        let error = compile_rotation_offsets(&offsets).unwrap_err();
+        assert_eq!(error.matches("Invalid rotation pattern").count(), 1);
+
+        let compiled = compile_rotation_offsets(&offsets[1..]).unwrap();
+        assert_eq!(rotation_offset("Package_TO_SOT_SMD:SOT-23", &compiled), 180.0);
+        assert_eq!(rotation_offset("C_0402", &compiled), 0.0);
+    }
+}
@@ -0,0 +1,371 @@
+use std::path::Path;
+use bevy_ecs::component::Tick;
+use bevy_ecs::prelude::*;
+use egui::Color32;
+use image::{ImageBuffer, Rgba, RgbaImage};
+use nalgebra::Point2;
+
+use crate::DemoLensApp;
+use crate::ecs::{DrillHoles, LayerGeometryData, LayerInfo, LayerType, Side};
+use crate::geometry::{board_polygons, rasterize_layer, shapes, Antialias, CoverageMask, DrillData, LayerGeometry, Polygon, RasterView};
+use crate::project_manager::appearance::{BoardAppearance, COPPER_COLOR};
+use super::{PngExporter, RasterOptions};
+
+// Realistic board preview
+// Composites one side the way the finished board looks: substrate, copper seen
+// through the solder mask, surface finish in the mask openings, silkscreen on
+// top and drilled holes punched through. Coordinates are raw gerber mm.
+
+/// Settings for a realistic export
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RealisticOptions {
+    pub side: Side,
+    pub appearance: BoardAppearance,
+    /// Show the bottom as seen from below
+    pub mirror_bottom: bool,
+}
+
+impl Default for RealisticOptions {
+    fn default() -> Self {
+        Self {
+            side: Side::Top,
+            appearance: BoardAppearance::default(),
+            mirror_bottom: true,
+        }
+    }
+}
+
+/// Layers of one board side needed for the realistic render
+#[derive(Debug, Clone, Default)]
+pub struct RealisticBoard {
+    pub outline: Option<LayerGeometry>,
+    pub copper: Option<LayerGeometry>,
+    /// Mask openings; without a mask layer the board is rendered unmasked
+    pub soldermask: Option<LayerGeometry>,
+    pub silkscreen: Option<LayerGeometry>,
+    pub drills: Vec<DrillData>,
+}
+
+impl RealisticBoard {
+    /// Gather the loaded layers of a side, regardless of their visibility
+    pub fn from_world(world: &mut World, side: Side) -> Self {
+        let mut board = RealisticBoard::default();
+        let mut bottom_copper: Option<(u8, LayerGeometry)> = None;
+
+        let mut layers = world.query::<(&LayerInfo, &LayerGeometryData)>();
+        for (info, geometry) in layers.iter(world) {
+            match info.layer_type {
+                LayerType::MechanicalOutline => board.outline = Some(geometry.0.clone()),
+                LayerType::Copper(1) if side == Side::Top => board.copper = Some(geometry.0.clone()),
+                LayerType::Copper(n) if side == Side::Bottom && n > 1 => {
+                    // The bottom is the highest numbered copper layer
+                    if bottom_copper.as_ref().is_none_or(|(number, _)| n > *number) {
+                        bottom_copper = Some((n, geometry.0.clone()));
+                    }
+                }
+                LayerType::Soldermask(layer_side) if layer_side == side => board.soldermask = Some(geometry.0.clone()),
+                LayerType::Silkscreen(layer_side) if layer_side == side => board.silkscreen = Some(geometry.0.clone()),
+                _ => {}
+            }
+        }
+        if let Some((_, copper)) = bottom_copper {
+            board.copper = Some(copper);
+        }
+
+        let mut drills = world.query::<&DrillHoles>();
+        board.drills = drills.iter(world).map(|holes| holes.0.clone()).collect();
+        board
+    }
+
+    /// Board extents, from the outline or else the side's layers
+    pub fn bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        if let Some(bounds) = self.outline.as_ref().and_then(|outline| outline.bounds()) {
+            return Some(bounds);
+        }
+        let corners: Vec<Point2<f64>> = [&self.copper, &self.soldermask, &self.silkscreen].into_iter()
+            .flatten()
+            .filter_map(|geometry| geometry.bounds())
+            .flat_map(|(min, max)| [min, max])
+            .collect();
+        shapes::points_bounds(&corners)
+    }
+
+    /// Board shape: the closed outline contours, or the bounds when there are none
+    fn board_shape(&self, tolerance: f64) -> Vec<Polygon> {
+        let polygons = self.outline.as_ref()
+            .map(|outline| board_polygons(outline, tolerance))
+            .unwrap_or_default();
+        if !polygons.is_empty() {
+            return polygons;
+        }
+        self.bounds()
+            .map(|(min, max)| vec![Polygon::new(vec![
+                min,
+                Point2::new(max.x, min.y),
+                max,
+                Point2::new(min.x, max.y),
+            ], true)])
+            .unwrap_or_default()
+    }
+
+    /// Drilled holes and slots as polygons
+    fn hole_polygons(&self, tolerance: f64) -> Vec<Polygon> {
+        let mut polygons = Vec::new();
+        for drill in &self.drills {
+            for hit in &drill.hits {
+                polygons.push(Polygon::new(shapes::circle_points(hit.position, hit.diameter / 2.0, tolerance), true));
+            }
+            for slot in &drill.slots {
+                let tip = shapes::circle_points(Point2::origin(), slot.diameter / 2.0, tolerance);
+                polygons.push(Polygon::new(shapes::sweep_convex(&tip, slot.start, slot.end), true));
+            }
+        }
+        polygons
+    }
+}
+
+/// Layer and drill file entities a render was made from, and the change tick then
+#[derive(Debug, Clone, PartialEq)]
+pub struct RealisticSource {
+    tick: Tick,
+    entities: Vec<Entity>,
+}
+
+impl RealisticSource {
+    /// Record the layers and drill files loaded now
+    pub fn capture(world: &mut World) -> Self {
+        let entities = source_entities(world, None).unwrap_or_default();
+        let tick = world.change_tick();
+        // Layers replaced after this point get a tick other than the recorded one
+        world.increment_change_tick();
+        Self { tick, entities }
+    }
+
+    /// Whether no layer or drill file was loaded, removed or changed since the capture
+    pub fn is_current(&self, world: &mut World) -> bool {
+        source_entities(world, Some(self.tick)).is_some_and(|entities| entities == self.entities)
+    }
+}
+
+/// Sorted layer and drill file entities, None when any changed after `since`
+fn source_entities(world: &mut World, since: Option<Tick>) -> Option<Vec<Entity>> {
+    let this_run = world.change_tick();
+    let changed = |tick: Tick| since.is_some_and(|since| tick.is_newer_than(since, this_run));
+
+    let mut entities = Vec::new();
+    let mut layers = world.query::<(Entity, Ref<LayerInfo>, Ref<LayerGeometryData>)>();
+    for (entity, info, geometry) in layers.iter(world) {
+        if changed(info.last_changed()) || changed(geometry.last_changed()) {
+            return None;
+        }
+        entities.push(entity);
+    }
+    let mut drills = world.query::<(Entity, Ref<DrillHoles>)>();
+    for (entity, holes) in drills.iter(world) {
+        if changed(holes.last_changed()) {
+            return None;
+        }
+        entities.push(entity);
+    }
+    entities.sort();
+    Some(entities)
+}
+
+/// Coverage of each ingredient of the realistic render, all the same size
+pub struct RealisticMasks {
+    pub board: CoverageMask,
+    pub copper: CoverageMask,
+    pub openings: CoverageMask,
+    pub silkscreen: CoverageMask,
+    pub holes: CoverageMask,
+}
+
+impl RealisticMasks {
+    /// Rasterize a board side; `mirror` flips it left to right about its centre
+    pub fn rasterize(board: &RealisticBoard, view: &RasterView, antialias: Antialias, mirror: bool) -> Self {
+        let (min, max) = board.bounds().unwrap_or((Point2::origin(), Point2::origin()));
+        let place = |point: Point2<f64>| if mirror { Point2::new(min.x + max.x - point.x, point.y) } else { point };
+        let placed = |polygons: Vec<Polygon>| -> Vec<Polygon> {
+            polygons.into_iter()
+                .map(|polygon| Polygon::new(polygon.points.iter().map(|p| place(*p)).collect(), polygon.dark))
+                .collect()
+        };
+        let layer = |geometry: &Option<LayerGeometry>| match geometry {
+            Some(geometry) => rasterize_layer(geometry, view, antialias, place),
+            None => CoverageMask::new(view.width, view.height),
+        };
+
+        let tolerance = view.tolerance();
+        // Cutouts are wound against the board outline, so one paint handles them
+        let mut board_mask = CoverageMask::new(view.width, view.height);
+        board_mask.paint(&placed(board.board_shape(tolerance)), view, antialias, true);
+        let mut holes = CoverageMask::new(view.width, view.height);
+        for hole in placed(board.hole_polygons(tolerance)) {
+            holes.paint(&[hole], view, antialias, true);
+        }
+
+        let mut openings = layer(&board.soldermask);
+        if board.soldermask.is_none() {
+            openings.data.fill(1.0);
+        }
+
+        Self {
+            board: board_mask,
+            copper: layer(&board.copper),
+            openings,
+            silkscreen: layer(&board.silkscreen),
+            holes,
+        }
+    }
+}
+
+/// Combine the masks into the final image
+pub fn compose_realistic(masks: &RealisticMasks, appearance: &BoardAppearance, background: Color32) -> RgbaImage {
+    let mix = |base: [f32; 3], color: [u8; 3], amount: f32| -> [f32; 3] {
+        let amount = amount.clamp(0.0, 1.0);
+        [0, 1, 2].map(|i| base[i] * (1.0 - amount) + color[i] as f32 * amount)
+    };
+    let finish = appearance.finish.color();
+    let finish = [finish.r(), finish.g(), finish.b()];
+    let background_rgb = [background.r(), background.g(), background.b()];
+
+    let mut img: RgbaImage = ImageBuffer::new(masks.board.width, masks.board.height);
+    for (i, pixel) in img.pixels_mut().enumerate() {
+        let board = masks.board.data[i] * (1.0 - masks.holes.data[i]);
+        let opening = masks.openings.data[i];
+        let copper = masks.copper.data[i] * board;
+
+        let mut color = appearance.substrate.map(f32::from);
+        color = mix(color, COPPER_COLOR, copper);
+        color = mix(color, appearance.soldermask, (1.0 - opening) * appearance.mask_opacity);
+        color = mix(color, finish, copper * opening);
+        let silk_clip = if appearance.clip_silkscreen { 1.0 - opening } else { 1.0 };
+        color = mix(color, appearance.silkscreen, masks.silkscreen.data[i] * silk_clip);
+
+        // Off the board and in the holes the background shows through
+        let background_alpha = background.a() as f32 / 255.0;
+        let color = mix(background_rgb.map(f32::from), color.map(|c| c.round() as u8), board);
+        let alpha = board + (1.0 - board) * background_alpha;
+        *pixel = Rgba([
+            color[0].round() as u8,
+            color[1].round() as u8,
+            color[2].round() as u8,
+            (alpha * 255.0).round() as u8,
+        ]);
+    }
+    img
+}
+
+/// Render one side of the board; the bottom is seen from below when `mirror_bottom` is set
+pub fn render_realistic(
+    board: &RealisticBoard,
+    side: Side,
+    appearance: &BoardAppearance,
+    options: &RasterOptions,
+    mirror_bottom: bool,
+) -> Result<RgbaImage, String> {
+    let (min, max) = board.bounds().ok_or("No board geometry to render")?;
+    if board.copper.is_none() && board.silkscreen.is_none() {
+        return Err(format!("No {} copper or silkscreen loaded", side_name(side)));
+    }
+    let view = PngExporter::raster_view(min, max, options)?;
+    let masks = RealisticMasks::rasterize(board, &view, options.antialias, side == Side::Bottom && mirror_bottom);
+    Ok(compose_realistic(&masks, appearance, options.background))
+}
+
+/// Export a realistic render of one side as PNG
+pub fn export_realistic_png(
+    app: &mut DemoLensApp,
+    output_path: &Path,
+    side: Side,
+    appearance: &BoardAppearance,
+    options: &RasterOptions,
+    mirror_bottom: bool,
+) -> Result<(), String> {
+    let board = RealisticBoard::from_world(&mut app.ecs_world, side);
+    let img = render_realistic(&board, side, appearance, options, mirror_bottom)?;
+    println!("Exporting realistic {} view to {:?} ({}x{} px at {} DPI)",
+             side_name(side), output_path, img.width(), img.height(), options.dpi);
+    img.save(output_path).map_err(|e| format!("Failed to save PNG: {}", e))
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Top => "top",
+        Side::Bottom => "bottom",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+    use crate::project_manager::appearance::SurfaceFinish;
+
+    fn layer(body: &str) -> LayerGeometry {
+        parse_gerber(&format!("%FSLAX26Y26*%\n%MOMM*%\n{}M02*\n", body)).unwrap()
+    }
+
+    #[test]
+    fn test_compose_layers() {
+        // 10x10 board with a 4x4 copper pad at (1,1), the mask open over its left half
+        let board = RealisticBoard {
+            outline: Some(layer("%ADD10C,0.1*%\nD10*\nX0Y0D02*\nX10000000Y0D01*\nX10000000Y10000000D01*\nX0Y10000000D01*\nX0Y0D01*\n")),
+            copper: Some(layer("%ADD10R,4X4*%\nD10*\nX3000000Y3000000D03*\n")),
+            soldermask: Some(layer("%ADD10R,2X4*%\nD10*\nX2000000Y3000000D03*\n")),
+            silkscreen: None,
+            drills: Vec::new(),
+        };
+        let appearance = BoardAppearance { mask_opacity: 1.0, finish: SurfaceFinish::Enig, ..Default::default() };
+        // The outline stroke adds 0.05 mm on each side
+        let options = RasterOptions { dpi: 25.4, antialias: Antialias::Off, background: Color32::TRANSPARENT, margin: 0.9 };
+        let img = render_realistic(&board, Side::Top, &appearance, &options, true).unwrap();
+        assert_eq!((img.width(), img.height()), (12, 12));
+
+        let enig = SurfaceFinish::Enig.color();
+        // Board (x, y) mm maps to pixel (x + 0.95, 10.95 - y)
+        assert_eq!(img.get_pixel(2, 8).0, [enig.r(), enig.g(), enig.b(), 255]);
+        let mask = appearance.soldermask;
+        assert_eq!(img.get_pixel(4, 8).0, [mask[0], mask[1], mask[2], 255]);
+        assert_eq!(img.get_pixel(8, 2).0, [mask[0], mask[1], mask[2], 255]);
+        assert_eq!(img.get_pixel(0, 0).0[3], 0);
+    }
+
+    #[test]
+    fn test_source_tracks_layer_changes() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let pad = "%FSLAX26Y26*%\n%MOMM*%\n%ADD10R,2X2*%\nD10*\nX1000000Y1000000D03*\nM02*\n";
+        let copper = crate::ecs::test_support::spawn_layer(&mut world, LayerType::Copper(1), pad);
+        let source = RealisticSource::capture(&mut world);
+        assert!(source.is_current(&mut world));
+
+        // Replaced by geometry with the same number of features
+        let moved = layer("%ADD10R,2X2*%\nD10*\nX5000000Y1000000D03*\n");
+        world.entity_mut(copper).insert(LayerGeometryData(moved));
+        assert!(!source.is_current(&mut world));
+
+        let source = RealisticSource::capture(&mut world);
+        crate::ecs::test_support::spawn_layer(&mut world, LayerType::Silkscreen(Side::Top), pad);
+        assert!(!source.is_current(&mut world));
+    }
+
+    #[test]
+    fn test_bottom_is_mirrored() {
+        let board = RealisticBoard {
+            outline: None,
+            copper: Some(layer("%ADD10R,2X2*%\nD10*\nX1000000Y1000000D03*\nX9000000Y1000000D02*\nX9000000Y9000000D03*\n")),
+            soldermask: None,
+            silkscreen: None,
+            drills: Vec::new(),
+        };
+        let options = RasterOptions { dpi: 25.4, antialias: Antialias::Off, background: Color32::BLACK, margin: 0.0 };
+        let appearance = BoardAppearance::default();
+        let top = render_realistic(&board, Side::Bottom, &appearance, &options, false).unwrap();
+        let bottom = render_realistic(&board, Side::Bottom, &appearance, &options, true).unwrap();
+        for y in 0..top.height() {
+            for x in 0..top.width() {
+                assert_eq!(top.get_pixel(x, y), bottom.get_pixel(top.width() - 1 - x, y));
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use nalgebra::Point2;
+
+use crate::DemoLensApp;
+use crate::geometry::{shapes, LayerGeometry};
+use crate::project_manager::annotations::{Annotation, AnnotationKind};
+use super::drawings::{DrawingSet, TitleBlock};
+use super::svg::layer_svg_content;
+
+// Design review report (HTML)
+// A single self-contained page: the board outline with the numbered annotations
+// as inline SVG, followed by a table of the review comments.
+
+const OPEN_COLOR: &str = "#d91a1a";
+const RESOLVED_COLOR: &str = "#1a9933";
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Extents of the outline and every annotation point
+fn review_bounds(outline: Option<&LayerGeometry>, annotations: &[Annotation]) -> Option<(Point2<f64>, Point2<f64>)> {
+    let mut corners = Vec::new();
+    if let Some((min, max)) = outline.and_then(|outline| outline.bounds()) {
+        corners.extend([min, max]);
+    }
+    for annotation in annotations {
+        corners.push(Point2::new(annotation.position.0, annotation.position.1));
+        if let Some((x, y)) = annotation.end {
+            corners.push(Point2::new(x, y));
+        }
+    }
+    shapes::points_bounds(&corners)
+}
+
+/// Board outline and annotations as an SVG element (board Y up, so Y is negated)
+fn review_svg(outline: Option<&LayerGeometry>, annotations: &[Annotation]) -> String {
+    let Some((min, max)) = review_bounds(outline, annotations) else {
+        return String::new();
+    };
+    let margin = ((max.x - min.x).max(max.y - min.y) * 0.05).max(2.0);
+    let view_box = (min.x - margin, -(max.y + margin), max.x - min.x + 2.0 * margin, max.y - min.y + 2.0 * margin);
+    // Annotation strokes and labels scale with the board
+    let unit = view_box.2.max(view_box.3) / 200.0;
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{:.4} {:.4} {:.4} {:.4}\" class=\"board\">",
+        view_box.0, view_box.1, view_box.2, view_box.3
+    );
+    if let Some(outline) = outline {
+        let (defs, content) = layer_svg_content(outline, "outline", view_box, |p| p);
+        if !defs.is_empty() {
+            let _ = write!(svg, "<defs>{}</defs>", defs);
+        }
+        let _ = write!(svg, "<g fill=\"#444\">\n{}</g>\n", content);
+    }
+
+    for annotation in annotations {
+        let color = if annotation.is_open() { OPEN_COLOR } else { RESOLVED_COLOR };
+        let (x, y) = (annotation.position.0, -annotation.position.1);
+        let _ = write!(svg, "<g stroke=\"{}\" stroke-width=\"{:.4}\" fill=\"none\">", color, unit * 0.6);
+        match (annotation.kind, annotation.end) {
+            (AnnotationKind::Rectangle, Some((ex, ey))) => {
+                let _ = write!(
+                    svg,
+                    "<rect x=\"{:.4}\" y=\"{:.4}\" width=\"{:.4}\" height=\"{:.4}\"/>",
+                    x.min(ex), y.min(-ey), (ex - x).abs(), (-ey - y).abs()
+                );
+            }
+            (AnnotationKind::Arrow, Some((ex, ey))) => {
+                let (ex, ey) = (ex, -ey);
+                let angle = (ey - y).atan2(ex - x);
+                let head = |offset: f64| (ex - unit * 4.0 * (angle + offset).cos(), ey - unit * 4.0 * (angle + offset).sin());
+                let (left, right) = (head(0.4), head(-0.4));
+                let _ = write!(
+                    svg,
+                    "<path d=\"M{:.4} {:.4}L{:.4} {:.4}M{:.4} {:.4}L{:.4} {:.4}L{:.4} {:.4}\"/>",
+                    x, y, ex, ey, left.0, left.1, ex, ey, right.0, right.1
+                );
+            }
+            _ => {
+                let _ = write!(svg, "<circle cx=\"{:.4}\" cy=\"{:.4}\" r=\"{:.4}\"/>", x, y, unit * 2.0);
+            }
+        }
+        let _ = writeln!(
+            svg,
+            "</g><text x=\"{:.4}\" y=\"{:.4}\" font-size=\"{:.4}\" fill=\"{}\" font-family=\"sans-serif\">{}</text>",
+            x + unit * 2.5, y - unit * 2.5, unit * 5.0, color, annotation.id
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Build the review page for the annotations
+pub fn generate_review_html(title: &TitleBlock, outline: Option<&LayerGeometry>, annotations: &[Annotation]) -> String {
+    let project = if title.project.is_empty() { "Untitled" } else { title.project.as_str() };
+    let open = annotations.iter().filter(|annotation| annotation.is_open()).count();
+
+    let mut html = String::new();
+    let _ = writeln!(html, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(html, "<title>{} - Design Review</title>", escape_html(project));
+    let _ = writeln!(
+        html,
+        "<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; color: #222; }}\n\
+         svg.board {{ width: 100%; max-height: 60vh; background: #f4f4f4; border: 1px solid #ccc; }}\n\
+         table {{ border-collapse: collapse; width: 100%; margin-top: 1.5em; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }}\n\
+         th {{ background: #eee; }}\n\
+         .open {{ color: {}; font-weight: bold; }}\n\
+         .resolved {{ color: {}; }}\n\
+         </style>\n</head>\n<body>",
+        OPEN_COLOR, RESOLVED_COLOR
+    );
+    let _ = writeln!(html, "<h1>{} - Design Review</h1>", escape_html(project));
+    let mut details = Vec::new();
+    if !title.revision.is_empty() {
+        details.push(format!("Revision {}", escape_html(&title.revision)));
+    }
+    if !title.date.is_empty() {
+        details.push(escape_html(&title.date));
+    }
+    if !title.company.is_empty() {
+        details.push(escape_html(&title.company));
+    }
+    details.push(format!("{} open, {} resolved", open, annotations.len() - open));
+    let _ = writeln!(html, "<p>{}</p>", details.join(" &middot; "));
+
+    html.push_str(&review_svg(outline, annotations));
+
+    let _ = writeln!(
+        html,
+        "<table>\n<tr><th>#</th><th>Status</th><th>Layer</th><th>Type</th><th>Location (mm)</th><th>Author</th><th>Date</th><th>Comment</th></tr>"
+    );
+    for annotation in annotations {
+        let (x, y) = annotation.center();
+        let status_class = if annotation.is_open() { "open" } else { "resolved" };
+        let _ = writeln!(
+            html,
+            "<tr><td>{}</td><td class=\"{}\">{}</td><td>{}</td><td>{}</td><td>{:.3}, {:.3}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            annotation.id,
+            status_class,
+            annotation.status.display_name(),
+            annotation.layer.display_name(),
+            annotation.kind.display_name(),
+            x,
+            y,
+            escape_html(&annotation.author),
+            annotation.created_at.format("%Y-%m-%d %H:%M"),
+            escape_html(&annotation.text).replace('\n', "<br>"),
+        );
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+    html
+}
+
+/// Write the review page for the session's annotations
+/// Returns the number of annotations in the report.
+pub fn export_review_html(app: &mut DemoLensApp, output_path: &Path, title: &TitleBlock) -> Result<usize, String> {
+    let annotations = app.review.annotations.clone();
+    if annotations.is_empty() {
+        return Err("No review annotations to export".to_string());
+    }
+    let set = DrawingSet::from_world(&mut app.ecs_world, Vec::new());
+    let html = generate_review_html(title, set.outline.as_ref(), &annotations);
+    std::fs::write(output_path, html).map_err(|e| format!("Failed to save HTML: {}", e))?;
+    Ok(annotations.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::LayerType;
+    use crate::project_manager::annotations::AnnotationStatus;
+
+    #[test]
+    fn test_review_html() {
+        let annotation = Annotation {
+            id: 7,
+            kind: AnnotationKind::Arrow,
+            layer: LayerType::Copper(1),
+            position: (1.0, 1.0),
+            end: Some((5.0, 3.0)),
+            text: "Neck <0.1 mm & too thin".to_string(),
+            author: "reviewer".to_string(),
+            status: AnnotationStatus::Open,
+            created_at: chrono::Utc::now(),
+        };
+        let title = TitleBlock { project: "Sensor".to_string(), ..Default::default() };
+        let html = generate_review_html(&title, None, &[annotation]);
+
+        assert!(html.contains("<title>Sensor - Design Review</title>"));
+        assert!(html.contains("Neck &lt;0.1 mm &amp; too thin"));
+        assert!(html.contains("1 open, 0 resolved"));
+        // Arrow is located by its tip, with Y flipped in the SVG
+        assert!(html.contains("5.000, 3.000"));
+        assert!(html.contains("<path d=\"M1.0000 -1.0000L5.0000 -3.0000"));
+    }
+}
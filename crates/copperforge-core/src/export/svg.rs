@@ -0,0 +1,278 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use egui::Color32;
+use nalgebra::Point2;
+
+use crate::DemoLensApp;
+use crate::geometry::{LayerGeometry, Polarity, Polygon, DEFAULT_TOLERANCE};
+use super::{collect_export_layers, export_layers_bounds, layer_file_stem, ExportLayer};
+
+// SVG export
+// Each layer becomes a <g> of filled paths in mm. Holes inside a feature use the
+// non-zero fill rule; clear polarity features are applied with masks so later
+// dark features on top of them still show.
+
+/// Settings for SVG exports
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SvgOptions {
+    /// Background rectangle, None for transparent
+    pub background: Option<Color32>,
+    /// Blank border around the board (mm)
+    pub margin: f64,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            background: None,
+            margin: 1.0,
+        }
+    }
+}
+
+/// "#rrggbb" form of a color
+pub(super) fn hex_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Board Y to SVG Y (down), without producing "-0"
+pub(super) fn svg_y(y: f64) -> f64 {
+    if y == 0.0 { 0.0 } else { -y }
+}
+
+/// XML id from a name, e.g. "top_copper_(l1)" to "top_copper_l1"
+/// Only [A-Za-z0-9_-] is kept so the id also works inside url(#...).
+pub fn svg_id(name: &str) -> String {
+    let id = name
+        .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    // Ids must start with a letter
+    if id.starts_with(|c: char| c.is_ascii_alphabetic()) { id } else { format!("id_{}", id) }
+}
+
+/// Path data for the polygons of one feature, Y flipped to SVG coordinates
+pub fn polygons_path_data(polygons: &[Polygon]) -> String {
+    let mut data = String::new();
+    for polygon in polygons.iter().filter(|p| p.points.len() >= 3) {
+        // Dark contours and holes wind in opposite directions for the non-zero rule
+        let polygon = polygon.clone().oriented();
+        for (i, point) in polygon.points.iter().enumerate() {
+            let command = if i == 0 { 'M' } else { 'L' };
+            let _ = write!(data, "{}{:.4} {:.4}", command, point.x, svg_y(point.y));
+        }
+        data.push('Z');
+    }
+    data
+}
+
+/// SVG markup of a layer as (mask definitions, group content)
+/// `id` must be unique within the document; it prefixes the mask ids.
+pub fn layer_svg_content(
+    geometry: &LayerGeometry,
+    id: &str,
+    view_box: (f64, f64, f64, f64),
+    transform: impl Fn(Point2<f64>) -> Point2<f64>,
+) -> (String, String) {
+    let id = svg_id(id);
+    let mut defs = String::new();
+    let mut content = String::new();
+    let mut clear_paths = String::new();
+    let mut mask_count = 0;
+
+    // Wrap everything drawn so far in a mask cutting out the pending clear features
+    let mut apply_clear = |content: &mut String, clear_paths: &mut String, defs: &mut String| {
+        if clear_paths.is_empty() {
+            return;
+        }
+        mask_count += 1;
+        let mask_id = format!("{}-clear{}", id, mask_count);
+        let (x, y, width, height) = view_box;
+        let _ = writeln!(
+            defs,
+            "<mask id=\"{}\" maskUnits=\"userSpaceOnUse\" x=\"{:.4}\" y=\"{:.4}\" width=\"{:.4}\" height=\"{:.4}\">\
+             <rect x=\"{:.4}\" y=\"{:.4}\" width=\"{:.4}\" height=\"{:.4}\" fill=\"white\"/>{}</mask>",
+            mask_id, x, y, width, height, x, y, width, height, clear_paths
+        );
+        *content = format!("<g mask=\"url(#{})\">\n{}</g>\n", mask_id, content);
+        clear_paths.clear();
+    };
+
+    for feature in &geometry.features {
+        let polygons: Vec<Polygon> = geometry.feature_polygons(feature, DEFAULT_TOLERANCE).into_iter()
+            .map(|p| Polygon::new(p.points.iter().map(|pt| transform(*pt)).collect(), p.dark))
+            .collect();
+        let data = polygons_path_data(&polygons);
+        if data.is_empty() {
+            continue;
+        }
+        match feature.polarity {
+            Polarity::Dark => {
+                apply_clear(&mut content, &mut clear_paths, &mut defs);
+                let _ = writeln!(content, "<path d=\"{}\"/>", data);
+            }
+            Polarity::Clear => {
+                let _ = write!(clear_paths, "<path d=\"{}\" fill=\"black\"/>", data);
+            }
+        }
+    }
+    apply_clear(&mut content, &mut clear_paths, &mut defs);
+
+    (defs, content)
+}
+
+pub struct SvgExporter;
+
+impl SvgExporter {
+    /// Build an SVG document of the layers, bottom to top
+    pub fn render_layers(layers: &[ExportLayer], options: &SvgOptions) -> Result<String, String> {
+        let (min, max) = export_layers_bounds(layers).ok_or("No layer geometry to export")?;
+        Ok(Self::render_layers_in_bounds(layers, min, max, options))
+    }
+
+    fn render_layers_in_bounds(layers: &[ExportLayer], min: Point2<f64>, max: Point2<f64>, options: &SvgOptions) -> String {
+        let margin = options.margin;
+        let view_box = (
+            min.x - margin,
+            -(max.y + margin),
+            max.x - min.x + 2.0 * margin,
+            max.y - min.y + 2.0 * margin,
+        );
+        let size = (format!("{:.4}mm", view_box.2), format!("{:.4}mm", view_box.3));
+        Self::render_layers_in_view_box(layers, view_box, size, options.background, "")
+    }
+
+    /// SVG document of the layers over a view box, with `overlay` markup drawn on top
+    /// `size` holds the width and height attributes of the document.
+    pub(super) fn render_layers_in_view_box(
+        layers: &[ExportLayer],
+        view_box: (f64, f64, f64, f64),
+        size: (String, String),
+        background: Option<Color32>,
+        overlay: &str,
+    ) -> String {
+        let mut defs = String::new();
+        let mut body = String::new();
+        if let Some(background) = background {
+            let _ = writeln!(
+                body,
+                "<rect x=\"{:.4}\" y=\"{:.4}\" width=\"{:.4}\" height=\"{:.4}\" fill=\"{}\"/>",
+                view_box.0, view_box.1, view_box.2, view_box.3, hex_color(background)
+            );
+        }
+
+        for layer in layers {
+            let id = svg_id(&layer_file_stem(&layer.layer_type));
+            let (layer_defs, content) = layer_svg_content(&layer.geometry, &id, view_box, |p| layer.transform.apply_to_position(p));
+            defs.push_str(&layer_defs);
+            let opacity = layer.opacity.clamp(0.0, 1.0) * layer.color.a() as f32 / 255.0;
+            let _ = write!(
+                body,
+                "<g id=\"{}\" inkscape:label=\"{}\" inkscape:groupmode=\"layer\" fill=\"{}\" fill-rule=\"nonzero\" opacity=\"{:.3}\">\n{}</g>\n",
+                id,
+                layer.layer_type.display_name(),
+                hex_color(layer.color),
+                opacity,
+                content
+            );
+        }
+
+        body.push_str(overlay);
+
+        let mut svg = String::new();
+        let _ = writeln!(svg, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>");
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:inkscape=\"http://www.inkscape.org/namespaces/inkscape\" \
+             width=\"{}\" height=\"{}\" viewBox=\"{:.4} {:.4} {:.4} {:.4}\">",
+            size.0, size.1, view_box.0, view_box.1, view_box.2, view_box.3
+        );
+        if !defs.is_empty() {
+            let _ = write!(svg, "<defs>\n{}</defs>\n", defs);
+        }
+        svg.push_str(&body);
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Export all visible layers as one SVG with a group per layer
+    pub fn export_composite_svg(app: &mut DemoLensApp, output_path: &Path, options: &SvgOptions) -> Result<(), String> {
+        let layers = collect_export_layers(&mut app.ecs_world, &app.display_manager);
+        if layers.is_empty() {
+            return Err("No visible layers to export".to_string());
+        }
+        let svg = Self::render_layers(&layers, options)?;
+        println!("Exporting composite of {} layers to {:?}", layers.len(), output_path);
+        std::fs::write(output_path, svg).map_err(|e| format!("Failed to save SVG: {}", e))
+    }
+
+    /// Export each visible layer as its own SVG, all with the same board bounds
+    pub fn export_layer_svgs(app: &mut DemoLensApp, output_dir: &Path, options: &SvgOptions) -> Result<Vec<PathBuf>, String> {
+        std::fs::create_dir_all(output_dir).map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+        let layers = collect_export_layers(&mut app.ecs_world, &app.display_manager);
+        let (min, max) = export_layers_bounds(&layers).ok_or("No visible layers to export")?;
+
+        let mut exported_files = Vec::new();
+        for layer in &layers {
+            let svg = Self::render_layers_in_bounds(std::slice::from_ref(layer), min, max, options);
+            let output_path = output_dir.join(format!("{}.svg", layer_file_stem(&layer.layer_type)));
+            std::fs::write(&output_path, svg).map_err(|e| format!("Failed to save SVG: {}", e))?;
+            exported_files.push(output_path);
+        }
+        Ok(exported_files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+
+    #[test]
+    fn test_clear_polarity_masks() {
+        let gerber = "%FSLAX26Y26*%\n%MOMM*%\n%ADD10R,4X4*%\n%ADD11C,2*%\n\
+            D10*\nX5000000Y5000000D03*\n%LPC*%\nD11*\nX5000000Y5000000D03*\n\
+            %LPD*%\nX9000000Y5000000D03*\nM02*\n";
+        let geometry = parse_gerber(gerber).unwrap();
+        let (defs, content) = layer_svg_content(&geometry, "top", (0.0, -10.0, 10.0, 10.0), |p| p);
+
+        assert_eq!(defs.matches("<mask").count(), 1);
+        assert!(content.starts_with("<g mask=\"url(#top-clear1)\">"));
+        // The dark flash after the clear one is drawn outside the mask
+        assert_eq!(content.matches("<path").count(), 2);
+        assert!(content.trim_end().ends_with("Z\"/>"));
+    }
+
+    #[test]
+    fn test_layer_ids_are_valid_xml() {
+        // Clear polarity copper: the layer id is also the mask id prefix
+        let mut world = crate::ecs::setup_ecs_world();
+        crate::ecs::test_support::spawn_layer(&mut world, crate::ecs::LayerType::Copper(1),
+            "%FSLAX26Y26*%\n%MOMM*%\n%ADD10R,4X4*%\n%ADD11C,2*%\n\
+            D10*\nX5000000Y5000000D03*\n%LPC*%\nD11*\nX5000000Y5000000D03*\nM02*\n");
+        let layers = collect_export_layers(&mut world, &crate::display::DisplayManager::new());
+        let svg = SvgExporter::render_layers(&layers, &SvgOptions::default()).unwrap();
+
+        assert!(svg.contains("<g id=\"top_copper_l1\""));
+        assert!(svg.contains("<mask id=\"top_copper_l1-clear1\""));
+        assert!(svg.contains("mask=\"url(#top_copper_l1-clear1)\""));
+        assert!(!svg.contains("(l1)"));
+        assert_eq!(svg_id("2nd layer"), "id_2nd_layer");
+    }
+
+    #[test]
+    fn test_path_data_flips_y() {
+        let square = Polygon::new(vec![
+            Point2::new(0.0, 0.0),
+            Point2::new(1.0, 0.0),
+            Point2::new(1.0, 2.0),
+            Point2::new(0.0, 2.0),
+        ], true);
+        assert_eq!(
+            polygons_path_data(&[square]),
+            "M0.0000 0.0000L1.0000 0.0000L1.0000 -2.0000L0.0000 -2.0000Z"
+        );
+    }
+}
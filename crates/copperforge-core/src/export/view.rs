@@ -0,0 +1,509 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use ab_glyph::{Font, FontArc, GlyphId, PxScale, ScaleFont};
+use bevy_ecs::prelude::*;
+use eframe::emath::Rect;
+use egui::Color32;
+use gerber_viewer::ViewState;
+use image::{ImageBuffer, Rgba, RgbaImage};
+use nalgebra::{Point2, Vector2};
+
+use crate::DemoLensApp;
+use crate::display::DisplayManager;
+use crate::drc_operations::types::Position;
+use crate::ecs::{format_measurement, Measurement, UnitsResource};
+use crate::geometry::{shapes, Antialias, CoverageMask, Polygon, RasterView};
+use super::svg::{hex_color, svg_y, SvgExporter};
+use super::{collect_export_layers, ExportLayer, PngExporter};
+
+// Current view export
+// Renders the area shown in the viewer, with the visible layers in their colors
+// and the chosen overlays, at an output width independent of the window size.
+// Overlay strokes keep their on-screen thickness relative to the view, and PNG
+// labels use the viewer's monospace font. Works on a world and a display
+// manager alone, so it also runs without the GUI.
+
+const MEASUREMENT_COLOR: Color32 = Color32::from_rgb(255, 190, 70);
+const DRC_MARKER_COLOR: Color32 = Color32::RED;
+/// Most grid dots drawn, as in the viewer
+const MAX_GRID_DOTS: i64 = 10_000;
+
+/// Area of the board to export, in viewer coordinates (mm)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewRegion {
+    pub min: Point2<f64>,
+    pub max: Point2<f64>,
+    /// Width of the viewer in screen pixels, overlays are scaled from it to the
+    /// output width. None draws them at their screen size in output pixels.
+    pub screen_width: Option<f32>,
+}
+
+impl ViewRegion {
+    /// Region shown by a view state in a viewport
+    pub fn from_view_state(view_state: &ViewState, viewport: &Rect) -> Self {
+        let top_left = view_state.screen_to_gerber_coords(viewport.min);
+        let bottom_right = view_state.screen_to_gerber_coords(viewport.max);
+        Self {
+            min: Point2::new(top_left.x.min(bottom_right.x), top_left.y.min(bottom_right.y)),
+            max: Point2::new(top_left.x.max(bottom_right.x), top_left.y.max(bottom_right.y)),
+            screen_width: Some(viewport.width()),
+        }
+    }
+
+    /// Region covering the given bounds, for exports without a viewer
+    pub fn from_bounds(min: Point2<f64>, max: Point2<f64>) -> Self {
+        Self { min, max, screen_width: None }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    /// Output size for a width in pixels, keeping the aspect ratio of the region
+    pub fn pixel_size(&self, width: u32) -> (u32, u32) {
+        let width = width.max(1);
+        let height = (width as f64 * self.height() / self.width()).round().max(1.0);
+        (width, height as u32)
+    }
+
+    fn raster_view(&self, width: u32) -> RasterView {
+        let (width, height) = self.pixel_size(width);
+        let scale = width as f64 / self.width();
+        RasterView {
+            scale,
+            translation: (-self.min.x * scale, self.max.y * scale),
+            width,
+            height,
+        }
+    }
+
+    /// Screen pixels per mm in the viewer, for sizing overlays
+    fn screen_scale(&self, width: u32) -> f64 {
+        match self.screen_width.filter(|screen_width| *screen_width > 0.0) {
+            Some(screen_width) => screen_width as f64 / self.width(),
+            None => self.raster_view(width).scale,
+        }
+    }
+
+    fn validate(&self, width: u32) -> Result<(), String> {
+        if !(self.width() > 0.0 && self.height() > 0.0) {
+            return Err("The view to export is empty".to_string());
+        }
+        let (width, height) = self.pixel_size(width);
+        if width as u64 * height as u64 > 400_000_000 {
+            return Err(format!("Image of {}x{} pixels is too large, lower the width", width, height));
+        }
+        Ok(())
+    }
+}
+
+/// Settings for exporting the current view
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewExportOptions {
+    /// Output width in pixels, the height follows the view's aspect ratio
+    pub width: u32,
+    pub antialias: Antialias,
+    /// Fully transparent leaves the background empty
+    pub background: Color32,
+    pub grid: bool,
+    pub drc_markers: bool,
+    pub measurements: bool,
+}
+
+impl Default for ViewExportOptions {
+    fn default() -> Self {
+        Self {
+            width: 3840,
+            antialias: Antialias::Low,
+            background: Color32::BLACK,
+            grid: false,
+            drc_markers: true,
+            measurements: true,
+        }
+    }
+}
+
+/// Overlays drawn over the layers, in viewer coordinates
+#[derive(Debug, Clone, Default)]
+pub struct ViewOverlays {
+    /// Grid spacing (mm) and dot radius (screen pixels)
+    pub grid: Option<(f64, f32)>,
+    pub drc_markers: Vec<Point2<f64>>,
+    /// Measurements with their labels
+    pub measurements: Vec<(Measurement, String)>,
+}
+
+impl ViewOverlays {
+    /// Overlays shown in the viewer, limited to those enabled in the options
+    pub fn from_app(app: &DemoLensApp, options: &ViewExportOptions) -> Self {
+        let grid = (options.grid && app.grid_settings.enabled)
+            .then(|| (app.grid_settings.spacing_mm as f64, app.grid_settings.dot_size));
+        let drc_markers = if options.drc_markers {
+            app.drc_manager.violations.iter()
+                .map(|violation| app.display_manager.board_to_viewer(Position::new(violation.x as f64, violation.y as f64), app.rotation_degrees))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let measurements = if options.measurements {
+            let units = app.ecs_world.get_resource::<UnitsResource>().cloned().unwrap_or_default();
+            crate::ecs::get_measurements(&app.ecs_world).into_iter()
+                .enumerate()
+                .map(|(index, measurement)| {
+                    let label = format!("#{} {}", index + 1, format_measurement(&measurement, &units));
+                    (measurement, label)
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Self { grid, drc_markers, measurements }
+    }
+}
+
+/// Overlay primitive in viewer coordinates, sizes in mm
+#[derive(Debug, Clone, PartialEq)]
+enum OverlayShape {
+    Line { points: Vec<Point2<f64>>, closed: bool, width: f64, color: Color32 },
+    Dot { center: Point2<f64>, radius: f64, color: Color32 },
+    Label { anchor: Point2<f64>, text: String, size: f64, color: Color32 },
+}
+
+/// Overlays as primitives, sized as the viewer draws them
+fn overlay_shapes(region: &ViewRegion, overlays: &ViewOverlays, width: u32) -> Vec<OverlayShape> {
+    let screen_scale = region.screen_scale(width);
+    let px = |pixels: f64| pixels / screen_scale;
+    let mut primitives = Vec::new();
+
+    if let Some((spacing, dot_size)) = overlays.grid.filter(|(spacing, _)| *spacing > 0.0) {
+        // Same visibility limits as the viewer grid
+        let spacing_screen = spacing * screen_scale;
+        let screen_extent = region.width().min(region.height()) * screen_scale;
+        let start_x = (region.min.x / spacing).ceil() as i64;
+        let end_x = (region.max.x / spacing).floor() as i64;
+        let start_y = (region.min.y / spacing).ceil() as i64;
+        let end_y = (region.max.y / spacing).floor() as i64;
+        let count = (end_x - start_x + 1).max(0) * (end_y - start_y + 1).max(0);
+        if spacing_screen >= 5.0 && spacing_screen <= screen_extent * 0.5 && count <= MAX_GRID_DOTS {
+            let alpha = if spacing_screen > 50.0 { 120 } else { 60 };
+            let color = Color32::from_rgba_unmultiplied(100, 100, 100, alpha);
+            for grid_x in start_x..=end_x {
+                for grid_y in start_y..=end_y {
+                    let center = Point2::new(grid_x as f64 * spacing, grid_y as f64 * spacing);
+                    primitives.push(OverlayShape::Dot { center, radius: px(dot_size as f64), color });
+                }
+            }
+        }
+    }
+
+    for (measurement, label) in &overlays.measurements {
+        let points = measurement.points();
+        if points.is_empty() {
+            continue;
+        }
+        let closed = matches!(measurement, Measurement::Area { .. });
+        primitives.push(OverlayShape::Line { points: points.clone(), closed, width: px(1.5), color: MEASUREMENT_COLOR });
+        for point in &points {
+            primitives.push(OverlayShape::Dot { center: *point, radius: px(2.5), color: MEASUREMENT_COLOR });
+        }
+        // Label at the vertex of an angle, the centroid of an area, else the midpoint
+        let anchor = match measurement {
+            Measurement::Angle { .. } => points[1],
+            _ => Point2::from(points.iter().fold(Vector2::zeros(), |sum, point| sum + point.coords) / points.len() as f64),
+        };
+        primitives.push(OverlayShape::Label {
+            anchor: anchor + Vector2::new(px(8.0), px(8.0)),
+            text: label.clone(),
+            size: px(13.0),
+            color: MEASUREMENT_COLOR,
+        });
+    }
+
+    // Crosses sized like the viewer's, which grow with the zoom
+    let half = px(3.0 * screen_scale.max(0.5)) / 2.0;
+    for marker in &overlays.drc_markers {
+        for (dx, dy) in [(1.0, 1.0), (1.0, -1.0)] {
+            primitives.push(OverlayShape::Line {
+                points: vec![
+                    Point2::new(marker.x - half * dx, marker.y - half * dy),
+                    Point2::new(marker.x + half * dx, marker.y + half * dy),
+                ],
+                closed: false,
+                width: px(2.0),
+                color: DRC_MARKER_COLOR,
+            });
+        }
+    }
+    primitives
+}
+
+/// Filled polygons of an overlay shape; labels have none
+fn shape_polygons(shape: &OverlayShape, tolerance: f64) -> Vec<Polygon> {
+    match shape {
+        OverlayShape::Line { points, closed, width, .. } => {
+            let pen = shapes::circle_points(Point2::origin(), width / 2.0, tolerance);
+            let mut segments: Vec<(Point2<f64>, Point2<f64>)> = points.windows(2).map(|pair| (pair[0], pair[1])).collect();
+            if *closed && points.len() > 2 {
+                segments.push((points[points.len() - 1], points[0]));
+            }
+            segments.into_iter()
+                .map(|(start, end)| Polygon::new(shapes::sweep_convex(&pen, start, end), true))
+                .collect()
+        }
+        OverlayShape::Dot { center, radius, .. } => vec![Polygon::new(shapes::circle_points(*center, *radius, tolerance), true)],
+        OverlayShape::Label { .. } => Vec::new(),
+    }
+}
+
+/// Monospace font of the viewer, for labels in raster exports
+fn label_font() -> Option<FontArc> {
+    let fonts = egui::FontDefinitions::default();
+    let name = fonts.families.get(&egui::FontFamily::Monospace)?.first()?;
+    let data = fonts.font_data.get(name)?;
+    FontArc::try_from_vec(data.font.to_vec()).ok()
+}
+
+/// Add the coverage of a line of text to a mask, its baseline starting at `anchor`
+fn paint_label(
+    mask: &mut CoverageMask,
+    font: &FontArc,
+    anchor: Point2<f64>,
+    text: &str,
+    size: f64,
+    view: &RasterView,
+    antialias: Antialias,
+) {
+    let scaled = font.as_scaled(PxScale::from((size * view.scale) as f32));
+    let (x, y) = view.to_pixel(anchor);
+    let mut caret = ab_glyph::point(x as f32, y as f32);
+    let mut previous: Option<GlyphId> = None;
+    for c in text.chars() {
+        let id = scaled.glyph_id(c);
+        if let Some(previous) = previous {
+            caret.x += scaled.kern(previous, id);
+        }
+        let glyph = id.with_scale_and_position(scaled.scale(), caret);
+        caret.x += scaled.h_advance(id);
+        previous = Some(id);
+
+        let Some(outline) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outline.px_bounds();
+        outline.draw(|glyph_x, glyph_y, coverage| {
+            let px = bounds.min.x as i64 + glyph_x as i64;
+            let py = bounds.min.y as i64 + glyph_y as i64;
+            if px < 0 || py < 0 || px >= mask.width as i64 || py >= mask.height as i64 {
+                return;
+            }
+            let coverage = if antialias == Antialias::Off { coverage.round() } else { coverage };
+            let cell = &mut mask.data[py as usize * mask.width as usize + px as usize];
+            *cell = (*cell + coverage).min(1.0);
+        });
+    }
+}
+
+/// Rasterize the visible layers and overlays of a region
+pub fn render_view_image(
+    world: &mut World,
+    display_manager: &DisplayManager,
+    region: &ViewRegion,
+    overlays: &ViewOverlays,
+    options: &ViewExportOptions,
+) -> Result<RgbaImage, String> {
+    region.validate(options.width)?;
+    let layers = collect_export_layers(world, display_manager);
+    Ok(render_layers_view(&layers, region, overlays, options))
+}
+
+fn render_layers_view(layers: &[ExportLayer], region: &ViewRegion, overlays: &ViewOverlays, options: &ViewExportOptions) -> RgbaImage {
+    let view = region.raster_view(options.width);
+    let background = options.background;
+    let mut img: RgbaImage = ImageBuffer::from_pixel(
+        view.width,
+        view.height,
+        Rgba([background.r(), background.g(), background.b(), background.a()]),
+    );
+    for layer in layers {
+        let mask = crate::geometry::rasterize_layer(&layer.geometry, &view, options.antialias, |p| layer.transform.apply_to_position(p));
+        PngExporter::blend_mask(&mut img, &mask, layer.color, layer.opacity);
+    }
+
+    // Consecutive shapes of one color share a mask
+    let tolerance = view.tolerance();
+    let font = if overlays.measurements.is_empty() { None } else { label_font() };
+    let mut current: Option<(Color32, CoverageMask)> = None;
+    for shape in overlay_shapes(region, overlays, options.width) {
+        let color = match &shape {
+            OverlayShape::Line { color, .. } | OverlayShape::Dot { color, .. } | OverlayShape::Label { color, .. } => *color,
+        };
+        if let Some((previous, mask)) = current.take_if(|(current_color, _)| *current_color != color) {
+            PngExporter::blend_mask(&mut img, &mask, previous, 1.0);
+        }
+        let (_, mask) = current.get_or_insert_with(|| (color, CoverageMask::new(view.width, view.height)));
+        match &shape {
+            OverlayShape::Label { anchor, text, size, .. } => {
+                if let Some(font) = &font {
+                    paint_label(mask, font, *anchor, text, *size, &view, options.antialias);
+                }
+            }
+            _ => mask.paint(&shape_polygons(&shape, tolerance), &view, options.antialias, true),
+        }
+    }
+    if let Some((color, mask)) = current {
+        PngExporter::blend_mask(&mut img, &mask, color, 1.0);
+    }
+    img
+}
+
+/// SVG document of the visible layers and overlays of a region, sized in pixels
+pub fn render_view_svg(
+    world: &mut World,
+    display_manager: &DisplayManager,
+    region: &ViewRegion,
+    overlays: &ViewOverlays,
+    options: &ViewExportOptions,
+) -> Result<String, String> {
+    region.validate(options.width)?;
+    let layers = collect_export_layers(world, display_manager);
+    Ok(render_layers_view_svg(&layers, region, overlays, options))
+}
+
+fn render_layers_view_svg(layers: &[ExportLayer], region: &ViewRegion, overlays: &ViewOverlays, options: &ViewExportOptions) -> String {
+    let view_box = (region.min.x, -region.max.y, region.width(), region.height());
+    let (width, height) = region.pixel_size(options.width);
+
+    let mut overlay = String::new();
+    for shape in overlay_shapes(region, overlays, options.width) {
+        match shape {
+            OverlayShape::Line { points, closed, width, color } => {
+                let mut data = String::new();
+                for (i, point) in points.iter().enumerate() {
+                    let _ = write!(data, "{}{:.4} {:.4}", if i == 0 { 'M' } else { 'L' }, point.x, svg_y(point.y));
+                }
+                if closed {
+                    data.push('Z');
+                }
+                let _ = writeln!(
+                    overlay,
+                    "<path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-opacity=\"{:.3}\" stroke-width=\"{:.4}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+                    data, hex_color(color), color.a() as f32 / 255.0, width
+                );
+            }
+            OverlayShape::Dot { center, radius, color } => {
+                let _ = writeln!(
+                    overlay,
+                    "<circle cx=\"{:.4}\" cy=\"{:.4}\" r=\"{:.4}\" fill=\"{}\" fill-opacity=\"{:.3}\"/>",
+                    center.x, svg_y(center.y), radius, hex_color(color), color.a() as f32 / 255.0
+                );
+            }
+            OverlayShape::Label { anchor, text, size, color } => {
+                let text = text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+                let _ = writeln!(
+                    overlay,
+                    "<text x=\"{:.4}\" y=\"{:.4}\" font-size=\"{:.4}\" font-family=\"monospace\" fill=\"{}\">{}</text>",
+                    anchor.x, svg_y(anchor.y), size, hex_color(color), text
+                );
+            }
+        }
+    }
+
+    let background = (options.background.a() > 0).then_some(options.background);
+    SvgExporter::render_layers_in_view_box(layers, view_box, (width.to_string(), height.to_string()), background, &overlay)
+}
+
+/// Region shown in the viewer at the last frame
+fn current_region(app: &DemoLensApp, viewport: Option<&Rect>) -> Result<ViewRegion, String> {
+    let viewport = viewport.copied().or(app.viewer_rect).ok_or("The viewer has not been drawn yet")?;
+    Ok(ViewRegion::from_view_state(&app.view_state, &viewport))
+}
+
+impl PngExporter {
+    /// Export the area shown in the viewer as a PNG
+    /// `viewport` defaults to the viewer's rect at the last frame.
+    pub fn export_current_view(
+        app: &mut DemoLensApp,
+        output_path: &Path,
+        viewport: Option<&Rect>,
+        options: &ViewExportOptions,
+    ) -> Result<(u32, u32), String> {
+        let region = current_region(app, viewport)?;
+        let overlays = ViewOverlays::from_app(app, options);
+        let img = render_view_image(&mut app.ecs_world, &app.display_manager, &region, &overlays, options)?;
+        println!("Exporting current view to {:?} ({}x{} px)", output_path, img.width(), img.height());
+        img.save(output_path).map_err(|e| format!("Failed to save PNG: {}", e))?;
+        Ok(img.dimensions())
+    }
+}
+
+impl SvgExporter {
+    /// Export the area shown in the viewer as an SVG
+    /// `viewport` defaults to the viewer's rect at the last frame.
+    pub fn export_current_view(
+        app: &mut DemoLensApp,
+        output_path: &Path,
+        viewport: Option<&Rect>,
+        options: &ViewExportOptions,
+    ) -> Result<(u32, u32), String> {
+        let region = current_region(app, viewport)?;
+        let overlays = ViewOverlays::from_app(app, options);
+        let svg = render_view_svg(&mut app.ecs_world, &app.display_manager, &region, &overlays, options)?;
+        println!("Exporting current view to {:?}", output_path);
+        std::fs::write(output_path, svg).map_err(|e| format!("Failed to save SVG: {}", e))?;
+        Ok(region.pixel_size(options.width))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::test_support::spawn_layer;
+    use crate::ecs::LayerType;
+
+    // A 2 mm square pad at the origin
+    const PAD: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,2.000000X2.000000*%\nD10*\nX0Y0D03*\nM02*\n";
+
+    #[test]
+    fn test_view_export_matches_region() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let display_manager = DisplayManager::new();
+        spawn_layer(&mut world, LayerType::Copper(1), PAD);
+        crate::ecs::update_layer_render_properties(&mut world, LayerType::Copper(1), Color32::RED);
+
+        // Viewer showing x -5..5, y -2.5..2.5 in an 800x400 window
+        let view_state = ViewState { scale: 80.0, base_scale: 80.0, translation: egui::Vec2::new(400.0, 200.0) };
+        let viewport = Rect::from_min_size(egui::Pos2::ZERO, egui::Vec2::new(800.0, 400.0));
+        let region = ViewRegion::from_view_state(&view_state, &viewport);
+        assert!((region.min - Point2::new(-5.0, -2.5)).norm() < 1e-6);
+        assert_eq!(region.pixel_size(2000), (2000, 1000));
+
+        let overlays = ViewOverlays { drc_markers: vec![Point2::new(3.0, 0.0)], ..Default::default() };
+        let options = ViewExportOptions { width: 2000, antialias: Antialias::Off, ..Default::default() };
+        let img = render_view_image(&mut world, &display_manager, &region, &overlays, &options).unwrap();
+        assert_eq!(img.dimensions(), (2000, 1000));
+        // Pad in the centre, background at the corner, DRC cross at x = 3 mm
+        assert_eq!(img.get_pixel(1000, 500).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(10, 10).0, [0, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1600, 500).0, [255, 0, 0, 255]);
+        assert_eq!(img.get_pixel(1400, 500).0, [0, 0, 0, 255]);
+
+        let svg = render_view_svg(&mut world, &display_manager, &region, &overlays, &options).unwrap();
+        assert!(svg.contains("width=\"2000\" height=\"1000\" viewBox=\"-5.0000 -2.5000 10.0000 5.0000\""));
+        assert!(svg.contains("stroke=\"#ff0000\""));
+
+        // Measurement label with its baseline at pixel (420, 880), clear of the line at y = 900
+        let measurement = Measurement::Distance { start: Point2::new(-4.0, -2.0), end: Point2::new(-2.0, -2.0) };
+        let overlays = ViewOverlays { measurements: vec![(measurement, "#1 2.000 mm".to_string())], ..Default::default() };
+        let img = render_view_image(&mut world, &display_manager, &region, &overlays, &options).unwrap();
+        let label_pixels = (420..700)
+            .flat_map(|x| (845..885).map(move |y| (x, y)))
+            .filter(|&(x, y)| img.get_pixel(x, y).0 != [0, 0, 0, 255])
+            .count();
+        assert!(label_pixels > 100);
+        assert_eq!(img.get_pixel(1000, 880).0, [0, 0, 0, 255]);
+    }
+}
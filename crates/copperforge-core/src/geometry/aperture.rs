@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+use nalgebra::{Point2, Vector2};
+
+use super::shapes::{self, Polygon};
+
+// Aperture definitions (%AD) and aperture macros (%AM)
+// Shapes are stored in mm, centred on the flash position
+
+/// Concrete aperture shape after macro evaluation
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApertureShape {
+    Circle { diameter: f64, hole: Option<f64> },
+    Rectangle { width: f64, height: f64, hole: Option<f64> },
+    Obround { width: f64, height: f64, hole: Option<f64> },
+    Polygon { diameter: f64, vertices: u32, rotation: f64, hole: Option<f64> },
+    Macro { name: String, primitives: Vec<MacroPrimitive> },
+}
+
+/// Evaluated aperture macro primitive
+#[derive(Debug, Clone, PartialEq)]
+pub enum MacroPrimitive {
+    Circle { dark: bool, diameter: f64, center: Point2<f64>, rotation: f64 },
+    VectorLine { dark: bool, width: f64, start: Point2<f64>, end: Point2<f64>, rotation: f64 },
+    CenterLine { dark: bool, width: f64, height: f64, center: Point2<f64>, rotation: f64 },
+    Outline { dark: bool, points: Vec<Point2<f64>>, rotation: f64 },
+    Polygon { dark: bool, vertices: u32, center: Point2<f64>, diameter: f64, rotation: f64 },
+    Thermal { center: Point2<f64>, outer: f64, inner: f64, gap: f64, rotation: f64 },
+}
+
+/// An aperture from the aperture dictionary
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aperture {
+    pub d_code: i32,
+    /// Template name: C, R, O, P or a macro name
+    pub template: String,
+    /// Parameter text exactly as written in the %AD command (file units)
+    pub raw_parameters: String,
+    pub shape: ApertureShape,
+    /// Aperture function from %TA.AperFunction, e.g. "SMDPad,CuDef" or "ViaPad"
+    pub function: Option<String>,
+}
+
+impl Aperture {
+    /// Whether the aperture function marks a component pad (not a via or conductor)
+    pub fn is_component_pad(&self) -> bool {
+        self.function.as_deref().is_some_and(is_pad_function)
+    }
+
+    /// Short human readable description, e.g. "R 1.390 x 1.390 mm"
+    pub fn describe(&self) -> String {
+        match &self.shape {
+            ApertureShape::Circle { diameter, .. } => format!("C Ø{:.3} mm", diameter),
+            ApertureShape::Rectangle { width, height, .. } => format!("R {:.3} x {:.3} mm", width, height),
+            ApertureShape::Obround { width, height, .. } => format!("O {:.3} x {:.3} mm", width, height),
+            ApertureShape::Polygon { diameter, vertices, .. } => format!("P{} Ø{:.3} mm", vertices, diameter),
+            ApertureShape::Macro { name, .. } => {
+                match self.size() {
+                    Some((w, h)) => format!("{} {:.3} x {:.3} mm", name, w, h),
+                    None => name.clone(),
+                }
+            }
+        }
+    }
+
+    /// Width and height of the aperture image
+    pub fn size(&self) -> Option<(f64, f64)> {
+        let polygons = self.polygons(shapes::DEFAULT_TOLERANCE);
+        let points: Vec<Point2<f64>> = polygons.iter()
+            .filter(|p| p.dark)
+            .flat_map(|p| p.points.iter().copied())
+            .collect();
+        shapes::points_bounds(&points).map(|(min, max)| (max.x - min.x, max.y - min.y))
+    }
+
+    /// Stroke width used when the aperture draws a track
+    pub fn stroke_width(&self) -> f64 {
+        match &self.shape {
+            ApertureShape::Circle { diameter, .. } => *diameter,
+            ApertureShape::Rectangle { width, height, .. } |
+            ApertureShape::Obround { width, height, .. } => width.min(*height),
+            ApertureShape::Polygon { diameter, .. } => *diameter,
+            ApertureShape::Macro { .. } => self.size().map(|(w, h)| w.min(h)).unwrap_or(0.0),
+        }
+    }
+
+    /// Outline of the aperture centred on the origin
+    pub fn polygons(&self, tolerance: f64) -> Vec<Polygon> {
+        let origin = Point2::origin();
+        let hole_polygon = |hole: &Option<f64>| {
+            hole.filter(|d| *d > 0.0)
+                .map(|d| Polygon::new(shapes::circle_points(origin, d / 2.0, tolerance), false))
+        };
+
+        let mut result = Vec::new();
+        match &self.shape {
+            ApertureShape::Circle { diameter, hole } => {
+                result.push(Polygon::new(shapes::circle_points(origin, diameter / 2.0, tolerance), true));
+                result.extend(hole_polygon(hole));
+            }
+            ApertureShape::Rectangle { width, height, hole } => {
+                result.push(Polygon::new(rectangle_points(origin, *width, *height), true));
+                result.extend(hole_polygon(hole));
+            }
+            ApertureShape::Obround { width, height, hole } => {
+                result.push(Polygon::new(obround_points(origin, *width, *height, tolerance), true));
+                result.extend(hole_polygon(hole));
+            }
+            ApertureShape::Polygon { diameter, vertices, rotation, hole } => {
+                result.push(Polygon::new(regular_polygon_points(origin, *diameter, *vertices, *rotation), true));
+                result.extend(hole_polygon(hole));
+            }
+            ApertureShape::Macro { primitives, .. } => {
+                for primitive in primitives {
+                    result.extend(primitive.polygons(tolerance));
+                }
+            }
+        }
+        result
+    }
+
+    /// Convex outline used to sweep tracks, when the aperture is convex
+    pub fn convex_outline(&self, tolerance: f64) -> Vec<Point2<f64>> {
+        let points: Vec<Point2<f64>> = self.polygons(tolerance).into_iter()
+            .filter(|p| p.dark)
+            .flat_map(|p| p.points)
+            .collect();
+        shapes::convex_hull(&points)
+    }
+}
+
+impl MacroPrimitive {
+    /// Outline of the primitive, rotated about the macro origin
+    pub fn polygons(&self, tolerance: f64) -> Vec<Polygon> {
+        match self {
+            MacroPrimitive::Circle { dark, diameter, center, rotation } => {
+                let c = shapes::rotate_point(*center, *rotation);
+                vec![Polygon::new(shapes::circle_points(c, diameter / 2.0, tolerance), *dark)]
+            }
+            MacroPrimitive::VectorLine { dark, width, start, end, rotation } => {
+                let dir = end - start;
+                let len = dir.norm();
+                if len < 1e-12 {
+                    return Vec::new();
+                }
+                let normal = Vector2::new(-dir.y, dir.x) / len * (width / 2.0);
+                let points = vec![start + normal, end + normal, end - normal, start - normal];
+                vec![Polygon::new(points, *dark).rotated(*rotation)]
+            }
+            MacroPrimitive::CenterLine { dark, width, height, center, rotation } => {
+                vec![Polygon::new(rectangle_points(*center, *width, *height), *dark).rotated(*rotation)]
+            }
+            MacroPrimitive::Outline { dark, points, rotation } => {
+                let mut points = points.clone();
+                if points.len() > 1 && (points[0] - points[points.len() - 1]).norm() < 1e-12 {
+                    points.pop();
+                }
+                vec![Polygon::new(points, *dark).rotated(*rotation)]
+            }
+            MacroPrimitive::Polygon { dark, vertices, center, diameter, rotation } => {
+                vec![Polygon::new(regular_polygon_points(*center, *diameter, *vertices, 0.0), *dark).rotated(*rotation)]
+            }
+            MacroPrimitive::Thermal { center, outer, inner, gap, rotation } => {
+                thermal_polygons(*center, *outer, *inner, *gap, tolerance)
+                    .into_iter()
+                    .map(|p| p.rotated(*rotation))
+                    .collect()
+            }
+        }
+    }
+}
+
+// ============================================================================
+// SHAPE HELPERS
+// ============================================================================
+
+pub fn rectangle_points(center: Point2<f64>, width: f64, height: f64) -> Vec<Point2<f64>> {
+    let (hw, hh) = (width / 2.0, height / 2.0);
+    vec![
+        Point2::new(center.x - hw, center.y - hh),
+        Point2::new(center.x + hw, center.y - hh),
+        Point2::new(center.x + hw, center.y + hh),
+        Point2::new(center.x - hw, center.y + hh),
+    ]
+}
+
+pub fn obround_points(center: Point2<f64>, width: f64, height: f64, tolerance: f64) -> Vec<Point2<f64>> {
+    let radius = width.min(height) / 2.0;
+    let circle = shapes::circle_points(Point2::origin(), radius, tolerance);
+    let (start, end) = if width >= height {
+        let d = (width - height) / 2.0;
+        (Point2::new(center.x - d, center.y), Point2::new(center.x + d, center.y))
+    } else {
+        let d = (height - width) / 2.0;
+        (Point2::new(center.x, center.y - d), Point2::new(center.x, center.y + d))
+    };
+    shapes::sweep_convex(&circle, start, end)
+}
+
+pub fn regular_polygon_points(center: Point2<f64>, diameter: f64, vertices: u32, rotation: f64) -> Vec<Point2<f64>> {
+    let n = vertices.max(3);
+    let radius = diameter / 2.0;
+    (0..n)
+        .map(|i| {
+            let angle = (rotation + 360.0 * i as f64 / n as f64).to_radians();
+            Point2::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+        })
+        .collect()
+}
+
+/// Thermal relief as four ring quarters separated by the gap cross
+fn thermal_polygons(center: Point2<f64>, outer: f64, inner: f64, gap: f64, tolerance: f64) -> Vec<Polygon> {
+    let (ro, ri, half_gap) = (outer / 2.0, inner / 2.0, gap / 2.0);
+    if ro <= half_gap * std::f64::consts::SQRT_2 {
+        return Vec::new();
+    }
+
+    // First quadrant piece, then rotated copies
+    let outer_start = (half_gap / ro).asin();
+    let outer_end = std::f64::consts::FRAC_PI_2 - outer_start;
+    let segments = shapes::segments_for_arc(ro, outer_end - outer_start, tolerance);
+
+    let mut piece = Vec::new();
+    for i in 0..=segments {
+        let a = outer_start + (outer_end - outer_start) * i as f64 / segments as f64;
+        piece.push(Point2::new(ro * a.cos(), ro * a.sin()));
+    }
+    if ri > half_gap * std::f64::consts::SQRT_2 {
+        let inner_start = (half_gap / ri).asin();
+        let inner_end = std::f64::consts::FRAC_PI_2 - inner_start;
+        let segments = shapes::segments_for_arc(ri, inner_end - inner_start, tolerance);
+        for i in (0..=segments).rev() {
+            let a = inner_start + (inner_end - inner_start) * i as f64 / segments as f64;
+            piece.push(Point2::new(ri * a.cos(), ri * a.sin()));
+        }
+    } else {
+        piece.push(Point2::new(half_gap, half_gap));
+    }
+
+    (0..4)
+        .map(|q| {
+            let points = piece.iter()
+                .map(|p| {
+                    let r = shapes::rotate_point(*p, 90.0 * q as f64);
+                    Point2::new(r.x + center.x, r.y + center.y)
+                })
+                .collect();
+            Polygon::new(points, true)
+        })
+        .collect()
+}
+
+// ============================================================================
+// APERTURE MACROS
+// ============================================================================
+
+/// One statement of an aperture macro body
+#[derive(Debug, Clone, PartialEq)]
+enum MacroStatement {
+    Assign(usize, String),
+    Primitive(u32, Vec<String>),
+}
+
+/// Aperture macro template (%AM)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApertureMacro {
+    pub name: String,
+    /// Body text exactly as written, without the name line
+    pub source: String,
+    statements: Vec<MacroStatement>,
+}
+
+impl ApertureMacro {
+    /// Parse the body of an %AM command (statements separated by '*')
+    pub fn parse(name: &str, body: &str) -> Result<Self, String> {
+        let mut statements = Vec::new();
+        for raw in body.split('*') {
+            let statement: String = raw.chars().filter(|c| !c.is_whitespace() || *c == ' ').collect();
+            let statement = statement.trim();
+            if statement.is_empty() {
+                continue;
+            }
+            // Comment primitive: "0 some text"
+            let code_field = statement.split(',').next().unwrap_or("");
+            if code_field.split_whitespace().next() == Some("0") {
+                continue;
+            }
+            if let Some(rest) = statement.strip_prefix('$') {
+                let (var, expr) = rest.split_once('=')
+                    .ok_or_else(|| format!("Invalid macro variable definition '{}' in {}", statement, name))?;
+                let var: usize = var.trim().parse()
+                    .map_err(|_| format!("Invalid macro variable '{}' in {}", var, name))?;
+                statements.push(MacroStatement::Assign(var, expr.replace(' ', "")));
+                continue;
+            }
+            let mut fields = statement.split(',').map(|f| f.replace(' ', ""));
+            let code: u32 = fields.next()
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| format!("Invalid macro primitive '{}' in {}", statement, name))?;
+            statements.push(MacroStatement::Primitive(code, fields.collect()));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            source: body.to_string(),
+            statements,
+        })
+    }
+
+    /// Evaluate the macro with the %AD parameters
+    /// `scale` converts lengths from file units to mm
+    pub fn instantiate(&self, parameters: &[f64], scale: f64) -> Result<Vec<MacroPrimitive>, String> {
+        let mut vars: HashMap<usize, f64> = parameters.iter().enumerate()
+            .map(|(i, v)| (i + 1, *v))
+            .collect();
+        let mut primitives = Vec::new();
+
+        for statement in &self.statements {
+            match statement {
+                MacroStatement::Assign(var, expr) => {
+                    let value = eval_expression(expr, &vars)?;
+                    vars.insert(*var, value);
+                }
+                MacroStatement::Primitive(code, fields) => {
+                    let values = fields.iter()
+                        .map(|f| eval_expression(f, &vars))
+                        .collect::<Result<Vec<f64>, String>>()?;
+                    if let Some(primitive) = build_primitive(*code, &values, scale)
+                        .map_err(|e| format!("{} in macro {}", e, self.name))? {
+                        primitives.push(primitive);
+                    }
+                }
+            }
+        }
+        Ok(primitives)
+    }
+}
+
+fn build_primitive(code: u32, v: &[f64], scale: f64) -> Result<Option<MacroPrimitive>, String> {
+    let get = |i: usize| -> Result<f64, String> {
+        v.get(i).copied().ok_or_else(|| format!("Primitive {} is missing parameter {}", code, i + 1))
+    };
+    let opt = |i: usize| v.get(i).copied().unwrap_or(0.0);
+    let point = |x: f64, y: f64| Point2::new(x * scale, y * scale);
+
+    let primitive = match code {
+        1 => MacroPrimitive::Circle {
+            dark: get(0)? != 0.0,
+            diameter: get(1)? * scale,
+            center: point(get(2)?, get(3)?),
+            rotation: opt(4),
+        },
+        2 | 20 => MacroPrimitive::VectorLine {
+            dark: get(0)? != 0.0,
+            width: get(1)? * scale,
+            start: point(get(2)?, get(3)?),
+            end: point(get(4)?, get(5)?),
+            rotation: opt(6),
+        },
+        21 => MacroPrimitive::CenterLine {
+            dark: get(0)? != 0.0,
+            width: get(1)? * scale,
+            height: get(2)? * scale,
+            center: point(get(3)?, get(4)?),
+            rotation: opt(5),
+        },
+        22 => {
+            // Lower-left line (deprecated), convert to a centre line
+            let (w, h) = (get(1)?, get(2)?);
+            MacroPrimitive::CenterLine {
+                dark: get(0)? != 0.0,
+                width: w * scale,
+                height: h * scale,
+                center: point(get(3)? + w / 2.0, get(4)? + h / 2.0),
+                rotation: opt(5),
+            }
+        }
+        4 => {
+            let count = get(1)? as usize;
+            let mut points = Vec::with_capacity(count + 1);
+            for i in 0..=count {
+                points.push(point(get(2 + i * 2)?, get(3 + i * 2)?));
+            }
+            MacroPrimitive::Outline {
+                dark: get(0)? != 0.0,
+                points,
+                rotation: opt(4 + count * 2),
+            }
+        }
+        5 => MacroPrimitive::Polygon {
+            dark: get(0)? != 0.0,
+            vertices: get(1)? as u32,
+            center: point(get(2)?, get(3)?),
+            diameter: get(4)? * scale,
+            rotation: opt(5),
+        },
+        7 => MacroPrimitive::Thermal {
+            center: point(get(0)?, get(1)?),
+            outer: get(2)? * scale,
+            inner: get(3)? * scale,
+            gap: get(4)? * scale,
+            rotation: opt(5),
+        },
+        // Moiré (6) is deprecated and not supported
+        _ => return Ok(None),
+    };
+    Ok(Some(primitive))
+}
+
+/// Evaluate an aperture macro arithmetic expression
+/// Supports numbers, $n variables, + - x / and parentheses
+pub fn eval_expression(expr: &str, vars: &HashMap<usize, f64>) -> Result<f64, String> {
+    let chars: Vec<char> = expr.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut pos = 0;
+    let value = parse_sum(&chars, &mut pos, vars)?;
+    if pos != chars.len() {
+        return Err(format!("Unexpected '{}' in expression '{}'", chars[pos], expr));
+    }
+    Ok(value)
+}
+
+fn parse_sum(chars: &[char], pos: &mut usize, vars: &HashMap<usize, f64>) -> Result<f64, String> {
+    let mut value = parse_product(chars, pos, vars)?;
+    while *pos < chars.len() {
+        match chars[*pos] {
+            '+' => { *pos += 1; value += parse_product(chars, pos, vars)?; }
+            '-' => { *pos += 1; value -= parse_product(chars, pos, vars)?; }
+            _ => break,
+        }
+    }
+    Ok(value)
+}
+
+fn parse_product(chars: &[char], pos: &mut usize, vars: &HashMap<usize, f64>) -> Result<f64, String> {
+    let mut value = parse_unary(chars, pos, vars)?;
+    while *pos < chars.len() {
+        match chars[*pos] {
+            'x' | 'X' => { *pos += 1; value *= parse_unary(chars, pos, vars)?; }
+            '/' => {
+                *pos += 1;
+                let divisor = parse_unary(chars, pos, vars)?;
+                value = if divisor == 0.0 { 0.0 } else { value / divisor };
+            }
+            _ => break,
+        }
+    }
+    Ok(value)
+}
+
+fn parse_unary(chars: &[char], pos: &mut usize, vars: &HashMap<usize, f64>) -> Result<f64, String> {
+    match chars.get(*pos) {
+        Some('-') => { *pos += 1; Ok(-parse_unary(chars, pos, vars)?) }
+        Some('+') => { *pos += 1; parse_unary(chars, pos, vars) }
+        Some('(') => {
+            *pos += 1;
+            let value = parse_sum(chars, pos, vars)?;
+            if chars.get(*pos) != Some(&')') {
+                return Err("Missing ')' in expression".to_string());
+            }
+            *pos += 1;
+            Ok(value)
+        }
+        Some('$') => {
+            *pos += 1;
+            let start = *pos;
+            while *pos < chars.len() && chars[*pos].is_ascii_digit() {
+                *pos += 1;
+            }
+            let var: usize = chars[start..*pos].iter().collect::<String>().parse()
+                .map_err(|_| "Invalid variable in expression".to_string())?;
+            // Undefined variables evaluate to zero
+            Ok(vars.get(&var).copied().unwrap_or(0.0))
+        }
+        Some(c) if c.is_ascii_digit() || *c == '.' => {
+            let start = *pos;
+            while *pos < chars.len() && (chars[*pos].is_ascii_digit() || chars[*pos] == '.') {
+                *pos += 1;
+            }
+            chars[start..*pos].iter().collect::<String>().parse()
+                .map_err(|_| "Invalid number in expression".to_string())
+        }
+        Some(c) => Err(format!("Unexpected '{}' in expression", c)),
+        None => Err("Unexpected end of expression".to_string()),
+    }
+}
+
+/// Whether an .AperFunction value describes a component pad
+pub fn is_pad_function(function: &str) -> bool {
+    const PAD_FUNCTIONS: [&str; 8] = [
+        "ComponentPad", "SMDPad", "BGAPad", "ConnectorPad",
+        "TestPad", "HeatsinkPad", "CastellatedPad", "WasherPad",
+    ];
+    let name = function.split(',').next().unwrap_or("");
+    PAD_FUNCTIONS.contains(&name)
+}
+
+/// Build an aperture from an %AD command body, e.g. "D10C,0.300000"
+pub fn parse_aperture_definition(
+    body: &str,
+    macros: &HashMap<String, ApertureMacro>,
+    scale: f64,
+) -> Result<Aperture, String> {
+    let rest = body.strip_prefix('D')
+        .ok_or_else(|| format!("Invalid aperture definition '{}'", body))?;
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let d_code: i32 = digits.parse()
+        .map_err(|_| format!("Invalid aperture number in '{}'", body))?;
+    let rest = &rest[digits.len()..];
+
+    let (template, raw_parameters) = match rest.split_once(',') {
+        Some((t, p)) => (t.to_string(), p.to_string()),
+        None => (rest.to_string(), String::new()),
+    };
+    let parameters = raw_parameters.split(['X', 'x'])
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| p.parse::<f64>().map_err(|_| format!("Invalid aperture parameter '{}' in D{}", p, d_code)))
+        .collect::<Result<Vec<f64>, String>>()?;
+    let param = |i: usize| parameters.get(i).copied().unwrap_or(0.0);
+    let hole = |i: usize| parameters.get(i).map(|h| h * scale);
+
+    let shape = match template.as_str() {
+        "C" => ApertureShape::Circle { diameter: param(0) * scale, hole: hole(1) },
+        "R" => ApertureShape::Rectangle { width: param(0) * scale, height: param(1) * scale, hole: hole(2) },
+        "O" => ApertureShape::Obround { width: param(0) * scale, height: param(1) * scale, hole: hole(2) },
+        "P" => ApertureShape::Polygon {
+            diameter: param(0) * scale,
+            vertices: param(1) as u32,
+            rotation: param(2),
+            hole: hole(3),
+        },
+        name => {
+            let aperture_macro = macros.get(name)
+                .ok_or_else(|| format!("Unknown aperture macro '{}' for D{}", name, d_code))?;
+            ApertureShape::Macro {
+                name: name.to_string(),
+                primitives: aperture_macro.instantiate(&parameters, scale)?,
+            }
+        }
+    };
+
+    Ok(Aperture { d_code, template, raw_parameters, shape, function: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_expression() {
+        let vars: HashMap<usize, f64> = [(1, 0.25), (2, 2.0)].into_iter().collect();
+        assert!((eval_expression("$1+$1", &vars).unwrap() - 0.5).abs() < 1e-12);
+        assert!((eval_expression("-$2x(1+2)/3", &vars).unwrap() + 2.0).abs() < 1e-12);
+        assert!((eval_expression("$9", &vars).unwrap()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_round_rect_macro() {
+        let body = "0 Rectangle with rounded corners*\n\
+                    4,1,4,$2,$3,$4,$5,$6,$7,$8,$9,$2,$3,0*\n\
+                    1,1,$1+$1,$2,$3*\n\
+                    1,1,$1+$1,$4,$5*\n\
+                    1,1,$1+$1,$6,$7*\n\
+                    1,1,$1+$1,$8,$9*\n\
+                    20,1,$1+$1,$2,$3,$4,$5,0*\n\
+                    20,1,$1+$1,$4,$5,$6,$7,0*\n\
+                    20,1,$1+$1,$6,$7,$8,$9,0*\n\
+                    20,1,$1+$1,$8,$9,$2,$3,0*";
+        let mut macros = HashMap::new();
+        macros.insert("RoundRect".to_string(), ApertureMacro::parse("RoundRect", body).unwrap());
+
+        let aperture = parse_aperture_definition(
+            "D15RoundRect,0.250000X-0.350000X-0.450000X0.350000X-0.450000X0.350000X0.450000X-0.350000X0.450000X0",
+            &macros,
+            1.0,
+        ).unwrap();
+        assert_eq!(aperture.d_code, 15);
+        match &aperture.shape {
+            ApertureShape::Macro { primitives, .. } => assert_eq!(primitives.len(), 9),
+            other => panic!("unexpected shape {:?}", other),
+        }
+        let (w, h) = aperture.size().unwrap();
+        assert!((w - 1.2).abs() < 0.01 && (h - 1.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_standard_apertures() {
+        let macros = HashMap::new();
+        let circle = parse_aperture_definition("D10C,0.300000", &macros, 1.0).unwrap();
+        assert_eq!(circle.shape, ApertureShape::Circle { diameter: 0.3, hole: None });
+
+        let rect = parse_aperture_definition("D14R,0.05X0.1", &macros, 25.4).unwrap();
+        assert!((rect.stroke_width() - 1.27).abs() < 1e-9);
+
+        let polygon = parse_aperture_definition("D41P,X3X0.5", &macros, 1.0);
+        assert!(polygon.is_ok());
+    }
+}
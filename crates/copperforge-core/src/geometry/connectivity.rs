@@ -0,0 +1,502 @@
+use nalgebra::Point2;
+
+use super::{shapes, DrillData, LayerGeometry, Polarity, Polygon};
+
+// Copper connectivity extraction
+// Overlapping dark features on a copper layer are merged into islands, and
+// plated holes join the islands they pass through into nets.
+// Clear polarity features and aperture holes are subtracted from the dark features
+// first, so copper cut apart by a clear object ends up in separate islands.
+// NonConductor features (copper text, logos) are left out.
+
+/// Disjoint set over indices, used to merge touching features
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    rank: Vec<u8>,
+}
+
+impl UnionFind {
+    pub fn new(size: usize) -> Self {
+        Self { parent: (0..size).collect(), rank: vec![0; size] }
+    }
+
+    pub fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+
+    pub fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra == rb {
+            return;
+        }
+        match self.rank[ra].cmp(&self.rank[rb]) {
+            std::cmp::Ordering::Less => self.parent[ra] = rb,
+            std::cmp::Ordering::Greater => self.parent[rb] = ra,
+            std::cmp::Ordering::Equal => {
+                self.parent[rb] = ra;
+                self.rank[ra] += 1;
+            }
+        }
+    }
+
+    /// Members of each set, in order of their first member
+    pub fn groups(&mut self) -> Vec<Vec<usize>> {
+        let mut index_of_root = std::collections::HashMap::new();
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for i in 0..self.parent.len() {
+            let root = self.find(i);
+            let index = *index_of_root.entry(root).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+            groups[index].push(i);
+        }
+        groups
+    }
+}
+
+/// Connected copper on a single layer
+#[derive(Debug, Clone)]
+pub struct Island {
+    /// Index of the layer in the input slice
+    pub layer: usize,
+    /// Indices into `LayerGeometry::features`
+    pub features: Vec<usize>,
+    pub min: Point2<f64>,
+    pub max: Point2<f64>,
+    /// Copper area in mm², overlaps counted once
+    pub area: f64,
+}
+
+/// A plated hole that joins copper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HoleRef {
+    /// Index of the drill file in the input slice
+    pub drill: usize,
+    /// Index into `DrillData::hits`, or into `DrillData::slots` when `slot` is set
+    pub index: usize,
+    pub slot: bool,
+}
+
+/// A set of islands connected through plated holes
+#[derive(Debug, Clone, Default)]
+pub struct Net {
+    /// Indices into `Connectivity::islands`
+    pub islands: Vec<usize>,
+    pub holes: Vec<HoleRef>,
+}
+
+/// Result of a connectivity extraction
+#[derive(Debug, Clone, Default)]
+pub struct Connectivity {
+    pub islands: Vec<Island>,
+    pub nets: Vec<Net>,
+    /// Net index of every island
+    pub island_nets: Vec<usize>,
+    /// Plated holes that touch no copper at all
+    pub unconnected_holes: Vec<HoleRef>,
+}
+
+impl Connectivity {
+    /// Islands whose net has neither a component pad nor a plated hole (dead copper)
+    pub fn dead_islands(&self, layers: &[&LayerGeometry]) -> Vec<usize> {
+        let mut net_is_live: Vec<bool> = self.nets.iter().map(|net| !net.holes.is_empty()).collect();
+        for (index, island) in self.islands.iter().enumerate() {
+            if layers[island.layer].has_component_pad(&island.features) {
+                net_is_live[self.island_nets[index]] = true;
+            }
+        }
+        (0..self.islands.len())
+            .filter(|&index| !net_is_live[self.island_nets[index]])
+            .collect()
+    }
+
+    /// Nets spanning more than one layer
+    pub fn multilayer_net_count(&self) -> usize {
+        self.nets.iter()
+            .filter(|net| {
+                let first = net.islands.first().map(|i| self.islands[*i].layer);
+                net.islands.iter().any(|i| Some(self.islands[*i].layer) != first)
+            })
+            .count()
+    }
+}
+
+/// Axis aligned bounds as (min, max)
+type Bounds = (Point2<f64>, Point2<f64>);
+
+/// Dark outline polygons and bounds of the copper left of each feature
+/// A feature cut apart by later clear features has one part per remaining piece.
+struct CopperParts {
+    /// Feature index of each part
+    features: Vec<usize>,
+    polygons: Vec<Vec<Polygon>>,
+    bounds: Vec<Option<Bounds>>,
+}
+
+impl CopperParts {
+    fn new(geometry: &LayerGeometry, tolerance: f64) -> Self {
+        // Area removed by each clear feature, as convex pieces with their bounds
+        let clear: Vec<(usize, Bounds, Vec<Point2<f64>>)> = geometry.features.iter().enumerate()
+            .filter(|(_, feature)| feature.polarity == Polarity::Clear)
+            .flat_map(|(index, feature)| {
+                geometry.feature_polygons(feature, tolerance).into_iter()
+                    .filter(|p| p.dark)
+                    .flat_map(|p| shapes::convex_pieces(&p.points))
+                    .filter_map(move |piece| Some((index, shapes::points_bounds(&piece)?, piece)))
+            })
+            .collect();
+
+        let mut parts = Self { features: Vec::new(), polygons: Vec::new(), bounds: Vec::new() };
+        for (index, feature) in geometry.features.iter().enumerate() {
+            // Copper text and graphics carry no net
+            if feature.polarity != Polarity::Dark || feature.is_non_conductor() {
+                continue;
+            }
+            let (polygons, holes): (Vec<Polygon>, Vec<Polygon>) = geometry.feature_polygons(feature, tolerance).into_iter()
+                .filter(|p| !p.points.is_empty())
+                .partition(|p| p.dark);
+            let points: Vec<Point2<f64>> = polygons.iter().flat_map(|p| p.points.iter().copied()).collect();
+            let Some(bounds) = shapes::points_bounds(&points) else {
+                continue;
+            };
+
+            // The feature's own aperture holes and clear features drawn later remove copper from it
+            let hole_pieces: Vec<Vec<Point2<f64>>> = holes.iter()
+                .flat_map(|hole| shapes::convex_pieces(&hole.points))
+                .collect();
+            let cuts: Vec<&[Point2<f64>]> = hole_pieces.iter()
+                .map(|piece| piece.as_slice())
+                .chain(clear.iter()
+                    .filter(|(clear_index, clear_bounds, _)| *clear_index > index && shapes::bounds_overlap(clear_bounds, &bounds, 1e-9))
+                    .map(|(_, _, piece)| piece.as_slice()))
+                .collect();
+            if cuts.is_empty() {
+                parts.push(index, polygons);
+                continue;
+            }
+
+            let mut pieces: Vec<Vec<Point2<f64>>> = polygons.iter()
+                .flat_map(|p| shapes::convex_pieces(&p.points))
+                .collect();
+            for cut in cuts {
+                pieces = pieces.into_iter()
+                    .flat_map(|piece| shapes::subtract_convex(&piece, cut))
+                    .collect();
+            }
+            let piece_bounds: Vec<_> = pieces.iter().map(|piece| shapes::points_bounds(piece)).collect();
+            let mut sets = union_touching(&piece_bounds, |a, b| shapes::polygon_distance(&pieces[a], &pieces[b]) < 1e-9);
+            for group in sets.groups() {
+                let polygons = group.iter().map(|&i| Polygon::new(pieces[i].clone(), true)).collect();
+                parts.push(index, polygons);
+            }
+        }
+        parts
+    }
+
+    fn push(&mut self, feature: usize, polygons: Vec<Polygon>) {
+        let points: Vec<Point2<f64>> = polygons.iter().flat_map(|p| p.points.iter().copied()).collect();
+        self.features.push(feature);
+        self.bounds.push(shapes::points_bounds(&points));
+        self.polygons.push(polygons);
+    }
+
+    fn touches(&self, a: usize, b: usize) -> bool {
+        self.polygons[a].iter().any(|pa| {
+            self.polygons[b].iter().any(|pb| shapes::polygons_intersect(&pa.points, &pb.points))
+        })
+    }
+
+    fn touches_shape(&self, part: usize, shape: &[Point2<f64>]) -> bool {
+        self.polygons[part].iter().any(|p| shapes::polygons_intersect(&p.points, shape))
+    }
+}
+
+/// Merge every pair of items whose bounds overlap and that `touch`
+/// Items are swept along x so only those with overlapping x ranges are compared.
+fn union_touching(
+    bounds: &[Option<Bounds>],
+    mut touch: impl FnMut(usize, usize) -> bool,
+) -> UnionFind {
+    let mut sets = UnionFind::new(bounds.len());
+    let mut order: Vec<usize> = (0..bounds.len()).filter(|i| bounds[*i].is_some()).collect();
+    order.sort_by(|a, b| bounds[*a].unwrap().0.x.total_cmp(&bounds[*b].unwrap().0.x));
+    for (position, &a) in order.iter().enumerate() {
+        let bounds_a = bounds[a].unwrap();
+        for &b in &order[position + 1..] {
+            let bounds_b = bounds[b].unwrap();
+            if bounds_b.0.x > bounds_a.1.x {
+                break;
+            }
+            if shapes::bounds_overlap(&bounds_a, &bounds_b, 1e-9) && touch(a, b) {
+                sets.union(a, b);
+            }
+        }
+    }
+    sets
+}
+
+/// Group the copper parts of a layer into islands
+/// Returns each island with the parts it is made of.
+fn layer_islands(layer: usize, parts: &CopperParts) -> Vec<(Island, Vec<usize>)> {
+    let mut sets = union_touching(&parts.bounds, |a, b| parts.touches(a, b));
+
+    sets.groups().into_iter()
+        .filter(|group| parts.bounds[group[0]].is_some())
+        .map(|group| {
+            let mut min = Point2::new(f64::MAX, f64::MAX);
+            let mut max = Point2::new(f64::MIN, f64::MIN);
+            let mut features = Vec::new();
+            for &part in &group {
+                let (pmin, pmax) = parts.bounds[part].unwrap();
+                min = Point2::new(min.x.min(pmin.x), min.y.min(pmin.y));
+                max = Point2::new(max.x.max(pmax.x), max.y.max(pmax.y));
+                if !features.contains(&parts.features[part]) {
+                    features.push(parts.features[part]);
+                }
+            }
+            let area = shapes::union_area(group.iter()
+                .flat_map(|&part| &parts.polygons[part])
+                .map(|p| p.points.as_slice()));
+            (Island { layer, features, min, max, area }, group)
+        })
+        .collect()
+}
+
+/// Outline of every plated hole in the drill files
+fn plated_holes(drills: &[&DrillData], tolerance: f64) -> Vec<(HoleRef, Vec<Point2<f64>>)> {
+    let mut holes = Vec::new();
+    for (drill, data) in drills.iter().enumerate() {
+        if data.plated == Some(false) {
+            continue;
+        }
+        for (index, hit) in data.hits.iter().enumerate() {
+            let outline = shapes::circle_points(hit.position, hit.diameter / 2.0, tolerance);
+            holes.push((HoleRef { drill, index, slot: false }, outline));
+        }
+        for (index, slot) in data.slots.iter().enumerate() {
+            let circle = shapes::circle_points(Point2::origin(), slot.diameter / 2.0, tolerance);
+            let outline = shapes::sweep_convex(&circle, slot.start, slot.end);
+            holes.push((HoleRef { drill, index, slot: true }, outline));
+        }
+    }
+    holes
+}
+
+/// Extract islands and nets from copper layers and drill files
+/// Non-plated drill files (`plated == Some(false)`) are skipped.
+pub fn extract_connectivity(layers: &[&LayerGeometry], drills: &[&DrillData], tolerance: f64) -> Connectivity {
+    let layer_parts: Vec<CopperParts> = layers.iter()
+        .map(|geometry| CopperParts::new(geometry, tolerance))
+        .collect();
+
+    let mut islands = Vec::new();
+    let mut island_parts: Vec<Vec<usize>> = Vec::new();
+    for (layer, parts) in layer_parts.iter().enumerate() {
+        for (island, parts) in layer_islands(layer, parts) {
+            islands.push(island);
+            island_parts.push(parts);
+        }
+    }
+
+    // Holes are extra nodes after the islands
+    let holes = plated_holes(drills, tolerance);
+    let mut sets = UnionFind::new(islands.len() + holes.len());
+    let mut unconnected_holes = Vec::new();
+    for (hole_index, (hole, outline)) in holes.iter().enumerate() {
+        let Some(hole_bounds) = shapes::points_bounds(outline) else {
+            continue;
+        };
+        let node = islands.len() + hole_index;
+        let mut connected = false;
+        for (island_index, island) in islands.iter().enumerate() {
+            if !shapes::bounds_overlap(&(island.min, island.max), &hole_bounds, 1e-9) {
+                continue;
+            }
+            let parts = &layer_parts[island.layer];
+            let touches = island_parts[island_index].iter().any(|&part| {
+                parts.bounds[part].is_some_and(|b| shapes::bounds_overlap(&b, &hole_bounds, 1e-9))
+                    && parts.touches_shape(part, outline)
+            });
+            if touches {
+                sets.union(node, island_index);
+                connected = true;
+            }
+        }
+        if !connected {
+            unconnected_holes.push(*hole);
+        }
+    }
+
+    let mut nets = Vec::new();
+    let mut island_nets = vec![0; islands.len()];
+    for group in sets.groups() {
+        let mut net = Net::default();
+        for node in group {
+            if node < islands.len() {
+                net.islands.push(node);
+            } else {
+                net.holes.push(holes[node - islands.len()].0);
+            }
+        }
+        if net.islands.is_empty() {
+            continue;
+        }
+        for &island in &net.islands {
+            island_nets[island] = nets.len();
+        }
+        nets.push(net);
+    }
+
+    Connectivity { islands, nets, island_nets, unconnected_holes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::{parse_excellon, parse_gerber};
+
+    const HEADER: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\n%ADD11C,1.000000*%\nD10*\n";
+
+    #[test]
+    fn test_islands_on_one_layer() {
+        // Two touching tracks and one separate track
+        let content = format!(
+            "{}X0Y0D02*\nX5000000Y0D01*\nX5000000Y5000000D01*\nX20000000Y0D02*\nX25000000Y0D01*\nM02*\n",
+            HEADER
+        );
+        let layer = parse_gerber(&content).unwrap();
+        let result = extract_connectivity(&[&layer], &[], 0.01);
+        assert_eq!(result.islands.len(), 2);
+        assert_eq!(result.nets.len(), 2);
+        assert_eq!(result.islands[0].features.len(), 2);
+    }
+
+    #[test]
+    fn test_drill_links_layers() {
+        let top = parse_gerber(&format!(
+            "{}X0Y0D02*\nX5000000Y0D01*\nD11*\nX5000000Y0D03*\nX30000000Y0D03*\nM02*\n", HEADER
+        )).unwrap();
+        let bottom = parse_gerber(&format!(
+            "{}D11*\nX5000000Y0D03*\nD10*\nX5000000Y0D02*\nX10000000Y0D01*\nM02*\n", HEADER
+        )).unwrap();
+        let drill = parse_excellon("M48\nMETRIC\nT1C0.400\n%\nT1\nX5.0Y0.0\nX50.0Y0.0\nM30\n").unwrap();
+
+        let result = extract_connectivity(&[&top, &bottom], &[&drill], 0.01);
+        // Top: track + via pad, lone pad. Bottom: via pad + track
+        assert_eq!(result.islands.len(), 3);
+        assert_eq!(result.nets.len(), 2);
+        assert_eq!(result.multilayer_net_count(), 1);
+        assert_eq!(result.unconnected_holes.len(), 1);
+        assert_eq!(result.island_nets[0], result.island_nets[2]);
+    }
+
+    #[test]
+    fn test_dead_islands() {
+        // Pad with a track, a separate pour, and a pour joined to the pad through a via
+        let top = parse_gerber(&format!(
+            "{}%TA.AperFunction,SMDPad,CuDef*%\n%ADD12R,1.000000X1.000000*%\n%TD*%\n\
+             D12*\nX0Y0D03*\nD10*\nX0Y0D02*\nX5000000Y0D01*\nD11*\nX5000000Y0D03*\n\
+             G36*\nX20000000Y0D02*\nX21000000Y0D01*\nX21000000Y1000000D01*\nX20000000Y0D01*\nG37*\nM02*\n",
+            HEADER
+        )).unwrap();
+        let bottom = parse_gerber(&format!(
+            "{}G36*\nX4000000Y-1000000D02*\nX6000000Y-1000000D01*\nX6000000Y1000000D01*\nX4000000Y-1000000D01*\nG37*\nM02*\n",
+            HEADER
+        )).unwrap();
+        let drill = parse_excellon("M48\nMETRIC\nT1C0.400\n%\nT1\nX5.0Y0.0\nM30\n").unwrap();
+
+        let result = extract_connectivity(&[&top, &bottom], &[&drill], 0.01);
+        let dead = result.dead_islands(&[&top, &bottom]);
+        assert_eq!(dead.len(), 1);
+        assert_eq!(result.islands[dead[0]].layer, 0);
+        assert!((result.islands[dead[0]].area - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_via_stitched_pour_is_live() {
+        // Top and bottom pours stitched by two vias, no pads anywhere, and a lone pour
+        let pour = "G36*\nX0Y0D02*\nX10000000Y0D01*\nX10000000Y4000000D01*\nX0Y4000000D01*\nX0Y0D01*\nG37*\n";
+        let top = parse_gerber(&format!(
+            "{}{}G36*\nX20000000Y0D02*\nX21000000Y0D01*\nX21000000Y1000000D01*\nX20000000Y0D01*\nG37*\nM02*\n",
+            HEADER, pour
+        )).unwrap();
+        let bottom = parse_gerber(&format!("{}{}M02*\n", HEADER, pour)).unwrap();
+        let drill = parse_excellon("M48\nMETRIC\nT1C0.300\n%\nT1\nX2.0Y2.0\nX8.0Y2.0\nM30\n").unwrap();
+
+        let result = extract_connectivity(&[&top, &bottom], &[&drill], 0.01);
+        assert_eq!(result.multilayer_net_count(), 1);
+        let dead = result.dead_islands(&[&top, &bottom]);
+        assert_eq!(dead.len(), 1);
+        assert_eq!(result.islands[dead[0]].layer, 0);
+        assert!((result.islands[dead[0]].area - 0.5).abs() < 1e-6);
+
+        // Without the drill file both pours are dead
+        let result = extract_connectivity(&[&top, &bottom], &[], 0.01);
+        assert_eq!(result.dead_islands(&[&top, &bottom]).len(), 3);
+    }
+
+    #[test]
+    fn test_clear_polarity_splits_copper() {
+        // A pour cut in two by a clear track, and a track drawn over the cut afterwards
+        let pour = "G36*\nX0Y0D02*\nX10000000Y0D01*\nX10000000Y4000000D01*\nX0Y4000000D01*\nX0Y0D01*\nG37*\n";
+        let cut = "%LPC*%\nD11*\nX5000000Y-1000000D02*\nX5000000Y5000000D01*\n%LPD*%\n";
+        let layer = parse_gerber(&format!("{}{}{}M02*\n", HEADER, pour, cut)).unwrap();
+        let result = extract_connectivity(&[&layer], &[], 0.01);
+        assert_eq!(result.islands.len(), 2);
+        assert_eq!(result.islands[0].features, vec![0]);
+        assert!((result.islands[0].area - 18.0).abs() < 1e-6);
+
+        let bridged = parse_gerber(&format!(
+            "{}{}{}D10*\nX3000000Y2000000D02*\nX7000000Y2000000D01*\nM02*\n", HEADER, pour, cut
+        )).unwrap();
+        let result = extract_connectivity(&[&bridged], &[], 0.01);
+        assert_eq!(result.islands.len(), 1);
+        assert_eq!(result.islands[0].features, vec![0, 2]);
+
+        // Copper that is cleared completely is gone
+        let cleared = parse_gerber(&format!("{}D10*\nX0Y0D03*\n%LPC*%\nD11*\nX0Y0D03*\nM02*\n", HEADER)).unwrap();
+        assert!(extract_connectivity(&[&cleared], &[], 0.01).islands.is_empty());
+    }
+
+    #[test]
+    fn test_island_area_counts_overlaps_once() {
+        // Two 2x2 pours sharing a 1x2 strip
+        let layer = parse_gerber(&format!(
+            "{}G36*\nX0Y0D02*\nX2000000Y0D01*\nX2000000Y2000000D01*\nX0Y2000000D01*\nX0Y0D01*\nG37*\n\
+             G36*\nX1000000Y0D02*\nX3000000Y0D01*\nX3000000Y2000000D01*\nX1000000Y2000000D01*\nX1000000Y0D01*\nG37*\nM02*\n",
+            HEADER
+        )).unwrap();
+        let result = extract_connectivity(&[&layer], &[], 0.01);
+        assert_eq!(result.islands.len(), 1);
+        assert!((result.islands[0].area - 6.0).abs() < 1e-6);
+
+        // A 2x2 pad with a 1 mm aperture hole, overlapped by a pad without one
+        let layer = parse_gerber(&format!(
+            "{}%ADD12R,2.000000X2.000000X1.000000*%\n%ADD13R,2.000000X2.000000*%\n\
+             D12*\nX0Y0D03*\nD13*\nX1000000Y0D03*\nM02*\n",
+            HEADER
+        )).unwrap();
+        let result = extract_connectivity(&[&layer], &[], 0.01);
+        assert_eq!(result.islands.len(), 1);
+        // Only the left half of the hole stays open
+        let expected = 6.0 - std::f64::consts::PI / 8.0;
+        assert!((result.islands[0].area - expected).abs() < 0.02);
+    }
+
+    #[test]
+    fn test_non_plated_holes_are_ignored() {
+        let top = parse_gerber(&format!("{}D11*\nX0Y0D03*\nM02*\n", HEADER)).unwrap();
+        let bottom = parse_gerber(&format!("{}D11*\nX0Y0D03*\nM02*\n", HEADER)).unwrap();
+        let mut drill = parse_excellon("M48\nMETRIC\nT1C0.400\n%\nT1\nX0.0Y0.0\nM30\n").unwrap();
+        drill.plated = Some(false);
+
+        let result = extract_connectivity(&[&top, &bottom], &[&drill], 0.01);
+        assert_eq!(result.nets.len(), 2);
+    }
+}
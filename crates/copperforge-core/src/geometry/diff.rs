@@ -0,0 +1,217 @@
+use nalgebra::Point2;
+
+use super::{rasterize_layer, Antialias, LayerGeometry, RasterView};
+
+// Revision comparison
+// Both revisions of a layer are rasterized on the same pixel grid and compared
+// pixel by pixel. Touching changed pixels are grouped into regions, which is
+// what the summary and the difference navigator work with.
+
+/// State of a pixel when going from the reference revision to the current one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffClass {
+    Empty,
+    Unchanged,
+    Added,
+    Removed,
+}
+
+/// Connected area of changed pixels, in board mm
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRegion {
+    pub min: Point2<f64>,
+    pub max: Point2<f64>,
+    /// Copper present only in the current revision (mm²)
+    pub added_area: f64,
+    /// Copper present only in the reference revision (mm²)
+    pub removed_area: f64,
+}
+
+impl DiffRegion {
+    pub fn center(&self) -> Point2<f64> {
+        nalgebra::center(&self.min, &self.max)
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match (self.added_area > 0.0, self.removed_area > 0.0) {
+            (true, true) => "changed",
+            (true, false) => "added",
+            _ => "removed",
+        }
+    }
+}
+
+/// Pixel comparison of two revisions of a layer
+#[derive(Debug, Clone)]
+pub struct LayerDiff {
+    pub view: RasterView,
+    pub classes: Vec<DiffClass>,
+    /// Changed regions, largest first
+    pub regions: Vec<DiffRegion>,
+}
+
+impl LayerDiff {
+    pub fn class(&self, x: u32, y: u32) -> DiffClass {
+        self.classes[y as usize * self.view.width as usize + x as usize]
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn added_area(&self) -> f64 {
+        self.regions.iter().map(|region| region.added_area).sum()
+    }
+
+    pub fn removed_area(&self) -> f64 {
+        self.regions.iter().map(|region| region.removed_area).sum()
+    }
+
+    /// Board area covered by the pixel grid as (min, max)
+    pub fn extent(&self) -> (Point2<f64>, Point2<f64>) {
+        let view = &self.view;
+        let min = Point2::new(-view.translation.0 / view.scale, (view.translation.1 - view.height as f64) / view.scale);
+        let max = Point2::new((view.width as f64 - view.translation.0) / view.scale, view.translation.1 / view.scale);
+        (min, max)
+    }
+}
+
+/// Compare two revisions of a layer, with the longest side of the grid at most `max_pixels`
+/// Returns None when neither revision has any copper.
+pub fn diff_layers(reference: &LayerGeometry, current: &LayerGeometry, max_pixels: u32) -> Option<LayerDiff> {
+    let (min, max) = match (reference.bounds(), current.bounds()) {
+        (Some((amin, amax)), Some((bmin, bmax))) => (
+            Point2::new(amin.x.min(bmin.x), amin.y.min(bmin.y)),
+            Point2::new(amax.x.max(bmax.x), amax.y.max(bmax.y)),
+        ),
+        (Some(bounds), None) | (None, Some(bounds)) => bounds,
+        (None, None) => return None,
+    };
+
+    // Finest grid that fits, but no finer than 1000 dpi
+    let longest = (max.x - min.x).max(max.y - min.y).max(1e-3);
+    let dpi = (max_pixels.max(1) as f64 / longest * 25.4).min(1000.0);
+    let margin = 25.4 / dpi;
+    let view = RasterView::from_bounds(min, max, dpi, margin);
+
+    let before = rasterize_layer(reference, &view, Antialias::Off, |p| p);
+    let after = rasterize_layer(current, &view, Antialias::Off, |p| p);
+    let classes: Vec<DiffClass> = before.data.iter().zip(&after.data)
+        .map(|(&was, &is)| match (was > 0.5, is > 0.5) {
+            (false, false) => DiffClass::Empty,
+            (true, true) => DiffClass::Unchanged,
+            (false, true) => DiffClass::Added,
+            (true, false) => DiffClass::Removed,
+        })
+        .collect();
+
+    let regions = changed_regions(&classes, &view);
+    Some(LayerDiff { view, classes, regions })
+}
+
+/// Group 8-connected changed pixels into regions
+fn changed_regions(classes: &[DiffClass], view: &RasterView) -> Vec<DiffRegion> {
+    let (width, height) = (view.width as usize, view.height as usize);
+    let changed = |index: usize| matches!(classes[index], DiffClass::Added | DiffClass::Removed);
+    let pixel_area = 1.0 / (view.scale * view.scale);
+
+    let mut visited = vec![false; classes.len()];
+    let mut stack = Vec::new();
+    let mut regions = Vec::new();
+
+    for start in 0..classes.len() {
+        if visited[start] || !changed(start) {
+            continue;
+        }
+        visited[start] = true;
+        stack.push(start);
+
+        let (mut left, mut top, mut right, mut bottom) = (width, height, 0, 0);
+        let (mut added, mut removed) = (0usize, 0usize);
+        while let Some(index) = stack.pop() {
+            let (x, y) = (index % width, index / width);
+            left = left.min(x);
+            right = right.max(x + 1);
+            top = top.min(y);
+            bottom = bottom.max(y + 1);
+            if classes[index] == DiffClass::Added {
+                added += 1;
+            } else {
+                removed += 1;
+            }
+
+            for ny in y.saturating_sub(1)..(y + 2).min(height) {
+                for nx in x.saturating_sub(1)..(x + 2).min(width) {
+                    let neighbour = ny * width + nx;
+                    if !visited[neighbour] && changed(neighbour) {
+                        visited[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        // Pixel box back to board coordinates (pixel y points down)
+        let to_board = |x: usize, y: usize| Point2::new(
+            (x as f64 - view.translation.0) / view.scale,
+            (view.translation.1 - y as f64) / view.scale,
+        );
+        let corner_a = to_board(left, bottom);
+        let corner_b = to_board(right, top);
+        regions.push(DiffRegion {
+            min: corner_a,
+            max: corner_b,
+            added_area: added as f64 * pixel_area,
+            removed_area: removed as f64 * pixel_area,
+        });
+    }
+
+    regions.sort_by(|a, b| (b.added_area + b.removed_area).total_cmp(&(a.added_area + a.removed_area)));
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+
+    const HEADER: &str = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,1.000000X1.000000*%\nD10*\n";
+
+    fn layer(flashes: &[(i64, i64)]) -> LayerGeometry {
+        let mut gerber = HEADER.to_string();
+        for (x, y) in flashes {
+            gerber.push_str(&format!("X{}Y{}D03*\n", x * 1_000_000, y * 1_000_000));
+        }
+        gerber.push_str("M02*\n");
+        parse_gerber(&gerber).unwrap()
+    }
+
+    #[test]
+    fn test_identical_revisions() {
+        let geometry = layer(&[(0, 0), (5, 0)]);
+        let diff = diff_layers(&geometry, &geometry, 256).unwrap();
+        assert!(diff.is_identical());
+        assert!(diff.classes.contains(&DiffClass::Unchanged));
+        assert!(diff_layers(&LayerGeometry::default(), &LayerGeometry::default(), 256).is_none());
+    }
+
+    #[test]
+    fn test_moved_pad() {
+        // The pad at (5, 0) moved to (10, 0)
+        let reference = layer(&[(0, 0), (5, 0)]);
+        let current = layer(&[(0, 0), (10, 0)]);
+        let diff = diff_layers(&reference, &current, 512).unwrap();
+
+        assert_eq!(diff.regions.len(), 2);
+        assert!((diff.added_area() - 1.0).abs() < 0.1);
+        assert!((diff.removed_area() - 1.0).abs() < 0.1);
+
+        let added = diff.regions.iter().find(|region| region.describe() == "added").unwrap();
+        assert!((added.center() - Point2::new(10.0, 0.0)).norm() < 0.1);
+        let removed = diff.regions.iter().find(|region| region.describe() == "removed").unwrap();
+        assert!((removed.center() - Point2::new(5.0, 0.0)).norm() < 0.1);
+
+        let (min, max) = diff.extent();
+        assert!(min.x < -0.5 && max.x > 10.5);
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::BTreeMap;
+use nalgebra::Point2;
+
+use super::Units;
+
+// Excellon drill file parser (as written by KiCad and most CAM tools)
+// Supports METRIC/INCH headers with LZ/TZ, tool definitions, drill hits
+// and routed slots (G85 and M15/M16 routing).
+
+/// A single drilled hole
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrillHit {
+    pub tool: u32,
+    pub position: Point2<f64>,
+    pub diameter: f64,
+}
+
+/// A routed slot (oval hole)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrillSlot {
+    pub tool: u32,
+    pub start: Point2<f64>,
+    pub end: Point2<f64>,
+    pub diameter: f64,
+}
+
+/// Parsed drill file, all values in mm
+#[derive(Debug, Clone, Default)]
+pub struct DrillData {
+    /// Tool number -> diameter
+    pub tools: BTreeMap<u32, f64>,
+    pub hits: Vec<DrillHit>,
+    pub slots: Vec<DrillSlot>,
+    /// Plated state when the file says so (file attributes or name)
+    pub plated: Option<bool>,
+    pub units: Units,
+}
+
+impl DrillData {
+    /// Number of holes per tool, including slots
+    pub fn hole_counts(&self) -> BTreeMap<u32, usize> {
+        let mut counts = BTreeMap::new();
+        for hit in &self.hits {
+            *counts.entry(hit.tool).or_insert(0) += 1;
+        }
+        for slot in &self.slots {
+            *counts.entry(slot.tool).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Bounds of all holes as (min, max)
+    pub fn bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        let mut points = Vec::new();
+        for hit in &self.hits {
+            let r = hit.diameter / 2.0;
+            points.push(Point2::new(hit.position.x - r, hit.position.y - r));
+            points.push(Point2::new(hit.position.x + r, hit.position.y + r));
+        }
+        for slot in &self.slots {
+            let r = slot.diameter / 2.0;
+            for p in [slot.start, slot.end] {
+                points.push(Point2::new(p.x - r, p.y - r));
+                points.push(Point2::new(p.x + r, p.y + r));
+            }
+        }
+        super::shapes::points_bounds(&points)
+    }
+}
+
+struct ExcellonParser {
+    data: DrillData,
+    leading_zeros: bool,
+    integer_digits: u32,
+    decimal_digits: u32,
+    current_tool: Option<u32>,
+    position: Point2<f64>,
+    incremental: bool,
+    routing: bool,
+    route_start: Option<Point2<f64>>,
+}
+
+impl ExcellonParser {
+    fn new() -> Self {
+        Self {
+            data: DrillData::default(),
+            leading_zeros: true,
+            integer_digits: 2,
+            decimal_digits: 4,
+            current_tool: None,
+            position: Point2::origin(),
+            incremental: false,
+            routing: false,
+            route_start: None,
+        }
+    }
+
+    fn set_units(&mut self, line: &str) {
+        if line.starts_with("INCH") {
+            self.data.units = Units::Inches;
+            self.integer_digits = 2;
+            self.decimal_digits = 4;
+        } else {
+            self.data.units = Units::Millimeters;
+            self.integer_digits = 3;
+            self.decimal_digits = 3;
+        }
+        if line.contains("TZ") {
+            // Trailing zeros kept, leading zeros omitted
+            self.leading_zeros = false;
+        } else if line.contains("LZ") {
+            self.leading_zeros = true;
+        }
+        // Explicit format such as "METRIC,LZ,000.000"
+        if let Some(format) = line.split(',').find(|f| f.contains('.') && f.chars().all(|c| c == '0' || c == '.'))
+            && let Some((int, dec)) = format.split_once('.')
+        {
+            self.integer_digits = int.len() as u32;
+            self.decimal_digits = dec.len() as u32;
+        }
+    }
+
+    fn coordinate(&self, value: &str) -> Option<f64> {
+        let raw = if value.contains('.') {
+            value.parse::<f64>().ok()?
+        } else {
+            let negative = value.starts_with('-');
+            let digits = value.trim_start_matches(['+', '-']);
+            let total = (self.integer_digits + self.decimal_digits) as usize;
+            // With leading zeros present the number is padded on the right
+            let digits = if self.leading_zeros && digits.len() < total {
+                format!("{:0<width$}", digits, width = total)
+            } else {
+                digits.to_string()
+            };
+            let v = digits.parse::<i64>().ok()? as f64 / 10f64.powi(self.decimal_digits as i32);
+            if negative { -v } else { v }
+        };
+        Some(raw * self.data.units.to_mm())
+    }
+
+    /// Parse X/Y from a line, keeping the current value for missing axes
+    fn read_point(&self, text: &str) -> Point2<f64> {
+        let mut x = None;
+        let mut y = None;
+        let mut letter = ' ';
+        let mut value = String::new();
+        let flush = |letter: char, value: &str, x: &mut Option<f64>, y: &mut Option<f64>| {
+            match letter {
+                'X' => *x = self.coordinate(value),
+                'Y' => *y = self.coordinate(value),
+                _ => {}
+            }
+        };
+        for c in text.chars() {
+            if c == 'X' || c == 'Y' {
+                flush(letter, &value, &mut x, &mut y);
+                letter = c;
+                value.clear();
+            } else if c.is_ascii_alphabetic() {
+                flush(letter, &value, &mut x, &mut y);
+                letter = ' ';
+                value.clear();
+            } else {
+                value.push(c);
+            }
+        }
+        flush(letter, &value, &mut x, &mut y);
+
+        if self.incremental {
+            Point2::new(self.position.x + x.unwrap_or(0.0), self.position.y + y.unwrap_or(0.0))
+        } else {
+            Point2::new(x.unwrap_or(self.position.x), y.unwrap_or(self.position.y))
+        }
+    }
+
+    fn tool_diameter(&self) -> f64 {
+        self.current_tool
+            .and_then(|t| self.data.tools.get(&t))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    fn header_line(&mut self, line: &str) {
+        if line.starts_with("METRIC") || line.starts_with("INCH") {
+            self.set_units(line);
+        } else if let Some(rest) = line.strip_prefix('T') {
+            self.tool_definition(rest);
+        }
+    }
+
+    /// "T1C0.0220" or "T01F00S00C0.800"
+    fn tool_definition(&mut self, rest: &str) {
+        let number: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let Ok(tool) = number.parse::<u32>() else {
+            return;
+        };
+        if let Some(c_pos) = rest.find('C') {
+            let diameter: String = rest[c_pos + 1..].chars()
+                .take_while(|c| c.is_ascii_digit() || *c == '.')
+                .collect();
+            if let Ok(diameter) = diameter.parse::<f64>() {
+                self.data.tools.insert(tool, diameter * self.data.units.to_mm());
+            }
+        }
+    }
+
+    fn body_line(&mut self, line: &str) {
+        if let Some(rest) = line.strip_prefix('T') {
+            // Tool definitions are also allowed in the body
+            if rest.contains('C') {
+                self.tool_definition(rest);
+            }
+            let number: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            self.current_tool = number.parse().ok().filter(|t| *t > 0);
+            return;
+        }
+
+        match line {
+            "G90" => { self.incremental = false; return; }
+            "G91" => { self.incremental = true; return; }
+            "G05" | "G81" => { self.routing = false; return; }
+            "M15" => { self.route_start = Some(self.position); return; }
+            "M16" | "M17" => { self.route_start = None; return; }
+            "M30" | "M00" => return,
+            _ => {}
+        }
+
+        if line.starts_with("METRIC") || line.starts_with("INCH") {
+            self.set_units(line);
+            return;
+        }
+
+        // Slot: X..Y..G85X..Y..
+        if let Some((start, end)) = line.split_once("G85") {
+            let start = self.read_point(start);
+            self.position = start;
+            let end = self.read_point(end);
+            self.position = end;
+            let (tool, diameter) = (self.current_tool.unwrap_or(0), self.tool_diameter());
+            self.data.slots.push(DrillSlot { tool, start, end, diameter });
+            return;
+        }
+
+        if let Some(rest) = line.strip_prefix("G00") {
+            self.routing = true;
+            self.position = self.read_point(rest);
+            return;
+        }
+
+        if let Some(rest) = line.strip_prefix("G01") {
+            let end = self.read_point(rest);
+            if let Some(start) = self.route_start {
+                let (tool, diameter) = (self.current_tool.unwrap_or(0), self.tool_diameter());
+                self.data.slots.push(DrillSlot { tool, start, end, diameter });
+                self.route_start = Some(end);
+            }
+            self.position = end;
+            return;
+        }
+
+        if line.starts_with('X') || line.starts_with('Y') {
+            self.position = self.read_point(line);
+            if !self.routing {
+                let (tool, diameter) = (self.current_tool.unwrap_or(0), self.tool_diameter());
+                self.data.hits.push(DrillHit { tool, position: self.position, diameter });
+            }
+        }
+    }
+}
+
+/// Parse Excellon drill content
+pub fn parse_excellon(content: &str) -> Result<DrillData, String> {
+    let mut parser = ExcellonParser::new();
+    let mut in_header = false;
+    let mut seen_header = false;
+
+    for raw_line in content.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // Comments may carry X2-style attributes, e.g. "; #@! TF.FileFunction,NonPlated,1,2,NPTH"
+        if let Some(comment) = line.strip_prefix(';') {
+            if comment.contains("TF.FileFunction") {
+                if comment.contains("NonPlated") {
+                    parser.data.plated = Some(false);
+                } else if comment.contains("Plated") {
+                    parser.data.plated = Some(true);
+                }
+            }
+            continue;
+        }
+
+        if line == "M48" {
+            in_header = true;
+            seen_header = true;
+            continue;
+        }
+        if in_header && (line == "%" || line == "M95") {
+            in_header = false;
+            continue;
+        }
+
+        if in_header {
+            parser.header_line(line);
+        } else {
+            parser.body_line(line);
+        }
+    }
+
+    if !seen_header {
+        return Err("Not an Excellon drill file (missing M48 header)".to_string());
+    }
+    Ok(parser.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kicad_drill() {
+        let content = "M48\nINCH\nT1C0.0220\nT2C0.0400\n%\nG90\nG05\nT1\nX3.532Y-1.673\nX3.532Y-1.798\nT2\nX4.024Y-1.2826\nT0\nM30\n";
+        let data = parse_excellon(content).unwrap();
+        assert_eq!(data.tools.len(), 2);
+        assert_eq!(data.hits.len(), 3);
+        assert!((data.hits[0].position.x - 89.7128).abs() < 1e-6);
+        assert!((data.hits[0].diameter - 0.5588).abs() < 1e-6);
+        assert_eq!(data.hole_counts()[&1], 2);
+    }
+
+    #[test]
+    fn test_parse_slots_and_implicit_decimals() {
+        let content = "M48\n; #@! TF.FileFunction,NonPlated,1,2,NPTH\nMETRIC,LZ,000.000\nT1C1.000\n%\nT1\n\
+                       X010000Y020000\nX010000Y020000G85X015000Y020000\nG00X0Y0\nM15\nG01X5.0Y0\nM16\nM30\n";
+        let data = parse_excellon(content).unwrap();
+        assert_eq!(data.plated, Some(false));
+        assert_eq!(data.hits.len(), 1);
+        assert!((data.hits[0].position.x - 10.0).abs() < 1e-9);
+        assert_eq!(data.slots.len(), 2);
+        assert!((data.slots[0].end.x - 15.0).abs() < 1e-9);
+    }
+}
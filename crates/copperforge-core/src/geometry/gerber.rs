@@ -0,0 +1,522 @@
+use std::collections::HashMap;
+use std::io::BufReader;
+use nalgebra::{Point2, Vector2};
+use gerber_viewer::gerber_parser::parse;
+use gerber_viewer::gerber_types::{
+    self, Command, Coordinates, DCode, ExtendedCode, FunctionCode, GCode, GerberCode,
+    InterpolationMode, MCode, Mirroring, Operation, QuadrantMode, StepAndRepeat,
+};
+
+use super::aperture::{self, ApertureMacro};
+use super::shapes;
+use super::{
+    LayerGeometry, GerberFeature, FeatureKind, Polarity, ApertureTransform,
+    Units, CoordinateFormat,
+};
+
+// Flattened geometry from the gerber_parser command stream
+// Walks the same commands gerber_viewer renders from, so analysis sees exactly
+// what is drawn. Handles apertures and macros, polarity, aperture transforms,
+// linear/circular interpolation, regions and step & repeat.
+
+/// Builder state while walking the command stream
+struct GeometryBuilder {
+    geometry: LayerGeometry,
+    macros: HashMap<String, ApertureMacro>,
+    interpolation: InterpolationMode,
+    multi_quadrant: bool,
+    region_mode: bool,
+    polarity: Polarity,
+    aperture_transform: ApertureTransform,
+    current_aperture: Option<i32>,
+    position: Point2<f64>,
+    contour: Vec<Point2<f64>>,
+    step_repeat: Option<StepRepeat>,
+    /// Current aperture attributes (%TA), attached to apertures when defined
+    aperture_attributes: HashMap<String, Vec<String>>,
+    /// Current object attributes (%TO), attached to every feature drawn
+    object_attributes: Vec<(String, Vec<String>)>,
+}
+
+struct StepRepeat {
+    start_index: usize,
+    x_count: u32,
+    y_count: u32,
+    x_step: f64,
+    y_step: f64,
+}
+
+/// Gerber text of an extended command without its delimiters, e.g. "ADD10C,0.3"
+fn extended_body(code: ExtendedCode) -> Result<String, String> {
+    let mut text = Vec::new();
+    Command::ExtendedCode(code).serialize(&mut text).map_err(|e| e.to_string())?;
+    let text = String::from_utf8(text).map_err(|e| e.to_string())?;
+    Ok(text.trim().trim_matches('%').trim_end_matches('*').to_string())
+}
+
+/// Split an attribute body such as ".AperFunction,SMDPad,CuDef" into name and values
+fn attribute_fields(body: &str) -> (String, Vec<String>) {
+    let mut fields = body.split(',');
+    let name = fields.next().unwrap_or("").to_string();
+    (name, fields.map(|f| f.to_string()).collect())
+}
+
+impl GeometryBuilder {
+    fn new() -> Self {
+        Self {
+            geometry: LayerGeometry::default(),
+            macros: HashMap::new(),
+            interpolation: InterpolationMode::Linear,
+            multi_quadrant: true,
+            region_mode: false,
+            polarity: Polarity::Dark,
+            aperture_transform: ApertureTransform::default(),
+            current_aperture: None,
+            position: Point2::origin(),
+            contour: Vec::new(),
+            step_repeat: None,
+            aperture_attributes: HashMap::new(),
+            object_attributes: Vec::new(),
+        }
+    }
+
+    fn scale(&self) -> f64 {
+        self.geometry.units.to_mm()
+    }
+
+    fn warn(&mut self, message: String) {
+        if !self.geometry.warnings.contains(&message) {
+            self.geometry.warnings.push(message);
+        }
+    }
+
+    fn push_feature(&mut self, kind: FeatureKind, aperture: Option<i32>) {
+        // Apertures carry their function; regions take the current attribute
+        let function = match aperture {
+            Some(d_code) => self.geometry.apertures.get(&d_code).and_then(|a| a.function.clone()),
+            None => self.aperture_attributes.get(".AperFunction").map(|v| v.join(",")),
+        };
+        self.geometry.features.push(GerberFeature {
+            kind,
+            polarity: self.polarity,
+            aperture,
+            aperture_transform: self.aperture_transform,
+            function,
+            attributes: self.object_attributes.clone(),
+        });
+    }
+
+    fn command(&mut self, command: Command) -> Result<(), String> {
+        match command {
+            Command::ExtendedCode(code) => self.extended(code),
+            Command::FunctionCode(FunctionCode::GCode(code)) => {
+                self.g_code(code);
+                Ok(())
+            }
+            Command::FunctionCode(FunctionCode::DCode(DCode::SelectAperture(code))) => {
+                if !self.geometry.apertures.contains_key(&code) {
+                    self.warn(format!("Aperture D{} used before definition", code));
+                }
+                self.current_aperture = Some(code);
+                Ok(())
+            }
+            Command::FunctionCode(FunctionCode::DCode(DCode::Operation(operation))) => {
+                self.operation(operation);
+                Ok(())
+            }
+            Command::FunctionCode(FunctionCode::MCode(MCode::EndOfFile)) => {
+                self.flush_step_repeat();
+                Ok(())
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Extended commands
+    // ------------------------------------------------------------------------
+
+    fn extended(&mut self, code: ExtendedCode) -> Result<(), String> {
+        match code {
+            ExtendedCode::CoordinateFormat(format) => {
+                self.geometry.format = CoordinateFormat { integer: format.integer, decimal: format.decimal };
+            }
+            ExtendedCode::Unit(unit) => {
+                self.geometry.units = match unit {
+                    gerber_types::Unit::Millimeters => Units::Millimeters,
+                    gerber_types::Unit::Inches => Units::Inches,
+                };
+            }
+            ExtendedCode::ApertureMacro(aperture_macro) => {
+                // Kept as Gerber text, which the RS-274X writer copies back out
+                let name = aperture_macro.name.clone();
+                let body = extended_body(ExtendedCode::ApertureMacro(aperture_macro))?;
+                let body = body.split_once('*').map_or("", |(_, body)| body.trim_start());
+                let aperture_macro = ApertureMacro::parse(&name, body)?;
+                self.geometry.macros.insert(name.clone(), aperture_macro.clone());
+                self.macros.insert(name, aperture_macro);
+            }
+            ExtendedCode::ApertureDefinition(definition) => {
+                let body = extended_body(ExtendedCode::ApertureDefinition(definition))?;
+                let definition = body.strip_prefix("AD").unwrap_or(&body);
+                let mut aperture = aperture::parse_aperture_definition(definition, &self.macros, self.scale())?;
+                aperture.function = self.aperture_attributes.get(".AperFunction").map(|v| v.join(","));
+                self.geometry.apertures.insert(aperture.d_code, aperture);
+            }
+            ExtendedCode::LoadPolarity(polarity) => {
+                self.polarity = match polarity {
+                    gerber_types::Polarity::Clear => Polarity::Clear,
+                    gerber_types::Polarity::Dark => Polarity::Dark,
+                };
+            }
+            ExtendedCode::LoadMirroring(mirroring) => {
+                self.aperture_transform.mirror_x = matches!(mirroring, Mirroring::X | Mirroring::XY);
+                self.aperture_transform.mirror_y = matches!(mirroring, Mirroring::Y | Mirroring::XY);
+            }
+            ExtendedCode::LoadRotation(rotation) => self.aperture_transform.rotation = rotation.rotation,
+            ExtendedCode::LoadScaling(scaling) => self.aperture_transform.scale = scaling.scale,
+            ExtendedCode::StepAndRepeat(StepAndRepeat::Open { repeat_x, repeat_y, distance_x, distance_y }) => {
+                self.flush_step_repeat();
+                self.step_repeat = Some(StepRepeat {
+                    start_index: self.geometry.features.len(),
+                    x_count: repeat_x,
+                    y_count: repeat_y,
+                    x_step: distance_x * self.scale(),
+                    y_step: distance_y * self.scale(),
+                });
+            }
+            ExtendedCode::StepAndRepeat(StepAndRepeat::Close) => self.flush_step_repeat(),
+            code @ ExtendedCode::FileAttribute(_) => {
+                let body = extended_body(code)?;
+                let attribute = attribute_fields(body.strip_prefix("TF").unwrap_or(&body));
+                self.geometry.file_attributes.push(attribute);
+            }
+            code @ ExtendedCode::ApertureAttribute(_) => {
+                let body = extended_body(code)?;
+                let (name, values) = attribute_fields(body.strip_prefix("TA").unwrap_or(&body));
+                self.aperture_attributes.insert(name, values);
+            }
+            code @ ExtendedCode::ObjectAttribute(_) => {
+                let body = extended_body(code)?;
+                let (name, values) = attribute_fields(body.strip_prefix("TO").unwrap_or(&body));
+                self.object_attributes.retain(|(key, _)| *key != name);
+                self.object_attributes.push((name, values));
+            }
+            ExtendedCode::DeleteAttribute(name) => {
+                // %TD*% deletes all attributes, %TD.Name*% only one
+                let name = name.trim();
+                if name.is_empty() {
+                    self.aperture_attributes.clear();
+                    self.object_attributes.clear();
+                } else {
+                    self.aperture_attributes.remove(name);
+                    self.object_attributes.retain(|(key, _)| key != name);
+                }
+            }
+            ExtendedCode::ImagePolarity(gerber_types::ImagePolarity::Negative) => {
+                self.warn("Negative image polarity (%IPNEG) is not supported".to_string());
+            }
+            ExtendedCode::ApertureBlock(_) => {
+                self.warn("Block apertures (%AB) are not supported".to_string());
+            }
+            _ => {
+                // Deprecated image commands don't change the geometry
+            }
+        }
+        Ok(())
+    }
+
+    fn flush_step_repeat(&mut self) {
+        let Some(sr) = self.step_repeat.take() else {
+            return;
+        };
+        let block: Vec<GerberFeature> = self.geometry.features[sr.start_index..].to_vec();
+        for ix in 0..sr.x_count {
+            for iy in 0..sr.y_count {
+                if ix == 0 && iy == 0 {
+                    continue;
+                }
+                let offset = Vector2::new(ix as f64 * sr.x_step, iy as f64 * sr.y_step);
+                self.geometry.features.extend(block.iter().map(|f| f.translated(offset)));
+            }
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Function codes
+    // ------------------------------------------------------------------------
+
+    fn g_code(&mut self, code: GCode) {
+        match code {
+            GCode::InterpolationMode(mode) => self.interpolation = mode,
+            GCode::RegionMode(true) => {
+                self.region_mode = true;
+                self.contour.clear();
+            }
+            GCode::RegionMode(false) => {
+                self.flush_contour();
+                self.region_mode = false;
+            }
+            GCode::QuadrantMode(mode) => self.multi_quadrant = mode == QuadrantMode::Multi,
+            GCode::Comment(_) => {}
+        }
+    }
+
+    /// Point in mm reached by the coordinates, missing axes keep the current position
+    fn target(&self, coordinates: Option<Coordinates>) -> Point2<f64> {
+        let scale = self.scale();
+        let Some(coordinates) = coordinates else {
+            return self.position;
+        };
+        Point2::new(
+            coordinates.x.map_or(self.position.x, |x| f64::from(x) * scale),
+            coordinates.y.map_or(self.position.y, |y| f64::from(y) * scale),
+        )
+    }
+
+    fn operation(&mut self, operation: Operation) {
+        match operation {
+            Operation::Interpolate(coordinates, offset) => {
+                let target = self.target(coordinates);
+                let scale = self.scale();
+                let offset = offset.map_or(Vector2::zeros(), |offset| Vector2::new(
+                    offset.x.map_or(0.0, |i| f64::from(i) * scale),
+                    offset.y.map_or(0.0, |j| f64::from(j) * scale),
+                ));
+                self.interpolate(target, offset);
+            }
+            Operation::Move(coordinates) => {
+                let target = self.target(coordinates);
+                if self.region_mode {
+                    self.flush_contour();
+                }
+                self.position = target;
+            }
+            Operation::Flash(coordinates) => {
+                let target = self.target(coordinates);
+                if self.region_mode {
+                    self.warn("Flash inside region ignored".to_string());
+                } else {
+                    self.push_feature(FeatureKind::Flash { position: target }, self.current_aperture);
+                }
+                self.position = target;
+            }
+        }
+    }
+
+    fn interpolate(&mut self, target: Point2<f64>, offset: Vector2<f64>) {
+        let start = self.position;
+        self.position = target;
+
+        let arc = match self.interpolation {
+            InterpolationMode::Linear => None,
+            InterpolationMode::ClockwiseCircular => Some(true),
+            InterpolationMode::CounterclockwiseCircular => Some(false),
+        };
+        if self.region_mode {
+            if self.contour.is_empty() {
+                self.contour.push(start);
+            }
+            match arc {
+                None => self.contour.push(target),
+                Some(clockwise) => {
+                    let center = self.arc_center(start, target, offset, clockwise);
+                    let points = shapes::arc_points(start, target, center, clockwise, shapes::DEFAULT_TOLERANCE);
+                    self.contour.extend(points.into_iter().skip(1));
+                }
+            }
+            return;
+        }
+
+        match arc {
+            None => self.push_feature(FeatureKind::Line { start, end: target }, self.current_aperture),
+            Some(clockwise) => {
+                let center = self.arc_center(start, target, offset, clockwise);
+                self.push_feature(FeatureKind::Arc { start, end: target, center, clockwise }, self.current_aperture);
+            }
+        }
+    }
+
+    /// Arc centre from the I/J offset, resolving signs in single quadrant mode
+    fn arc_center(&self, start: Point2<f64>, end: Point2<f64>, offset: Vector2<f64>, clockwise: bool) -> Point2<f64> {
+        if self.multi_quadrant {
+            return start + offset;
+        }
+
+        let candidates = [
+            Vector2::new(offset.x.abs(), offset.y.abs()),
+            Vector2::new(-offset.x.abs(), offset.y.abs()),
+            Vector2::new(offset.x.abs(), -offset.y.abs()),
+            Vector2::new(-offset.x.abs(), -offset.y.abs()),
+        ];
+        candidates.iter()
+            .map(|c| start + c)
+            .filter(|center| {
+                shapes::arc_sweep(start, end, *center, clockwise).abs() <= std::f64::consts::FRAC_PI_2 + 1e-6
+            })
+            .min_by(|a, b| {
+                let da = ((start - a).norm() - (end - a).norm()).abs();
+                let db = ((start - b).norm() - (end - b).norm()).abs();
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(start + offset)
+    }
+
+    fn flush_contour(&mut self) {
+        let mut points = std::mem::take(&mut self.contour);
+        if points.len() > 1 && (points[0] - points[points.len() - 1]).norm() < 1e-9 {
+            points.pop();
+        }
+        if points.len() >= 3 {
+            self.push_feature(FeatureKind::Region { points }, None);
+        }
+    }
+}
+
+/// Build the flattened geometry of RS-274X content
+/// Commands gerber_parser rejects are skipped, as they are by the renderer, and
+/// reported in the layer warnings.
+pub fn parse_gerber(content: &str) -> Result<LayerGeometry, String> {
+    let doc = parse(BufReader::new(content.as_bytes()))
+        .map_err(|(_, e)| format!("Failed to read Gerber content: {}", e))?;
+    let mut builder = GeometryBuilder::new();
+
+    for command in doc.commands {
+        match command {
+            Ok(command) => builder.command(command)?,
+            Err(e) => builder.warn(e.to_string()),
+        }
+    }
+
+    if builder.region_mode {
+        builder.warn("Region not closed with G37".to_string());
+        builder.flush_contour();
+    }
+    builder.flush_step_repeat();
+
+    Ok(builder.geometry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "%FSLAX46Y46*%\n%MOMM*%\n%LPD*%\nG01*\n\
+        %ADD10C,0.300000*%\n%ADD11R,1.000000X2.000000*%\n\
+        D10*\nX0Y0D02*\nX10000000Y0D01*\n\
+        G75*\nG03*\nX0Y10000000I-10000000J0D01*\nG01*\n\
+        D11*\nX5000000Y5000000D03*\n\
+        %LPC*%\nG36*\nX1000000Y1000000D02*\nX2000000Y1000000D01*\nX2000000Y2000000D01*\nX1000000Y1000000D01*\nG37*\n\
+        M02*\n";
+
+    #[test]
+    fn test_parse_features() {
+        let geometry = parse_gerber(SAMPLE).unwrap();
+        assert_eq!(geometry.apertures.len(), 2);
+        assert_eq!(geometry.features.len(), 4);
+
+        assert_eq!(geometry.features[0].kind, FeatureKind::Line {
+            start: Point2::new(0.0, 0.0),
+            end: Point2::new(10.0, 0.0),
+        });
+        match &geometry.features[1].kind {
+            FeatureKind::Arc { center, clockwise, .. } => {
+                assert!((center.x).abs() < 1e-9 && (center.y).abs() < 1e-9);
+                assert!(!clockwise);
+            }
+            other => panic!("expected arc, got {:?}", other),
+        }
+        assert_eq!(geometry.features[2].aperture, Some(11));
+        match &geometry.features[3].kind {
+            FeatureKind::Region { points } => assert_eq!(points.len(), 3),
+            other => panic!("expected region, got {:?}", other),
+        }
+        assert_eq!(geometry.features[3].polarity, Polarity::Clear);
+    }
+
+    #[test]
+    fn test_aperture_functions() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n\
+            %TA.AperFunction,SMDPad,CuDef*%\n%ADD10R,1.000000X1.000000*%\n%TD*%\n\
+            %TA.AperFunction,Conductor*%\n%ADD11C,0.250000*%\n\
+            D10*\nX0Y0D03*\nD11*\nX0Y0D02*\nX1000000Y0D01*\n\
+            G36*\nX0Y0D02*\nX1000000Y0D01*\nX1000000Y1000000D01*\nX0Y0D01*\nG37*\n\
+            %TD*%\nM02*\n";
+        let geometry = parse_gerber(content).unwrap();
+        assert_eq!(geometry.apertures[&10].function.as_deref(), Some("SMDPad,CuDef"));
+        assert!(geometry.apertures[&10].is_component_pad());
+        assert!(!geometry.apertures[&11].is_component_pad());
+        assert_eq!(geometry.features[0].function.as_deref(), Some("SMDPad,CuDef"));
+        assert_eq!(geometry.features[2].function.as_deref(), Some("Conductor"));
+    }
+
+    #[test]
+    fn test_object_attributes() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,0.250000*%\nD10*\n\
+            %TO.N,GND*%\n%TO.C,R1*%\nX0Y0D02*\nX1000000Y0D01*\n\
+            %TO.N,VCC*%\nX2000000Y0D01*\n%TD.C*%\nX3000000Y0D01*\n%TD*%\nX4000000Y0D01*\nM02*\n";
+        let geometry = parse_gerber(content).unwrap();
+        assert_eq!(geometry.features.len(), 4);
+        assert_eq!(geometry.features[0].attribute(".N"), Some(&["GND".to_string()][..]));
+        assert_eq!(geometry.features[0].attribute(".C"), Some(&["R1".to_string()][..]));
+        assert_eq!(geometry.features[1].attribute(".N"), Some(&["VCC".to_string()][..]));
+        assert_eq!(geometry.features[2].attribute(".C"), None);
+        assert!(geometry.features[3].attributes.is_empty());
+    }
+
+    #[test]
+    fn test_net_and_pin_attributes() {
+        // KiCad style: pads carry .P and .N, unconnected copper an empty .N
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10R,1.000000X1.000000*%\nD10*\n\
+            %TO.P,U1,3,GPIO*%\n%TO.N,/SDA*%\nX0Y0D03*\n%TD*%\n\
+            %TO.N,*%\nX2000000Y0D03*\n%TD*%\nM02*\n";
+        let geometry = parse_gerber(content).unwrap();
+        let pad = &geometry.features[0];
+        assert_eq!(pad.net(), Some("/SDA"));
+        assert!(pad.in_net("/SDA"));
+        assert_eq!(pad.component(), Some("U1"));
+        assert_eq!(pad.pin(), Some(("U1", "3")));
+        assert_eq!(geometry.features[1].net(), None);
+        assert_eq!(geometry.features[1].component(), None);
+    }
+
+    #[test]
+    fn test_inch_units() {
+        let content = "%FSLAX24Y24*%\n%MOIN*%\n%ADD10C,0.010*%\nD10*\nX10000Y15000D03*\nM02*\n";
+        let geometry = parse_gerber(content).unwrap();
+        match geometry.features[0].kind {
+            FeatureKind::Flash { position } => {
+                assert!((position.x - 25.4).abs() < 1e-9);
+                assert!((position.y - 38.1).abs() < 1e-9);
+            }
+            ref other => panic!("expected flash, got {:?}", other),
+        }
+        assert!((geometry.apertures[&10].stroke_width() - 0.254).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_step_repeat() {
+        let content = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,1*%\n%SRX2Y3I5.0J10.0*%\nD10*\nX0Y0D03*\n%SR*%\nM02*\n";
+        let geometry = parse_gerber(content).unwrap();
+        assert_eq!(geometry.features.len(), 6);
+    }
+
+    #[test]
+    fn test_feature_bounds() {
+        let geometry = parse_gerber(SAMPLE).unwrap();
+        let (min, max) = geometry.feature_bounds(&geometry.features[0]).unwrap();
+        assert!((min.x + 0.15).abs() < 0.01 && (max.x - 10.15).abs() < 0.01);
+        assert!((max.y - min.y - 0.3).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_feature_at() {
+        let geometry = parse_gerber(SAMPLE).unwrap();
+        assert_eq!(geometry.feature_at(Point2::new(5.0, 0.1), 0.0), Some(0));
+        assert_eq!(geometry.feature_at(Point2::new(5.0, 0.3), 0.0), None);
+        assert_eq!(geometry.feature_at(Point2::new(5.0, 0.3), 0.2), Some(0));
+        assert_eq!(geometry.feature_at(Point2::new(7.071, 7.071), 0.0), Some(1));
+        assert_eq!(geometry.feature_at(Point2::new(5.0, 5.5), 0.0), Some(2));
+        // Clear regions are features too and can be picked
+        assert_eq!(geometry.feature_at(Point2::new(1.7, 1.2), 0.0), Some(3));
+    }
+}
@@ -0,0 +1,502 @@
+// Gerber and drill geometry
+// `GerberLayer` from gerber_viewer only exposes rendering, so analysis and
+// export features work on the flattened geometry produced here instead.
+// `gerber.rs` builds features from the gerber_parser command stream and the
+// Excellon parser (`excellon.rs`) reads drill files, all coordinates in mm, and
+// `connectivity.rs` groups copper features into islands and nets,
+// `raster.rs` renders layers to pixel coverage without a GPU, and
+// `outline.rs` chains board outline strokes into closed contours.
+
+pub mod shapes;
+pub mod aperture;
+pub mod gerber;
+pub mod excellon;
+pub mod connectivity;
+pub mod raster;
+pub mod outline;
+pub mod diff;
+pub mod snap;
+
+pub use shapes::{Polygon, DEFAULT_TOLERANCE};
+pub use aperture::{Aperture, ApertureShape, ApertureMacro, MacroPrimitive};
+pub use gerber::parse_gerber;
+pub use excellon::{parse_excellon, DrillData, DrillHit, DrillSlot};
+pub use connectivity::{extract_connectivity, Connectivity, Island, Net, HoleRef};
+pub use raster::{rasterize_layer, Antialias, CoverageMask, RasterView};
+pub use outline::{board_polygons, outline_contours, OutlineContour, OutlineSegment};
+pub use diff::{diff_layers, DiffClass, DiffRegion, LayerDiff};
+pub use snap::{best_snap, snap_to_drills, snap_to_layer, SnapKind, SnapPoint};
+
+use std::collections::BTreeMap;
+use nalgebra::{Matrix2, Point2, Vector2};
+
+/// Units used in the source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Units {
+    #[default]
+    Millimeters,
+    Inches,
+}
+
+impl Units {
+    /// Factor converting file units to mm
+    pub fn to_mm(&self) -> f64 {
+        match self {
+            Units::Millimeters => 1.0,
+            Units::Inches => 25.4,
+        }
+    }
+}
+
+/// Coordinate format from %FS (integer and decimal digits)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CoordinateFormat {
+    pub integer: u8,
+    pub decimal: u8,
+}
+
+impl Default for CoordinateFormat {
+    fn default() -> Self {
+        Self { integer: 4, decimal: 6 }
+    }
+}
+
+/// Layer polarity from %LP
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Polarity {
+    #[default]
+    Dark,
+    Clear,
+}
+
+/// Aperture transformation from %LM, %LR and %LS
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ApertureTransform {
+    pub mirror_x: bool,
+    pub mirror_y: bool,
+    pub rotation: f64,
+    pub scale: f64,
+}
+
+impl Default for ApertureTransform {
+    fn default() -> Self {
+        Self { mirror_x: false, mirror_y: false, rotation: 0.0, scale: 1.0 }
+    }
+}
+
+impl ApertureTransform {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply to a point of an aperture image: mirror, then rotate, then scale
+    pub fn apply(&self, point: Point2<f64>) -> Point2<f64> {
+        let mut p = point;
+        if self.mirror_x {
+            p.x = -p.x;
+        }
+        if self.mirror_y {
+            p.y = -p.y;
+        }
+        let p = shapes::rotate_point(p, self.rotation);
+        Point2::new(p.x * self.scale, p.y * self.scale)
+    }
+}
+
+/// Affine map from layer coordinates to output coordinates (mm)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LayerTransform {
+    pub linear: Matrix2<f64>,
+    pub offset: Vector2<f64>,
+}
+
+impl Default for LayerTransform {
+    fn default() -> Self {
+        Self { linear: Matrix2::identity(), offset: Vector2::zeros() }
+    }
+}
+
+impl LayerTransform {
+    /// Sample an affine point mapping, e.g. a viewer transform
+    pub fn from_fn(map: impl Fn(Point2<f64>) -> Point2<f64>) -> Self {
+        let origin = map(Point2::origin());
+        let ex = map(Point2::new(1.0, 0.0)) - origin;
+        let ey = map(Point2::new(0.0, 1.0)) - origin;
+        Self { linear: Matrix2::from_columns(&[ex, ey]), offset: origin.coords }
+    }
+
+    pub fn apply(&self, point: Point2<f64>) -> Point2<f64> {
+        Point2::from(self.linear * point.coords + self.offset)
+    }
+
+    /// Mapping back from the transformed space, None for degenerate transforms
+    pub fn inverse(&self) -> Option<Self> {
+        let linear = self.linear.try_inverse()?;
+        Some(Self { linear, offset: -(linear * self.offset) })
+    }
+
+    /// Length scale factor (1.0 unless the transform scales)
+    pub fn scale(&self) -> f64 {
+        self.linear.determinant().abs().sqrt()
+    }
+
+    pub fn is_mirrored(&self) -> bool {
+        self.linear.determinant() < 0.0
+    }
+
+    /// Rotation, mirroring and uniform scale only, so circles stay circles
+    pub fn is_conformal(&self) -> bool {
+        let ex = self.linear.column(0);
+        let ey = self.linear.column(1);
+        (ex.norm() - ey.norm()).abs() < 1e-9 && ex.dot(&ey).abs() < 1e-9
+    }
+
+    /// Aperture transform of a feature once the layer transform is applied
+    pub fn compose(&self, base: &ApertureTransform) -> ApertureTransform {
+        let l = &self.linear;
+        let scale = l.determinant().abs().sqrt() * base.scale;
+        if self.is_mirrored() {
+            // L = s * R(angle) * mirror_x, and mirror_x * R(b) = R(-b) * mirror_x
+            let angle = (-l[(1, 0)]).atan2(-l[(0, 0)]).to_degrees();
+            ApertureTransform {
+                mirror_x: !base.mirror_x,
+                mirror_y: base.mirror_y,
+                rotation: normalize_degrees(angle - base.rotation),
+                scale,
+            }
+        } else {
+            let angle = l[(1, 0)].atan2(l[(0, 0)]).to_degrees();
+            ApertureTransform {
+                rotation: normalize_degrees(angle + base.rotation),
+                scale,
+                ..*base
+            }
+        }
+    }
+}
+
+fn normalize_degrees(degrees: f64) -> f64 {
+    let degrees = degrees.rem_euclid(360.0);
+    // Snap float noise, so 90° is written as 90 and not 89.99999999
+    let rounded = (degrees * 1e6).round() / 1e6;
+    if rounded >= 360.0 { 0.0 } else { rounded }
+}
+
+/// Graphical object kinds
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureKind {
+    Flash { position: Point2<f64> },
+    Line { start: Point2<f64>, end: Point2<f64> },
+    Arc { start: Point2<f64>, end: Point2<f64>, center: Point2<f64>, clockwise: bool },
+    Region { points: Vec<Point2<f64>> },
+}
+
+/// One graphical object of a Gerber layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct GerberFeature {
+    pub kind: FeatureKind,
+    pub polarity: Polarity,
+    /// Aperture D-code (None for regions)
+    pub aperture: Option<i32>,
+    pub aperture_transform: ApertureTransform,
+    /// Aperture function (%TA.AperFunction) of the aperture or region
+    pub function: Option<String>,
+    /// Object attributes (%TO) in effect when the feature was created, e.g. ".N" -> ["GND"]
+    pub attributes: Vec<(String, Vec<String>)>,
+}
+
+impl GerberFeature {
+    pub fn translated(&self, offset: Vector2<f64>) -> Self {
+        let kind = match &self.kind {
+            FeatureKind::Flash { position } => FeatureKind::Flash { position: position + offset },
+            FeatureKind::Line { start, end } => FeatureKind::Line { start: start + offset, end: end + offset },
+            FeatureKind::Arc { start, end, center, clockwise } => FeatureKind::Arc {
+                start: start + offset,
+                end: end + offset,
+                center: center + offset,
+                clockwise: *clockwise,
+            },
+            FeatureKind::Region { points } => FeatureKind::Region {
+                points: points.iter().map(|p| p + offset).collect(),
+            },
+        };
+        Self { kind, ..self.clone() }
+    }
+
+    /// Whether the feature is a component pad according to its aperture function
+    pub fn is_component_pad(&self) -> bool {
+        self.function.as_deref().is_some_and(aperture::is_pad_function)
+    }
+
+    /// Copper that is not part of any net (text, logos), from .AperFunction,NonConductor
+    pub fn is_non_conductor(&self) -> bool {
+        self.function.as_deref().is_some_and(|f| f.starts_with("NonConductor"))
+    }
+
+    /// Get an object attribute by name (e.g. ".N")
+    pub fn attribute(&self, name: &str) -> Option<&[String]> {
+        self.attributes.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Net name from .N (KiCad writes an empty name for unconnected copper)
+    pub fn net(&self) -> Option<&str> {
+        self.attribute(".N")?.first()
+            .map(String::as_str)
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Whether the feature belongs to a net (.N may list several merged nets)
+    pub fn in_net(&self, net: &str) -> bool {
+        self.attribute(".N").is_some_and(|names| names.iter().any(|name| name == net))
+    }
+
+    /// Reference designator from .C, or from the .P pin of a pad
+    pub fn component(&self) -> Option<&str> {
+        self.attribute(".C")
+            .or_else(|| self.attribute(".P"))?
+            .first()
+            .map(String::as_str)
+            .filter(|reference| !reference.is_empty())
+    }
+
+    /// Reference designator and pin number from .P, e.g. ("R1", "2")
+    pub fn pin(&self) -> Option<(&str, &str)> {
+        match self.attribute(".P")? {
+            [reference, pin, ..] => Some((reference.as_str(), pin.as_str())),
+            _ => None,
+        }
+    }
+
+    /// Short name of the feature kind
+    pub fn kind_name(&self) -> &'static str {
+        match self.kind {
+            FeatureKind::Flash { .. } => "Flash",
+            FeatureKind::Line { .. } => "Track",
+            FeatureKind::Arc { .. } => "Arc",
+            FeatureKind::Region { .. } => "Region",
+        }
+    }
+
+    /// Reference point of the feature (flash position, track midpoint, region centroid)
+    pub fn anchor(&self) -> Point2<f64> {
+        match &self.kind {
+            FeatureKind::Flash { position } => *position,
+            FeatureKind::Line { start, end } => Point2::new((start.x + end.x) / 2.0, (start.y + end.y) / 2.0),
+            FeatureKind::Arc { start, end, center, clockwise } => {
+                let sweep = shapes::arc_sweep(*start, *end, *center, *clockwise);
+                let radius = (start - center).norm();
+                let angle = (start.y - center.y).atan2(start.x - center.x) + sweep / 2.0;
+                Point2::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+            }
+            FeatureKind::Region { points } => {
+                if points.is_empty() {
+                    return Point2::origin();
+                }
+                let n = points.len() as f64;
+                Point2::new(
+                    points.iter().map(|p| p.x).sum::<f64>() / n,
+                    points.iter().map(|p| p.y).sum::<f64>() / n,
+                )
+            }
+        }
+    }
+}
+
+/// Flattened geometry of one Gerber file, all coordinates in mm
+#[derive(Debug, Clone, Default)]
+pub struct LayerGeometry {
+    pub features: Vec<GerberFeature>,
+    pub apertures: BTreeMap<i32, Aperture>,
+    pub macros: BTreeMap<String, ApertureMacro>,
+    /// Units and coordinate format of the source file
+    pub units: Units,
+    pub format: CoordinateFormat,
+    /// File attributes (%TF), e.g. ".FileFunction" -> ["Copper", "L1", "Top"]
+    pub file_attributes: Vec<(String, Vec<String>)>,
+    /// Non-fatal problems found while parsing
+    pub warnings: Vec<String>,
+}
+
+impl LayerGeometry {
+    /// Get a file attribute by name (e.g. ".FileFunction")
+    pub fn file_attribute(&self, name: &str) -> Option<&[String]> {
+        self.file_attributes.iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, values)| values.as_slice())
+    }
+
+    /// Outline polygons of a feature in board coordinates
+    /// Within a feature, clear polygons are holes (e.g. aperture holes)
+    pub fn feature_polygons(&self, feature: &GerberFeature, tolerance: f64) -> Vec<Polygon> {
+        let aperture = feature.aperture.and_then(|d| self.apertures.get(&d));
+        let transform = &feature.aperture_transform;
+        let aperture_polygons = |tolerance: f64| -> Vec<Polygon> {
+            match aperture {
+                Some(aperture) => aperture.polygons(tolerance).into_iter()
+                    .map(|p| {
+                        if transform.is_identity() {
+                            p
+                        } else {
+                            Polygon::new(p.points.iter().map(|pt| transform.apply(*pt)).collect(), p.dark)
+                        }
+                    })
+                    .collect(),
+                None => Vec::new(),
+            }
+        };
+        let convex_outline = |tolerance: f64| -> Vec<Point2<f64>> {
+            let points: Vec<Point2<f64>> = aperture_polygons(tolerance).into_iter()
+                .filter(|p| p.dark)
+                .flat_map(|p| p.points)
+                .collect();
+            shapes::convex_hull(&points)
+        };
+
+        match &feature.kind {
+            FeatureKind::Flash { position } => {
+                aperture_polygons(tolerance).into_iter()
+                    .map(|p| p.translated(position.coords))
+                    .collect()
+            }
+            FeatureKind::Line { start, end } => {
+                let outline = convex_outline(tolerance);
+                if outline.is_empty() {
+                    return vec![Polygon::new(vec![*start, *end], true)];
+                }
+                vec![Polygon::new(shapes::sweep_convex(&outline, *start, *end), true)]
+            }
+            FeatureKind::Arc { start, end, center, clockwise } => {
+                let path = shapes::arc_points(*start, *end, *center, *clockwise, tolerance);
+                match aperture.map(|a| &a.shape) {
+                    Some(ApertureShape::Circle { diameter, .. }) => {
+                        let width = diameter * transform.scale;
+                        vec![Polygon::new(arc_band(&path, *center, width, tolerance), true)]
+                    }
+                    _ => {
+                        let outline = convex_outline(tolerance);
+                        if outline.is_empty() {
+                            return vec![Polygon::new(path, true)];
+                        }
+                        path.windows(2)
+                            .map(|w| Polygon::new(shapes::sweep_convex(&outline, w[0], w[1]), true))
+                            .collect()
+                    }
+                }
+            }
+            FeatureKind::Region { points } => vec![Polygon::new(points.clone(), true)],
+        }
+    }
+
+    /// Whether a point lies on the copper of a feature (inside a dark outline, outside its holes)
+    pub fn feature_contains(&self, feature: &GerberFeature, point: Point2<f64>, tolerance: f64) -> bool {
+        let polygons = self.feature_polygons(feature, tolerance);
+        polygons.iter().any(|p| p.dark && p.contains(point))
+            && !polygons.iter().any(|p| !p.dark && p.contains(point))
+    }
+
+    /// Index of the topmost feature under a point
+    /// Features within `radius` of the point also count, so thin tracks can be picked.
+    /// Later features are drawn over earlier ones, so the search runs backwards.
+    pub fn feature_at(&self, point: Point2<f64>, radius: f64) -> Option<usize> {
+        self.features.iter().enumerate().rev()
+            .find(|(_, feature)| {
+                let polygons = self.feature_polygons(feature, DEFAULT_TOLERANCE);
+                let near = polygons.iter()
+                    .filter(|p| p.dark)
+                    .any(|p| {
+                        p.contains(point) || p.points.iter()
+                            .zip(p.points.iter().cycle().skip(1))
+                            .any(|(a, b)| shapes::point_segment_distance(point, *a, *b) <= radius)
+                    });
+                near && !polygons.iter().any(|p| !p.dark && p.contains(point))
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Whether the file carries aperture functions (X2 attributes)
+    pub fn has_aperture_functions(&self) -> bool {
+        self.features.iter().any(|f| f.function.is_some())
+    }
+
+    /// Whether any of the given features is a component pad
+    /// Without X2 attributes every flash is taken as a pad.
+    pub fn has_component_pad(&self, features: &[usize]) -> bool {
+        let use_functions = self.has_aperture_functions();
+        features.iter()
+            .filter_map(|&index| self.features.get(index))
+            .any(|feature| {
+                if use_functions {
+                    feature.is_component_pad()
+                } else {
+                    matches!(feature.kind, FeatureKind::Flash { .. })
+                }
+            })
+    }
+
+    /// Bounds of a single feature as (min, max)
+    pub fn feature_bounds(&self, feature: &GerberFeature) -> Option<(Point2<f64>, Point2<f64>)> {
+        let points: Vec<Point2<f64>> = self.feature_polygons(feature, 0.05).into_iter()
+            .filter(|p| p.dark)
+            .flat_map(|p| p.points)
+            .collect();
+        shapes::points_bounds(&points)
+    }
+
+    /// Bounds of all dark features as (min, max)
+    pub fn bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        let mut result: Option<(Point2<f64>, Point2<f64>)> = None;
+        for feature in self.features.iter().filter(|f| f.polarity == Polarity::Dark) {
+            if let Some((min, max)) = self.feature_bounds(feature) {
+                result = Some(match result {
+                    None => (min, max),
+                    Some((rmin, rmax)) => (
+                        Point2::new(rmin.x.min(min.x), rmin.y.min(min.y)),
+                        Point2::new(rmax.x.max(max.x), rmax.y.max(max.y)),
+                    ),
+                });
+            }
+        }
+        result
+    }
+}
+
+/// Outline of an arc drawn with a round aperture
+fn arc_band(path: &[Point2<f64>], center: Point2<f64>, width: f64, tolerance: f64) -> Vec<Point2<f64>> {
+    let half = width / 2.0;
+    if path.len() < 2 || half <= 0.0 {
+        return path.to_vec();
+    }
+    let offset = |p: Point2<f64>, d: f64| {
+        let dir = p - center;
+        let len = dir.norm();
+        if len < 1e-12 { p } else { p + dir / len * d }
+    };
+
+    // Caps turn the same way as the arc so they bulge away from the arc body
+    let (r0, r1) = (path[0] - center, path[1] - center);
+    let direction = if r0.x * r1.y - r0.y * r1.x >= 0.0 { 1.0 } else { -1.0 };
+
+    let mut points: Vec<Point2<f64>> = path.iter().map(|p| offset(*p, half)).collect();
+    // End cap
+    let end = path[path.len() - 1];
+    points.extend(cap_points(end, offset(end, half), half, direction, tolerance));
+    points.extend(path.iter().rev().map(|p| offset(*p, -half)));
+    // Start cap
+    let start = path[0];
+    points.extend(cap_points(start, offset(start, -half), half, direction, tolerance));
+    points
+}
+
+/// Half circle from `from` around `center`, excluding the end points
+fn cap_points(center: Point2<f64>, from: Point2<f64>, radius: f64, direction: f64, tolerance: f64) -> Vec<Point2<f64>> {
+    let a0 = (from.y - center.y).atan2(from.x - center.x);
+    let segments = shapes::segments_for_arc(radius, std::f64::consts::PI, tolerance);
+    (1..segments)
+        .map(|i| {
+            let a = a0 + direction * std::f64::consts::PI * i as f64 / segments as f64;
+            Point2::new(center.x + radius * a.cos(), center.y + radius * a.sin())
+        })
+        .collect()
+}
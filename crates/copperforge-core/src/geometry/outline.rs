@@ -0,0 +1,205 @@
+use nalgebra::Point2;
+
+use super::{shapes, FeatureKind, LayerGeometry, Polygon};
+
+// Board outline contours
+// Outline layers draw the board edge as strokes. The stroke centre lines are
+// chained end to end into contours; closed contours nested inside another one
+// are cutouts.
+
+/// Default distance within which stroke ends are joined (mm)
+pub const JOIN_TOLERANCE: f64 = 0.01;
+
+/// One piece of an outline contour
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutlineSegment {
+    Line { start: Point2<f64>, end: Point2<f64> },
+    Arc { start: Point2<f64>, end: Point2<f64>, center: Point2<f64>, clockwise: bool },
+}
+
+impl OutlineSegment {
+    pub fn start(&self) -> Point2<f64> {
+        match self {
+            OutlineSegment::Line { start, .. } | OutlineSegment::Arc { start, .. } => *start,
+        }
+    }
+
+    pub fn end(&self) -> Point2<f64> {
+        match self {
+            OutlineSegment::Line { end, .. } | OutlineSegment::Arc { end, .. } => *end,
+        }
+    }
+
+    /// Same segment traversed the other way
+    pub fn reversed(&self) -> Self {
+        match self {
+            OutlineSegment::Line { start, end } => OutlineSegment::Line { start: *end, end: *start },
+            OutlineSegment::Arc { start, end, center, clockwise } => OutlineSegment::Arc {
+                start: *end,
+                end: *start,
+                center: *center,
+                clockwise: !clockwise,
+            },
+        }
+    }
+
+    /// Points along the segment, including both ends
+    pub fn points(&self, tolerance: f64) -> Vec<Point2<f64>> {
+        match self {
+            OutlineSegment::Line { start, end } => vec![*start, *end],
+            OutlineSegment::Arc { start, end, center, clockwise } => {
+                shapes::arc_points(*start, *end, *center, *clockwise, tolerance)
+            }
+        }
+    }
+}
+
+/// Chained outline segments
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlineContour {
+    pub segments: Vec<OutlineSegment>,
+    pub closed: bool,
+}
+
+impl OutlineContour {
+    /// Polyline of the contour; closed contours do not repeat the first point
+    pub fn points(&self, tolerance: f64) -> Vec<Point2<f64>> {
+        let mut points: Vec<Point2<f64>> = Vec::new();
+        for segment in &self.segments {
+            let segment_points = segment.points(tolerance);
+            let skip = usize::from(!points.is_empty());
+            points.extend(segment_points.into_iter().skip(skip));
+        }
+        if self.closed && points.len() > 1 && (points[0] - points[points.len() - 1]).norm() < JOIN_TOLERANCE {
+            points.pop();
+        }
+        points
+    }
+}
+
+/// Chain the strokes and regions of an outline layer into contours
+pub fn outline_contours(geometry: &LayerGeometry, join_tolerance: f64) -> Vec<OutlineContour> {
+    let mut contours = Vec::new();
+    let mut open: Vec<OutlineSegment> = Vec::new();
+
+    for feature in &geometry.features {
+        match &feature.kind {
+            FeatureKind::Line { start, end } if (start - end).norm() > 1e-9 => {
+                open.push(OutlineSegment::Line { start: *start, end: *end });
+            }
+            FeatureKind::Arc { start, end, center, clockwise } => {
+                let segment = OutlineSegment::Arc { start: *start, end: *end, center: *center, clockwise: *clockwise };
+                // A full circle is a closed contour on its own
+                if (start - end).norm() <= 1e-9 {
+                    contours.push(OutlineContour { segments: vec![segment], closed: true });
+                } else {
+                    open.push(segment);
+                }
+            }
+            FeatureKind::Region { points } if points.len() >= 3 => {
+                let segments = points.iter().zip(points.iter().cycle().skip(1))
+                    .filter(|(a, b)| (*a - *b).norm() > 1e-9)
+                    .map(|(a, b)| OutlineSegment::Line { start: *a, end: *b })
+                    .collect();
+                contours.push(OutlineContour { segments, closed: true });
+            }
+            _ => {}
+        }
+    }
+
+    let near = |a: Point2<f64>, b: Point2<f64>| (a - b).norm() <= join_tolerance;
+    let mut used = vec![false; open.len()];
+    for first in 0..open.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut chain = vec![open[first].clone()];
+
+        // Grow the end, then the start, until nothing connects
+        loop {
+            let end = chain[chain.len() - 1].end();
+            if near(end, chain[0].start()) && chain.len() > 1 {
+                break;
+            }
+            let Some(next) = (0..open.len()).find(|&i| !used[i] && (near(open[i].start(), end) || near(open[i].end(), end))) else {
+                break;
+            };
+            used[next] = true;
+            let segment = if near(open[next].start(), end) { open[next].clone() } else { open[next].reversed() };
+            chain.push(segment);
+        }
+        let closed = chain.len() > 1 && near(chain[chain.len() - 1].end(), chain[0].start());
+        if !closed {
+            loop {
+                let start = chain[0].start();
+                let Some(previous) = (0..open.len()).find(|&i| !used[i] && (near(open[i].start(), start) || near(open[i].end(), start))) else {
+                    break;
+                };
+                used[previous] = true;
+                let segment = if near(open[previous].end(), start) { open[previous].clone() } else { open[previous].reversed() };
+                chain.insert(0, segment);
+            }
+        }
+        let closed = chain.len() > 1 && near(chain[chain.len() - 1].end(), chain[0].start());
+        contours.push(OutlineContour { segments: chain, closed });
+    }
+    contours
+}
+
+/// Filled board shape: closed contours, with cutouts as clear polygons
+/// Nesting alternates, so an island inside a cutout is board again.
+pub fn board_polygons(geometry: &LayerGeometry, tolerance: f64) -> Vec<Polygon> {
+    let contours: Vec<Vec<Point2<f64>>> = outline_contours(geometry, JOIN_TOLERANCE).iter()
+        .filter(|contour| contour.closed)
+        .map(|contour| contour.points(tolerance))
+        .filter(|points| points.len() >= 3)
+        .collect();
+
+    contours.iter().enumerate()
+        .map(|(i, points)| {
+            let depth = contours.iter().enumerate()
+                .filter(|(j, other)| *j != i && shapes::point_in_polygon(points[0], other))
+                .count();
+            Polygon::new(points.clone(), depth % 2 == 0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+
+    #[test]
+    fn test_chains_shuffled_strokes() {
+        // Square drawn as four strokes in mixed order and direction, plus a round cutout
+        let gerber = "%FSLAX26Y26*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\n\
+            X0Y0D02*\nX10000000Y0D01*\n\
+            X10000000Y10000000D02*\nX0Y10000000D01*\n\
+            X10000000Y10000000D02*\nX10000000Y0D01*\n\
+            X0Y0D02*\nX0Y10000000D01*\n\
+            G75*\nX7000000Y5000000D02*\nG03X7000000Y5000000I-2000000J0D01*\nM02*\n";
+        let geometry = parse_gerber(gerber).unwrap();
+
+        let contours = outline_contours(&geometry, JOIN_TOLERANCE);
+        assert_eq!(contours.len(), 2);
+        assert!(contours.iter().all(|contour| contour.closed));
+
+        let polygons = board_polygons(&geometry, 0.01);
+        let board = polygons.iter().find(|p| p.dark).unwrap();
+        let cutout = polygons.iter().find(|p| !p.dark).unwrap();
+        assert!((board.area() - 100.0).abs() < 1e-6);
+        assert!((cutout.area() - std::f64::consts::PI * 4.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn test_open_outline() {
+        let gerber = "%FSLAX26Y26*%\n%MOMM*%\n%ADD10C,0.1*%\nD10*\nX0Y0D02*\nX5000000Y0D01*\nX5000000Y5000000D01*\nM02*\n";
+        let geometry = parse_gerber(gerber).unwrap();
+        let contours = outline_contours(&geometry, JOIN_TOLERANCE);
+        assert_eq!(contours.len(), 1);
+        assert!(!contours[0].closed);
+        assert!(board_polygons(&geometry, 0.01).is_empty());
+    }
+}
@@ -0,0 +1,317 @@
+use nalgebra::Point2;
+
+use super::{shapes, LayerGeometry, Polarity, Polygon};
+
+// Software rasterizer for layer geometry
+// Scanline fill with a non-zero winding rule, so aperture holes and clear
+// polarity features are cut out exactly. Runs without egui or a GPU.
+
+/// Anti-aliasing quality, as the number of sub-scanlines per pixel row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Antialias {
+    /// Pixel centre sampling, hard edges
+    Off,
+    /// 4 sub-scanlines per pixel
+    #[default]
+    Low,
+    /// 16 sub-scanlines per pixel
+    High,
+}
+
+impl Antialias {
+    pub fn samples(&self) -> usize {
+        match self {
+            Antialias::Off => 1,
+            Antialias::Low => 4,
+            Antialias::High => 16,
+        }
+    }
+
+    pub fn all() -> [Antialias; 3] {
+        [Antialias::Off, Antialias::Low, Antialias::High]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Antialias::Off => "Off",
+            Antialias::Low => "4x",
+            Antialias::High => "16x",
+        }
+    }
+}
+
+/// Mapping from board coordinates (mm) to pixels, Y pointing down
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RasterView {
+    /// Pixels per mm
+    pub scale: f64,
+    /// Pixel position of the board origin
+    pub translation: (f64, f64),
+    pub width: u32,
+    pub height: u32,
+}
+
+impl RasterView {
+    /// View covering the given bounds at a resolution in dots per inch
+    pub fn from_bounds(min: Point2<f64>, max: Point2<f64>, dpi: f64, margin: f64) -> Self {
+        let scale = dpi / 25.4;
+        let width = ((max.x - min.x + 2.0 * margin) * scale).ceil().max(1.0);
+        let height = ((max.y - min.y + 2.0 * margin) * scale).ceil().max(1.0);
+        Self {
+            scale,
+            translation: ((margin - min.x) * scale, (max.y + margin) * scale),
+            width: width as u32,
+            height: height as u32,
+        }
+    }
+
+    pub fn to_pixel(&self, point: Point2<f64>) -> (f64, f64) {
+        (
+            self.translation.0 + point.x * self.scale,
+            self.translation.1 - point.y * self.scale,
+        )
+    }
+
+    /// Chord tolerance giving arcs that look smooth at this resolution (mm)
+    pub fn tolerance(&self) -> f64 {
+        (0.25 / self.scale).min(shapes::DEFAULT_TOLERANCE)
+    }
+}
+
+/// Per-pixel coverage (0.0 - 1.0) of a layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageMask {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<f32>,
+}
+
+/// Polygon edge in pixel space, y0 < y1
+struct Edge {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    winding: i32,
+}
+
+impl CoverageMask {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height, data: vec![0.0; width as usize * height as usize] }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> f32 {
+        self.data[y as usize * self.width as usize + x as usize]
+    }
+
+    /// Fraction of pixels with any coverage
+    pub fn filled_fraction(&self) -> f64 {
+        if self.data.is_empty() {
+            return 0.0;
+        }
+        self.data.iter().filter(|&&c| c > 0.0).count() as f64 / self.data.len() as f64
+    }
+
+    /// Paint the polygons of one feature: dark adds coverage, clear removes it
+    /// Clear polygons inside the set are holes of the feature itself.
+    pub fn paint(&mut self, polygons: &[Polygon], view: &RasterView, antialias: Antialias, dark: bool) {
+        let mut edges = Vec::new();
+        let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+        let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+
+        for polygon in polygons.iter().filter(|p| p.points.len() >= 3) {
+            // Dark contours counter-clockwise, holes clockwise
+            let polygon = polygon.clone().oriented();
+            let pixels: Vec<(f64, f64)> = polygon.points.iter().map(|p| view.to_pixel(*p)).collect();
+            for (i, &(x0, y0)) in pixels.iter().enumerate() {
+                let (x1, y1) = pixels[(i + 1) % pixels.len()];
+                min_x = min_x.min(x0);
+                max_x = max_x.max(x0);
+                min_y = min_y.min(y0);
+                max_y = max_y.max(y0);
+                if y0 == y1 {
+                    continue;
+                }
+                // A counter-clockwise contour runs downwards in pixel space on its left side
+                if y0 < y1 {
+                    edges.push(Edge { x0, y0, x1, y1, winding: 1 });
+                } else {
+                    edges.push(Edge { x0: x1, y0: y1, x1: x0, y1: y0, winding: -1 });
+                }
+            }
+        }
+        if edges.is_empty() {
+            return;
+        }
+
+        // Pixel box touched by the feature, clipped to the mask
+        let left = min_x.floor().max(0.0) as usize;
+        let right = (max_x.ceil().min(self.width as f64)).max(0.0) as usize;
+        let top = min_y.floor().max(0.0) as usize;
+        let bottom = (max_y.ceil().min(self.height as f64)).max(0.0) as usize;
+        if left >= right || top >= bottom {
+            return;
+        }
+
+        let samples = antialias.samples();
+        let weight = 1.0 / samples as f32;
+        let span_width = right - left;
+        let mut row = vec![0.0f32; span_width];
+        let mut crossings: Vec<(f64, i32)> = Vec::new();
+
+        for y in top..bottom {
+            row.iter_mut().for_each(|c| *c = 0.0);
+            for sample in 0..samples {
+                let sy = y as f64 + (sample as f64 + 0.5) / samples as f64;
+                crossings.clear();
+                for edge in edges.iter().filter(|e| e.y0 <= sy && sy < e.y1) {
+                    let t = (sy - edge.y0) / (edge.y1 - edge.y0);
+                    crossings.push((edge.x0 + t * (edge.x1 - edge.x0), edge.winding));
+                }
+                crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                let mut winding = 0;
+                let mut span_start = 0.0;
+                for &(x, w) in &crossings {
+                    let was_inside = winding > 0;
+                    winding += w;
+                    if !was_inside && winding > 0 {
+                        span_start = x;
+                    } else if was_inside && winding <= 0 {
+                        add_span(&mut row, span_start - left as f64, x - left as f64, weight, antialias);
+                    }
+                }
+            }
+
+            let offset = y * self.width as usize + left;
+            for (i, &coverage) in row.iter().enumerate() {
+                let coverage = coverage.min(1.0);
+                if coverage <= 0.0 {
+                    continue;
+                }
+                let pixel = &mut self.data[offset + i];
+                if dark {
+                    *pixel += coverage * (1.0 - *pixel);
+                } else {
+                    *pixel *= 1.0 - coverage;
+                }
+            }
+        }
+    }
+}
+
+/// Add a horizontal span [start, end) to a row of pixel coverage
+fn add_span(row: &mut [f32], start: f64, end: f64, weight: f32, antialias: Antialias) {
+    let width = row.len() as f64;
+    if antialias == Antialias::Off {
+        // Pixels whose centre lies inside the span
+        let first = (start - 0.5).ceil().max(0.0) as usize;
+        let last = ((end - 0.5).ceil().min(width)).max(0.0) as usize;
+        for pixel in row.iter_mut().take(last).skip(first) {
+            *pixel += weight;
+        }
+        return;
+    }
+
+    let start = start.clamp(0.0, width);
+    let end = end.clamp(0.0, width);
+    if end <= start {
+        return;
+    }
+    let first = start.floor() as usize;
+    let last = end.floor() as usize;
+    if first == last {
+        row[first] += (end - start) as f32 * weight;
+        return;
+    }
+    row[first] += (first as f64 + 1.0 - start) as f32 * weight;
+    for pixel in &mut row[first + 1..last] {
+        *pixel += weight;
+    }
+    if last < row.len() {
+        row[last] += (end - last as f64) as f32 * weight;
+    }
+}
+
+/// Rasterize a layer in feature order, honouring polarity
+/// `transform` maps board coordinates before rasterizing (mirroring, rotation, offsets).
+pub fn rasterize_layer(
+    geometry: &LayerGeometry,
+    view: &RasterView,
+    antialias: Antialias,
+    transform: impl Fn(Point2<f64>) -> Point2<f64>,
+) -> CoverageMask {
+    let mut mask = CoverageMask::new(view.width, view.height);
+    let tolerance = view.tolerance();
+
+    for feature in &geometry.features {
+        let polygons: Vec<Polygon> = geometry.feature_polygons(feature, tolerance).into_iter()
+            .map(|p| Polygon::new(p.points.iter().map(|pt| transform(*pt)).collect(), p.dark))
+            .collect();
+        mask.paint(&polygons, view, antialias, feature.polarity == Polarity::Dark);
+    }
+    mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+
+    fn square(x: f64, y: f64, size: f64, dark: bool) -> Polygon {
+        Polygon::new(vec![
+            Point2::new(x, y),
+            Point2::new(x + size, y),
+            Point2::new(x + size, y + size),
+            Point2::new(x, y + size),
+        ], dark)
+    }
+
+    #[test]
+    fn test_square_with_hole() {
+        let view = RasterView::from_bounds(Point2::new(0.0, 0.0), Point2::new(10.0, 10.0), 25.4, 0.0);
+        assert_eq!((view.width, view.height), (10, 10));
+
+        let mut mask = CoverageMask::new(view.width, view.height);
+        mask.paint(&[square(2.0, 2.0, 6.0, true), square(4.0, 4.0, 2.0, false)], &view, Antialias::Low, true);
+        assert_eq!(mask.get(2, 2), 1.0);
+        assert_eq!(mask.get(4, 4), 0.0);
+        assert_eq!(mask.get(1, 1), 0.0);
+        // Pixel row 7 is board y 2..3, inside the square
+        assert_eq!(mask.get(3, 7), 1.0);
+        let filled: f32 = mask.data.iter().sum();
+        assert!((filled - 32.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_antialiased_edge() {
+        let view = RasterView::from_bounds(Point2::new(0.0, 0.0), Point2::new(4.0, 4.0), 25.4, 0.0);
+        let mut mask = CoverageMask::new(view.width, view.height);
+        mask.paint(&[square(0.5, 0.5, 3.0, true)], &view, Antialias::High, true);
+        assert!((mask.get(0, 0) - 0.25).abs() < 1e-3);
+        assert!((mask.get(1, 0) - 0.5).abs() < 1e-3);
+        assert_eq!(mask.get(1, 1), 1.0);
+
+        let mut hard = CoverageMask::new(view.width, view.height);
+        hard.paint(&[square(0.5, 0.5, 3.0, true)], &view, Antialias::Off, true);
+        assert!(hard.data.iter().all(|&c| c == 0.0 || c == 1.0));
+    }
+
+    #[test]
+    fn test_clear_polarity() {
+        let gerber = "%FSLAX26Y26*%\n%MOMM*%\n%ADD10R,4X4*%\n%ADD11C,2*%\n\
+            D10*\nX5000000Y5000000D03*\n%LPC*%\nD11*\nX5000000Y5000000D03*\nM02*\n";
+        let geometry = parse_gerber(gerber).unwrap();
+        let view = RasterView::from_bounds(Point2::new(0.0, 0.0), Point2::new(10.0, 10.0), 254.0, 0.0);
+        let mask = rasterize_layer(&geometry, &view, Antialias::Low, |p| p);
+
+        // Centre of the flash is cleared, the corners of the rectangle stay
+        assert_eq!(mask.get(50, 50), 0.0);
+        assert_eq!(mask.get(32, 32), 1.0);
+        assert_eq!(mask.get(10, 10), 0.0);
+
+        let mirrored = rasterize_layer(&geometry, &view, Antialias::Low, |p| Point2::new(10.0 - p.x, p.y));
+        assert_eq!(mirrored.get(50, 50), 0.0);
+        assert!((mirrored.filled_fraction() - mask.filled_fraction()).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,560 @@
+use nalgebra::{Point2, Vector2};
+
+// Polygon helpers used by the geometry module
+// All coordinates are in mm
+
+/// Default chord tolerance used when approximating arcs and circles (mm)
+pub const DEFAULT_TOLERANCE: f64 = 0.005;
+
+/// A closed contour, either adding copper (dark) or removing it (clear)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    pub points: Vec<Point2<f64>>,
+    pub dark: bool,
+}
+
+impl Polygon {
+    pub fn new(points: Vec<Point2<f64>>, dark: bool) -> Self {
+        Self { points, dark }
+    }
+
+    /// Signed area (positive for counter-clockwise contours)
+    pub fn signed_area(&self) -> f64 {
+        signed_area(&self.points)
+    }
+
+    pub fn area(&self) -> f64 {
+        self.signed_area().abs()
+    }
+
+    /// Axis aligned bounds as (min, max)
+    pub fn bounds(&self) -> Option<(Point2<f64>, Point2<f64>)> {
+        points_bounds(&self.points)
+    }
+
+    pub fn translated(&self, offset: Vector2<f64>) -> Self {
+        Self {
+            points: self.points.iter().map(|p| p + offset).collect(),
+            dark: self.dark,
+        }
+    }
+
+    pub fn rotated(&self, degrees: f64) -> Self {
+        Self {
+            points: self.points.iter().map(|p| rotate_point(*p, degrees)).collect(),
+            dark: self.dark,
+        }
+    }
+
+    pub fn contains(&self, point: Point2<f64>) -> bool {
+        point_in_polygon(point, &self.points)
+    }
+
+    /// Make the contour counter-clockwise for dark and clockwise for clear polygons,
+    /// so a non-zero fill of a feature's polygons leaves clear areas empty
+    pub fn oriented(mut self) -> Self {
+        let ccw = self.signed_area() >= 0.0;
+        if ccw != self.dark {
+            self.points.reverse();
+        }
+        self
+    }
+}
+
+/// Signed area of a contour (shoelace formula)
+pub fn signed_area(points: &[Point2<f64>]) -> f64 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+    let mut sum = 0.0;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        sum += a.x * b.y - b.x * a.y;
+    }
+    sum / 2.0
+}
+
+/// Bounds of a point list as (min, max)
+pub fn points_bounds(points: &[Point2<f64>]) -> Option<(Point2<f64>, Point2<f64>)> {
+    let first = points.first()?;
+    let mut min = *first;
+    let mut max = *first;
+    for p in points.iter().skip(1) {
+        min.x = min.x.min(p.x);
+        min.y = min.y.min(p.y);
+        max.x = max.x.max(p.x);
+        max.y = max.y.max(p.y);
+    }
+    Some((min, max))
+}
+
+/// Check whether two (min, max) bounds overlap, with optional margin
+pub fn bounds_overlap(a: &(Point2<f64>, Point2<f64>), b: &(Point2<f64>, Point2<f64>), margin: f64) -> bool {
+    a.0.x <= b.1.x + margin && b.0.x <= a.1.x + margin &&
+    a.0.y <= b.1.y + margin && b.0.y <= a.1.y + margin
+}
+
+/// Rotate a point around the origin (degrees, counter-clockwise)
+pub fn rotate_point(point: Point2<f64>, degrees: f64) -> Point2<f64> {
+    if degrees == 0.0 {
+        return point;
+    }
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    Point2::new(point.x * cos - point.y * sin, point.x * sin + point.y * cos)
+}
+
+/// Number of segments needed to keep the chord error below `tolerance`
+pub fn segments_for_arc(radius: f64, sweep_radians: f64, tolerance: f64) -> usize {
+    if radius <= tolerance || radius <= 0.0 {
+        return 4;
+    }
+    let step = 2.0 * (1.0 - tolerance / radius).clamp(-1.0, 1.0).acos();
+    let step = if step > 0.0 { step } else { std::f64::consts::PI / 16.0 };
+    ((sweep_radians.abs() / step).ceil() as usize).clamp(4, 1024)
+}
+
+/// Circle approximation (counter-clockwise)
+pub fn circle_points(center: Point2<f64>, radius: f64, tolerance: f64) -> Vec<Point2<f64>> {
+    let segments = segments_for_arc(radius, std::f64::consts::TAU, tolerance).max(12);
+    (0..segments)
+        .map(|i| {
+            let angle = std::f64::consts::TAU * i as f64 / segments as f64;
+            Point2::new(center.x + radius * angle.cos(), center.y + radius * angle.sin())
+        })
+        .collect()
+}
+
+/// Sweep angle of an arc from start to end around center (radians, signed)
+/// A full circle is returned when start and end coincide
+pub fn arc_sweep(start: Point2<f64>, end: Point2<f64>, center: Point2<f64>, clockwise: bool) -> f64 {
+    let a0 = (start.y - center.y).atan2(start.x - center.x);
+    let a1 = (end.y - center.y).atan2(end.x - center.x);
+    let mut sweep = a1 - a0;
+    if clockwise {
+        while sweep >= 0.0 {
+            sweep -= std::f64::consts::TAU;
+        }
+    } else {
+        while sweep <= 0.0 {
+            sweep += std::f64::consts::TAU;
+        }
+    }
+    // Keep tiny arcs tiny instead of turning them into full circles
+    if (start - end).norm() > 1e-9 && sweep.abs() > std::f64::consts::TAU - 1e-9 {
+        sweep = 0.0;
+    }
+    sweep
+}
+
+/// Points along an arc, including start and end
+pub fn arc_points(
+    start: Point2<f64>,
+    end: Point2<f64>,
+    center: Point2<f64>,
+    clockwise: bool,
+    tolerance: f64,
+) -> Vec<Point2<f64>> {
+    let radius = (start - center).norm();
+    let sweep = arc_sweep(start, end, center, clockwise);
+    let segments = segments_for_arc(radius, sweep, tolerance);
+    let a0 = (start.y - center.y).atan2(start.x - center.x);
+
+    let mut points = Vec::with_capacity(segments + 1);
+    points.push(start);
+    for i in 1..segments {
+        let angle = a0 + sweep * i as f64 / segments as f64;
+        points.push(Point2::new(center.x + radius * angle.cos(), center.y + radius * angle.sin()));
+    }
+    points.push(end);
+    points
+}
+
+/// Convex hull (counter-clockwise, monotone chain)
+pub fn convex_hull(points: &[Point2<f64>]) -> Vec<Point2<f64>> {
+    let mut pts: Vec<Point2<f64>> = points.to_vec();
+    pts.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(std::cmp::Ordering::Equal)
+        .then(a.y.partial_cmp(&b.y).unwrap_or(std::cmp::Ordering::Equal)));
+    pts.dedup_by(|a, b| (a.x - b.x).abs() < 1e-12 && (a.y - b.y).abs() < 1e-12);
+    if pts.len() < 3 {
+        return pts;
+    }
+
+    let cross = |o: Point2<f64>, a: Point2<f64>, b: Point2<f64>| {
+        (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+    };
+
+    let mut lower: Vec<Point2<f64>> = Vec::new();
+    for p in &pts {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], *p) <= 0.0 {
+            lower.pop();
+        }
+        lower.push(*p);
+    }
+    let mut upper: Vec<Point2<f64>> = Vec::new();
+    for p in pts.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], *p) <= 0.0 {
+            upper.pop();
+        }
+        upper.push(*p);
+    }
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower
+}
+
+/// Sweep a convex shape along a straight segment
+pub fn sweep_convex(shape: &[Point2<f64>], start: Point2<f64>, end: Point2<f64>) -> Vec<Point2<f64>> {
+    let mut points = Vec::with_capacity(shape.len() * 2);
+    points.extend(shape.iter().map(|p| Point2::new(p.x + start.x, p.y + start.y)));
+    points.extend(shape.iter().map(|p| Point2::new(p.x + end.x, p.y + end.y)));
+    convex_hull(&points)
+}
+
+/// Whether a contour is convex (either orientation)
+pub fn is_convex(points: &[Point2<f64>]) -> bool {
+    let n = points.len();
+    if n < 3 {
+        return false;
+    }
+    let mut sign = 0.0;
+    for i in 0..n {
+        let (a, b, c) = (points[i], points[(i + 1) % n], points[(i + 2) % n]);
+        let cross = (b.x - a.x) * (c.y - b.y) - (b.y - a.y) * (c.x - b.x);
+        if cross.abs() < 1e-12 {
+            continue;
+        }
+        if sign * cross < 0.0 {
+            return false;
+        }
+        sign = cross;
+    }
+    true
+}
+
+/// Split a simple contour into convex pieces (counter-clockwise)
+/// Convex contours are returned whole, others are triangulated.
+pub fn convex_pieces(points: &[Point2<f64>]) -> Vec<Vec<Point2<f64>>> {
+    if points.len() < 3 {
+        return Vec::new();
+    }
+    if is_convex(points) {
+        let mut piece = points.to_vec();
+        if signed_area(&piece) < 0.0 {
+            piece.reverse();
+        }
+        return vec![piece];
+    }
+    let data: Vec<f64> = points.iter().flat_map(|p| [p.x, p.y]).collect();
+    let Ok(triangles) = earcutr::earcut(&data, &[], 2) else {
+        return Vec::new();
+    };
+    triangles.chunks_exact(3)
+        .map(|t| {
+            let mut triangle = vec![points[t[0]], points[t[1]], points[t[2]]];
+            if signed_area(&triangle) < 0.0 {
+                triangle.reverse();
+            }
+            triangle
+        })
+        .collect()
+}
+
+/// Keep the part of a convex contour left of the directed line a -> b (Sutherland-Hodgman)
+fn clip_left_of(points: &[Point2<f64>], a: Point2<f64>, b: Point2<f64>) -> Vec<Point2<f64>> {
+    let side = |p: Point2<f64>| (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    let mut clipped = Vec::with_capacity(points.len() + 1);
+    for i in 0..points.len() {
+        let (p, q) = (points[i], points[(i + 1) % points.len()]);
+        let (sp, sq) = (side(p), side(q));
+        if sp >= 0.0 {
+            clipped.push(p);
+        }
+        if (sp >= 0.0) != (sq >= 0.0) {
+            let t = sp / (sp - sq);
+            clipped.push(p + (q - p) * t);
+        }
+    }
+    clipped
+}
+
+/// Subtract a convex contour from a convex contour, both counter-clockwise
+/// The remainder is returned as convex pieces, one per edge of `clip` it lies beyond.
+pub fn subtract_convex(subject: &[Point2<f64>], clip: &[Point2<f64>]) -> Vec<Vec<Point2<f64>>> {
+    let (Some(bs), Some(bc)) = (points_bounds(subject), points_bounds(clip)) else {
+        return vec![subject.to_vec()];
+    };
+    if !bounds_overlap(&bs, &bc, 0.0) || !polygons_intersect(subject, clip) {
+        return vec![subject.to_vec()];
+    }
+    let mut pieces = Vec::new();
+    let mut inside = subject.to_vec();
+    for i in 0..clip.len() {
+        let (a, b) = (clip[i], clip[(i + 1) % clip.len()]);
+        // Right of the edge is outside the clip contour
+        let outside = clip_left_of(&inside, b, a);
+        if signed_area(&outside) > 1e-12 {
+            pieces.push(outside);
+        }
+        inside = clip_left_of(&inside, a, b);
+        if signed_area(&inside) <= 1e-12 {
+            break;
+        }
+    }
+    pieces
+}
+
+/// Area covered by a set of simple contours, counting overlaps once
+/// Each convex piece only adds what is left after removing the pieces before it.
+pub fn union_area<'a>(contours: impl IntoIterator<Item = &'a [Point2<f64>]>) -> f64 {
+    let pieces: Vec<Vec<Point2<f64>>> = contours.into_iter().flat_map(convex_pieces).collect();
+    let bounds: Vec<_> = pieces.iter().map(|piece| points_bounds(piece)).collect();
+    let mut area = 0.0;
+    for (index, piece) in pieces.iter().enumerate() {
+        let Some(piece_bounds) = bounds[index] else {
+            continue;
+        };
+        let mut remainder = vec![piece.clone()];
+        for (earlier, earlier_bounds) in pieces[..index].iter().zip(&bounds) {
+            if !earlier_bounds.is_some_and(|b| bounds_overlap(&b, &piece_bounds, 0.0)) {
+                continue;
+            }
+            remainder = remainder.into_iter()
+                .flat_map(|part| subtract_convex(&part, earlier))
+                .collect();
+            if remainder.is_empty() {
+                break;
+            }
+        }
+        area += remainder.iter().map(|part| signed_area(part)).sum::<f64>();
+    }
+    area
+}
+
+/// Even-odd point in polygon test
+pub fn point_in_polygon(point: Point2<f64>, polygon: &[Point2<f64>]) -> bool {
+    let mut inside = false;
+    let n = polygon.len();
+    if n < 3 {
+        return false;
+    }
+    let mut j = n - 1;
+    for i in 0..n {
+        let (pi, pj) = (polygon[i], polygon[j]);
+        if (pi.y > point.y) != (pj.y > point.y) {
+            let x = (pj.x - pi.x) * (point.y - pi.y) / (pj.y - pi.y) + pi.x;
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        j = i;
+    }
+    inside
+}
+
+/// Distance from a point to a segment
+pub fn point_segment_distance(point: Point2<f64>, a: Point2<f64>, b: Point2<f64>) -> f64 {
+    let ab = b - a;
+    let len_sq = ab.norm_squared();
+    if len_sq < 1e-18 {
+        return (point - a).norm();
+    }
+    let t = ((point - a).dot(&ab) / len_sq).clamp(0.0, 1.0);
+    (point - (a + ab * t)).norm()
+}
+
+/// Closest point on a segment to a point
+pub fn closest_point_on_segment(point: Point2<f64>, a: Point2<f64>, b: Point2<f64>) -> Point2<f64> {
+    let ab = b - a;
+    let len_sq = ab.norm_squared();
+    if len_sq < 1e-18 {
+        return a;
+    }
+    let t = ((point - a).dot(&ab) / len_sq).clamp(0.0, 1.0);
+    a + ab * t
+}
+
+/// Check whether two segments intersect (including touching)
+pub fn segments_intersect(a1: Point2<f64>, a2: Point2<f64>, b1: Point2<f64>, b2: Point2<f64>) -> bool {
+    let orient = |p: Point2<f64>, q: Point2<f64>, r: Point2<f64>| {
+        let v = (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
+        if v.abs() < 1e-12 { 0 } else if v > 0.0 { 1 } else { -1 }
+    };
+    let on_segment = |p: Point2<f64>, q: Point2<f64>, r: Point2<f64>| {
+        r.x >= p.x.min(q.x) - 1e-12 && r.x <= p.x.max(q.x) + 1e-12 &&
+        r.y >= p.y.min(q.y) - 1e-12 && r.y <= p.y.max(q.y) + 1e-12
+    };
+
+    let o1 = orient(a1, a2, b1);
+    let o2 = orient(a1, a2, b2);
+    let o3 = orient(b1, b2, a1);
+    let o4 = orient(b1, b2, a2);
+
+    if o1 != o2 && o3 != o4 {
+        return true;
+    }
+    (o1 == 0 && on_segment(a1, a2, b1)) ||
+    (o2 == 0 && on_segment(a1, a2, b2)) ||
+    (o3 == 0 && on_segment(b1, b2, a1)) ||
+    (o4 == 0 && on_segment(b1, b2, a2))
+}
+
+/// Minimum distance between two segments
+pub fn segment_segment_distance(a1: Point2<f64>, a2: Point2<f64>, b1: Point2<f64>, b2: Point2<f64>) -> f64 {
+    if segments_intersect(a1, a2, b1, b2) {
+        return 0.0;
+    }
+    point_segment_distance(a1, b1, b2)
+        .min(point_segment_distance(a2, b1, b2))
+        .min(point_segment_distance(b1, a1, a2))
+        .min(point_segment_distance(b2, a1, a2))
+}
+
+/// Check whether two contours overlap or touch
+pub fn polygons_intersect(a: &[Point2<f64>], b: &[Point2<f64>]) -> bool {
+    let (Some(ba), Some(bb)) = (points_bounds(a), points_bounds(b)) else {
+        return false;
+    };
+    if !bounds_overlap(&ba, &bb, 1e-9) {
+        return false;
+    }
+    if point_in_polygon(a[0], b) || point_in_polygon(b[0], a) {
+        return true;
+    }
+    // Only edges inside the other contour's bounds can cross it
+    let edges = |points: &[Point2<f64>], bounds: &(Point2<f64>, Point2<f64>)| -> Vec<(Point2<f64>, Point2<f64>)> {
+        (0..points.len())
+            .map(|i| (points[i], points[(i + 1) % points.len()]))
+            .filter(|(p, q)| {
+                let edge = (Point2::new(p.x.min(q.x), p.y.min(q.y)), Point2::new(p.x.max(q.x), p.y.max(q.y)));
+                bounds_overlap(&edge, bounds, 1e-9)
+            })
+            .collect()
+    };
+    let edges_a = edges(a, &bb);
+    let edges_b = edges(b, &ba);
+    edges_a.iter().any(|(a1, a2)| {
+        edges_b.iter().any(|(b1, b2)| segments_intersect(*a1, *a2, *b1, *b2))
+    })
+}
+
+/// Minimum distance between the edges of two contours (0 when they overlap)
+pub fn polygon_distance(a: &[Point2<f64>], b: &[Point2<f64>]) -> f64 {
+    if polygons_intersect(a, b) {
+        return 0.0;
+    }
+    let mut best = f64::MAX;
+    for i in 0..a.len() {
+        let (a1, a2) = (a[i], a[(i + 1) % a.len()]);
+        for j in 0..b.len() {
+            best = best.min(segment_segment_distance(a1, a2, b[j], b[(j + 1) % b.len()]));
+        }
+    }
+    best
+}
+
+/// Closest pair of points between the edges of two contours
+pub fn closest_points(a: &[Point2<f64>], b: &[Point2<f64>]) -> Option<(Point2<f64>, Point2<f64>, f64)> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+    let mut best: Option<(Point2<f64>, Point2<f64>, f64)> = None;
+    let mut consider = |p: Point2<f64>, q: Point2<f64>| {
+        let d = (p - q).norm();
+        if best.is_none_or(|(_, _, bd)| d < bd) {
+            best = Some((p, q, d));
+        }
+    };
+    for i in 0..a.len() {
+        let (a1, a2) = (a[i], a[(i + 1) % a.len()]);
+        for j in 0..b.len() {
+            let (b1, b2) = (b[j], b[(j + 1) % b.len()]);
+            consider(a1, closest_point_on_segment(a1, b1, b2));
+            consider(a2, closest_point_on_segment(a2, b1, b2));
+            consider(closest_point_on_segment(b1, a1, a2), b1);
+            consider(closest_point_on_segment(b2, a1, a2), b2);
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f64, y: f64, size: f64) -> Vec<Point2<f64>> {
+        vec![
+            Point2::new(x, y),
+            Point2::new(x + size, y),
+            Point2::new(x + size, y + size),
+            Point2::new(x, y + size),
+        ]
+    }
+
+    #[test]
+    fn test_area_and_orientation() {
+        let poly = Polygon::new(square(0.0, 0.0, 2.0), true);
+        assert!((poly.signed_area() - 4.0).abs() < 1e-9);
+
+        let clear = Polygon::new(square(0.0, 0.0, 2.0), false).oriented();
+        assert!(clear.signed_area() < 0.0);
+    }
+
+    #[test]
+    fn test_polygons_intersect() {
+        assert!(polygons_intersect(&square(0.0, 0.0, 1.0), &square(0.5, 0.5, 1.0)));
+        assert!(polygons_intersect(&square(0.0, 0.0, 1.0), &square(1.0, 0.0, 1.0)));  // touching
+        assert!(polygons_intersect(&square(0.0, 0.0, 4.0), &square(1.0, 1.0, 1.0)));  // contained
+        assert!(!polygons_intersect(&square(0.0, 0.0, 1.0), &square(2.0, 0.0, 1.0)));
+        assert!((polygon_distance(&square(0.0, 0.0, 1.0), &square(2.0, 0.0, 1.0)) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_arc_points() {
+        let pts = arc_points(Point2::new(1.0, 0.0), Point2::new(0.0, 1.0), Point2::origin(), false, 0.001);
+        assert!(pts.len() > 4);
+        for p in &pts {
+            assert!(((p.x * p.x + p.y * p.y).sqrt() - 1.0).abs() < 1e-9);
+        }
+        let sweep = arc_sweep(Point2::new(1.0, 0.0), Point2::new(0.0, 1.0), Point2::origin(), true);
+        assert!((sweep + 1.5 * std::f64::consts::PI).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_convex_hull_sweep() {
+        let shape = square(-0.5, -0.5, 1.0);
+        let swept = sweep_convex(&shape, Point2::new(0.0, 0.0), Point2::new(10.0, 0.0));
+        assert_eq!(swept.len(), 4);
+        assert!((signed_area(&swept) - 11.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_subtract_convex() {
+        // A square with a smaller square cut out of its middle leaves a ring of area 3
+        let pieces = subtract_convex(&square(0.0, 0.0, 2.0), &square(0.5, 0.5, 1.0));
+        let area: f64 = pieces.iter().map(|p| signed_area(p)).sum();
+        assert!((area - 3.0).abs() < 1e-9);
+        assert!(pieces.iter().all(|p| is_convex(p)));
+
+        assert!(subtract_convex(&square(0.0, 0.0, 1.0), &square(-1.0, -1.0, 3.0)).is_empty());
+        assert_eq!(subtract_convex(&square(0.0, 0.0, 1.0), &square(5.0, 0.0, 1.0)).len(), 1);
+
+        // An L shape is not convex and gets triangulated
+        let l_shape = vec![
+            Point2::new(0.0, 0.0), Point2::new(2.0, 0.0), Point2::new(2.0, 1.0),
+            Point2::new(1.0, 1.0), Point2::new(1.0, 2.0), Point2::new(0.0, 2.0),
+        ];
+        assert!(!is_convex(&l_shape));
+        let area: f64 = convex_pieces(&l_shape).iter().map(|p| signed_area(p)).sum();
+        assert!((area - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_union_area() {
+        // Two 2x2 squares sharing a 1x2 strip, and a square inside the first
+        let contours = [square(0.0, 0.0, 2.0), square(1.0, 0.0, 2.0), square(0.5, 0.5, 0.5)];
+        assert!((union_area(contours.iter().map(|c| c.as_slice())) - 6.0).abs() < 1e-9);
+        assert_eq!(union_area(std::iter::empty()), 0.0);
+    }
+}
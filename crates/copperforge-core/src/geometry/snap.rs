@@ -0,0 +1,193 @@
+use std::collections::BTreeMap;
+use nalgebra::Point2;
+
+use super::{shapes, DrillData, FeatureKind, GerberFeature, LayerGeometry, DEFAULT_TOLERANCE};
+
+// Snapping to feature geometry
+// Finds the characteristic point of a layer or drill file nearest to the cursor:
+// pad and drill centres, track ends and vertices first, then the nearest point
+// on a track centreline or a feature edge.
+
+/// What a snapped point lies on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapKind {
+    /// Flash (pad) centre or arc centre
+    Center,
+    /// Track or arc end point
+    Endpoint,
+    /// Region vertex
+    Vertex,
+    DrillCenter,
+    /// Nearest point on a track or arc centreline
+    Centerline,
+    /// Nearest point on a feature outline
+    Edge,
+}
+
+impl SnapKind {
+    /// Discrete points win over points along lines and edges
+    pub fn is_point(&self) -> bool {
+        matches!(self, SnapKind::Center | SnapKind::Endpoint | SnapKind::Vertex | SnapKind::DrillCenter)
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SnapKind::Center => "Centre",
+            SnapKind::Endpoint => "End point",
+            SnapKind::Vertex => "Vertex",
+            SnapKind::DrillCenter => "Drill centre",
+            SnapKind::Centerline => "Centreline",
+            SnapKind::Edge => "Edge",
+        }
+    }
+}
+
+/// Snapped point and the distance from the cursor to it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SnapPoint {
+    pub point: Point2<f64>,
+    pub kind: SnapKind,
+    pub distance: f64,
+}
+
+impl SnapPoint {
+    /// Whether this snap should be taken over another one
+    pub fn is_better_than(&self, other: &SnapPoint) -> bool {
+        match (self.kind.is_point(), other.kind.is_point()) {
+            (true, false) => true,
+            (false, true) => false,
+            _ => self.distance < other.distance,
+        }
+    }
+}
+
+/// Keep the better of two candidates
+pub fn best_snap(current: Option<SnapPoint>, candidate: Option<SnapPoint>) -> Option<SnapPoint> {
+    match (current, candidate) {
+        (Some(a), Some(b)) => Some(if b.is_better_than(&a) { b } else { a }),
+        (a, b) => a.or(b),
+    }
+}
+
+struct Snapper {
+    cursor: Point2<f64>,
+    radius: f64,
+    best: Option<SnapPoint>,
+}
+
+impl Snapper {
+    fn consider(&mut self, point: Point2<f64>, kind: SnapKind) {
+        let distance = (point - self.cursor).norm();
+        if distance <= self.radius {
+            self.best = best_snap(self.best, Some(SnapPoint { point, kind, distance }));
+        }
+    }
+
+    fn consider_path(&mut self, points: &[Point2<f64>], closed: bool, kind: SnapKind) {
+        let segments = if closed { points.len() } else { points.len().saturating_sub(1) };
+        for i in 0..segments {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            self.consider(shapes::closest_point_on_segment(self.cursor, a, b), kind);
+        }
+    }
+}
+
+/// Nearest snap point of a layer within `radius` of `point`
+pub fn snap_to_layer(geometry: &LayerGeometry, point: Point2<f64>, radius: f64) -> Option<SnapPoint> {
+    let mut snapper = Snapper { cursor: point, radius, best: None };
+    // Half diagonal of each aperture, the furthest copper reaches from a flash or track
+    let apertures: BTreeMap<i32, f64> = geometry.apertures.iter()
+        .map(|(d_code, aperture)| (*d_code, aperture.size().map_or(0.0, |(width, height)| width.hypot(height) / 2.0)))
+        .collect();
+    for feature in &geometry.features {
+        let reach = feature.aperture
+            .and_then(|d| apertures.get(&d))
+            .map_or(0.0, |half_diagonal| half_diagonal * feature.aperture_transform.scale);
+        if !near_feature(feature, point, reach + radius) {
+            continue;
+        }
+        match &feature.kind {
+            FeatureKind::Flash { position } => snapper.consider(*position, SnapKind::Center),
+            FeatureKind::Line { start, end } => {
+                snapper.consider(*start, SnapKind::Endpoint);
+                snapper.consider(*end, SnapKind::Endpoint);
+                snapper.consider_path(&[*start, *end], false, SnapKind::Centerline);
+            }
+            FeatureKind::Arc { start, end, center, clockwise } => {
+                snapper.consider(*start, SnapKind::Endpoint);
+                snapper.consider(*end, SnapKind::Endpoint);
+                snapper.consider(*center, SnapKind::Center);
+                let path = shapes::arc_points(*start, *end, *center, *clockwise, DEFAULT_TOLERANCE);
+                snapper.consider_path(&path, false, SnapKind::Centerline);
+            }
+            FeatureKind::Region { points } => {
+                for vertex in points {
+                    snapper.consider(*vertex, SnapKind::Vertex);
+                }
+            }
+        }
+        for polygon in geometry.feature_polygons(feature, DEFAULT_TOLERANCE) {
+            if polygon.points.len() >= 3 {
+                snapper.consider_path(&polygon.points, true, SnapKind::Edge);
+            }
+        }
+    }
+    snapper.best
+}
+
+/// Nearest drill or slot centre within `radius` of `point`
+pub fn snap_to_drills(drills: &DrillData, point: Point2<f64>, radius: f64) -> Option<SnapPoint> {
+    let mut snapper = Snapper { cursor: point, radius, best: None };
+    for hit in &drills.hits {
+        snapper.consider(hit.position, SnapKind::DrillCenter);
+    }
+    for slot in &drills.slots {
+        snapper.consider(slot.start, SnapKind::DrillCenter);
+        snapper.consider(slot.end, SnapKind::DrillCenter);
+        snapper.consider_path(&[slot.start, slot.end], false, SnapKind::Centerline);
+    }
+    snapper.best
+}
+
+/// Cheap check that a feature's copper can come within `reach` of a point, before building its outline
+fn near_feature(feature: &GerberFeature, point: Point2<f64>, reach: f64) -> bool {
+    match &feature.kind {
+        FeatureKind::Flash { position } => (position - point).norm() <= reach,
+        FeatureKind::Line { start, end } => shapes::point_segment_distance(point, *start, *end) <= reach,
+        FeatureKind::Arc { start, center, .. } => {
+            let arc_radius = (start - center).norm();
+            ((point - center).norm() - arc_radius).abs() <= reach
+        }
+        FeatureKind::Region { points } => shapes::points_bounds(points).is_some_and(|(min, max)| {
+            point.x >= min.x - reach && point.x <= max.x + reach && point.y >= min.y - reach && point.y <= max.y + reach
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::parse_gerber;
+
+    #[test]
+    fn test_snap_to_layer() {
+        // A 1 mm pad at the origin and a 0.2 mm track from (5, 0) to (10, 0)
+        let gerber = "%FSLAX46Y46*%\n%MOMM*%\n%ADD10C,1.000000*%\n%ADD11C,0.200000*%\n\
+            D10*\nX0Y0D03*\nD11*\nX5000000Y0D02*\nX10000000Y0D01*\nM02*\n";
+        let geometry = parse_gerber(gerber).unwrap();
+
+        // Pad centre wins over its edge, even when the edge is closer
+        let snap = snap_to_layer(&geometry, Point2::new(0.4, 0.0), 0.5).unwrap();
+        assert_eq!(snap.kind, SnapKind::Center);
+        assert!(snap.point.coords.norm() < 1e-9);
+
+        let snap = snap_to_layer(&geometry, Point2::new(5.1, 0.05), 0.2).unwrap();
+        assert_eq!(snap.kind, SnapKind::Endpoint);
+
+        let snap = snap_to_layer(&geometry, Point2::new(7.5, 0.02), 0.2).unwrap();
+        assert_eq!(snap.kind, SnapKind::Centerline);
+        assert!((snap.point - Point2::new(7.5, 0.0)).norm() < 1e-9);
+
+        assert!(snap_to_layer(&geometry, Point2::new(2.5, 3.0), 0.5).is_none());
+    }
+}
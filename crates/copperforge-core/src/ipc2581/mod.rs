@@ -0,0 +1,72 @@
+// IPC-2581 import and export
+// IPC-2581 (revisions B and C) describes a whole board in one XML file:
+// layers, stackup, drills, components and the BOM. `reader.rs` turns such a
+// file into an `Ipc2581Board` with Gerber-style geometry, `writer.rs` does
+// the reverse, and `systems.rs` moves boards in and out of the ECS world so
+// Gerber and IPC-2581 workflows can be bridged in both directions.
+
+pub mod reader;
+pub mod writer;
+pub mod systems;
+
+pub use reader::parse_ipc2581;
+pub use writer::{export_ipc2581, ipc_layer_name, write_ipc2581, Ipc2581Report};
+pub use systems::{collect_ipc2581_board, load_ipc2581_system, Ipc2581LoadSummary};
+
+use crate::ecs::{LayerType, StackupLayer};
+use crate::geometry::{DrillData, LayerGeometry};
+use crate::project_manager::bom::BomComponent;
+
+/// Schema revision of an IPC-2581 file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Ipc2581Revision {
+    B,
+    #[default]
+    C,
+}
+
+impl Ipc2581Revision {
+    pub fn all() -> [Ipc2581Revision; 2] {
+        [Ipc2581Revision::B, Ipc2581Revision::C]
+    }
+
+    /// Value of the root `revision` attribute
+    pub fn code(&self) -> &'static str {
+        match self {
+            Ipc2581Revision::B => "B",
+            Ipc2581Revision::C => "C",
+        }
+    }
+}
+
+/// One image layer of the board
+#[derive(Debug, Clone)]
+pub struct Ipc2581Layer {
+    /// Layer name in the IPC-2581 file
+    pub name: String,
+    pub layer_type: LayerType,
+    pub geometry: LayerGeometry,
+}
+
+/// Board content exchanged through IPC-2581, all values in mm
+#[derive(Debug, Clone, Default)]
+pub struct Ipc2581Board {
+    pub name: String,
+    pub revision: Ipc2581Revision,
+    /// Image layers, including the board profile as the mechanical outline
+    pub layers: Vec<Ipc2581Layer>,
+    /// Top to bottom, empty when the file has no stackup
+    pub stackup: Vec<StackupLayer>,
+    /// Drill files by name, plated state set
+    pub drills: Vec<(String, DrillData)>,
+    pub components: Vec<BomComponent>,
+    /// Content that was skipped or approximated
+    pub warnings: Vec<String>,
+}
+
+impl Ipc2581Board {
+    /// Number of copper layers
+    pub fn copper_count(&self) -> usize {
+        self.layers.iter().filter(|layer| layer.layer_type.is_copper()).count()
+    }
+}
@@ -0,0 +1,912 @@
+use std::collections::{BTreeMap, HashMap};
+use nalgebra::{Point2, Vector2};
+use roxmltree::{Document, Node};
+
+use crate::ecs::{LayerType, Side, StackupLayer, StackupLayerKind};
+use crate::export::gerber::standard_parameters;
+use crate::geometry::{
+    shapes, Aperture, ApertureShape, ApertureTransform, DrillData, DrillHit, FeatureKind, GerberFeature,
+    LayerGeometry, Polarity, Units, DEFAULT_TOLERANCE,
+};
+use crate::project_manager::bom::BomComponent;
+use super::{Ipc2581Board, Ipc2581Layer, Ipc2581Revision};
+
+// IPC-2581 reader
+// Layer features become Gerber-style geometry: standard primitives that have a
+// Gerber aperture (circle, rectangle, oval) are flashed, other pad shapes and
+// contours become regions, lines and arcs are stroked with a round aperture.
+// Contour cutouts are joined into their outline with zero width cuts, so no
+// clear polarity is needed. Every shape of a pad or feature is rotated, mirrored
+// and scaled by its Xform about the item Location. Holes are split into plated
+// and non-plated drills.
+
+/// Stroke width of the board profile on the outline layer (mm)
+pub const PROFILE_WIDTH: f64 = 0.1;
+
+// ============================================================================
+// XML HELPERS
+// ============================================================================
+
+fn children<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |n| n.is_element() && n.tag_name().name() == name)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &'static str) -> Option<Node<'a, 'input>> {
+    children(node, name).next()
+}
+
+fn number(node: Node, name: &str) -> Option<f64> {
+    node.attribute(name)?.trim().parse().ok()
+}
+
+fn flag(node: Node, name: &str) -> bool {
+    node.attribute(name).is_some_and(|v| v.eq_ignore_ascii_case("true") || v == "1")
+}
+
+/// Factor converting a `units` attribute to mm
+fn unit_scale(units: Option<&str>) -> f64 {
+    match units.unwrap_or("MILLIMETER").to_ascii_uppercase().as_str() {
+        "MICRON" => 0.001,
+        "INCH" => 25.4,
+        "MILS" | "MIL" => 0.0254,
+        _ => 1.0,
+    }
+}
+
+// ============================================================================
+// SHAPES
+// ============================================================================
+
+/// Dictionary shape, centred on the origin
+#[derive(Debug, Clone)]
+enum Primitive {
+    /// Shape with a Gerber aperture equivalent
+    Aperture(ApertureShape),
+    /// Filled outlines, cutouts already joined in
+    Outline(Vec<Vec<Point2<f64>>>),
+}
+
+/// One step of a polygon or polyline
+#[derive(Debug, Clone, Copy)]
+enum PolyStep {
+    Line(Point2<f64>),
+    Arc { end: Point2<f64>, center: Point2<f64>, clockwise: bool },
+}
+
+/// Path from PolyBegin / PolyStepSegment / PolyStepCurve children
+#[derive(Debug, Clone)]
+struct PolyPath {
+    start: Point2<f64>,
+    steps: Vec<PolyStep>,
+}
+
+/// Item placement: the Xform about the item origin, then moved to its Location
+#[derive(Debug, Clone, Copy, Default)]
+struct Placement {
+    offset: Vector2<f64>,
+    transform: ApertureTransform,
+}
+
+impl Placement {
+    fn apply(&self, point: Point2<f64>) -> Point2<f64> {
+        self.transform.apply(point) + self.offset
+    }
+
+    /// Arc direction after placement, a mirror reverses it
+    fn clockwise(&self, clockwise: bool) -> bool {
+        clockwise != (self.transform.mirror_x != self.transform.mirror_y)
+    }
+}
+
+impl PolyPath {
+    fn parse(node: Node, scale: f64, placement: Placement) -> Option<Self> {
+        let point = |n: Node, x: &str, y: &str| Some(placement.apply(Point2::new(number(n, x)? * scale, number(n, y)? * scale)));
+        let mut path: Option<PolyPath> = None;
+        for step in node.children().filter(|n| n.is_element()) {
+            match step.tag_name().name() {
+                "PolyBegin" => path = Some(PolyPath { start: point(step, "x", "y")?, steps: Vec::new() }),
+                "PolyStepSegment" => path.as_mut()?.steps.push(PolyStep::Line(point(step, "x", "y")?)),
+                "PolyStepCurve" => path.as_mut()?.steps.push(PolyStep::Arc {
+                    end: point(step, "x", "y")?,
+                    center: point(step, "centerX", "centerY")?,
+                    clockwise: placement.clockwise(flag(step, "clockwise")),
+                }),
+                _ => {}
+            }
+        }
+        path
+    }
+
+    /// Lines and arcs along the path
+    fn segments(&self) -> Vec<FeatureKind> {
+        let mut current = self.start;
+        self.steps.iter()
+            .map(|step| {
+                let kind = match *step {
+                    PolyStep::Line(end) => FeatureKind::Line { start: current, end },
+                    PolyStep::Arc { end, center, clockwise } => FeatureKind::Arc { start: current, end, center, clockwise },
+                };
+                current = match *step {
+                    PolyStep::Line(end) | PolyStep::Arc { end, .. } => end,
+                };
+                kind
+            })
+            .collect()
+    }
+
+    /// Flattened outline, without repeating the first point
+    fn points(&self) -> Vec<Point2<f64>> {
+        let mut points = vec![self.start];
+        for segment in self.segments() {
+            match segment {
+                FeatureKind::Line { end, .. } => points.push(end),
+                FeatureKind::Arc { start, end, center, clockwise } => {
+                    points.extend(shapes::arc_points(start, end, center, clockwise, DEFAULT_TOLERANCE).into_iter().skip(1));
+                }
+                _ => {}
+            }
+        }
+        if points.len() > 1 && (points[0] - points[points.len() - 1]).norm() < 1e-9 {
+            points.pop();
+        }
+        points
+    }
+}
+
+/// Single outline with the cutouts joined in through zero width cuts
+fn keyhole(outer: Vec<Point2<f64>>, cutouts: Vec<Vec<Point2<f64>>>) -> Vec<Point2<f64>> {
+    let mut points = outer;
+    if shapes::signed_area(&points) < 0.0 {
+        points.reverse();
+    }
+    for mut hole in cutouts.into_iter().filter(|hole| hole.len() >= 3) {
+        if shapes::signed_area(&hole) > 0.0 {
+            hole.reverse();
+        }
+        // Join at the closest pair of vertices
+        let mut best = (0, 0, f64::MAX);
+        for (i, p) in points.iter().enumerate() {
+            for (j, q) in hole.iter().enumerate() {
+                let distance = (p - q).norm();
+                if distance < best.2 {
+                    best = (i, j, distance);
+                }
+            }
+        }
+        let (i, j, _) = best;
+        let mut joined = points[..=i].to_vec();
+        joined.extend(hole[j..].iter().chain(&hole[..=j]));
+        joined.extend_from_slice(&points[i..]);
+        points = joined;
+    }
+    points
+}
+
+/// Outline of a Contour element (Polygon plus Cutouts)
+fn contour_points(node: Node, scale: f64, placement: Placement) -> Option<Vec<Point2<f64>>> {
+    let outer = PolyPath::parse(child(node, "Polygon")?, scale, placement)?.points();
+    let cutouts = children(node, "Cutout")
+        .filter_map(|cutout| PolyPath::parse(cutout, scale, placement))
+        .map(|path| path.points())
+        .collect();
+    Some(keyhole(outer, cutouts))
+}
+
+fn rounded_rect(width: f64, height: f64, radius: f64) -> Vec<Point2<f64>> {
+    let r = radius.clamp(0.0, width.min(height) / 2.0);
+    let (hx, hy) = (width / 2.0 - r, height / 2.0 - r);
+    if r <= 0.0 {
+        return vec![Point2::new(hx, -hy), Point2::new(hx, hy), Point2::new(-hx, hy), Point2::new(-hx, -hy)];
+    }
+    let mut points = Vec::new();
+    for (cx, cy, angle) in [(hx, -hy, -90.0_f64), (hx, hy, 0.0), (-hx, hy, 90.0), (-hx, -hy, 180.0)] {
+        let center = Point2::new(cx, cy);
+        let (a0, a1) = (angle.to_radians(), (angle + 90.0).to_radians());
+        let start = center + Vector2::new(a0.cos(), a0.sin()) * r;
+        let end = center + Vector2::new(a1.cos(), a1.sin()) * r;
+        points.extend(shapes::arc_points(start, end, center, false, DEFAULT_TOLERANCE));
+    }
+    points
+}
+
+fn chamfered_rect(width: f64, height: f64, chamfer: f64) -> Vec<Point2<f64>> {
+    let c = chamfer.clamp(0.0, width.min(height) / 2.0);
+    let (hx, hy) = (width / 2.0, height / 2.0);
+    vec![
+        Point2::new(-hx + c, -hy), Point2::new(hx - c, -hy), Point2::new(hx, -hy + c), Point2::new(hx, hy - c),
+        Point2::new(hx - c, hy), Point2::new(-hx + c, hy), Point2::new(-hx, hy - c), Point2::new(-hx, -hy + c),
+    ]
+}
+
+/// Standard primitive element, None when unsupported
+fn standard_primitive(node: Node, scale: f64) -> Option<Primitive> {
+    let dim = |name: &str| number(node, name).map(|v| v * scale);
+    let primitive = match node.tag_name().name() {
+        "Circle" => Primitive::Aperture(ApertureShape::Circle { diameter: dim("diameter")?, hole: None }),
+        "RectCenter" => Primitive::Aperture(ApertureShape::Rectangle { width: dim("width")?, height: dim("height")?, hole: None }),
+        "Oval" => Primitive::Aperture(ApertureShape::Obround { width: dim("width")?, height: dim("height")?, hole: None }),
+        "Donut" if node.attribute("shape").is_none_or(|s| s == "ROUND") => Primitive::Aperture(ApertureShape::Circle {
+            diameter: dim("outerDiameter")?,
+            hole: Some(dim("innerDiameter")?),
+        }),
+        "RectRound" => Primitive::Outline(vec![rounded_rect(dim("width")?, dim("height")?, dim("radius").unwrap_or(0.0))]),
+        "RectCham" => Primitive::Outline(vec![chamfered_rect(dim("width")?, dim("height")?, dim("chamfer").unwrap_or(0.0))]),
+        "Contour" => Primitive::Outline(vec![contour_points(node, scale, Placement::default())?]),
+        _ => return None,
+    };
+    Some(primitive)
+}
+
+// ============================================================================
+// LAYER BUILDING
+// ============================================================================
+
+/// Collects features and the apertures they need for one layer
+#[derive(Default)]
+struct LayerBuilder {
+    geometry: LayerGeometry,
+    codes: HashMap<String, i32>,
+}
+
+impl LayerBuilder {
+    fn aperture(&mut self, shape: ApertureShape, function: Option<&str>) -> i32 {
+        let key = format!("{:?}|{:?}", shape, function);
+        if let Some(code) = self.codes.get(&key) {
+            return *code;
+        }
+        let d_code = 10 + self.codes.len() as i32;
+        let template = match shape {
+            ApertureShape::Circle { .. } => "C",
+            ApertureShape::Rectangle { .. } => "R",
+            ApertureShape::Obround { .. } => "O",
+            ApertureShape::Polygon { .. } | ApertureShape::Macro { .. } => "P",
+        };
+        let aperture = Aperture {
+            d_code,
+            template: template.to_string(),
+            raw_parameters: standard_parameters(&shape, Units::Millimeters).unwrap_or_default(),
+            shape,
+            function: function.map(str::to_string),
+        };
+        self.geometry.apertures.insert(d_code, aperture);
+        self.codes.insert(key, d_code);
+        d_code
+    }
+
+    fn push(&mut self, kind: FeatureKind, polarity: Polarity, aperture: Option<i32>, transform: ApertureTransform, function: Option<&str>) {
+        self.geometry.features.push(GerberFeature {
+            kind,
+            polarity,
+            aperture,
+            aperture_transform: transform,
+            function: function.map(str::to_string),
+            attributes: Vec::new(),
+        });
+    }
+
+    fn stroke(&mut self, kind: FeatureKind, width: f64, polarity: Polarity) {
+        let code = self.aperture(ApertureShape::Circle { diameter: width, hole: None }, None);
+        self.push(kind, polarity, Some(code), ApertureTransform::default(), None);
+    }
+
+    fn region(&mut self, points: Vec<Point2<f64>>, polarity: Polarity, function: Option<&str>) {
+        if points.len() >= 3 {
+            self.push(FeatureKind::Region { points }, polarity, None, ApertureTransform::default(), function);
+        }
+    }
+
+    fn place(&mut self, primitive: &Primitive, position: Point2<f64>, transform: ApertureTransform, polarity: Polarity, function: Option<&str>) {
+        match primitive {
+            Primitive::Aperture(shape) => {
+                let code = self.aperture(shape.clone(), function);
+                self.push(FeatureKind::Flash { position }, polarity, Some(code), transform, function);
+            }
+            Primitive::Outline(outlines) => {
+                for outline in outlines {
+                    let points = outline.iter().map(|p| transform.apply(*p) + position.coords).collect();
+                    self.region(points, polarity, function);
+                }
+            }
+        }
+    }
+}
+
+/// Role of a CadData layer, from its layerFunction and side
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LayerRole {
+    Copper,
+    Soldermask(Side),
+    Silkscreen(Side),
+    Paste(Side),
+    Dielectric,
+    Drill,
+    Other,
+}
+
+fn layer_role(function: &str, side: &str) -> LayerRole {
+    let side = if side.eq_ignore_ascii_case("BOTTOM") { Side::Bottom } else { Side::Top };
+    let function = function.to_ascii_uppercase();
+    match function.as_str() {
+        "CONDUCTOR" | "SIGNAL" | "PLANE" | "MIXED" | "CONDFILM" | "CONDFOIL" | "POWER_GROUND" => LayerRole::Copper,
+        "SOLDERMASK" => LayerRole::Soldermask(side),
+        "SILKSCREEN" | "LEGEND" => LayerRole::Silkscreen(side),
+        "SOLDERPASTE" | "PASTEMASK" => LayerRole::Paste(side),
+        "DRILL" => LayerRole::Drill,
+        f if f.starts_with("DIEL") => LayerRole::Dielectric,
+        _ => LayerRole::Other,
+    }
+}
+
+/// A drilled hole read from a layer
+struct Hole {
+    position: Point2<f64>,
+    diameter: f64,
+    plated: bool,
+}
+
+/// BOM data for one reference designator
+#[derive(Default, Clone)]
+struct BomEntry {
+    value: String,
+    description: String,
+    populate: bool,
+    fields: BTreeMap<String, String>,
+}
+
+/// Parse state shared by all layers: dictionaries and units
+struct Reader {
+    /// CadHeader units to mm
+    scale: f64,
+    primitives: HashMap<String, Primitive>,
+    line_widths: HashMap<String, f64>,
+    warnings: Vec<String>,
+}
+
+impl Reader {
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+
+    fn read_dictionaries(&mut self, content: Node) {
+        for dictionary in children(content, "DictionaryStandard") {
+            let scale = unit_scale(dictionary.attribute("units"));
+            for entry in children(dictionary, "EntryStandard") {
+                let Some(id) = entry.attribute("id") else { continue };
+                match entry.children().find(|n| n.is_element()).and_then(|shape| standard_primitive(shape, scale)) {
+                    Some(primitive) => {
+                        self.primitives.insert(id.to_string(), primitive);
+                    }
+                    None => self.warn(format!("Unsupported standard primitive '{}'", id)),
+                }
+            }
+        }
+
+        for dictionary in children(content, "DictionaryUser") {
+            let scale = unit_scale(dictionary.attribute("units"));
+            for entry in children(dictionary, "EntryUser") {
+                let Some(id) = entry.attribute("id") else { continue };
+                let outlines: Vec<Vec<Point2<f64>>> = entry.descendants()
+                    .filter(|n| n.is_element() && n.tag_name().name() == "Contour")
+                    .filter_map(|contour| contour_points(contour, scale, Placement::default()))
+                    .collect();
+                if outlines.is_empty() {
+                    self.warn(format!("User primitive '{}' has no contour and was skipped", id));
+                } else {
+                    self.primitives.insert(id.to_string(), Primitive::Outline(outlines));
+                }
+            }
+        }
+
+        for dictionary in children(content, "DictionaryLineDesc") {
+            let scale = unit_scale(dictionary.attribute("units"));
+            for entry in children(dictionary, "EntryLineDesc") {
+                if let (Some(id), Some(width)) = (entry.attribute("id"), child(entry, "LineDesc").and_then(|d| number(d, "lineWidth"))) {
+                    self.line_widths.insert(id.to_string(), width * scale);
+                }
+            }
+        }
+    }
+
+    /// Stroke width from a LineDescRef or inline LineDesc
+    fn line_width(&self, node: Node) -> f64 {
+        if let Some(width) = child(node, "LineDescRef")
+            .and_then(|r| r.attribute("id"))
+            .and_then(|id| self.line_widths.get(id))
+        {
+            return *width;
+        }
+        child(node, "LineDesc").and_then(|d| number(d, "lineWidth")).map_or(0.0, |w| w * self.scale)
+    }
+
+    fn point(&self, node: Node, x: &str, y: &str) -> Option<Point2<f64>> {
+        Some(Point2::new(number(node, x)? * self.scale, number(node, y)? * self.scale))
+    }
+
+    /// One shape element of a Pad or Features item
+    fn read_shape(&mut self, builder: &mut LayerBuilder, node: Node, placement: Placement, polarity: Polarity, function: Option<&str>) {
+        let position = Point2::from(placement.offset);
+        let transform = placement.transform;
+        let width = self.line_width(node) * transform.scale;
+        match node.tag_name().name() {
+            "Xform" | "Location" | "PinRef" | "NonstandardAttribute" => {}
+            "StandardPrimitiveRef" | "UserPrimitiveRef" => {
+                let id = node.attribute("id").unwrap_or("");
+                match self.primitives.get(id).cloned() {
+                    Some(primitive) => builder.place(&primitive, position, transform, polarity, function),
+                    None => self.warn(format!("Reference to unknown primitive '{}'", id)),
+                }
+            }
+            "Line" => {
+                if let (Some(start), Some(end)) = (self.point(node, "startX", "startY"), self.point(node, "endX", "endY")) {
+                    let kind = FeatureKind::Line { start: placement.apply(start), end: placement.apply(end) };
+                    builder.stroke(kind, width, polarity);
+                }
+            }
+            "Arc" => {
+                if let (Some(start), Some(end), Some(center)) = (
+                    self.point(node, "startX", "startY"),
+                    self.point(node, "endX", "endY"),
+                    self.point(node, "centerX", "centerY"),
+                ) {
+                    let kind = FeatureKind::Arc {
+                        start: placement.apply(start),
+                        end: placement.apply(end),
+                        center: placement.apply(center),
+                        clockwise: placement.clockwise(flag(node, "clockwise")),
+                    };
+                    builder.stroke(kind, width, polarity);
+                }
+            }
+            "Polyline" => {
+                if let Some(path) = PolyPath::parse(node, self.scale, placement) {
+                    for segment in path.segments() {
+                        builder.stroke(segment, width, polarity);
+                    }
+                }
+            }
+            "Contour" => {
+                if let Some(points) = contour_points(node, self.scale, placement) {
+                    builder.region(points, polarity, function);
+                }
+            }
+            "UserSpecial" => {
+                for shape in node.children().filter(|n| n.is_element()) {
+                    self.read_shape(builder, shape, placement, polarity, function);
+                }
+            }
+            name => match standard_primitive(node, self.scale) {
+                Some(primitive) => builder.place(&primitive, position, transform, polarity, function),
+                None => self.warn(format!("Unsupported feature '{}'", name)),
+            },
+        }
+    }
+
+    /// A Pad or Features element: placement plus shapes
+    fn read_item(&mut self, builder: &mut LayerBuilder, item: Node, polarity: Polarity, function: Option<&str>) {
+        let offset = child(item, "Location")
+            .and_then(|location| self.point(location, "x", "y"))
+            .map_or(Vector2::zeros(), |p| p.coords);
+        let transform = child(item, "Xform").map_or(ApertureTransform::default(), |xform| ApertureTransform {
+            mirror_x: flag(xform, "mirror"),
+            mirror_y: false,
+            rotation: number(xform, "rotation").unwrap_or(0.0),
+            scale: number(xform, "scale").unwrap_or(1.0),
+        });
+        let placement = Placement { offset, transform };
+        for shape in item.children().filter(|n| n.is_element()) {
+            self.read_shape(builder, shape, placement, polarity, function);
+        }
+    }
+
+    fn read_layer_feature(&mut self, layer_feature: Node, builder: &mut LayerBuilder, holes: &mut Vec<Hole>) {
+        for set in children(layer_feature, "Set") {
+            let polarity = if set.attribute("polarity") == Some("NEGATIVE") { Polarity::Clear } else { Polarity::Dark };
+            let via = set.attribute("padUsage") == Some("VIA") || set.attribute("geometry").is_some_and(|g| g.contains("VIA"));
+            for item in set.children().filter(|n| n.is_element()) {
+                match item.tag_name().name() {
+                    "Pad" => {
+                        let function = if via { "ViaPad" } else { "ComponentPad" };
+                        self.read_item(builder, item, polarity, Some(function));
+                    }
+                    "Features" => self.read_item(builder, item, polarity, None),
+                    "Hole" => {
+                        if let (Some(position), Some(diameter)) = (self.point(item, "x", "y"), number(item, "diameter")) {
+                            let plated = item.attribute("platingStatus").is_none_or(|status| status != "NONPLATED");
+                            holes.push(Hole { position, diameter: diameter * self.scale, plated });
+                        }
+                    }
+                    "SlotCavity" => self.warn("Slot cavities are not supported and were skipped".to_string()),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Board profile as outline strokes
+    fn read_profile(&self, profile: Node) -> LayerGeometry {
+        let mut builder = LayerBuilder::default();
+        let paths = children(profile, "Polygon")
+            .chain(children(profile, "Cutout"))
+            .filter_map(|polygon| PolyPath::parse(polygon, self.scale, Placement::default()));
+        for path in paths {
+            for segment in path.segments() {
+                builder.stroke(segment, PROFILE_WIDTH, Polarity::Dark);
+            }
+        }
+        builder.geometry
+    }
+}
+
+/// Material and dielectric constant of each Spec, by name
+fn read_specs(cad_header: Option<Node>) -> HashMap<String, (Option<String>, Option<f64>)> {
+    let mut specs = HashMap::new();
+    let Some(header) = cad_header else {
+        return specs;
+    };
+    for spec in children(header, "Spec") {
+        let Some(name) = spec.attribute("name") else { continue };
+        let property = |node: Node, attribute: &str| -> Option<String> {
+            node.attribute(attribute)
+                .or_else(|| child(node, "Property").and_then(|p| p.attribute(attribute)))
+                .map(str::to_string)
+        };
+        let material = children(spec, "General")
+            .find(|g| g.attribute("type") == Some("MATERIAL"))
+            .and_then(|g| property(g, "text"));
+        let dielectric_constant = children(spec, "Dielectric")
+            .find(|d| d.attribute("type") == Some("DIELECTRIC_CONSTANT"))
+            .and_then(|d| property(d, "value"))
+            .and_then(|v| v.parse().ok());
+        specs.insert(name.to_string(), (material, dielectric_constant));
+    }
+    specs
+}
+
+/// BOM entries by reference designator
+fn read_bom(root: Node) -> HashMap<String, BomEntry> {
+    let mut entries = HashMap::new();
+    for item in children(root, "Bom").flat_map(|bom| children(bom, "BomItem")) {
+        let mut entry = BomEntry {
+            description: item.attribute("description").unwrap_or("").to_string(),
+            ..BomEntry::default()
+        };
+        if let Some(part) = item.attribute("OEMDesignNumberRef").filter(|p| !p.is_empty()) {
+            entry.fields.insert("Part Number".to_string(), part.to_string());
+        }
+        for textual in item.descendants().filter(|n| n.is_element() && n.tag_name().name() == "Textual") {
+            let name = textual.attribute("textualCharacteristicName").unwrap_or("");
+            let value = textual.attribute("textualCharacteristicValue").unwrap_or("").to_string();
+            match name.to_ascii_uppercase().as_str() {
+                "" => {}
+                "VALUE" => entry.value = value,
+                "DESCRIPTION" => entry.description = value,
+                _ => {
+                    entry.fields.insert(name.to_string(), value);
+                }
+            }
+        }
+        for refdes in children(item, "RefDes") {
+            let Some(name) = refdes.attribute("name") else { continue };
+            let populate = refdes.attribute("populate").is_none_or(|p| !p.eq_ignore_ascii_case("false"));
+            entries.insert(name.to_string(), BomEntry { populate, ..entry.clone() });
+        }
+    }
+    entries
+}
+
+/// Drill data for the holes of one plating state, one tool per diameter
+fn drill_data(holes: &[Hole], plated: bool) -> DrillData {
+    let mut data = DrillData { plated: Some(plated), units: Units::Millimeters, ..DrillData::default() };
+    for hole in holes.iter().filter(|hole| hole.plated == plated) {
+        let tool = match data.tools.iter().find(|(_, diameter)| (**diameter - hole.diameter).abs() < 1e-6) {
+            Some((tool, _)) => *tool,
+            None => {
+                let tool = data.tools.len() as u32 + 1;
+                data.tools.insert(tool, hole.diameter);
+                tool
+            }
+        };
+        data.hits.push(DrillHit { tool, position: hole.position, diameter: hole.diameter });
+    }
+    data
+}
+
+/// Parse an IPC-2581 revision B or C file
+pub fn parse_ipc2581(content: &str) -> Result<Ipc2581Board, String> {
+    let document = Document::parse(content).map_err(|e| format!("Invalid XML: {}", e))?;
+    let root = document.root_element();
+    if root.tag_name().name() != "IPC-2581" {
+        return Err("Not an IPC-2581 file".to_string());
+    }
+
+    let mut board = Ipc2581Board {
+        revision: if root.attribute("revision").is_some_and(|r| r.starts_with('B')) { Ipc2581Revision::B } else { Ipc2581Revision::C },
+        ..Ipc2581Board::default()
+    };
+
+    let ecad = child(root, "Ecad").ok_or("IPC-2581 file has no Ecad section")?;
+    let cad_header = child(ecad, "CadHeader");
+    let cad_data = child(ecad, "CadData").ok_or("IPC-2581 file has no CadData section")?;
+    board.name = ecad.attribute("name").unwrap_or("").to_string();
+
+    let mut reader = Reader {
+        scale: unit_scale(cad_header.and_then(|h| h.attribute("units"))),
+        primitives: HashMap::new(),
+        line_widths: HashMap::new(),
+        warnings: Vec::new(),
+    };
+    if let Some(content) = child(root, "Content") {
+        reader.read_dictionaries(content);
+    }
+
+    // Layer table, then the stack order (stackup sequence, else document order)
+    let roles: Vec<(String, LayerRole)> = children(cad_data, "Layer")
+        .filter_map(|layer| Some((
+            layer.attribute("name")?.to_string(),
+            layer_role(layer.attribute("layerFunction").unwrap_or(""), layer.attribute("side").unwrap_or("")),
+        )))
+        .collect();
+    let role_of = |name: &str| roles.iter().find(|(n, _)| n == name).map(|(_, role)| *role);
+
+    let mut stack: Vec<(i64, String, f64, Option<String>)> = child(cad_data, "Stackup")
+        .map(|stackup| stackup.descendants()
+            .filter(|n| n.is_element() && n.tag_name().name() == "StackupLayer")
+            .enumerate()
+            .filter_map(|(index, layer)| Some((
+                number(layer, "sequence").map_or(index as i64, |s| s as i64),
+                layer.attribute("layerOrGroupRef")?.to_string(),
+                number(layer, "thickness").unwrap_or(0.0) * reader.scale,
+                child(layer, "SpecRef").and_then(|s| s.attribute("id")).map(str::to_string),
+            )))
+            .collect())
+        .unwrap_or_default();
+    stack.sort_by_key(|(sequence, ..)| *sequence);
+
+    let mut copper_order: Vec<String> = stack.iter()
+        .filter(|(_, name, ..)| role_of(name) == Some(LayerRole::Copper))
+        .map(|(_, name, ..)| name.clone())
+        .collect();
+    if copper_order.is_empty() {
+        copper_order = roles.iter().filter(|(_, role)| *role == LayerRole::Copper).map(|(name, _)| name.clone()).collect();
+    }
+    let layer_type_of = |name: &str| -> Option<LayerType> {
+        match role_of(name)? {
+            LayerRole::Copper => copper_order.iter().position(|n| n == name).map(|i| LayerType::Copper(i as u8 + 1)),
+            LayerRole::Soldermask(side) => Some(LayerType::Soldermask(side)),
+            LayerRole::Silkscreen(side) => Some(LayerType::Silkscreen(side)),
+            LayerRole::Paste(side) => Some(LayerType::Paste(side)),
+            _ => None,
+        }
+    };
+
+    let specs = read_specs(cad_header);
+    for (_, name, thickness, spec) in &stack {
+        let kind = match role_of(name) {
+            Some(LayerRole::Copper) => StackupLayerKind::Copper,
+            Some(LayerRole::Dielectric) => StackupLayerKind::Dielectric,
+            Some(LayerRole::Soldermask(_)) => StackupLayerKind::Soldermask,
+            Some(LayerRole::Silkscreen(_)) => StackupLayerKind::Silkscreen,
+            Some(LayerRole::Paste(_)) => StackupLayerKind::Paste,
+            _ => continue,
+        };
+        let (material, dielectric_constant) = spec.as_ref().and_then(|id| specs.get(id)).cloned().unwrap_or_default();
+        board.stackup.push(StackupLayer {
+            name: name.clone(),
+            kind,
+            layer_type: layer_type_of(name),
+            thickness: *thickness,
+            material,
+            dielectric_constant,
+        });
+    }
+
+    let step = child(cad_data, "Step").ok_or("IPC-2581 file has no Step")?;
+    if board.name.is_empty() {
+        board.name = step.attribute("name").unwrap_or("").to_string();
+    }
+
+    if let Some(profile) = child(step, "Profile") {
+        board.layers.push(Ipc2581Layer {
+            name: "PROFILE".to_string(),
+            layer_type: LayerType::MechanicalOutline,
+            geometry: reader.read_profile(profile),
+        });
+    }
+
+    let mut holes = Vec::new();
+    for layer_feature in children(step, "LayerFeature") {
+        let name = layer_feature.attribute("layerRef").unwrap_or("");
+        let mut builder = LayerBuilder::default();
+        reader.read_layer_feature(layer_feature, &mut builder, &mut holes);
+        match layer_type_of(name) {
+            Some(layer_type) if board.layers.iter().any(|layer| layer.layer_type == layer_type) => {
+                reader.warn(format!("Layer {} maps to {} a second time and was skipped", name, layer_type.display_name()));
+            }
+            Some(layer_type) => board.layers.push(Ipc2581Layer { name: name.to_string(), layer_type, geometry: builder.geometry }),
+            None if !builder.geometry.features.is_empty() => {
+                reader.warn(format!("Layer {} has no viewer equivalent and was skipped", name));
+            }
+            None => {}
+        }
+    }
+    board.layers.sort_by_key(|layer| crate::ecs::layer_type_to_z_order(&layer.layer_type));
+
+    for plated in [true, false] {
+        let data = drill_data(&holes, plated);
+        if !data.hits.is_empty() {
+            board.drills.push((if plated { "PTH" } else { "NPTH" }.to_string(), data));
+        }
+    }
+
+    // Components, with value and fields from the BOM
+    let layer_sides: HashMap<String, String> = children(cad_data, "Layer")
+        .filter_map(|layer| Some((layer.attribute("name")?.to_string(), layer.attribute("side").unwrap_or("").to_string())))
+        .collect();
+    let mut bom = read_bom(root);
+    for component in children(step, "Component") {
+        let Some(reference) = component.attribute("refDes") else { continue };
+        let layer_ref = component.attribute("layerRef").unwrap_or("");
+        let bottom = layer_sides.get(layer_ref).is_some_and(|side| side.eq_ignore_ascii_case("BOTTOM"))
+            || layer_ref.to_ascii_uppercase().starts_with("BOT")
+            || layer_ref.starts_with("B.");
+        let position = child(component, "Location").and_then(|l| reader.point(l, "x", "y")).unwrap_or(Point2::origin());
+        let entry = bom.remove(reference).unwrap_or(BomEntry { populate: true, ..BomEntry::default() });
+        board.components.push(BomComponent {
+            item_number: (board.components.len() + 1).to_string(),
+            reference: reference.to_string(),
+            description: entry.description,
+            // BOM locations are Y-down like KiCad, IPC-2581 is Y-up
+            x_location: position.x,
+            y_location: -position.y,
+            orientation: child(component, "Xform").and_then(|x| number(x, "rotation")).unwrap_or(0.0),
+            value: if entry.value.is_empty() { component.attribute("part").unwrap_or("").to_string() } else { entry.value },
+            footprint: component.attribute("packageRef").unwrap_or("").to_string(),
+            layer: if bottom { "B.Cu" } else { "F.Cu" }.to_string(),
+            exclude_from_bom: false,
+            do_not_populate: !entry.populate,
+            fields: entry.fields,
+        });
+    }
+    if !bom.is_empty() {
+        reader.warn(format!("{} BOM reference(s) have no placed component", bom.len()));
+    }
+
+    board.warnings = reader.warnings;
+    Ok(board)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<IPC-2581 revision="B" xmlns="http://webstds.ipc.org/2581">
+  <Content roleRef="Owner">
+    <DictionaryStandard units="MILLIMETER">
+      <EntryStandard id="RECT_1"><RectCenter width="1.0" height="0.5"/></EntryStandard>
+      <EntryStandard id="RR_1"><RectRound width="2.0" height="1.0" radius="0.25"/></EntryStandard>
+      <EntryStandard id="CIRCLE_1"><Circle diameter="0.6"/></EntryStandard>
+    </DictionaryStandard>
+    <DictionaryLineDesc units="MILLIMETER">
+      <EntryLineDesc id="LINE_1"><LineDesc lineEnd="ROUND" lineWidth="0.2"/></EntryLineDesc>
+    </DictionaryLineDesc>
+  </Content>
+  <Bom name="demo">
+    <BomItem OEMDesignNumberRef="RC0603-10K" quantity="1" category="ELECTRICAL">
+      <RefDes name="R1" packageRef="R_0603" populate="true" layerRef="TOP"/>
+      <Characteristics category="ELECTRICAL">
+        <Textual textualCharacteristicName="VALUE" textualCharacteristicValue="10k"/>
+      </Characteristics>
+    </BomItem>
+  </Bom>
+  <Ecad name="demo">
+    <CadHeader units="MILLIMETER">
+      <Spec name="CORE"><General type="MATERIAL"><Property text="FR4"/></General><Dielectric type="DIELECTRIC_CONSTANT"><Property value="4.5"/></Dielectric></Spec>
+    </CadHeader>
+    <CadData>
+      <Layer name="F.Cu" layerFunction="SIGNAL" side="TOP" polarity="POSITIVE"/>
+      <Layer name="CORE_1" layerFunction="DIELCORE" side="INTERNAL" polarity="POSITIVE"/>
+      <Layer name="B.Cu" layerFunction="SIGNAL" side="BOTTOM" polarity="POSITIVE"/>
+      <Layer name="F.Mask" layerFunction="SOLDERMASK" side="TOP" polarity="POSITIVE"/>
+      <Layer name="DRILL" layerFunction="DRILL" side="ALL" polarity="POSITIVE"/>
+      <Stackup name="stack" overallThickness="1.6">
+        <StackupGroup name="group">
+          <StackupLayer layerOrGroupRef="F.Mask" thickness="0.01" sequence="0"/>
+          <StackupLayer layerOrGroupRef="F.Cu" thickness="0.035" sequence="1"/>
+          <StackupLayer layerOrGroupRef="CORE_1" thickness="1.51" sequence="2"><SpecRef id="CORE"/></StackupLayer>
+          <StackupLayer layerOrGroupRef="B.Cu" thickness="0.035" sequence="3"/>
+        </StackupGroup>
+      </Stackup>
+      <Step name="demo">
+        <Profile>
+          <Polygon>
+            <PolyBegin x="0" y="0"/><PolyStepSegment x="20" y="0"/><PolyStepSegment x="20" y="10"/>
+            <PolyStepCurve x="0" y="10" centerX="10" centerY="10" clockwise="false"/><PolyStepSegment x="0" y="0"/>
+          </Polygon>
+        </Profile>
+        <Component refDes="R1" packageRef="R_0603" layerRef="B.Cu" part="RES">
+          <Xform rotation="90"/><Location x="5" y="5"/>
+        </Component>
+        <LayerFeature layerRef="F.Cu">
+          <Set net="GND">
+            <Pad><Xform rotation="90"/><Location x="5" y="5"/><StandardPrimitiveRef id="RECT_1"/></Pad>
+            <Pad><Location x="8" y="5"/><StandardPrimitiveRef id="RR_1"/></Pad>
+            <Features><Line startX="5" startY="5" endX="8" endY="5"><LineDescRef id="LINE_1"/></Line></Features>
+            <Features><Contour>
+              <Polygon><PolyBegin x="10" y="0"/><PolyStepSegment x="14" y="0"/><PolyStepSegment x="14" y="4"/><PolyStepSegment x="10" y="4"/><PolyStepSegment x="10" y="0"/></Polygon>
+              <Cutout><PolyBegin x="11" y="1"/><PolyStepSegment x="11" y="3"/><PolyStepSegment x="13" y="3"/><PolyStepSegment x="13" y="1"/><PolyStepSegment x="11" y="1"/></Cutout>
+            </Contour></Features>
+          </Set>
+          <Set padUsage="VIA"><Pad><Location x="2" y="2"/><StandardPrimitiveRef id="CIRCLE_1"/></Pad></Set>
+        </LayerFeature>
+        <LayerFeature layerRef="DRILL">
+          <Set><Hole name="H1" diameter="0.3" platingStatus="VIA" x="2" y="2"/></Set>
+          <Set><Hole name="H2" diameter="3.2" platingStatus="NONPLATED" x="17" y="7"/></Set>
+        </LayerFeature>
+      </Step>
+    </CadData>
+  </Ecad>
+</IPC-2581>"#;
+
+    #[test]
+    fn test_parse_layers_and_stackup() {
+        let board = parse_ipc2581(SAMPLE).unwrap();
+        assert_eq!(board.revision, Ipc2581Revision::B);
+        assert_eq!(board.copper_count(), 1);
+
+        let outline = board.layers.iter().find(|l| l.layer_type == LayerType::MechanicalOutline).unwrap();
+        assert_eq!(outline.geometry.features.len(), 4);
+        assert!(matches!(outline.geometry.features[2].kind, FeatureKind::Arc { clockwise: false, .. }));
+
+        let copper = board.layers.iter().find(|l| l.layer_type == LayerType::Copper(1)).unwrap();
+        let kinds: Vec<&FeatureKind> = copper.geometry.features.iter().map(|f| &f.kind).collect();
+        assert!(matches!(kinds[0], FeatureKind::Flash { .. }));
+        assert!(matches!(kinds[1], FeatureKind::Region { .. }));
+        assert!(matches!(kinds[2], FeatureKind::Line { .. }));
+        let flash = &copper.geometry.features[0];
+        assert_eq!(flash.aperture_transform.rotation, 90.0);
+        assert!(copper.geometry.apertures[&flash.aperture.unwrap()].is_component_pad());
+        assert_eq!(copper.geometry.features.last().unwrap().function.as_deref(), Some("ViaPad"));
+
+        // The cutout is joined into the outline: area 16 - 4
+        let FeatureKind::Region { points } = kinds[3] else { panic!("contour is not a region") };
+        assert!((shapes::signed_area(points).abs() - 12.0).abs() < 1e-9);
+
+        let names: Vec<&str> = board.stackup.iter().map(|l| l.name.as_str()).collect();
+        assert_eq!(names, ["F.Mask", "F.Cu", "CORE_1", "B.Cu"]);
+        assert_eq!(board.stackup[2].material.as_deref(), Some("FR4"));
+        assert_eq!(board.stackup[2].dielectric_constant, Some(4.5));
+        assert_eq!(board.stackup[3].layer_type, Some(LayerType::Copper(2)));
+    }
+
+    #[test]
+    fn test_contour_pad_follows_xform() {
+        // A 2x1 contour rotated a quarter turn about its Location at (30, 0)
+        let pad = r#"<Set><Pad><Xform rotation="90"/><Location x="30" y="0"/><Contour>
+              <Polygon><PolyBegin x="0" y="0"/><PolyStepSegment x="2" y="0"/><PolyStepSegment x="2" y="1"/><PolyStepSegment x="0" y="1"/><PolyStepSegment x="0" y="0"/></Polygon>
+            </Contour></Pad></Set>
+          <Set padUsage="VIA">"#;
+        let board = parse_ipc2581(&SAMPLE.replace(r#"<Set padUsage="VIA">"#, pad)).unwrap();
+        let copper = board.layers.iter().find(|l| l.layer_type == LayerType::Copper(1)).unwrap();
+        let FeatureKind::Region { points } = &copper.geometry.features[4].kind else { panic!("contour pad is not a region") };
+        let (min, max) = shapes::points_bounds(points).unwrap();
+        assert!((min - Point2::new(29.0, 0.0)).norm() < 1e-9);
+        assert!((max - Point2::new(30.0, 2.0)).norm() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_drills_and_components() {
+        let board = parse_ipc2581(SAMPLE).unwrap();
+        assert_eq!(board.drills.len(), 2);
+        assert_eq!(board.drills[0].0, "PTH");
+        assert_eq!(board.drills[1].1.hits[0].diameter, 3.2);
+
+        assert_eq!(board.components.len(), 1);
+        let r1 = &board.components[0];
+        assert_eq!((r1.value.as_str(), r1.footprint.as_str(), r1.layer.as_str()), ("10k", "R_0603", "B.Cu"));
+        assert_eq!((r1.x_location, r1.y_location, r1.orientation), (5.0, -5.0, 90.0));
+        assert_eq!(r1.fields.get("Part Number").map(String::as_str), Some("RC0603-10K"));
+    }
+}
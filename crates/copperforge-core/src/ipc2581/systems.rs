@@ -0,0 +1,138 @@
+use std::io::BufReader;
+use bevy_ecs::prelude::*;
+use gerber_viewer::gerber_parser::parse;
+use gerber_viewer::GerberLayer;
+
+use crate::ecs::{BoardComponent, BoardStackup, DrillFileInfo, DrillHoles, LayerGeometryData, LayerInfo};
+use crate::export::gerber::write_gerber;
+use crate::geometry::LayerTransform;
+use crate::project_manager::bom::BomComponent;
+use super::writer::ipc_layer_name;
+use super::{Ipc2581Board, Ipc2581Layer};
+
+// IPC-2581 systems
+// The viewer draws layers from parsed Gerber commands, so imported layers are
+// written out as RS-274X and loaded like any Gerber file. Components become
+// BoardComponent entities and the stackup goes into the BoardStackup resource.
+
+/// What an IPC-2581 import added to the world
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ipc2581LoadSummary {
+    pub layers: usize,
+    pub drills: usize,
+    pub components: usize,
+    pub stackup_layers: usize,
+    pub warnings: Vec<String>,
+}
+
+/// System to load a parsed IPC-2581 board, replacing everything loaded before
+pub fn load_ipc2581_system(world: &mut World, board: Ipc2581Board) -> Result<Ipc2581LoadSummary, String> {
+    crate::ecs::clear_all_layers_system(world);
+
+    let mut summary = Ipc2581LoadSummary {
+        stackup_layers: board.stackup.len(),
+        warnings: board.warnings,
+        ..Ipc2581LoadSummary::default()
+    };
+
+    for layer in board.layers {
+        if layer.geometry.features.is_empty() {
+            continue;
+        }
+        let (content, _) = write_gerber(&[(&layer.geometry, LayerTransform::default())])?;
+        let doc = parse(BufReader::new(content.as_bytes()))
+            .map_err(|_| format!("Could not build the viewer image of layer {}", layer.name))?;
+        let gerber_layer = GerberLayer::new(doc.into_commands());
+
+        crate::ecs::create_gerber_layer_entity(world, layer.layer_type, gerber_layer, Some(content), None, true);
+        crate::ecs::add_layer_assignment(world, layer.name, layer.layer_type);
+        summary.layers += 1;
+    }
+
+    for (name, drill_data) in board.drills {
+        crate::ecs::create_drill_entity(world, name, drill_data, None);
+        summary.drills += 1;
+    }
+
+    summary.components = board.components.len();
+    for component in board.components {
+        world.spawn(BoardComponent(component));
+    }
+
+    world.insert_resource(BoardStackup(board.stackup));
+    Ok(summary)
+}
+
+/// Board content of the world for export, in file coordinates
+pub fn collect_ipc2581_board(world: &mut World, name: &str, components: Vec<BomComponent>) -> Ipc2581Board {
+    let mut layers: Vec<Ipc2581Layer> = {
+        let mut query = world.query::<(&LayerInfo, &LayerGeometryData)>();
+        query.iter(world)
+            .map(|(info, geometry)| Ipc2581Layer {
+                name: String::new(),
+                layer_type: info.layer_type,
+                geometry: geometry.0.clone(),
+            })
+            .collect()
+    };
+    layers.sort_by_key(|layer| crate::ecs::layer_type_to_z_order(&layer.layer_type));
+
+    let stackup = world.get_resource::<BoardStackup>()
+        .map(|stackup| stackup.0.clone())
+        .unwrap_or_default();
+    let bottom_copper = layers.iter().map(|layer| layer.layer_type)
+        .chain(stackup.iter().filter_map(|layer| layer.layer_type))
+        .filter_map(|layer_type| layer_type.copper_layer_number())
+        .max()
+        .unwrap_or(1);
+    for layer in &mut layers {
+        layer.name = ipc_layer_name(&layer.layer_type, bottom_copper);
+    }
+
+    let drills = {
+        let mut query = world.query::<(&DrillFileInfo, &DrillHoles)>();
+        query.iter(world)
+            .map(|(info, holes)| {
+                let mut data = holes.0.clone();
+                data.plated = Some(info.plated);
+                (info.name.clone(), data)
+            })
+            .collect()
+    };
+
+    Ipc2581Board {
+        name: name.to_string(),
+        layers,
+        stackup,
+        drills,
+        components,
+        ..Ipc2581Board::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::LayerType;
+    use crate::ipc2581::parse_ipc2581;
+    use crate::ipc2581::reader::tests::SAMPLE;
+
+    #[test]
+    fn test_load_and_collect() {
+        let mut world = crate::ecs::setup_ecs_world();
+        let board = parse_ipc2581(SAMPLE).unwrap();
+        let summary = load_ipc2581_system(&mut world, board).unwrap();
+        assert_eq!((summary.layers, summary.drills, summary.components, summary.stackup_layers), (2, 2, 1, 4));
+        assert!(crate::ecs::get_layer_by_type(&mut world, LayerType::Copper(1)).is_some());
+
+        let collected = collect_ipc2581_board(&mut world, "demo", Vec::new());
+        let names: Vec<&str> = collected.layers.iter().map(|layer| layer.name.as_str()).collect();
+        assert_eq!(names, ["PROFILE", "TOP"]);
+        assert_eq!(collected.stackup.len(), 4);
+        assert_eq!(collected.drills.iter().filter(|(_, data)| data.plated == Some(false)).count(), 1);
+
+        crate::ecs::clear_all_layers_system(&mut world);
+        assert!(world.resource::<BoardStackup>().0.is_empty());
+        assert_eq!(world.query::<&BoardComponent>().iter(&world).count(), 0);
+    }
+}
@@ -0,0 +1,723 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::Path;
+use nalgebra::Point2;
+
+use crate::DemoLensApp;
+use crate::ecs::{LayerType, Side, StackupLayer, StackupLayerKind};
+use crate::export::bom::{custom_field_names, group_components, BomLine};
+use crate::export::gerber::decimal;
+use crate::geometry::{
+    aperture, outline_contours, shapes, ApertureShape, ApertureTransform, FeatureKind, GerberFeature, LayerGeometry,
+    OutlineSegment, Polarity, Polygon, DEFAULT_TOLERANCE,
+};
+use crate::geometry::outline::JOIN_TOLERANCE;
+use super::{Ipc2581Board, Ipc2581Revision};
+
+// IPC-2581 writer
+// Writes the subset of the schema the reader understands, which is the same
+// in revisions B and C; only the root revision attribute differs. Flashes of
+// circle, rectangle and obround apertures become standard primitives, other
+// apertures user primitives built from their outlines. Round strokes stay
+// lines and arcs, other strokes and regions become contours. The mechanical
+// outline is written as the step profile and drills as holes on one drill
+// layer. Packages are referenced by footprint name only.
+
+/// Result of writing an IPC-2581 file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Ipc2581Report {
+    pub layers: usize,
+    pub features: usize,
+    pub holes: usize,
+    pub components: usize,
+    pub warnings: Vec<String>,
+}
+
+impl Ipc2581Report {
+    fn warn(&mut self, message: String) {
+        if !self.warnings.contains(&message) {
+            self.warnings.push(message);
+        }
+    }
+}
+
+/// IPC-2581 layer name of a viewer layer, e.g. "TOP", "INNER_2" or "SOLDERMASK_BOTTOM"
+pub fn ipc_layer_name(layer_type: &LayerType, bottom_copper: u8) -> String {
+    let side = |side: &Side| match side {
+        Side::Top => "TOP",
+        Side::Bottom => "BOTTOM",
+    };
+    match layer_type {
+        LayerType::Copper(1) => "TOP".to_string(),
+        LayerType::Copper(n) if *n == bottom_copper => "BOTTOM".to_string(),
+        LayerType::Copper(n) => format!("INNER_{}", n),
+        LayerType::Silkscreen(s) => format!("SILKSCREEN_{}", side(s)),
+        LayerType::Soldermask(s) => format!("SOLDERMASK_{}", side(s)),
+        LayerType::Paste(s) => format!("PASTE_{}", side(s)),
+        LayerType::MechanicalOutline => "PROFILE".to_string(),
+    }
+}
+
+/// layerFunction and side attributes of a layer
+fn layer_attributes(layer_type: &LayerType, bottom_copper: u8) -> (&'static str, &'static str) {
+    let side = |side: &Side| match side {
+        Side::Top => "TOP",
+        Side::Bottom => "BOTTOM",
+    };
+    match layer_type {
+        LayerType::Copper(1) => ("CONDUCTOR", "TOP"),
+        LayerType::Copper(n) if *n == bottom_copper => ("CONDUCTOR", "BOTTOM"),
+        LayerType::Copper(_) => ("CONDUCTOR", "INTERNAL"),
+        LayerType::Silkscreen(s) => ("SILKSCREEN", side(s)),
+        LayerType::Soldermask(s) => ("SOLDERMASK", side(s)),
+        LayerType::Paste(s) => ("SOLDERPASTE", side(s)),
+        LayerType::MechanicalOutline => ("DOCUMENT", "ALL"),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn xy(point: Point2<f64>) -> String {
+    format!(r#"x="{}" y="{}""#, decimal(point.x), decimal(point.y))
+}
+
+// ============================================================================
+// PATHS
+// ============================================================================
+
+/// Closed polygon through the points, as a Polygon or Cutout element
+fn points_path(tag: &str, points: &[Point2<f64>]) -> String {
+    let mut out = format!("<{}><PolyBegin {}/>", tag, xy(points[0]));
+    for point in points.iter().skip(1).chain(std::iter::once(&points[0])) {
+        let _ = write!(out, "<PolyStepSegment {}/>", xy(*point));
+    }
+    let _ = write!(out, "</{}>", tag);
+    out
+}
+
+/// Closed outline contour with its arcs, as a Polygon or Cutout element
+fn segments_path(tag: &str, segments: &[OutlineSegment]) -> String {
+    let mut out = format!("<{}><PolyBegin {}/>", tag, xy(segments[0].start()));
+    for segment in segments {
+        match segment {
+            OutlineSegment::Line { end, .. } => {
+                let _ = write!(out, "<PolyStepSegment {}/>", xy(*end));
+            }
+            OutlineSegment::Arc { end, center, clockwise, .. } => {
+                let _ = write!(
+                    out,
+                    r#"<PolyStepCurve {} centerX="{}" centerY="{}" clockwise="{}"/>"#,
+                    xy(*end), decimal(center.x), decimal(center.y), clockwise
+                );
+            }
+        }
+    }
+    let _ = write!(out, "</{}>", tag);
+    out
+}
+
+/// Contour elements for polygons, clear polygons becoming cutouts of the dark one around them
+fn contours(polygons: &[Polygon]) -> String {
+    let mut out = String::new();
+    for dark in polygons.iter().filter(|p| p.dark && p.points.len() >= 3) {
+        out.push_str("<Contour>");
+        out.push_str(&points_path("Polygon", &dark.points));
+        let cutouts = polygons.iter()
+            .filter(|p| !p.dark && p.points.len() >= 3 && dark.contains(p.points[0]));
+        for cutout in cutouts {
+            out.push_str(&points_path("Cutout", &cutout.points));
+        }
+        out.push_str("</Contour>");
+    }
+    out
+}
+
+// ============================================================================
+// DICTIONARIES
+// ============================================================================
+
+/// Dictionary entries by content, in first use order
+#[derive(Default)]
+struct Entries {
+    ids: HashMap<String, String>,
+    order: Vec<(String, String)>,
+}
+
+impl Entries {
+    fn id(&mut self, prefix: &str, content: String) -> String {
+        if let Some(id) = self.ids.get(&content) {
+            return id.clone();
+        }
+        let id = format!("{}_{}", prefix, self.order.len() + 1);
+        self.ids.insert(content.clone(), id.clone());
+        self.order.push((id.clone(), content));
+        id
+    }
+}
+
+#[derive(Default)]
+struct Dictionaries {
+    standard: Entries,
+    user: Entries,
+    line_descs: Entries,
+}
+
+impl Dictionaries {
+    /// Primitive reference for an aperture, standard when the shape has an equivalent
+    fn primitive_ref(&mut self, shape: &ApertureShape, polygons: impl FnOnce() -> Vec<Polygon>) -> String {
+        let standard = match shape {
+            ApertureShape::Circle { diameter, hole: None } => Some(format!(r#"<Circle diameter="{}"/>"#, decimal(*diameter))),
+            ApertureShape::Circle { diameter, hole: Some(hole) } => Some(format!(
+                r#"<Donut shape="ROUND" outerDiameter="{}" innerDiameter="{}"/>"#,
+                decimal(*diameter), decimal(*hole)
+            )),
+            ApertureShape::Rectangle { width, height, hole: None } => {
+                Some(format!(r#"<RectCenter width="{}" height="{}"/>"#, decimal(*width), decimal(*height)))
+            }
+            ApertureShape::Obround { width, height, hole: None } => {
+                Some(format!(r#"<Oval width="{}" height="{}"/>"#, decimal(*width), decimal(*height)))
+            }
+            _ => None,
+        };
+        match standard {
+            Some(element) => format!(r#"<StandardPrimitiveRef id="{}"/>"#, self.standard.id("PRIMITIVE", element)),
+            None => {
+                let element = format!("<UserSpecial>{}</UserSpecial>", contours(&polygons()));
+                format!(r#"<UserPrimitiveRef id="{}"/>"#, self.user.id("USER", element))
+            }
+        }
+    }
+
+    fn line_desc_ref(&mut self, width: f64) -> String {
+        let element = format!(r#"<LineDesc lineEnd="ROUND" lineWidth="{}"/>"#, decimal(width));
+        format!(r#"<LineDescRef id="{}"/>"#, self.line_descs.id("LINE", element))
+    }
+
+    fn write(&self, out: &mut String) {
+        let _ = writeln!(out, r#"    <DictionaryStandard units="MILLIMETER">"#);
+        for (id, element) in &self.standard.order {
+            let _ = writeln!(out, r#"      <EntryStandard id="{}">{}</EntryStandard>"#, id, element);
+        }
+        let _ = writeln!(out, "    </DictionaryStandard>");
+        let _ = writeln!(out, r#"    <DictionaryUser units="MILLIMETER">"#);
+        for (id, element) in &self.user.order {
+            let _ = writeln!(out, r#"      <EntryUser id="{}">{}</EntryUser>"#, id, element);
+        }
+        let _ = writeln!(out, "    </DictionaryUser>");
+        let _ = writeln!(out, r#"    <DictionaryLineDesc units="MILLIMETER">"#);
+        for (id, element) in &self.line_descs.order {
+            let _ = writeln!(out, r#"      <EntryLineDesc id="{}">{}</EntryLineDesc>"#, id, element);
+        }
+        let _ = writeln!(out, "    </DictionaryLineDesc>");
+    }
+}
+
+// ============================================================================
+// LAYER FEATURES
+// ============================================================================
+
+/// Set attributes a feature goes under: polarity and pad usage
+fn set_attributes(feature: &GerberFeature) -> String {
+    let mut attributes = String::new();
+    if feature.polarity == Polarity::Clear {
+        attributes.push_str(r#" polarity="NEGATIVE""#);
+    }
+    if matches!(feature.kind, FeatureKind::Flash { .. }) {
+        match feature.function.as_deref() {
+            Some(function) if function.starts_with("ViaPad") => attributes.push_str(r#" padUsage="VIA""#),
+            Some(function) if aperture::is_pad_function(function) => attributes.push_str(r#" padUsage="TERMINATION""#),
+            _ => {}
+        }
+    }
+    attributes
+}
+
+/// Xform element for an aperture transform, empty for the identity
+fn xform(transform: &ApertureTransform) -> String {
+    // IPC-2581 only mirrors about the Y axis; a mirror about X is that plus 180°
+    let (mirror, rotation) = if transform.mirror_y {
+        (!transform.mirror_x, transform.rotation + 180.0)
+    } else {
+        (transform.mirror_x, transform.rotation)
+    };
+    let rotation = rotation.rem_euclid(360.0);
+    let mut attributes = String::new();
+    if rotation.abs() > 1e-9 {
+        let _ = write!(attributes, r#" rotation="{}""#, decimal(rotation));
+    }
+    if mirror {
+        attributes.push_str(r#" mirror="true""#);
+    }
+    if (transform.scale - 1.0).abs() > 1e-9 {
+        let _ = write!(attributes, r#" scale="{}""#, decimal(transform.scale));
+    }
+    if attributes.is_empty() { String::new() } else { format!("<Xform{}/>", attributes) }
+}
+
+/// One Pad or Features element
+fn feature_element(geometry: &LayerGeometry, feature: &GerberFeature, dictionaries: &mut Dictionaries) -> Result<String, String> {
+    let aperture = feature.aperture.map(|code| {
+        geometry.apertures.get(&code).ok_or_else(|| format!("Skipped features using undefined aperture D{}", code))
+    }).transpose()?;
+    let round_width = match aperture.map(|aperture| &aperture.shape) {
+        Some(ApertureShape::Circle { diameter, .. }) => Some(diameter * feature.aperture_transform.scale),
+        _ => None,
+    };
+
+    let element = match (&feature.kind, aperture, round_width) {
+        (FeatureKind::Region { points }, ..) => {
+            format!("<Features>{}</Features>", contours(&[Polygon::new(points.clone(), true)]))
+        }
+        (FeatureKind::Flash { position }, Some(aperture), _) => {
+            let primitive = dictionaries.primitive_ref(&aperture.shape, || aperture.polygons(DEFAULT_TOLERANCE));
+            let element = if set_attributes(feature).contains("padUsage") { "Pad" } else { "Features" };
+            format!("<{0}>{1}<Location {2}/>{3}</{0}>", element, xform(&feature.aperture_transform), xy(*position), primitive)
+        }
+        (FeatureKind::Line { start, end }, _, Some(width)) => format!(
+            r#"<Features><Line startX="{}" startY="{}" endX="{}" endY="{}">{}</Line></Features>"#,
+            decimal(start.x), decimal(start.y), decimal(end.x), decimal(end.y),
+            dictionaries.line_desc_ref(width)
+        ),
+        (FeatureKind::Arc { start, end, center, clockwise }, _, Some(width)) => format!(
+            r#"<Features><Arc startX="{}" startY="{}" endX="{}" endY="{}" centerX="{}" centerY="{}" clockwise="{}">{}</Arc></Features>"#,
+            decimal(start.x), decimal(start.y), decimal(end.x), decimal(end.y), decimal(center.x), decimal(center.y), clockwise,
+            dictionaries.line_desc_ref(width)
+        ),
+        // Strokes of other apertures are written as the area they cover
+        (FeatureKind::Line { .. } | FeatureKind::Arc { .. }, Some(_), None) => {
+            format!("<Features>{}</Features>", contours(&geometry.feature_polygons(feature, DEFAULT_TOLERANCE)))
+        }
+        (_, None, _) => return Err("Skipped strokes and flashes without an aperture".to_string()),
+    };
+    Ok(element)
+}
+
+/// LayerFeature element of one image layer
+fn write_layer_feature(
+    out: &mut String,
+    name: &str,
+    geometry: &LayerGeometry,
+    dictionaries: &mut Dictionaries,
+    report: &mut Ipc2581Report,
+) {
+    let _ = writeln!(out, r#"        <LayerFeature layerRef="{}">"#, escape(name));
+    // Consecutive features with the same polarity and pad usage share a Set
+    let mut open_set: Option<String> = None;
+    for feature in &geometry.features {
+        if matches!(&feature.kind, FeatureKind::Region { points } if points.len() < 3) {
+            continue;
+        }
+        let element = match feature_element(geometry, feature, dictionaries) {
+            Ok(element) => element,
+            Err(message) => {
+                report.warn(message);
+                continue;
+            }
+        };
+        let attributes = set_attributes(feature);
+        if open_set.as_ref() != Some(&attributes) {
+            if open_set.is_some() {
+                let _ = writeln!(out, "          </Set>");
+            }
+            let _ = writeln!(out, "          <Set{}>", attributes);
+            open_set = Some(attributes);
+        }
+        let _ = writeln!(out, "            {}", element);
+        report.features += 1;
+    }
+    if open_set.is_some() {
+        let _ = writeln!(out, "          </Set>");
+    }
+    let _ = writeln!(out, "        </LayerFeature>");
+}
+
+/// Profile element from the mechanical outline, else the bounds of all layers
+fn write_profile(out: &mut String, board: &Ipc2581Board, report: &mut Ipc2581Report) -> Result<(), String> {
+    let mut closed: Vec<(f64, Vec<OutlineSegment>)> = board.layers.iter()
+        .filter(|layer| layer.layer_type == LayerType::MechanicalOutline)
+        .flat_map(|layer| outline_contours(&layer.geometry, JOIN_TOLERANCE))
+        .filter(|contour| contour.closed && !contour.segments.is_empty())
+        .map(|contour| (shapes::signed_area(&contour.points(DEFAULT_TOLERANCE)).abs(), contour.segments))
+        .collect();
+    // The largest contour is the board, the others are cutouts
+    closed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let _ = writeln!(out, "        <Profile>");
+    if closed.is_empty() {
+        let (min, max) = board.layers.iter()
+            .filter_map(|layer| layer.geometry.bounds())
+            .reduce(|(a_min, a_max), (b_min, b_max)| (
+                Point2::new(a_min.x.min(b_min.x), a_min.y.min(b_min.y)),
+                Point2::new(a_max.x.max(b_max.x), a_max.y.max(b_max.y)),
+            ))
+            .ok_or("No board outline or layer geometry to export")?;
+        report.warn("No closed board outline, the profile is the bounding box of all layers".to_string());
+        let corners = [min, Point2::new(max.x, min.y), max, Point2::new(min.x, max.y)];
+        let _ = writeln!(out, "          {}", points_path("Polygon", &corners));
+    } else {
+        for (index, (_, segments)) in closed.iter().enumerate() {
+            let tag = if index == 0 { "Polygon" } else { "Cutout" };
+            let _ = writeln!(out, "          {}", segments_path(tag, segments));
+        }
+    }
+    let _ = writeln!(out, "        </Profile>");
+    Ok(())
+}
+
+// ============================================================================
+// DOCUMENT
+// ============================================================================
+
+/// Declared layer: name, layerFunction, side
+type LayerDeclaration = (String, &'static str, &'static str);
+
+/// Serialize a board to IPC-2581 XML
+pub fn write_ipc2581(board: &Ipc2581Board) -> Result<(String, Ipc2581Report), String> {
+    let mut report = Ipc2581Report::default();
+    let name = if board.name.is_empty() { "board".to_string() } else { escape(&board.name) };
+
+    let bottom_copper = board.layers.iter().map(|layer| layer.layer_type)
+        .chain(board.stackup.iter().filter_map(|layer| layer.layer_type))
+        .filter_map(|layer_type| layer_type.copper_layer_number())
+        .max()
+        .unwrap_or(1);
+
+    // Image layers, copper in stack order first
+    let mut image_layers: Vec<_> = board.layers.iter()
+        .filter(|layer| layer.layer_type != LayerType::MechanicalOutline)
+        .collect();
+    image_layers.sort_by_key(|layer| match layer.layer_type {
+        LayerType::Copper(n) => (0, n),
+        _ => (1, 0),
+    });
+    let layer_name = |layer_type: LayerType| -> Option<String> {
+        image_layers.iter().find(|layer| layer.layer_type == layer_type).map(|layer| escape(&layer.name))
+    };
+
+    let mut declarations: Vec<LayerDeclaration> = image_layers.iter()
+        .map(|layer| {
+            let (function, side) = layer_attributes(&layer.layer_type, bottom_copper);
+            (escape(&layer.name), function, side)
+        })
+        .collect();
+
+    // Stackup layers without an image layer of their own are declared too
+    let mut stack: Vec<(String, &StackupLayer)> = Vec::new();
+    for layer in &board.stackup {
+        let existing = layer.layer_type.and_then(&layer_name);
+        let name = existing.clone().unwrap_or_else(|| escape(&layer.name));
+        if existing.is_none() && !declarations.iter().any(|(declared, ..)| *declared == name) {
+            let (function, side) = match (layer.kind, layer.layer_type) {
+                (StackupLayerKind::Dielectric, _) => ("DIELCORE", "INTERNAL"),
+                (_, Some(layer_type)) => layer_attributes(&layer_type, bottom_copper),
+                (StackupLayerKind::Copper, None) => ("CONDUCTOR", "INTERNAL"),
+                (StackupLayerKind::Soldermask, None) => ("SOLDERMASK", "TOP"),
+                (StackupLayerKind::Silkscreen, None) => ("SILKSCREEN", "TOP"),
+                (StackupLayerKind::Paste, None) => ("SOLDERPASTE", "TOP"),
+            };
+            declarations.push((name.clone(), function, side));
+        }
+        stack.push((name, layer));
+    }
+
+    let has_holes = board.drills.iter().any(|(_, data)| !data.hits.is_empty());
+    if has_holes {
+        declarations.push(("DRILL".to_string(), "DRILL", "ALL"));
+    }
+
+    // Step body first, it fills the dictionaries
+    let mut dictionaries = Dictionaries::default();
+    let mut step = String::new();
+    let _ = writeln!(step, r#"      <Step name="{}">"#, name);
+    let _ = writeln!(step, r#"        <Datum x="0" y="0"/>"#);
+    write_profile(&mut step, board, &mut report)?;
+
+    let lines = group_components(&board.components, true, &custom_field_names(&board.components));
+    let part_of = |line: &BomLine| -> String {
+        line.fields.get("Part Number").cloned()
+            .unwrap_or_else(|| format!("{}_{}", line.value, line.footprint))
+    };
+    let component_layer = |bottom: bool| -> String {
+        let layer_type = if bottom { LayerType::Copper(bottom_copper) } else { LayerType::Copper(1) };
+        layer_name(layer_type)
+            .or_else(|| stack.iter().find(|(_, layer)| layer.layer_type == Some(layer_type)).map(|(name, _)| name.clone()))
+            .unwrap_or_else(|| ipc_layer_name(&layer_type, bottom_copper))
+    };
+    for component in &board.components {
+        let bottom = component.layer == "B.Cu";
+        let part = lines.iter()
+            .find(|line| line.references.contains(&component.reference))
+            .map(part_of)
+            .unwrap_or_else(|| component.value.clone());
+        let _ = writeln!(
+            step,
+            r#"        <Component refDes="{}" packageRef="{}" layerRef="{}" part="{}">"#,
+            escape(&component.reference), escape(&component.footprint), component_layer(bottom), escape(&part)
+        );
+        let transform = ApertureTransform { mirror_x: bottom, rotation: component.orientation, ..ApertureTransform::default() };
+        let element = xform(&transform);
+        if !element.is_empty() {
+            let _ = writeln!(step, "          {}", element);
+        }
+        // BOM locations are Y-down like KiCad, IPC-2581 is Y-up
+        let _ = writeln!(step, "          <Location {}/>", xy(Point2::new(component.x_location, -component.y_location)));
+        let _ = writeln!(step, "        </Component>");
+        report.components += 1;
+    }
+
+    for layer in &image_layers {
+        write_layer_feature(&mut step, &escape(&layer.name), &layer.geometry, &mut dictionaries, &mut report);
+        report.layers += 1;
+    }
+
+    if has_holes {
+        let _ = writeln!(step, r#"        <LayerFeature layerRef="DRILL">"#);
+        for (drill_name, data) in &board.drills {
+            let plated = data.plated.unwrap_or_else(|| !drill_name.to_uppercase().contains("NPTH"));
+            let status = if plated { "PLATED" } else { "NONPLATED" };
+            if !data.slots.is_empty() {
+                report.warn(format!("{} routed slot(s) in {} were not written", data.slots.len(), drill_name));
+            }
+            if data.hits.is_empty() {
+                continue;
+            }
+            let _ = writeln!(step, "          <Set>");
+            for hit in &data.hits {
+                report.holes += 1;
+                let _ = writeln!(
+                    step,
+                    r#"            <Hole name="H{}" diameter="{}" platingStatus="{}" plusTol="0" minusTol="0" {}/>"#,
+                    report.holes, decimal(hit.diameter), status, xy(hit.position)
+                );
+            }
+            let _ = writeln!(step, "          </Set>");
+        }
+        let _ = writeln!(step, "        </LayerFeature>");
+    }
+    let _ = writeln!(step, "      </Step>");
+
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        out,
+        r#"<IPC-2581 revision="{}" xmlns="http://webstds.ipc.org/2581" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#,
+        board.revision.code()
+    );
+
+    // Content: what the file holds, and the dictionaries
+    let _ = writeln!(out, r#"  <Content roleRef="Owner">"#);
+    let _ = writeln!(out, r#"    <FunctionMode mode="ASSEMBLY"/>"#);
+    let _ = writeln!(out, r#"    <StepRef name="{}"/>"#, name);
+    for (layer, ..) in &declarations {
+        let _ = writeln!(out, r#"    <LayerRef name="{}"/>"#, layer);
+    }
+    if !lines.is_empty() {
+        let _ = writeln!(out, r#"    <BomRef name="{}_BOM"/>"#, name);
+    }
+    dictionaries.write(&mut out);
+    let _ = writeln!(out, "  </Content>");
+
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let _ = writeln!(out, "  <LogisticHeader>");
+    let _ = writeln!(out, r#"    <Role id="Owner" roleFunction="SENDER"/>"#);
+    let _ = writeln!(out, r#"    <Enterprise id="CopperForge" code="NONE"/>"#);
+    let _ = writeln!(out, r#"    <Person name="CopperForge" enterpriseRef="CopperForge" roleRef="Owner"/>"#);
+    let _ = writeln!(out, "  </LogisticHeader>");
+    let _ = writeln!(out, r#"  <HistoryRecord number="1" origination="{0}" software="CopperForge" lastChange="{0}">"#, now);
+    let _ = writeln!(out, r#"    <FileRevision fileRevisionId="1" comment="" label="">"#);
+    let _ = writeln!(out, r#"      <SoftwarePackage name="CopperForge" revision="{}" vendor="CopperForge"/>"#, env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "    </FileRevision>");
+    let _ = writeln!(out, "  </HistoryRecord>");
+
+    if !lines.is_empty() {
+        let _ = writeln!(out, r#"  <Bom name="{}_BOM">"#, name);
+        let _ = writeln!(out, r#"    <BomHeader assembly="{0}" revision="1"><StepRef name="{0}"/></BomHeader>"#, name);
+        for line in &lines {
+            let _ = writeln!(
+                out,
+                r#"    <BomItem OEMDesignNumberRef="{}" quantity="{}" category="ELECTRICAL" description="{}">"#,
+                escape(&part_of(line)), line.quantity(), escape(&line.description)
+            );
+            for reference in &line.references {
+                let bottom = board.components.iter()
+                    .find(|component| component.reference == *reference)
+                    .is_some_and(|component| component.layer == "B.Cu");
+                let _ = writeln!(
+                    out,
+                    r#"      <RefDes name="{}" packageRef="{}" populate="{}" layerRef="{}"/>"#,
+                    escape(reference), escape(&line.footprint), !line.dnp, component_layer(bottom)
+                );
+            }
+            let mut characteristics: BTreeMap<&str, &str> = line.fields.iter()
+                .filter(|(field, _)| field.as_str() != "Part Number")
+                .map(|(field, value)| (field.as_str(), value.as_str()))
+                .collect();
+            characteristics.insert("VALUE", &line.value);
+            let _ = writeln!(out, r#"      <Characteristics category="ELECTRICAL">"#);
+            for (field, value) in characteristics {
+                let _ = writeln!(
+                    out,
+                    r#"        <Textual definitionSource="CopperForge" textualCharacteristicName="{}" textualCharacteristicValue="{}"/>"#,
+                    escape(field), escape(value)
+                );
+            }
+            let _ = writeln!(out, "      </Characteristics>");
+            let _ = writeln!(out, "    </BomItem>");
+        }
+        let _ = writeln!(out, "  </Bom>");
+    }
+
+    let _ = writeln!(out, r#"  <Ecad name="{}">"#, name);
+    let _ = writeln!(out, r#"    <CadHeader units="MILLIMETER">"#);
+    let spec_name = |index: usize| format!("SPEC_{}", index + 1);
+    for (index, (_, layer)) in stack.iter().enumerate() {
+        if layer.material.is_none() && layer.dielectric_constant.is_none() {
+            continue;
+        }
+        let _ = write!(out, r#"      <Spec name="{}">"#, spec_name(index));
+        if let Some(material) = &layer.material {
+            let _ = write!(out, r#"<General type="MATERIAL"><Property text="{}"/></General>"#, escape(material));
+        }
+        if let Some(constant) = layer.dielectric_constant {
+            let _ = write!(out, r#"<Dielectric type="DIELECTRIC_CONSTANT"><Property value="{}"/></Dielectric>"#, decimal(constant));
+        }
+        let _ = writeln!(out, "</Spec>");
+    }
+    let _ = writeln!(out, "    </CadHeader>");
+
+    let _ = writeln!(out, "    <CadData>");
+    for (layer, function, side) in &declarations {
+        let _ = writeln!(out, r#"      <Layer name="{}" layerFunction="{}" side="{}" polarity="POSITIVE"/>"#, layer, function, side);
+    }
+    if !stack.is_empty() {
+        let total = decimal(stack.iter().map(|(_, layer)| layer.thickness).sum());
+        let _ = writeln!(out, r#"      <Stackup name="PRIMARY" overallThickness="{}" whereMeasured="METAL" tolPlus="0" tolMinus="0">"#, total);
+        let _ = writeln!(out, r#"        <StackupGroup name="PRIMARY" thickness="{}" tolPlus="0" tolMinus="0">"#, total);
+        for (index, (layer_ref, layer)) in stack.iter().enumerate() {
+            let spec = if layer.material.is_some() || layer.dielectric_constant.is_some() {
+                format!(r#"<SpecRef id="{}"/>"#, spec_name(index))
+            } else {
+                String::new()
+            };
+            let _ = writeln!(
+                out,
+                r#"          <StackupLayer layerOrGroupRef="{}" thickness="{}" tolPlus="0" tolMinus="0" sequence="{}">{}</StackupLayer>"#,
+                layer_ref, decimal(layer.thickness), index + 1, spec
+            );
+        }
+        let _ = writeln!(out, "        </StackupGroup>");
+        let _ = writeln!(out, "      </Stackup>");
+    }
+    out.push_str(&step);
+    let _ = writeln!(out, "    </CadData>");
+    let _ = writeln!(out, "  </Ecad>");
+    let _ = writeln!(out, "</IPC-2581>");
+    Ok((out, report))
+}
+
+/// Export the loaded layers, drills, stackup and BOM as an IPC-2581 file
+pub fn export_ipc2581(app: &mut DemoLensApp, output_path: &Path, revision: Ipc2581Revision) -> Result<Ipc2581Report, String> {
+    let name = app.project_manager.get_pcb_path()
+        .and_then(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "board".to_string());
+    let components = crate::export::loaded_bom_components(app);
+    let mut board = super::collect_ipc2581_board(&mut app.ecs_world, &name, components);
+    if board.layers.is_empty() {
+        return Err("No layers loaded".to_string());
+    }
+    board.revision = revision;
+
+    let (content, report) = write_ipc2581(&board)?;
+    std::fs::write(output_path, content).map_err(|e| format!("Failed to save IPC-2581: {}", e))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc2581::{parse_ipc2581, Ipc2581Layer};
+    use crate::ipc2581::reader::tests::SAMPLE;
+
+    #[test]
+    fn test_round_trip() {
+        let original = parse_ipc2581(SAMPLE).unwrap();
+        let (content, report) = write_ipc2581(&Ipc2581Board { revision: Ipc2581Revision::C, ..original.clone() }).unwrap();
+        assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+        assert!(content.contains(r#"revision="C""#));
+
+        let written = parse_ipc2581(&content).unwrap();
+        assert!(written.warnings.is_empty(), "{:?}", written.warnings);
+        assert_eq!(written.revision, Ipc2581Revision::C);
+        assert_eq!(written.layers.len(), original.layers.len());
+        for (a, b) in written.layers.iter().zip(&original.layers) {
+            assert_eq!(a.layer_type, b.layer_type);
+            assert_eq!(a.geometry.features.len(), b.geometry.features.len(), "{}", a.name);
+        }
+
+        let copper = |board: &Ipc2581Board| board.layers.iter()
+            .find(|l| l.layer_type == LayerType::Copper(1))
+            .map(|l| l.geometry.clone())
+            .unwrap();
+        let (a, b) = (copper(&written), copper(&original));
+        assert_eq!(a.features[0].aperture_transform.rotation, 90.0);
+        assert_eq!(a.apertures[&a.features[0].aperture.unwrap()].shape, b.apertures[&b.features[0].aperture.unwrap()].shape);
+        assert_eq!(a.features.last().unwrap().function.as_deref(), Some("ViaPad"));
+
+        let stack: Vec<(StackupLayerKind, Option<LayerType>)> = written.stackup.iter().map(|l| (l.kind, l.layer_type)).collect();
+        assert_eq!(stack, original.stackup.iter().map(|l| (l.kind, l.layer_type)).collect::<Vec<_>>());
+        assert_eq!(written.stackup[2].dielectric_constant, Some(4.5));
+
+        assert_eq!(written.drills.len(), 2);
+        assert_eq!(written.drills[1].1.hits[0].position, Point2::new(17.0, 7.0));
+
+        let r1 = &written.components[0];
+        assert_eq!((r1.value.as_str(), r1.layer.as_str(), r1.orientation), ("10k", "B.Cu", 90.0));
+        assert_eq!((r1.x_location, r1.y_location), (original.components[0].x_location, original.components[0].y_location));
+
+        // Flipped back to viewer coordinates the part sits on its pad
+        let position = Point2::new(r1.x_location, -r1.y_location);
+        let on_pad = written.layers.iter()
+            .filter(|l| l.layer_type.is_copper())
+            .flat_map(|l| &l.geometry.features)
+            .any(|f| matches!(f.kind, FeatureKind::Flash { position: pad } if (pad - position).norm() < 1e-9));
+        assert!(on_pad);
+    }
+
+    #[test]
+    fn test_user_primitive_and_profile_fallback() {
+        let geometry = crate::geometry::parse_gerber(
+            "%FSLAX46Y46*%\n%MOMM*%\n%ADD10P,1.0X6*%\n%ADD11R,2.0X1.0*%\nD10*\nX1000000Y1000000D03*\nD11*\nX0Y0D02*\nX5000000Y0D01*\nM02*\n",
+        ).unwrap();
+        let board = Ipc2581Board {
+            name: "R&D".to_string(),
+            layers: vec![Ipc2581Layer { name: "TOP".to_string(), layer_type: LayerType::Copper(1), geometry }],
+            ..Ipc2581Board::default()
+        };
+        let (content, report) = write_ipc2581(&board).unwrap();
+        assert_eq!(report.warnings.len(), 1);
+        assert!(content.contains(r#"<Ecad name="R&amp;D">"#));
+        assert!(content.contains("<UserPrimitiveRef"));
+
+        let written = parse_ipc2581(&content).unwrap();
+        let copper = written.layers.iter().find(|l| l.layer_type == LayerType::Copper(1)).unwrap();
+        // The hexagon and the rectangle stroke both become regions
+        assert_eq!(copper.geometry.features.len(), 2);
+        assert!(copper.geometry.features.iter().all(|f| matches!(f.kind, FeatureKind::Region { .. })));
+        let outline = written.layers.iter().find(|l| l.layer_type == LayerType::MechanicalOutline).unwrap();
+        assert_eq!(outline.geometry.features.len(), 4);
+    }
+}
@@ -5,6 +5,9 @@ pub mod display;
 pub mod drc_operations;
 pub mod ecs;
 pub mod export;
+pub mod geometry;
+pub mod ipc2581;
+pub mod netlist;
 // layer_operations module removed - all functionality moved to ECS
 pub mod navigation;
 pub mod platform;
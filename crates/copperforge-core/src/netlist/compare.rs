@@ -0,0 +1,141 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::ipc356::{TestPoint, TestPointKind};
+
+// Netlist vs Gerber comparison
+// Every test point is resolved to the extracted copper net under it. A CAD net
+// spread over several copper nets is an open, a copper net carrying several
+// CAD nets is a short.
+
+/// A CAD net whose test points are not all on the same copper
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetOpen {
+    pub net: String,
+    /// Test point indices, one group per piece of copper
+    pub fragments: Vec<Vec<usize>>,
+}
+
+/// Copper that joins test points of different CAD nets
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetShort {
+    /// Extracted (copper) net id
+    pub copper_net: usize,
+    pub nets: Vec<String>,
+    pub test_points: Vec<usize>,
+}
+
+/// Result of comparing a netlist against extracted connectivity
+#[derive(Debug, Clone, Default)]
+pub struct NetlistComparison {
+    pub opens: Vec<NetOpen>,
+    pub shorts: Vec<NetShort>,
+    /// Test points with no copper under them
+    pub missing_copper: Vec<usize>,
+    pub checked_nets: usize,
+    pub checked_points: usize,
+}
+
+impl NetlistComparison {
+    pub fn is_clean(&self) -> bool {
+        self.opens.is_empty() && self.shorts.is_empty() && self.missing_copper.is_empty()
+    }
+
+    /// Indices of all test points involved in a problem
+    pub fn problem_points(&self) -> BTreeSet<usize> {
+        let mut points: BTreeSet<usize> = self.missing_copper.iter().copied().collect();
+        for open in &self.opens {
+            points.extend(open.fragments.iter().flatten());
+        }
+        for short in &self.shorts {
+            points.extend(short.test_points.iter());
+        }
+        points
+    }
+}
+
+/// Compare test points against the copper net resolved under each of them
+/// `copper_nets[i]` is the extracted net at `test_points[i]`, None when there is no copper.
+pub fn compare_netlist(test_points: &[TestPoint], copper_nets: &[Option<usize>]) -> NetlistComparison {
+    let mut comparison = NetlistComparison::default();
+    let mut by_net: BTreeMap<&str, BTreeMap<usize, Vec<usize>>> = BTreeMap::new();
+    let mut by_copper: BTreeMap<usize, BTreeMap<&str, Vec<usize>>> = BTreeMap::new();
+
+    for (index, point) in test_points.iter().enumerate() {
+        // Tooling holes and unconnected pins carry no net
+        if point.kind == TestPointKind::ToolingHole || !point.is_connected() {
+            continue;
+        }
+        comparison.checked_points += 1;
+        let Some(copper_net) = copper_nets.get(index).copied().flatten() else {
+            comparison.missing_copper.push(index);
+            continue;
+        };
+        by_net.entry(point.net.as_str()).or_default().entry(copper_net).or_default().push(index);
+        by_copper.entry(copper_net).or_default().entry(point.net.as_str()).or_default().push(index);
+    }
+    comparison.checked_nets = by_net.len();
+
+    for (net, pieces) in by_net {
+        if pieces.len() > 1 {
+            comparison.opens.push(NetOpen {
+                net: net.to_string(),
+                fragments: pieces.into_values().collect(),
+            });
+        }
+    }
+
+    for (copper_net, nets) in by_copper {
+        if nets.len() > 1 {
+            comparison.shorts.push(NetShort {
+                copper_net,
+                nets: nets.keys().map(|n| n.to_string()).collect(),
+                test_points: nets.into_values().flatten().collect(),
+            });
+        }
+    }
+
+    comparison
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point2;
+
+    fn point(net: &str) -> TestPoint {
+        TestPoint {
+            kind: TestPointKind::Smd,
+            net: net.to_string(),
+            reference: "U1".to_string(),
+            pin: "1".to_string(),
+            position: Point2::origin(),
+            hole_diameter: None,
+            plated: true,
+            access: 1,
+            size: (1.0, 1.0),
+            rotation: 0.0,
+            midpoint: false,
+        }
+    }
+
+    #[test]
+    fn test_clean_comparison() {
+        let points = vec![point("GND"), point("GND"), point("VCC")];
+        let result = compare_netlist(&points, &[Some(0), Some(0), Some(1)]);
+        assert!(result.is_clean());
+        assert_eq!(result.checked_nets, 2);
+    }
+
+    #[test]
+    fn test_opens_and_shorts() {
+        let points = vec![point("GND"), point("GND"), point("VCC"), point("SIG"), point("N/C")];
+        let result = compare_netlist(&points, &[Some(0), Some(1), Some(1), None, Some(2)]);
+        assert_eq!(result.opens.len(), 1);
+        assert_eq!(result.opens[0].net, "GND");
+        assert_eq!(result.shorts.len(), 1);
+        assert_eq!(result.shorts[0].nets, vec!["GND".to_string(), "VCC".to_string()]);
+        assert_eq!(result.missing_copper, vec![3]);
+        assert_eq!(result.checked_points, 4);
+        assert!(!result.problem_points().contains(&4));
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::{BTreeMap, HashMap};
+use nalgebra::Point2;
+
+use crate::geometry::Units;
+
+// IPC-D-356(A) test netlist parser
+// Records are fixed-column: net name in columns 4-17, reference designator in
+// 21-26, pin in 28-31, followed by hole, access, position and size fields.
+
+/// Net name IPC-D-356 uses for unconnected pins
+pub const NO_CONNECT: &str = "N/C";
+
+/// Type of a test point record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestPointKind {
+    /// 317: through-hole pad or via
+    ThroughHole,
+    /// 327: surface mount pad
+    Smd,
+    /// 367: non-plated tooling hole
+    ToolingHole,
+}
+
+/// One test point (pad, via or hole) of the netlist, all values in mm
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestPoint {
+    pub kind: TestPointKind,
+    pub net: String,
+    /// Reference designator ("VIA" for vias)
+    pub reference: String,
+    pub pin: String,
+    pub position: Point2<f64>,
+    pub hole_diameter: Option<f64>,
+    pub plated: bool,
+    /// 0 = accessible from both sides, otherwise the copper layer number
+    pub access: u8,
+    pub size: (f64, f64),
+    pub rotation: f64,
+    /// Mid-point of a net (not a pin end point)
+    pub midpoint: bool,
+}
+
+impl TestPoint {
+    /// Whether the test point belongs to a real net
+    pub fn is_connected(&self) -> bool {
+        !self.net.is_empty() && self.net != NO_CONNECT
+    }
+
+    /// "U1-3" style label
+    pub fn label(&self) -> String {
+        if self.pin.is_empty() {
+            self.reference.clone()
+        } else {
+            format!("{}-{}", self.reference, self.pin)
+        }
+    }
+}
+
+/// Parsed IPC-D-356 file
+#[derive(Debug, Clone, Default)]
+pub struct Ipc356Netlist {
+    pub job: Option<String>,
+    pub units: Units,
+    pub test_points: Vec<TestPoint>,
+}
+
+impl Ipc356Netlist {
+    /// Test point indices grouped by net name (unconnected pins excluded)
+    pub fn nets(&self) -> BTreeMap<String, Vec<usize>> {
+        let mut nets: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, point) in self.test_points.iter().enumerate() {
+            if point.is_connected() {
+                nets.entry(point.net.clone()).or_default().push(index);
+            }
+        }
+        nets
+    }
+}
+
+/// Characters `start..end` of a line (0-based), trimmed
+fn field(line: &str, start: usize, end: usize) -> &str {
+    let end = end.min(line.len());
+    if start >= end {
+        return "";
+    }
+    line.get(start..end).unwrap_or("").trim()
+}
+
+/// Split "D0300PA00X +054610Y-053340X0600Y0000R000S3" into (letter, value) pairs
+fn data_fields(text: &str) -> Vec<(char, String)> {
+    let mut fields: Vec<(char, String)> = Vec::new();
+    for c in text.chars() {
+        if c.is_ascii_alphabetic() {
+            fields.push((c.to_ascii_uppercase(), String::new()));
+        } else if let Some((_, value)) = fields.last_mut()
+            && (c.is_ascii_digit() || c == '+' || c == '-')
+        {
+            value.push(c);
+        }
+    }
+    fields
+}
+
+/// Parse IPC-D-356 content
+pub fn parse_ipc356(content: &str) -> Result<Ipc356Netlist, String> {
+    let mut netlist = Ipc356Netlist::default();
+    let mut aliases: HashMap<String, String> = HashMap::new();
+    // Resolution of one coordinate unit in mm (CUST 0: 0.0001 inch)
+    let mut resolution = 0.00254;
+    let mut seen_record = false;
+
+    for (line_number, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim_end();
+        if line.len() < 3 {
+            continue;
+        }
+
+        if line.starts_with('C') {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix('P') {
+            let rest = rest.trim();
+            let (name, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let value = value.trim();
+            match name {
+                "JOB" => netlist.job = Some(value.to_string()),
+                "UNITS" => {
+                    if value.ends_with('1') || value.starts_with("SI") {
+                        netlist.units = Units::Millimeters;
+                        resolution = 0.001;
+                    } else {
+                        netlist.units = Units::Inches;
+                        resolution = 0.00254;
+                    }
+                }
+                _ if name.starts_with("NNAME") => {
+                    aliases.insert(name.to_string(), value.to_string());
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let kind = match line.get(..3) {
+            Some("317") | Some("017") => TestPointKind::ThroughHole,
+            Some("327") | Some("027") => TestPointKind::Smd,
+            Some("367") => TestPointKind::ToolingHole,
+            Some("999") => break,
+            // Conductor, outline and continuation records are not needed
+            _ => continue,
+        };
+        seen_record = true;
+
+        let net = field(line, 3, 17);
+        let net = aliases.get(net).cloned().unwrap_or_else(|| net.to_string());
+        let reference = field(line, 20, 26).to_string();
+        let pin = field(line, 27, 31).to_string();
+        let midpoint = field(line, 31, 32) == "M";
+
+        let mut point = TestPoint {
+            kind,
+            net,
+            reference,
+            pin,
+            position: Point2::origin(),
+            hole_diameter: None,
+            plated: kind != TestPointKind::ToolingHole,
+            access: 0,
+            size: (0.0, 0.0),
+            rotation: 0.0,
+            midpoint,
+        };
+
+        let mut coordinates = 0;
+        let mut sizes = 0;
+        let mut position = (None, None);
+        for (letter, value) in data_fields(line.get(32..).unwrap_or("")) {
+            let number = value.parse::<f64>().ok();
+            match letter {
+                'D' => point.hole_diameter = number.map(|v| v * resolution),
+                'P' => point.plated = true,
+                'U' => point.plated = false,
+                'A' => point.access = number.map(|v| v as u8).unwrap_or(0),
+                'X' | 'Y' if coordinates < 2 => {
+                    coordinates += 1;
+                    let value = number.map(|v| v * resolution);
+                    if letter == 'X' { position.0 = value } else { position.1 = value }
+                }
+                'X' | 'Y' => {
+                    sizes += 1;
+                    let value = number.map(|v| v * resolution).unwrap_or(0.0);
+                    if letter == 'X' { point.size.0 = value } else { point.size.1 = value }
+                }
+                'R' => point.rotation = number.unwrap_or(0.0),
+                _ => {}
+            }
+        }
+        let (Some(x), Some(y)) = position else {
+            return Err(format!("Line {}: test point without coordinates", line_number + 1));
+        };
+        point.position = Point2::new(x, y);
+        // Round pads only give the X size
+        if sizes == 1 || point.size.1 == 0.0 {
+            point.size.1 = point.size.0;
+        }
+
+        netlist.test_points.push(point);
+    }
+
+    if !seen_record {
+        return Err("No IPC-D-356 test records found".to_string());
+    }
+    Ok(netlist)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+C  IPC-D-356 generated by KiCad
+P  JOB   cmod_s7.kicad_pcb
+P  UNITS CUST 0
+P  NNAME1   /very/long/net/name/for/alias
+317GND              VIA   -     D0150PA00X+035320Y-016730X0300Y0000R000
+327/LED0            D1    -1    A01X+036250Y-020000X0240Y0260R090S2
+317NNAME1           J1    -2    D0400PA00X+040000Y-020000X0700Y0700R000
+327N/C              U2    -4    A01X+041000Y-021000X0200Y0600R000S2
+999
+";
+
+    #[test]
+    fn test_parse_records() {
+        let netlist = parse_ipc356(SAMPLE).unwrap();
+        assert_eq!(netlist.job.as_deref(), Some("cmod_s7.kicad_pcb"));
+        assert_eq!(netlist.test_points.len(), 4);
+
+        let via = &netlist.test_points[0];
+        assert_eq!(via.kind, TestPointKind::ThroughHole);
+        assert_eq!(via.net, "GND");
+        assert_eq!(via.reference, "VIA");
+        assert!((via.position.x - 89.7128).abs() < 1e-6);
+        assert!((via.hole_diameter.unwrap() - 0.381).abs() < 1e-6);
+        assert!((via.size.1 - 0.762).abs() < 1e-6);
+
+        let pad = &netlist.test_points[1];
+        assert_eq!(pad.label(), "D1-1");
+        assert_eq!(pad.access, 1);
+        assert_eq!(pad.hole_diameter, None);
+        assert!((pad.rotation - 90.0).abs() < 1e-9);
+
+        assert_eq!(netlist.test_points[2].net, "/very/long/net/name/for/alias");
+        assert!(!netlist.test_points[3].is_connected());
+        assert_eq!(netlist.nets().len(), 3);
+    }
+
+    #[test]
+    fn test_metric_units() {
+        let content = "P  UNITS CUST 1\n317NET1             VIA   -     D0300PA00X+010000Y-005000X0600Y0000R000\n999\n";
+        let netlist = parse_ipc356(content).unwrap();
+        let point = &netlist.test_points[0];
+        assert!((point.position.x - 10.0).abs() < 1e-9);
+        assert!((point.position.y + 5.0).abs() < 1e-9);
+        assert!((point.hole_diameter.unwrap() - 0.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rejects_non_netlist() {
+        assert!(parse_ipc356("G04 not a netlist*\nM02*\n").is_err());
+    }
+}
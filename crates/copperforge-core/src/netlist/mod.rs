@@ -0,0 +1,14 @@
+// Netlist import and comparison
+// IPC-D-356 test netlists are loaded as test point entities and compared
+// against the copper connectivity extracted from the loaded Gerber layers.
+
+pub mod ipc356;
+pub mod compare;
+pub mod systems;
+
+pub use ipc356::{parse_ipc356, Ipc356Netlist, TestPoint, TestPointKind};
+pub use compare::{compare_netlist, NetlistComparison, NetOpen, NetShort};
+pub use systems::{
+    load_netlist_system, clear_netlist_system, compare_netlist_system,
+    get_netlist_test_points, get_netlist_overlay_points,
+};
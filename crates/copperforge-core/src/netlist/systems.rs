@@ -0,0 +1,144 @@
+use bevy_ecs::prelude::*;
+use nalgebra::Point2;
+
+use crate::ecs::{
+    LayerType, LayerInfo, LayerGeometryData, CopperIsland,
+    NetlistTestPoint, NetlistProblem,
+};
+use crate::geometry::DEFAULT_TOLERANCE;
+use super::compare::{compare_netlist, NetlistComparison};
+use super::ipc356::{Ipc356Netlist, TestPoint, TestPointKind};
+
+// Netlist systems
+// Test points are spawned as entities and resolved against the copper island
+// entities produced by the connectivity system.
+
+/// System to remove all test point entities
+pub fn clear_netlist_system(world: &mut World) {
+    let entities_to_remove: Vec<Entity> = {
+        let mut query = world.query_filtered::<Entity, With<NetlistTestPoint>>();
+        query.iter(world).collect()
+    };
+
+    for entity in entities_to_remove {
+        world.despawn(entity);
+    }
+}
+
+/// System to load a parsed netlist, replacing any previous one
+/// Returns the number of test points spawned
+pub fn load_netlist_system(world: &mut World, netlist: Ipc356Netlist) -> usize {
+    clear_netlist_system(world);
+
+    let count = netlist.test_points.len();
+    for (index, point) in netlist.test_points.into_iter().enumerate() {
+        world.spawn(NetlistTestPoint { index, point });
+    }
+    count
+}
+
+/// Get the loaded test points in file order
+pub fn get_netlist_test_points(world: &mut World) -> Vec<TestPoint> {
+    let mut query = world.query::<&NetlistTestPoint>();
+    let mut points: Vec<&NetlistTestPoint> = query.iter(world).collect();
+    points.sort_by_key(|tp| tp.index);
+    points.into_iter().map(|tp| tp.point.clone()).collect()
+}
+
+/// Test point positions for the viewer overlay, with their problem state
+pub fn get_netlist_overlay_points(world: &mut World) -> Vec<(Point2<f64>, f64, bool)> {
+    let mut query = world.query::<(&NetlistTestPoint, Has<NetlistProblem>)>();
+    query.iter(world)
+        .map(|(tp, problem)| (tp.point.position, tp.point.size.0.max(tp.point.size.1), problem))
+        .collect()
+}
+
+/// Copper layers a test point can be probed from
+fn access_layers(point: &TestPoint, copper_layers: &[(Entity, LayerType)]) -> Vec<Entity> {
+    if point.access == 0 || point.kind == TestPointKind::ThroughHole {
+        return copper_layers.iter().map(|(entity, _)| *entity).collect();
+    }
+    // Access codes number layers from the top; the last one is the bottom layer
+    copper_layers.iter()
+        .find(|(_, layer_type)| *layer_type == LayerType::Copper(point.access))
+        .or_else(|| copper_layers.last())
+        .map(|(entity, _)| vec![*entity])
+        .unwrap_or_default()
+}
+
+/// Extracted net under a point on one of the given layers
+fn copper_net_at(world: &mut World, point: Point2<f64>, layers: &[Entity]) -> Option<usize> {
+    let candidates: Vec<(Entity, usize, Vec<usize>)> = {
+        let mut query = world.query::<&CopperIsland>();
+        query.iter(world)
+            .filter(|island| layers.contains(&island.layer_entity))
+            .filter(|island| {
+                point.x >= island.bounds.min.x && point.x <= island.bounds.max.x
+                    && point.y >= island.bounds.min.y && point.y <= island.bounds.max.y
+            })
+            .map(|island| (island.layer_entity, island.net_id, island.features.clone()))
+            .collect()
+    };
+
+    for (layer_entity, net_id, features) in candidates {
+        let Some(geometry) = world.get::<LayerGeometryData>(layer_entity) else {
+            continue;
+        };
+        let hit = features.iter()
+            .filter_map(|&index| geometry.0.features.get(index))
+            .any(|feature| geometry.0.feature_contains(feature, point, DEFAULT_TOLERANCE));
+        if hit {
+            return Some(net_id);
+        }
+    }
+    None
+}
+
+/// System to compare the loaded netlist against the copper connectivity
+/// Re-extracts connectivity first when copper geometry changed since the last run.
+/// Marks test points involved in a problem with `NetlistProblem`.
+pub fn compare_netlist_system(world: &mut World) -> Result<NetlistComparison, String> {
+    let test_points: Vec<(Entity, TestPoint)> = {
+        let mut query = world.query::<(Entity, &NetlistTestPoint)>();
+        let mut points: Vec<(Entity, &NetlistTestPoint)> = query.iter(world).collect();
+        points.sort_by_key(|(_, tp)| tp.index);
+        points.into_iter().map(|(entity, tp)| (entity, tp.point.clone())).collect()
+    };
+    if test_points.is_empty() {
+        return Err("No netlist loaded".to_string());
+    }
+
+    crate::drc_operations::update_connectivity_system(world)?;
+
+    let mut copper_layers: Vec<(Entity, LayerType)> = {
+        let mut query = world.query_filtered::<(Entity, &LayerInfo), With<LayerGeometryData>>();
+        query.iter(world)
+            .filter(|(_, info)| info.layer_type.is_copper())
+            .map(|(entity, info)| (entity, info.layer_type))
+            .collect()
+    };
+    copper_layers.sort_by_key(|(_, layer_type)| match layer_type {
+        LayerType::Copper(number) => *number,
+        _ => u8::MAX,
+    });
+
+    let copper_nets: Vec<Option<usize>> = test_points.iter()
+        .map(|(_, point)| {
+            let layers = access_layers(point, &copper_layers);
+            copper_net_at(world, point.position, &layers)
+        })
+        .collect();
+
+    let points: Vec<TestPoint> = test_points.iter().map(|(_, point)| point.clone()).collect();
+    let comparison = compare_netlist(&points, &copper_nets);
+
+    // Refresh problem markers for the overlay
+    for (entity, _) in &test_points {
+        world.entity_mut(*entity).remove::<NetlistProblem>();
+    }
+    for index in comparison.problem_points() {
+        world.entity_mut(test_points[index].0).insert(NetlistProblem);
+    }
+
+    Ok(comparison)
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use egui_file_dialog::FileDialog;
+use super::presets::LayerPreset;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ProjectState {
@@ -45,6 +46,9 @@ pub struct ProjectConfig {
     pub user_timezone: Option<String>,
     pub use_24_hour_clock: bool,
     pub global_units_mils: bool, // true = mils, false = mm
+    /// Layer visibility presets saved by the user
+    #[serde(default)]
+    pub layer_presets: Vec<LayerPreset>,
 }
 
 impl Default for ProjectConfig {
@@ -56,6 +60,7 @@ impl Default for ProjectConfig {
             user_timezone: None,
             use_24_hour_clock: false, // Default to 12-hour
             global_units_mils: false, // Default to mm
+            layer_presets: Vec::new(),
         }
     }
 }
@@ -1,7 +1,9 @@
 pub mod manager;
 pub mod constants;
 pub mod defaults;
+pub mod presets;
 
 // Re-export the main types for easy access
 pub use manager::{ProjectManager, ProjectState};
+pub use presets::{shipped_layer_presets, LayerPreset, LayerPresetEntry};
 pub use defaults::load_demo_gerber; // load_default_gerbers removed with LayerManager
\ No newline at end of file
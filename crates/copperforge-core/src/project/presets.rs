@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::{LayerType, Side};
+
+// Layer visibility presets
+// A named set of per-layer visibility, opacity and color plus the view side.
+// User presets are saved in the project config; the shipped ones are built for
+// the copper layers of the loaded board.
+
+/// Settings of one layer in a preset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerPresetEntry {
+    pub layer_type: LayerType,
+    pub visible: bool,
+    pub opacity: f32,
+    /// Premultiplied RGBA, None keeps the layer's current color
+    pub color: Option<[u8; 4]>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerPreset {
+    pub name: String,
+    /// View side to switch to (true = top), None keeps the current side
+    pub showing_top: Option<bool>,
+    /// Loaded layers not listed here are hidden
+    pub layers: Vec<LayerPresetEntry>,
+}
+
+impl LayerPreset {
+    /// Preset showing `layers` at full opacity in their current colors
+    fn showing(name: &str, showing_top: Option<bool>, layers: &[(LayerType, f32)]) -> Self {
+        Self {
+            name: name.to_string(),
+            showing_top,
+            layers: layers.iter()
+                .map(|&(layer_type, opacity)| LayerPresetEntry { layer_type, visible: true, opacity, color: None })
+                .collect(),
+        }
+    }
+
+    pub fn entry(&self, layer_type: LayerType) -> Option<&LayerPresetEntry> {
+        self.layers.iter().find(|entry| entry.layer_type == layer_type)
+    }
+}
+
+/// Presets shipped with the viewer, for a board with the given copper layer numbers
+/// The highest copper number is taken as the bottom layer.
+pub fn shipped_layer_presets(copper_layers: &[u8]) -> Vec<LayerPreset> {
+    let bottom_copper = copper_layers.iter().copied().filter(|n| *n > 1).max();
+    let inner: Vec<(LayerType, f32)> = copper_layers.iter()
+        .filter(|n| **n > 1 && Some(**n) != bottom_copper)
+        .map(|n| (LayerType::Copper(*n), 1.0))
+        .collect();
+    let all: Vec<(LayerType, f32)> = copper_layers.iter().map(|n| (LayerType::Copper(*n), 1.0))
+        .chain([Side::Top, Side::Bottom].into_iter().flat_map(|side| [
+            (LayerType::Silkscreen(side), 1.0),
+            (LayerType::Soldermask(side), 1.0),
+            (LayerType::Paste(side), 1.0),
+        ]))
+        .chain([(LayerType::MechanicalOutline, 1.0)])
+        .collect();
+
+    let mut presets = vec![
+        LayerPreset::showing("All Layers", None, &all),
+        LayerPreset::showing("Top Assembly", Some(true), &[
+            (LayerType::Copper(1), 0.4),
+            (LayerType::Paste(Side::Top), 0.8),
+            (LayerType::Silkscreen(Side::Top), 1.0),
+            (LayerType::MechanicalOutline, 1.0),
+        ]),
+    ];
+    if let Some(bottom) = bottom_copper {
+        presets.push(LayerPreset::showing("Bottom Assembly", Some(false), &[
+            (LayerType::Copper(bottom), 0.4),
+            (LayerType::Paste(Side::Bottom), 0.8),
+            (LayerType::Silkscreen(Side::Bottom), 1.0),
+            (LayerType::MechanicalOutline, 1.0),
+        ]));
+    }
+    presets.push(LayerPreset::showing("Top Copper + Mask", Some(true), &[
+        (LayerType::Copper(1), 1.0),
+        (LayerType::Soldermask(Side::Top), 0.6),
+        (LayerType::MechanicalOutline, 1.0),
+    ]));
+    presets.push(LayerPreset::showing("Bottom Mask + Paste", Some(false), &[
+        (LayerType::Soldermask(Side::Bottom), 0.8),
+        (LayerType::Paste(Side::Bottom), 1.0),
+        (LayerType::MechanicalOutline, 1.0),
+    ]));
+    if !inner.is_empty() {
+        let mut layers = inner;
+        layers.push((LayerType::MechanicalOutline, 1.0));
+        presets.push(LayerPreset::showing("Inner Planes Only", None, &layers));
+    }
+    presets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_presets_follow_copper_count() {
+        let two_layer = shipped_layer_presets(&[1, 2]);
+        assert!(two_layer.iter().all(|preset| preset.name != "Inner Planes Only"));
+        let bottom = two_layer.iter().find(|preset| preset.name == "Bottom Assembly").unwrap();
+        assert!(bottom.entry(LayerType::Copper(2)).is_some());
+        assert_eq!(bottom.showing_top, Some(false));
+
+        let four_layer = shipped_layer_presets(&[1, 2, 3, 4]);
+        let inner = four_layer.iter().find(|preset| preset.name == "Inner Planes Only").unwrap();
+        let copper: Vec<LayerType> = inner.layers.iter()
+            .map(|entry| entry.layer_type)
+            .filter(|layer_type| matches!(layer_type, LayerType::Copper(_)))
+            .collect();
+        assert_eq!(copper, vec![LayerType::Copper(2), LayerType::Copper(3)]);
+        let bottom = four_layer.iter().find(|preset| preset.name == "Bottom Assembly").unwrap();
+        assert!(bottom.entry(LayerType::Copper(4)).is_some());
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::ecs::LayerType;
+
+// Board review annotations
+// Markers, arrows, rectangles and text comments placed on a layer during design
+// review. Positions are Gerber viewer coordinates in mm (Y up), as returned by
+// the cursor position, not the Y-down KiCad space of the BOM component
+// locations. Stored per project, next to the project record.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnnotationKind {
+    #[default]
+    Marker,
+    /// From `position` to `end`, pointing at `end`
+    Arrow,
+    /// Corners at `position` and `end`
+    Rectangle,
+    Text,
+}
+
+impl AnnotationKind {
+    pub fn all() -> [AnnotationKind; 4] {
+        [AnnotationKind::Marker, AnnotationKind::Arrow, AnnotationKind::Rectangle, AnnotationKind::Text]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AnnotationKind::Marker => "Marker",
+            AnnotationKind::Arrow => "Arrow",
+            AnnotationKind::Rectangle => "Rectangle",
+            AnnotationKind::Text => "Text",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            AnnotationKind::Marker => "📍",
+            AnnotationKind::Arrow => "➡",
+            AnnotationKind::Rectangle => "⬜",
+            AnnotationKind::Text => "🗛",
+        }
+    }
+
+    /// Placed with two clicks instead of one
+    pub fn has_end(&self) -> bool {
+        matches!(self, AnnotationKind::Arrow | AnnotationKind::Rectangle)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AnnotationStatus {
+    #[default]
+    Open,
+    Resolved,
+}
+
+impl AnnotationStatus {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            AnnotationStatus::Open => "Open",
+            AnnotationStatus::Resolved => "Resolved",
+        }
+    }
+
+    pub fn toggled(&self) -> AnnotationStatus {
+        match self {
+            AnnotationStatus::Open => AnnotationStatus::Resolved,
+            AnnotationStatus::Resolved => AnnotationStatus::Open,
+        }
+    }
+}
+
+/// A review comment at a board location
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Number shown on the board and in the review outputs, unique per project
+    pub id: u32,
+    pub kind: AnnotationKind,
+    pub layer: LayerType,
+    pub position: (f64, f64),
+    /// Second point of arrows and rectangles
+    pub end: Option<(f64, f64)>,
+    pub text: String,
+    pub author: String,
+    pub status: AnnotationStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Annotation {
+    /// Point to centre the view on
+    pub fn center(&self) -> (f64, f64) {
+        match (self.kind, self.end) {
+            (AnnotationKind::Rectangle, Some((x, y))) => ((self.position.0 + x) / 2.0, (self.position.1 + y) / 2.0),
+            // Arrows point at the spot under review
+            (AnnotationKind::Arrow, Some(end)) => end,
+            _ => self.position,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status == AnnotationStatus::Open
+    }
+}
+
+/// Number for a new annotation
+pub fn next_annotation_id(annotations: &[Annotation]) -> u32 {
+    annotations.iter().map(|annotation| annotation.id).max().unwrap_or(0) + 1
+}
+
+/// Next open annotation after (or before) `current` in list order, wrapping around
+pub fn step_open_annotation(annotations: &[Annotation], current: Option<u32>, forward: bool) -> Option<u32> {
+    let open: Vec<u32> = annotations.iter()
+        .filter(|annotation| annotation.is_open())
+        .map(|annotation| annotation.id)
+        .collect();
+    if open.is_empty() {
+        return None;
+    }
+    let position = current.and_then(|id| open.iter().position(|open_id| *open_id == id));
+    let index = match (position, forward) {
+        (None, true) => 0,
+        (None, false) => open.len() - 1,
+        (Some(i), true) => (i + 1) % open.len(),
+        (Some(i), false) => (i + open.len() - 1) % open.len(),
+    };
+    Some(open[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Side;
+
+    fn annotation(id: u32, status: AnnotationStatus) -> Annotation {
+        Annotation {
+            id,
+            kind: AnnotationKind::Rectangle,
+            layer: LayerType::Silkscreen(Side::Top),
+            position: (0.0, 0.0),
+            end: Some((4.0, 2.0)),
+            text: "Reference overlaps pad".to_string(),
+            author: "reviewer".to_string(),
+            status,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_annotation_ids_and_navigation() {
+        let annotations = vec![
+            annotation(1, AnnotationStatus::Open),
+            annotation(3, AnnotationStatus::Resolved),
+            annotation(4, AnnotationStatus::Open),
+        ];
+        assert_eq!(next_annotation_id(&annotations), 5);
+        assert_eq!(next_annotation_id(&[]), 1);
+        assert_eq!(annotations[0].center(), (2.0, 1.0));
+
+        // Resolved annotations are skipped
+        assert_eq!(step_open_annotation(&annotations, None, true), Some(1));
+        assert_eq!(step_open_annotation(&annotations, Some(1), true), Some(4));
+        assert_eq!(step_open_annotation(&annotations, Some(4), true), Some(1));
+        assert_eq!(step_open_annotation(&annotations, Some(1), false), Some(4));
+
+        // Stored with bincode like the project record
+        let bytes = bincode::serialize(&annotations).unwrap();
+        let restored: Vec<Annotation> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored, annotations);
+    }
+}
@@ -0,0 +1,81 @@
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+
+// Board appearance for the realistic preview
+// Stored per project, next to the project record in the database.
+
+/// Plating on copper left exposed by the solder mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SurfaceFinish {
+    #[default]
+    Hasl,
+    Enig,
+    /// Organic coating, looks like bare copper
+    Osp,
+}
+
+impl SurfaceFinish {
+    pub fn all() -> [SurfaceFinish; 3] {
+        [SurfaceFinish::Hasl, SurfaceFinish::Enig, SurfaceFinish::Osp]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SurfaceFinish::Hasl => "HASL",
+            SurfaceFinish::Enig => "ENIG",
+            SurfaceFinish::Osp => "OSP",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            SurfaceFinish::Hasl => Color32::from_rgb(200, 202, 206),
+            SurfaceFinish::Enig => Color32::from_rgb(222, 180, 84),
+            SurfaceFinish::Osp => Color32::from_rgb(196, 122, 74),
+        }
+    }
+}
+
+/// Colors and finish of the manufactured board, as RGB
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoardAppearance {
+    pub substrate: [u8; 3],
+    pub soldermask: [u8; 3],
+    /// How much of the copper and substrate shows through the mask (0.0 - 1.0)
+    pub mask_opacity: f32,
+    pub silkscreen: [u8; 3],
+    pub finish: SurfaceFinish,
+    /// Leave silkscreen off pads, as fabs do
+    pub clip_silkscreen: bool,
+}
+
+impl Default for BoardAppearance {
+    fn default() -> Self {
+        Self {
+            substrate: [184, 170, 120],
+            soldermask: MASK_PRESETS[0].1,
+            mask_opacity: 0.85,
+            silkscreen: [245, 245, 245],
+            finish: SurfaceFinish::default(),
+            clip_silkscreen: true,
+        }
+    }
+}
+
+/// Common solder mask colors offered by fabs
+pub const MASK_PRESETS: [(&str, [u8; 3]); 7] = [
+    ("Green", [20, 110, 50]),
+    ("Red", [160, 30, 30]),
+    ("Blue", [20, 60, 140]),
+    ("Black", [25, 25, 25]),
+    ("White", [235, 235, 235]),
+    ("Purple", [90, 40, 130]),
+    ("Yellow", [210, 180, 30]),
+];
+
+/// Color of bare copper seen through the mask
+pub const COPPER_COLOR: [u8; 3] = [184, 115, 51];
+
+pub fn rgb(color: [u8; 3]) -> Color32 {
+    Color32::from_rgb(color[0], color[1], color[2])
+}
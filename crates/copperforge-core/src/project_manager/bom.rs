@@ -4,6 +4,7 @@
 /// that were previously embedded in the UI layer.
 
 use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 /// Component data for the BOM table
@@ -18,6 +19,43 @@ pub struct BomComponent {
     pub orientation: f64,
     pub value: String,
     pub footprint: String,
+    /// Board layer of the footprint ("F.Cu" or "B.Cu")
+    pub layer: String,
+    /// Excluded from the bill of materials in KiCad
+    pub exclude_from_bom: bool,
+    /// Do not populate (DNP) in KiCad
+    pub do_not_populate: bool,
+    /// Extra fields such as manufacturer or MPN, by field name
+    pub fields: BTreeMap<String, String>,
+}
+
+/// Component layout stored by project databases before the layer, BOM flags and
+/// fields were added
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct LegacyBomComponent {
+    pub item_number: String,
+    pub reference: String,
+    pub description: String,
+    pub x_location: f64,
+    pub y_location: f64,
+    pub orientation: f64,
+    pub value: String,
+    pub footprint: String,
+}
+
+impl From<LegacyBomComponent> for BomComponent {
+    fn from(legacy: LegacyBomComponent) -> Self {
+        Self {
+            item_number: legacy.item_number,
+            description: legacy.description,
+            x_location: legacy.x_location,
+            y_location: legacy.y_location,
+            orientation: legacy.orientation,
+            value: legacy.value,
+            footprint: legacy.footprint,
+            ..Self::new(legacy.reference)
+        }
+    }
 }
 
 /// Events sent from UI to BOM backend
@@ -60,6 +98,10 @@ impl BomComponent {
             orientation: 0.0,
             value: String::new(),
             footprint: String::new(),
+            layer: String::new(),
+            exclude_from_bom: false,
+            do_not_populate: false,
+            fields: BTreeMap::new(),
         }
     }
     
@@ -68,6 +110,11 @@ impl BomComponent {
         (self.x_location, self.y_location)
     }
     
+    /// Whether the component is placed on the bottom side
+    pub fn is_bottom(&self) -> bool {
+        self.layer.starts_with("B.") || self.layer.eq_ignore_ascii_case("bottom")
+    }
+    
     /// Check if this component matches a filter string
     pub fn matches_filter(&self, filter: &str) -> bool {
         if filter.is_empty() {
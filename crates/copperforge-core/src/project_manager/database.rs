@@ -1,7 +1,11 @@
+use bincode::Options;
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
 use std::path::{Path, PathBuf};
 use chrono::{DateTime, Utc};
-use crate::project_manager::bom::BomComponent;
+use crate::project_manager::annotations::Annotation;
+use crate::project_manager::appearance::BoardAppearance;
+use crate::project_manager::bom::{BomComponent, LegacyBomComponent};
 
 /// Database manager for project storage
 pub struct ProjectDatabase {
@@ -29,6 +33,52 @@ pub struct ProjectData {
     pub notes: String,
 }
 
+/// Layout version of the records under `project:{id}`, stored under `schema:{id}`
+/// 0: original components, 1: with board layer, BOM flags and fields.
+/// Records saved before the version key existed are told apart by decoding.
+pub const PROJECT_SCHEMA_VERSION: u32 = 1;
+
+/// Project record of schema 0, before `BomComponent` gained its layer and BOM flags
+#[derive(Deserialize)]
+struct LegacyProjectData {
+    metadata: ProjectMetadata,
+    bom_components: Vec<LegacyBomComponent>,
+    notes: String,
+}
+
+impl From<LegacyProjectData> for ProjectData {
+    fn from(legacy: LegacyProjectData) -> Self {
+        Self {
+            metadata: legacy.metadata,
+            bom_components: legacy.bom_components.into_iter().map(BomComponent::from).collect(),
+            notes: legacy.notes,
+        }
+    }
+}
+
+/// Decode a bincode record that must use up all of `bytes`
+/// `#[serde(default)]` does not apply to bincode, so a record of another layout
+/// either fails here or leaves bytes over.
+fn decode_exact<T: DeserializeOwned>(bytes: &[u8]) -> bincode::Result<T> {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+}
+
+/// Decode a project record of the given schema version into the current layout
+fn decode_project(version: u32, bytes: &[u8]) -> Result<ProjectData, ProjectDatabaseError> {
+    let decoded = match version {
+        0 => decode_exact::<LegacyProjectData>(bytes).map(ProjectData::from),
+        PROJECT_SCHEMA_VERSION => decode_exact(bytes),
+        _ => return Err(ProjectDatabaseError::Deserialization(format!(
+            "Project schema version {} is newer than supported version {}",
+            version, PROJECT_SCHEMA_VERSION
+        ))),
+    };
+    decoded.map_err(|e| ProjectDatabaseError::Deserialization(e.to_string()))
+}
+
 impl ProjectDatabase {
     /// Create a new project database
     pub fn new(db_path: &Path) -> Result<Self, ProjectDatabaseError> {
@@ -46,6 +96,10 @@ impl ProjectDatabase {
         
         self.db.insert(key.as_bytes(), value)
             .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
+        let schema = bincode::serialize(&PROJECT_SCHEMA_VERSION)
+            .map_err(|e| ProjectDatabaseError::Serialization(e.to_string()))?;
+        self.db.insert(format!("schema:{}", project.metadata.id).as_bytes(), schema)
+            .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
         
         // Update index for quick lookups
         self.update_project_index(&project.metadata)?;
@@ -60,8 +114,15 @@ impl ProjectDatabase {
         if let Some(value) = self.db.get(key.as_bytes())
             .map_err(|e| ProjectDatabaseError::DatabaseRead(e.to_string()))? {
             
-            let project: ProjectData = bincode::deserialize(&value)
-                .map_err(|e| ProjectDatabaseError::Deserialization(e.to_string()))?;
+            let project = match self.load_schema_version(project_id)? {
+                Some(version) => decode_project(version, &value),
+                // Records saved before the version key, newest layout first
+                None => (0..=PROJECT_SCHEMA_VERSION).rev()
+                    .find_map(|version| decode_project(version, &value).ok())
+                    .ok_or_else(|| ProjectDatabaseError::Deserialization(
+                        "Project record matches no known schema".to_string()
+                    )),
+            }?;
             
             Ok(Some(project))
         } else {
@@ -69,6 +130,18 @@ impl ProjectDatabase {
         }
     }
 
+    /// Schema version of a stored project, None for records saved before versioning
+    fn load_schema_version(&self, project_id: &str) -> Result<Option<u32>, ProjectDatabaseError> {
+        let key = format!("schema:{}", project_id);
+        match self.db.get(key.as_bytes())
+            .map_err(|e| ProjectDatabaseError::DatabaseRead(e.to_string()))? {
+            Some(value) => bincode::deserialize(&value)
+                .map(Some)
+                .map_err(|e| ProjectDatabaseError::Deserialization(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
     /// List all projects (metadata only for performance)
     pub fn list_projects(&self) -> Result<Vec<ProjectMetadata>, ProjectDatabaseError> {
         let mut projects = Vec::new();
@@ -99,6 +172,12 @@ impl ProjectDatabase {
         
         self.db.remove(key.as_bytes())
             .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
+        self.db.remove(format!("appearance:{}", project_id).as_bytes())
+            .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
+        self.db.remove(format!("annotations:{}", project_id).as_bytes())
+            .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
+        self.db.remove(format!("schema:{}", project_id).as_bytes())
+            .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
         
         // Remove from index
         self.remove_from_project_index(project_id)?;
@@ -138,6 +217,56 @@ impl ProjectDatabase {
         Ok(None)
     }
 
+    /// Save the realistic preview appearance of a project
+    /// Kept under its own key so project records stay readable by older versions.
+    pub fn save_board_appearance(&self, project_id: &str, appearance: &BoardAppearance) -> Result<(), ProjectDatabaseError> {
+        let key = format!("appearance:{}", project_id);
+        let value = bincode::serialize(appearance)
+            .map_err(|e| ProjectDatabaseError::Serialization(e.to_string()))?;
+        
+        self.db.insert(key.as_bytes(), value)
+            .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
+        
+        Ok(())
+    }
+
+    /// Load the realistic preview appearance of a project, if one was saved
+    pub fn load_board_appearance(&self, project_id: &str) -> Result<Option<BoardAppearance>, ProjectDatabaseError> {
+        let key = format!("appearance:{}", project_id);
+        
+        match self.db.get(key.as_bytes())
+            .map_err(|e| ProjectDatabaseError::DatabaseRead(e.to_string()))? {
+            Some(value) => bincode::deserialize(&value)
+                .map(Some)
+                .map_err(|e| ProjectDatabaseError::Deserialization(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Save the review annotations of a project, replacing any saved before
+    pub fn save_annotations(&self, project_id: &str, annotations: &[Annotation]) -> Result<(), ProjectDatabaseError> {
+        let key = format!("annotations:{}", project_id);
+        let value = bincode::serialize(annotations)
+            .map_err(|e| ProjectDatabaseError::Serialization(e.to_string()))?;
+        
+        self.db.insert(key.as_bytes(), value)
+            .map_err(|e| ProjectDatabaseError::DatabaseWrite(e.to_string()))?;
+        
+        Ok(())
+    }
+
+    /// Load the review annotations of a project (empty if none were saved)
+    pub fn load_annotations(&self, project_id: &str) -> Result<Vec<Annotation>, ProjectDatabaseError> {
+        let key = format!("annotations:{}", project_id);
+        
+        match self.db.get(key.as_bytes())
+            .map_err(|e| ProjectDatabaseError::DatabaseRead(e.to_string()))? {
+            Some(value) => bincode::deserialize(&value)
+                .map_err(|e| ProjectDatabaseError::Deserialization(e.to_string())),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Update project index for quick listings
     fn update_project_index(&self, metadata: &ProjectMetadata) -> Result<(), ProjectDatabaseError> {
         let mut project_ids: Vec<String> = if let Some(index_data) = self.db.get(b"index:projects")
@@ -235,4 +364,78 @@ pub fn generate_project_id() -> String {
         .as_millis();
     
     format!("proj_{}", timestamp)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_temporary() -> ProjectDatabase {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        ProjectDatabase { db }
+    }
+
+    fn metadata(id: &str) -> ProjectMetadata {
+        ProjectMetadata {
+            id: id.to_string(),
+            name: "Board".to_string(),
+            description: String::new(),
+            pcb_file_path: PathBuf::from("board.kicad_pcb"),
+            created_at: Utc::now(),
+            last_modified: Utc::now(),
+            version: "1.0".to_string(),
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_load_unversioned_original_record() {
+        let database = open_temporary();
+        // bincode writes structs as their fields in order, so a tuple stands in
+        // for a record saved with the original component layout
+        let component = ("1", "R1", "Resistor", 10.0f64, -5.0f64, 90.0f64, "10k", "R_0603");
+        let record = bincode::serialize(&(metadata("p1"), vec![component], "notes")).unwrap();
+        database.db.insert(b"project:p1", record).unwrap();
+
+        let project = database.load_project("p1").unwrap().unwrap();
+        assert_eq!(project.notes, "notes");
+        let component = &project.bom_components[0];
+        assert_eq!(component.reference, "R1");
+        assert_eq!(component.footprint, "R_0603");
+        assert!(component.layer.is_empty());
+        assert!(!component.exclude_from_bom);
+        assert!(component.fields.is_empty());
+    }
+
+    #[test]
+    fn test_schema_version_round_trip() {
+        let database = open_temporary();
+        let mut component = BomComponent::new("U1".to_string());
+        component.layer = "F.Cu".to_string();
+        component.do_not_populate = true;
+        component.fields.insert("MPN".to_string(), "STM32F103".to_string());
+        let project = ProjectData { metadata: metadata("p2"), bom_components: vec![component], notes: String::new() };
+        database.save_project(&project).unwrap();
+        assert_eq!(database.load_schema_version("p2").unwrap(), Some(PROJECT_SCHEMA_VERSION));
+
+        let loaded = database.load_project("p2").unwrap().unwrap();
+        assert_eq!(loaded.bom_components[0].layer, "F.Cu");
+        assert!(loaded.bom_components[0].do_not_populate);
+        assert_eq!(loaded.bom_components[0].fields["MPN"], "STM32F103");
+
+        // An original record with its version key migrates from schema 0
+        let component = ("1", "C1", "Capacitor", 1.0f64, 2.0f64, 0.0f64, "100n", "C_0402");
+        let record = bincode::serialize(&(metadata("p3"), vec![component], "")).unwrap();
+        database.db.insert(b"project:p3", record).unwrap();
+        database.db.insert(b"schema:p3", bincode::serialize(&0u32).unwrap()).unwrap();
+        let loaded = database.load_project("p3").unwrap().unwrap();
+        assert_eq!(loaded.bom_components[0].reference, "C1");
+        assert!(loaded.bom_components[0].layer.is_empty());
+
+        database.db.insert(b"schema:p3", bincode::serialize(&(PROJECT_SCHEMA_VERSION + 1)).unwrap()).unwrap();
+        assert!(database.load_project("p3").is_err());
+
+        database.delete_project("p2").unwrap();
+        assert_eq!(database.load_schema_version("p2").unwrap(), None);
+    }
+}
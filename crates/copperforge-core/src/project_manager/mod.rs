@@ -1,8 +1,12 @@
 pub mod database;
 pub mod bom;
+pub mod appearance;
+pub mod annotations;
 
 use database::{ProjectDatabase, ProjectData, ProjectMetadata, generate_project_id, ProjectDatabaseError};
 use bom::BomComponent;
+use appearance::BoardAppearance;
+use annotations::Annotation;
 use std::path::{Path, PathBuf};
 use chrono::Utc;
 
@@ -188,6 +192,50 @@ impl ProjectManagerState {
         }
     }
 
+    /// Appearance saved for the current project, if any
+    pub fn load_board_appearance(&self) -> Option<BoardAppearance> {
+        let project = self.current_project.as_ref()?;
+        self.database.as_ref()?
+            .load_board_appearance(&project.metadata.id)
+            .ok()
+            .flatten()
+    }
+
+    /// Save the appearance for the current project
+    pub fn save_board_appearance(&self, appearance: &BoardAppearance) -> Result<(), ProjectDatabaseError> {
+        let Some(ref project) = self.current_project else {
+            return Err(ProjectDatabaseError::DatabaseRead("No current project loaded".to_string()));
+        };
+        if let Some(ref database) = self.database {
+            database.save_board_appearance(&project.metadata.id, appearance)
+        } else {
+            Err(ProjectDatabaseError::DatabaseRead("Database not initialized".to_string()))
+        }
+    }
+
+    /// Review annotations saved for the current project
+    pub fn load_annotations(&self) -> Result<Vec<Annotation>, ProjectDatabaseError> {
+        let Some(ref project) = self.current_project else {
+            return Ok(Vec::new());
+        };
+        match self.database {
+            Some(ref database) => database.load_annotations(&project.metadata.id),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Save the review annotations for the current project
+    pub fn save_annotations(&self, annotations: &[Annotation]) -> Result<(), ProjectDatabaseError> {
+        let Some(ref project) = self.current_project else {
+            return Err(ProjectDatabaseError::DatabaseRead("No current project loaded".to_string()));
+        };
+        if let Some(ref database) = self.database {
+            database.save_annotations(&project.metadata.id, annotations)
+        } else {
+            Err(ProjectDatabaseError::DatabaseRead("Database not initialized".to_string()))
+        }
+    }
+
     /// Reset create dialog
     pub fn reset_create_dialog(&mut self) {
         self.show_create_dialog = false;
@@ -1,5 +1,7 @@
 #![allow(dead_code)]
 use crate::DemoLensApp;
+use crate::export::{export_bom, BomColumn, BomExportFormat, BomExportOptions};
+use crate::export::bom::custom_field_names;
 use crate::ecs::{UnitsResource, mm_to_nm, nm_to_mils};
 use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState};
 use egui_lens::LogColors;
@@ -41,6 +43,10 @@ pub struct BomPanelState {
     // Cross-probing
     pub selected_component: Value<Option<BomComponent>>,
     pub cross_probe_signal: Signal<BomComponent>,
+    
+    // Export settings
+    pub export_options: BomExportOptions,
+    pub new_field_name: String,
 }
 
 impl BomPanelState {
@@ -106,6 +112,8 @@ impl BomPanelState {
             last_info,
             selected_component: Value::new(None),
             cross_probe_signal,
+            export_options: BomExportOptions::default(),
+            new_field_name: String::new(),
         };
         
         (state, slot_to_backend, signal_from_backend, cross_probe_slot)
@@ -292,6 +300,10 @@ fn try_fetch_components_blocking() -> Result<Vec<BomComponent>, String> {
                     orientation: fp.rotation,
                     value: fp.value.clone(),
                     footprint: fp.footprint_name.clone(),
+                    layer: fp.layer.clone(),
+                    exclude_from_bom: fp.exclude_from_bom,
+                    do_not_populate: fp.do_not_populate,
+                    fields: fp.fields.clone(),
                 };
                 components.push(component);
             }
@@ -480,6 +492,9 @@ pub fn show_bom_panel(
             ui.label(format!("Components: {}/{}", filtered_count, total_count));
         });
         
+        // Export controls
+        show_bom_export_controls(ui, bom_state, &logger);
+        
         // Last update time
         let connection_status = bom_state.connection_status.lock().unwrap().clone();
         if matches!(connection_status, ConnectionStatus::Connected) {
@@ -507,6 +522,80 @@ pub fn show_bom_panel(
     }
 }
 
+/// Grouped BOM export: format, columns and custom fields
+fn show_bom_export_controls(ui: &mut egui::Ui, bom_state: &mut BomPanelState, logger: &ReactiveEventLogger) {
+    let options = &mut bom_state.export_options;
+    
+    ui.horizontal(|ui| {
+        ui.label("Export:");
+        egui::ComboBox::from_id_salt("bom_export_format")
+            .selected_text(options.format.display_name())
+            .show_ui(ui, |ui| {
+                for format in BomExportFormat::all() {
+                    ui.selectable_value(&mut options.format, format, format.display_name());
+                }
+            });
+        
+        ui.checkbox(&mut options.include_dnp, "Include DNP");
+        
+        ui.menu_button("Columns", |ui| {
+            let components = bom_state.components.lock().unwrap();
+            let mut available = BomColumn::standard();
+            available.extend(custom_field_names(&components).into_iter().map(BomColumn::Field));
+            drop(components);
+            for column in options.columns.iter().filter(|c| matches!(c, BomColumn::Field(_))) {
+                if !available.contains(column) {
+                    available.push(column.clone());
+                }
+            }
+            
+            for column in available {
+                let mut enabled = options.columns.contains(&column);
+                if ui.checkbox(&mut enabled, column.header()).changed() {
+                    if enabled {
+                        options.columns.push(column);
+                    } else {
+                        options.columns.retain(|c| *c != column);
+                    }
+                }
+            }
+            
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut bom_state.new_field_name)
+                    .hint_text("Custom field")
+                    .desired_width(100.0));
+                let name = bom_state.new_field_name.trim().to_string();
+                if ui.button("➕").clicked() && !name.is_empty() {
+                    let column = BomColumn::Field(name);
+                    if !options.columns.contains(&column) {
+                        options.columns.push(column);
+                    }
+                    bom_state.new_field_name.clear();
+                }
+            });
+        });
+        
+        if ui.button("💾 Export BOM").clicked() {
+            let format = options.format;
+            if let Some(output_path) = rfd::FileDialog::new()
+                .add_filter(format.display_name(), &[format.extension()])
+                .set_file_name(format!("bom.{}", format.extension()))
+                .save_file()
+            {
+                let components = bom_state.components.lock().unwrap().clone();
+                match export_bom(&components, &output_path, options) {
+                    Ok(lines) => logger.log_info(&format!(
+                        "💾 Exported BOM with {} lines ({} components) to {}",
+                        lines, components.len(), output_path.display()
+                    )),
+                    Err(e) => logger.log_error(&format!("BOM export failed: {}", e)),
+                }
+            }
+        }
+    });
+}
+
 /// Show the BOM table using TableBuilder with cross-probing support
 fn show_bom_table_optimized(ui: &mut egui::Ui, components: &[BomComponent], filter_text: &str, is_mils: bool, selected_component: &mut Option<BomComponent>, cross_probe_signal: &Signal<BomComponent>) {
     let filter_lower = filter_text.to_lowercase();
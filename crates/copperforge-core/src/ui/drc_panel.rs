@@ -1,5 +1,6 @@
 use crate::{DemoLensApp, project::constants::LOG_TYPE_DRC, ecs::LayerType};
-use crate::drc_operations::TraceQualityType;
+use crate::drc_operations::{TraceQualityType, DrcParameterValue};
+use crate::drc_operations::types::DrcRules;
 use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
 use egui_mobius_reactive::Dynamic;
 
@@ -24,14 +25,8 @@ pub fn show_drc_panel<'a>(
                     logger.log_info(&format!("Using {} ruleset", ruleset));
                     logger.log_info("Analyzing Gerber files with imageproc trace detection");
                     
-                    // Run the actual DRC check (now includes OpenCV)
-                    // Convert ECS layers to legacy format for DRC compatibility
-                    let legacy_layers = convert_ecs_to_legacy_layers(&mut app.ecs_world);
-                    let violations = crate::drc_operations::run_simple_drc_check(
-                        &legacy_layers,
-                        &app.drc_manager.rules,
-                        &mut app.drc_manager.trace_quality_issues
-                    );
+                    // Run the DRC systems and registered rules over the ECS layer entities
+                    let violations = app.drc_manager.run_drc(&mut app.ecs_world);
                     
                     logger.log_info("Running imageproc edge detection and morphological analysis");
                     logger.log_info("Checking trace widths with Canny edge detection");
@@ -148,6 +143,15 @@ pub fn show_drc_panel<'a>(
                 }
             });
             
+            // Dead copper island area (always mm²)
+            ui.horizontal(|ui| {
+                ui.label("Min Island Area:");
+                ui.add(egui::DragValue::new(&mut app.drc_manager.rules.min_island_area)
+                    .speed(0.05)
+                    .range(0.0..=100.0)
+                    .suffix(" mm²"));
+            });
+            
             ui.add_space(8.0);
             
             // Preset buttons
@@ -202,14 +206,8 @@ pub fn show_drc_panel<'a>(
                     logger.log_info("Starting Design Rule Check with custom settings");
                     logger.log_info("Analyzing Gerber files...");
                     
-                    // Run the actual DRC check (now includes OpenCV)
-                    // Convert ECS layers to legacy format for DRC compatibility
-                    let legacy_layers = convert_ecs_to_legacy_layers(&mut app.ecs_world);
-                    let violations = crate::drc_operations::run_simple_drc_check(
-                        &legacy_layers,
-                        &app.drc_manager.rules,
-                        &mut app.drc_manager.trace_quality_issues
-                    );
+                    // Run the DRC systems and registered rules over the ECS layer entities
+                    let violations = app.drc_manager.run_drc(&mut app.ecs_world);
                     
                     logger.log_info("Running imageproc edge detection and morphological analysis");
                     logger.log_info("Checking trace widths with Canny edge detection");
@@ -297,6 +295,182 @@ pub fn show_drc_panel<'a>(
     
     ui.add_space(4.0);
     
+    // Registered (pluggable) DRC rules
+    if !app.drc_manager.rule_registry.is_empty() {
+        egui::CollapsingHeader::new("Additional Rules")
+            .default_open(false)
+            .show(ui, |ui| {
+                ui.add_space(4.0);
+                let use_mils = app.drc_manager.rules.use_mils;
+                
+                for entry in app.drc_manager.rule_registry.iter_mut() {
+                    let rule_name = entry.rule.name().to_string();
+                    ui.horizontal(|ui| {
+                        if ui.checkbox(&mut entry.enabled, &rule_name).changed() {
+                            logger.log_custom(
+                                LOG_TYPE_DRC,
+                                &format!("{} DRC rule '{}'", if entry.enabled { "Enabled" } else { "Disabled" }, rule_name)
+                            );
+                        }
+                    });
+                    
+                    let description = entry.rule.description();
+                    if !description.is_empty() {
+                        ui.label(egui::RichText::new(description).color(egui::Color32::GRAY));
+                    }
+                    
+                    // Parameter editors from the rule's schema
+                    ui.add_enabled_ui(entry.enabled, |ui| {
+                        for spec in entry.rule.parameters() {
+                            let Some(value) = entry.parameters.0.get_mut(&spec.key) else {
+                                continue;
+                            };
+                            let response = ui.horizontal(|ui| {
+                                ui.label(format!("{}:", spec.label));
+                                match value {
+                                    DrcParameterValue::Length(mm) => {
+                                        let mut display_value = if use_mils { DrcRules::mm_to_mils(*mm) } else { *mm };
+                                        if ui.add(egui::DragValue::new(&mut display_value)
+                                            .speed(if use_mils { 0.1 } else { 0.01 })
+                                            .suffix(if use_mils { " mils" } else { " mm" })).changed() {
+                                            *mm = if use_mils { DrcRules::mils_to_mm(display_value) } else { display_value };
+                                        }
+                                    }
+                                    DrcParameterValue::Number(number) => {
+                                        ui.add(egui::DragValue::new(number).speed(0.1));
+                                    }
+                                    DrcParameterValue::Integer(integer) => {
+                                        ui.add(egui::DragValue::new(integer));
+                                    }
+                                    DrcParameterValue::Flag(flag) => {
+                                        ui.checkbox(flag, "");
+                                    }
+                                    DrcParameterValue::Text(text) => {
+                                        ui.text_edit_singleline(text);
+                                    }
+                                }
+                            }).response;
+                            if !spec.description.is_empty() {
+                                response.on_hover_text(&spec.description);
+                            }
+                        }
+                    });
+                    ui.add_space(4.0);
+                }
+            });
+        
+        ui.add_space(4.0);
+    }
+    
+    // Copper connectivity (islands and nets extracted from copper + plated drills)
+    egui::CollapsingHeader::new("Connectivity")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.add_space(4.0);
+            if ui.button("🔗 Extract Connectivity").clicked() {
+                match crate::drc_operations::extract_connectivity_system(&mut app.ecs_world) {
+                    Ok((island_count, net_count)) => {
+                        logger.log_custom(
+                            LOG_TYPE_DRC,
+                            &format!("🔗 Extracted {} nets from {} copper islands", net_count, island_count)
+                        );
+                    }
+                    Err(e) => {
+                        logger.log_warning(&format!("Cannot extract connectivity: {}", e));
+                    }
+                }
+            }
+            
+            let nets = crate::drc_operations::get_extracted_nets(&mut app.ecs_world);
+            if !nets.is_empty() {
+                let multilayer = nets.iter().filter(|net| net.layers.len() > 1).count();
+                let single_island = nets.iter().filter(|net| net.islands.len() == 1 && net.hole_count == 0).count();
+                ui.label(format!("Nets: {} ({} multi-layer)", nets.len(), multilayer));
+                ui.label(format!("Single-island nets without holes: {}", single_island));
+            }
+            ui.add_space(4.0);
+        });
+    
+    ui.add_space(4.0);
+    
+    // IPC-D-356 netlist import and comparison against extracted connectivity
+    egui::CollapsingHeader::new("IPC-D-356 Netlist")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.button("📂 Load Netlist...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("IPC-D-356", &["ipc", "d356", "net", "txt"])
+                        .set_title("Select IPC-D-356 Netlist")
+                        .pick_file()
+                    {
+                        match std::fs::read_to_string(&path)
+                            .map_err(|e| e.to_string())
+                            .and_then(|content| crate::netlist::parse_ipc356(&content))
+                        {
+                            Ok(netlist) => {
+                                let net_count = netlist.nets().len();
+                                let point_count = crate::netlist::load_netlist_system(&mut app.ecs_world, netlist);
+                                logger.log_info(&format!(
+                                    "📋 Loaded {} test points in {} nets from {}",
+                                    point_count, net_count, path.display()
+                                ));
+                            }
+                            Err(e) => {
+                                logger.log_error(&format!("Failed to load IPC-D-356 netlist: {}", e));
+                            }
+                        }
+                    }
+                }
+                
+                if ui.button("🔍 Compare with Copper").clicked() {
+                    match crate::netlist::compare_netlist_system(&mut app.ecs_world) {
+                        Ok(comparison) => {
+                            let test_points = crate::netlist::get_netlist_test_points(&mut app.ecs_world);
+                            let label = |index: &usize| test_points[*index].label();
+                            
+                            if comparison.is_clean() {
+                                logger.log_custom(
+                                    LOG_TYPE_DRC,
+                                    &format!("✅ Netlist matches copper ({} nets, {} test points)", comparison.checked_nets, comparison.checked_points)
+                                );
+                            }
+                            for open in &comparison.opens {
+                                let pieces: Vec<String> = open.fragments.iter()
+                                    .map(|fragment| fragment.iter().map(label).collect::<Vec<_>>().join(", "))
+                                    .collect();
+                                logger.log_error(&format!("❌ Open in net {}: {} pieces [{}]", open.net, pieces.len(), pieces.join("] [")));
+                            }
+                            for short in &comparison.shorts {
+                                logger.log_error(&format!("❌ Short between nets {}", short.nets.join(", ")));
+                            }
+                            for index in &comparison.missing_copper {
+                                let point = &test_points[*index];
+                                logger.log_warning(&format!("⚠️  No copper under {} (net {})", point.label(), point.net));
+                            }
+                        }
+                        Err(e) => {
+                            logger.log_warning(&format!("Cannot compare netlist: {}", e));
+                        }
+                    }
+                }
+            });
+            
+            let test_points = crate::netlist::get_netlist_test_points(&mut app.ecs_world);
+            if !test_points.is_empty() {
+                ui.label(format!("Test points: {}", test_points.len()));
+                ui.checkbox(&mut app.show_netlist_overlay, "Show test points in viewer");
+                if ui.button("🗑 Clear Netlist").clicked() {
+                    crate::netlist::clear_netlist_system(&mut app.ecs_world);
+                    logger.log_info("Cleared IPC-D-356 netlist");
+                }
+            }
+            ui.add_space(4.0);
+        });
+    
+    ui.add_space(4.0);
+    
     // Trace Quality Analysis section
     egui::CollapsingHeader::new("Trace Quality Analysis")
         .default_open(true)
@@ -331,6 +505,17 @@ pub fn show_drc_panel<'a>(
                     } else { 
                         egui::Color32::from_rgb(46, 204, 113) 
                     }));
+                    
+                ui.separator();
+                    
+                let dead_count = app.drc_manager.dead_copper.len();
+                ui.label("Dead Copper:");
+                ui.label(egui::RichText::new(&format!("{}", dead_count))
+                    .color(if dead_count > 0 { 
+                        egui::Color32::from_rgb(231, 76, 60) 
+                    } else { 
+                        egui::Color32::from_rgb(46, 204, 113) 
+                    }));
             });
             
             ui.add_space(8.0);
@@ -340,14 +525,8 @@ pub fn show_drc_panel<'a>(
                 if ui.button("🔍 Analyze Corners").clicked() {
                     logger.log_info("Starting trace quality analysis...");
                     
-                    // Run the DRC check which includes quality analysis
-                    // Convert ECS layers to legacy format for DRC compatibility
-                    let legacy_layers = convert_ecs_to_legacy_layers(&mut app.ecs_world);
-                    let _violations = crate::drc_operations::run_simple_drc_check(
-                        &legacy_layers,
-                        &app.drc_manager.rules,
-                        &mut app.drc_manager.trace_quality_issues
-                    );
+                    // Run the DRC systems which include quality analysis
+                    let _violations = app.drc_manager.run_drc(&mut app.ecs_world);
                     
                     let corner_issues = app.drc_manager.trace_quality_issues.iter()
                         .filter(|issue| matches!(issue.issue_type, TraceQualityType::SharpCorner))
@@ -368,7 +547,17 @@ pub fn show_drc_panel<'a>(
                         }
                     }
                     
-                    if corner_issues == 0 && jog_issues == 0 {
+                    // Log dead copper islands
+                    for island in &app.drc_manager.dead_copper {
+                        logger.log_custom(
+                            LOG_TYPE_DRC,
+                            &format!("🏝 Dead copper on {} at ({:.2}, {:.2}): {:.3} mm²{}",
+                                island.layer, island.location.0, island.location.1, island.area,
+                                if island.below_threshold { " (below minimum area)" } else { "" })
+                        );
+                    }
+                    
+                    if corner_issues == 0 && jog_issues == 0 && app.drc_manager.dead_copper.is_empty() {
                         logger.log_info("✅ No trace quality issues found - excellent routing!");
                     }
                 }
@@ -461,6 +650,16 @@ pub fn show_drc_panel<'a>(
                 }
             });
             
+            ui.horizontal(|ui| {
+                if !app.drc_manager.dead_copper.is_empty() {
+                    if ui.button("🗑 Clear Dead Copper Overlay").clicked() {
+                        app.drc_manager.dead_copper.clear();
+                        logger.log_info("Cleared dead copper visualization");
+                    }
+                    ui.label(format!("({} islands)", app.drc_manager.dead_copper.len()));
+                }
+            });
+            
             ui.add_space(4.0);
             
             // Show detailed issues if any exist
@@ -494,54 +693,3 @@ pub fn show_drc_panel<'a>(
             }
         });
 }
-
-/// Helper function to convert ECS layers to legacy format for DRC compatibility
-use std::collections::HashMap;
-use gerber_viewer::GerberLayer;
-
-/// Simple LayerInfo for DRC compatibility (replaces layer_operations::LayerInfo)
-#[derive(Debug)]
-pub struct LayerInfo {
-    pub layer_type: LayerType,
-    pub gerber_layer: Option<GerberLayer>,
-    pub raw_gerber_data: Option<String>,
-    pub visible: bool,
-    pub color: egui::Color32,
-}
-
-impl LayerInfo {
-    pub fn new(layer_type: LayerType, gerber_layer: Option<GerberLayer>, raw_gerber_data: Option<String>, visible: bool) -> Self {
-        Self {
-            layer_type,
-            gerber_layer,
-            raw_gerber_data,
-            visible,
-            color: layer_type.color(),
-        }
-    }
-}
-
-fn convert_ecs_to_legacy_layers(world: &mut bevy_ecs::world::World) -> HashMap<LayerType, LayerInfo> {
-    let mut legacy_layers = HashMap::new();
-    
-    for layer_type in LayerType::all() {
-        if let Some((_entity, layer_info, gerber_data, visibility)) = crate::ecs::get_layer_data(world, layer_type) {
-            // Create legacy LayerInfo from ECS data
-            let mut legacy_layer_info = LayerInfo::new(
-                layer_info.layer_type,
-                Some(gerber_data.0.clone()),
-                None, // Raw gerber data not needed for DRC
-                visibility.visible,
-            );
-            
-            // Get color from ECS render properties
-            if let Some(render_props) = crate::ecs::get_layer_render_properties(world, layer_type) {
-                legacy_layer_info.color = render_props.color;
-            }
-            
-            legacy_layers.insert(layer_type, legacy_layer_info);
-        }
-    }
-    
-    legacy_layers
-}
\ No newline at end of file
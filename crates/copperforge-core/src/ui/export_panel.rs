@@ -0,0 +1,767 @@
+use crate::DemoLensApp;
+use std::collections::HashMap;
+use crate::export::{
+    build_fab_package, export_drawings_pdf, export_dxf, export_gerbers, export_placement, export_realistic_png, DrawingOptions,
+    DxfFeatureMode, DxfOptions, FabHouse, GerberExportOptions, PackageOptions, PageSize, PlacementFormat, PlacementOptions, PlacementOrigin, PlacementUnit, PngExporter,
+    RasterOptions, RealisticOptions, RotationOffset, SvgExporter, SvgOptions, TitleBlock, ViewExportOptions, ViewRegion,
+};
+use crate::ecs::{stackup_notes, BoardStackup, LayerType, Side};
+use crate::ipc2581::{export_ipc2581, Ipc2581Revision};
+use crate::geometry::Antialias;
+use crate::ui::realistic_view::{save_project_appearance, show_appearance_controls};
+use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
+use egui_mobius_reactive::Dynamic;
+
+/// Output file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    #[default]
+    Png,
+    Svg,
+    /// Fabrication and assembly drawings
+    Pdf,
+    /// Pick-and-place (centroid) file
+    Placement,
+    /// Zip of everything needed to order boards
+    Package,
+    /// Board as manufactured, as PNG
+    Realistic,
+    /// Outline, layers and drills for mechanical CAD
+    Dxf,
+    /// Layers rewritten as RS-274X with the view transform applied
+    Gerber,
+    /// Whole board with stackup and BOM in one IPC-2581 file
+    Ipc2581,
+}
+
+/// What a PNG or SVG export covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportScope {
+    /// All visible layers in one file
+    #[default]
+    Composite,
+    /// One file per layer
+    PerLayer,
+    /// The area shown in the viewer, with overlays
+    CurrentView,
+}
+
+/// Export settings kept between frames
+#[derive(Clone, Default)]
+pub struct ExportPanelState {
+    pub format: ExportFormat,
+    pub raster: RasterOptions,
+    pub svg: SvgOptions,
+    pub scope: ExportScope,
+    pub view: ViewExportOptions,
+    pub drawings: DrawingOptions,
+    pub placement: PlacementOptions,
+    /// Rotation offset table per fab layout, filled with defaults on first use
+    pub rotation_tables: HashMap<PlacementFormat, Vec<RotationOffset>>,
+    pub package: PackageOptions,
+    /// Realistic render settings, the appearance is saved with the project
+    pub realistic: RealisticOptions,
+    pub dxf: DxfOptions,
+    pub gerber: GerberExportOptions,
+    pub ipc2581_revision: Ipc2581Revision,
+}
+
+pub fn show_export_panel<'a>(
+    ui: &mut egui::Ui,
+    app: &'a mut DemoLensApp,
+    logger_state: &'a Dynamic<ReactiveEventLoggerState>,
+    log_colors: &'a Dynamic<LogColors>,
+) {
+    let logger = ReactiveEventLogger::with_colors(logger_state, log_colors);
+
+    egui::CollapsingHeader::new("Export")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Format:");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Png, "PNG");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Svg, "SVG");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Pdf, "PDF Drawings");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Placement, "Pick & Place");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Package, "Fab Package");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Realistic, "Realistic");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Dxf, "DXF");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Gerber, "Gerber");
+                ui.selectable_value(&mut app.export_state.format, ExportFormat::Ipc2581, "IPC-2581");
+            });
+
+            if matches!(app.export_state.format, ExportFormat::Png | ExportFormat::Svg) {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut app.export_state.scope, ExportScope::Composite, "Composite");
+                    ui.selectable_value(&mut app.export_state.scope, ExportScope::PerLayer, "Per Layer");
+                    ui.selectable_value(&mut app.export_state.scope, ExportScope::CurrentView, "Current View");
+                });
+            }
+
+            let current_view = app.export_state.scope == ExportScope::CurrentView;
+            match app.export_state.format {
+                ExportFormat::Png | ExportFormat::Svg if current_view => show_view_options(ui, app),
+                ExportFormat::Png => show_raster_options(ui, &mut app.export_state.raster),
+                ExportFormat::Svg => {
+                    ui.horizontal(|ui| {
+                        let mut transparent = app.export_state.svg.background.is_none();
+                        if ui.checkbox(&mut transparent, "Transparent background").changed() {
+                            app.export_state.svg.background = if transparent { None } else { Some(egui::Color32::BLACK) };
+                        }
+                        if let Some(background) = app.export_state.svg.background.as_mut() {
+                            ui.color_edit_button_srgba(background);
+                        }
+                    });
+                }
+                ExportFormat::Pdf => show_drawing_options(ui, app),
+                ExportFormat::Placement => show_placement_options(ui, app),
+                ExportFormat::Package => show_package_options(ui, app),
+                ExportFormat::Realistic => show_realistic_options(ui, app, &logger),
+                ExportFormat::Dxf => show_dxf_options(ui, app),
+                ExportFormat::Gerber => show_gerber_options(ui, app),
+                ExportFormat::Ipc2581 => {
+                    ui.horizontal(|ui| {
+                        ui.label("Revision:");
+                        for revision in Ipc2581Revision::all() {
+                            ui.selectable_value(&mut app.export_state.ipc2581_revision, revision, revision.code());
+                        }
+                    });
+                }
+            }
+
+            ui.add_space(4.0);
+
+            match app.export_state.format {
+                ExportFormat::Png => {
+                    if ui.button("📷 Export PNG").clicked() {
+                        export_png(app, &logger);
+                    }
+                }
+                ExportFormat::Svg => {
+                    if ui.button("🖊 Export SVG").clicked() {
+                        export_svg(app, &logger);
+                    }
+                }
+                ExportFormat::Pdf => {
+                    if ui.button("📐 Export Drawings").clicked() {
+                        export_drawings(app, &logger);
+                    }
+                }
+                ExportFormat::Placement => {
+                    if ui.button("📍 Export Pick & Place").clicked() {
+                        export_pick_and_place(app, &logger);
+                    }
+                }
+                ExportFormat::Package => {
+                    if ui.button("📦 Build Fab Package").clicked() {
+                        export_package(app, &logger);
+                    }
+                }
+                ExportFormat::Realistic => {
+                    if ui.button("🎨 Export Realistic PNG").clicked() {
+                        export_realistic(app, &logger);
+                    }
+                }
+                ExportFormat::Dxf => {
+                    if ui.button("📏 Export DXF").clicked() {
+                        export_mechanical_dxf(app, &logger);
+                    }
+                }
+                ExportFormat::Gerber => {
+                    if ui.button("🔁 Export Gerber").clicked() {
+                        export_transformed_gerbers(app, &logger);
+                    }
+                }
+                ExportFormat::Ipc2581 => {
+                    if ui.button("🔗 Export IPC-2581").clicked() {
+                        export_board_ipc2581(app, &logger);
+                    }
+                }
+            }
+        });
+}
+
+fn show_raster_options(ui: &mut egui::Ui, raster: &mut RasterOptions) {
+    ui.horizontal(|ui| {
+        ui.label("Resolution:");
+        ui.add(egui::DragValue::new(&mut raster.dpi)
+            .speed(10.0)
+            .range(50.0..=4800.0)
+            .suffix(" DPI"));
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Anti-aliasing:");
+        for antialias in Antialias::all() {
+            ui.selectable_value(&mut raster.antialias, antialias, antialias.display_name());
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Background:");
+        ui.color_edit_button_srgba(&mut raster.background);
+    });
+}
+
+fn show_view_options(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let region = app.viewer_rect.map(|viewport| ViewRegion::from_view_state(&app.view_state, &viewport));
+    let svg = app.export_state.format == ExportFormat::Svg;
+    let options = &mut app.export_state.view;
+
+    ui.horizontal(|ui| {
+        ui.label("Width:");
+        ui.add(egui::DragValue::new(&mut options.width)
+            .speed(10.0)
+            .range(100..=20000)
+            .suffix(" px"));
+        if let Some(region) = region.filter(|region| region.width() > 0.0 && region.height() > 0.0) {
+            let (width, height) = region.pixel_size(options.width);
+            ui.label(egui::RichText::new(format!("{} x {} px", width, height)).weak());
+        }
+    });
+
+    if !svg {
+        ui.horizontal(|ui| {
+            ui.label("Anti-aliasing:");
+            for antialias in Antialias::all() {
+                ui.selectable_value(&mut options.antialias, antialias, antialias.display_name());
+            }
+        });
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Background:");
+        ui.color_edit_button_srgba(&mut options.background);
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Overlays:");
+        ui.checkbox(&mut options.grid, "Grid");
+        ui.checkbox(&mut options.drc_markers, "DRC Markers");
+        ui.checkbox(&mut options.measurements, "Measurements")
+            .on_hover_text("Labels are only written to SVG");
+    });
+}
+
+fn export_current_view(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.view;
+    let svg = app.export_state.format == ExportFormat::Svg;
+    let (filter, extension) = if svg { ("SVG image", "svg") } else { ("PNG image", "png") };
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter(filter, &[extension])
+        .set_file_name(format!("view.{}", extension))
+        .save_file()
+    else {
+        return;
+    };
+
+    let result = if svg {
+        SvgExporter::export_current_view(app, &output_path, None, &options)
+    } else {
+        PngExporter::export_current_view(app, &output_path, None, &options)
+    };
+    match result {
+        Ok((width, height)) => logger.log_info(&format!("🖼 Exported current view at {}x{} px to {}", width, height, output_path.display())),
+        Err(e) => logger.log_error(&format!("View export failed: {}", e)),
+    }
+}
+
+fn export_png(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.raster;
+
+    if app.export_state.scope == ExportScope::CurrentView {
+        export_current_view(app, logger);
+    } else if app.export_state.scope == ExportScope::PerLayer {
+        let Some(output_dir) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+        match PngExporter::export_layer_pngs(app, &output_dir, &options) {
+            Ok(files) => {
+                logger.log_info(&format!("📷 Exported {} layer PNGs at {:.0} DPI to {}", files.len(), options.dpi, output_dir.display()));
+                for file_path in files {
+                    if let Some(filename) = file_path.file_name() {
+                        logger.log_info(&format!("  • {}", filename.to_string_lossy()));
+                    }
+                }
+            }
+            Err(e) => logger.log_error(&format!("PNG export failed: {}", e)),
+        }
+    } else {
+        let Some(output_path) = rfd::FileDialog::new()
+            .add_filter("PNG image", &["png"])
+            .set_file_name("board.png")
+            .save_file()
+        else {
+            return;
+        };
+        match PngExporter::export_composite_png(app, &output_path, &options) {
+            Ok(()) => logger.log_info(&format!("📷 Exported composite PNG at {:.0} DPI to {}", options.dpi, output_path.display())),
+            Err(e) => logger.log_error(&format!("PNG export failed: {}", e)),
+        }
+    }
+}
+
+fn export_svg(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.svg;
+
+    if app.export_state.scope == ExportScope::CurrentView {
+        export_current_view(app, logger);
+    } else if app.export_state.scope == ExportScope::PerLayer {
+        let Some(output_dir) = rfd::FileDialog::new().pick_folder() else {
+            return;
+        };
+        match SvgExporter::export_layer_svgs(app, &output_dir, &options) {
+            Ok(files) => {
+                logger.log_info(&format!("🖊 Exported {} layer SVGs to {}", files.len(), output_dir.display()));
+                for file_path in files {
+                    if let Some(filename) = file_path.file_name() {
+                        logger.log_info(&format!("  • {}", filename.to_string_lossy()));
+                    }
+                }
+            }
+            Err(e) => logger.log_error(&format!("SVG export failed: {}", e)),
+        }
+    } else {
+        let Some(output_path) = rfd::FileDialog::new()
+            .add_filter("SVG image", &["svg"])
+            .set_file_name("board.svg")
+            .save_file()
+        else {
+            return;
+        };
+        match SvgExporter::export_composite_svg(app, &output_path, &options) {
+            Ok(()) => logger.log_info(&format!("🖊 Exported composite SVG to {}", output_path.display())),
+            Err(e) => logger.log_error(&format!("SVG export failed: {}", e)),
+        }
+    }
+}
+
+fn show_drawing_options(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let options = &mut app.export_state.drawings;
+
+    ui.horizontal(|ui| {
+        ui.label("Page:");
+        for page_size in PageSize::all() {
+            ui.selectable_value(&mut options.page_size, page_size, page_size.display_name());
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut options.fabrication, "Fabrication");
+        ui.checkbox(&mut options.assembly_top, "Assembly Top");
+        ui.checkbox(&mut options.assembly_bottom, "Assembly Bottom");
+        ui.checkbox(&mut options.review, "Review Comments")
+            .on_hover_text("Sheet with the review annotations");
+    });
+
+    ui.label("Title block:");
+    egui::Grid::new("drawing_title_block").num_columns(2).show(ui, |ui| {
+        ui.label("Project:");
+        ui.text_edit_singleline(&mut options.title.project);
+        ui.end_row();
+        ui.label("Revision:");
+        ui.text_edit_singleline(&mut options.title.revision);
+        ui.end_row();
+        ui.label("Date:");
+        ui.text_edit_singleline(&mut options.title.date);
+        ui.end_row();
+        ui.label("Company:");
+        ui.text_edit_singleline(&mut options.title.company);
+        ui.end_row();
+    });
+
+    let metadata = app.project_manager_state.as_ref()
+        .and_then(|state| state.current_project.as_ref())
+        .map(|project| project.metadata.clone());
+    if ui.add_enabled(metadata.is_some(), egui::Button::new("Fill from project")).clicked() {
+        if let Some(metadata) = metadata {
+            let company = std::mem::take(&mut app.export_state.drawings.title.company);
+            app.export_state.drawings.title = TitleBlock { company, ..TitleBlock::from_metadata(&metadata) };
+        }
+    }
+
+    ui.label("Fabrication notes:");
+    let mut notes = app.export_state.drawings.fab_notes.join("\n");
+    if ui.add(egui::TextEdit::multiline(&mut notes).hint_text("From the board stackup").desired_rows(4)).changed() {
+        app.export_state.drawings.fab_notes = notes.lines().map(str::to_string).collect();
+    }
+    let stackup_notes = app.ecs_world.get_resource::<BoardStackup>()
+        .map(|stackup| stackup_notes(&stackup.0))
+        .unwrap_or_default();
+    if ui.add_enabled(!stackup_notes.is_empty(), egui::Button::new("Fill from stackup"))
+        .on_disabled_hover_text("Load a stackup from KiCad or a Gerber job file first")
+        .clicked()
+    {
+        app.export_state.drawings.fab_notes = stackup_notes;
+    }
+}
+
+fn export_drawings(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let mut options = app.export_state.drawings.clone();
+    if options.title.project.is_empty() {
+        options.title.project = app.project_manager.get_pcb_path()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+
+    let file_name = if options.title.project.is_empty() {
+        "drawings.pdf".to_string()
+    } else {
+        format!("{}-drawings.pdf", options.title.project)
+    };
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter("PDF document", &["pdf"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+
+    match export_drawings_pdf(app, &output_path, &options) {
+        Ok(components) => {
+            logger.log_info(&format!("📐 Exported drawings to {}", output_path.display()));
+            if components == 0 && (options.assembly_top || options.assembly_bottom) {
+                logger.log_warning("No BOM components loaded, assembly drawings have no reference designators");
+            }
+        }
+        Err(e) => logger.log_error(&format!("Drawing export failed: {}", e)),
+    }
+}
+
+fn show_placement_options(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let state = &mut app.export_state;
+
+    ui.horizontal(|ui| {
+        ui.label("Layout:");
+        for format in PlacementFormat::all() {
+            ui.selectable_value(&mut state.placement.format, format, format.display_name());
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Origin:");
+        ui.selectable_value(&mut state.placement.origin, PlacementOrigin::Board, "Board");
+        ui.add_enabled_ui(app.origin_has_been_set, |ui| {
+            ui.selectable_value(&mut state.placement.origin, PlacementOrigin::User, "User")
+                .on_disabled_hover_text("Set an origin in the view first");
+        });
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Units:");
+        ui.selectable_value(&mut state.placement.unit, PlacementUnit::Millimeters, "mm");
+        ui.selectable_value(&mut state.placement.unit, PlacementUnit::Mils, "mil");
+    });
+
+    ui.checkbox(&mut state.placement.skip_empty_footprints, "Skip components without footprint");
+
+    let format = state.placement.format;
+    let table = state.rotation_tables.entry(format).or_insert_with(|| format.default_rotation_offsets());
+    egui::CollapsingHeader::new(format!("Rotation Offsets ({})", format.display_name()))
+        .default_open(false)
+        .show(ui, |ui| {
+            let mut remove = None;
+            egui::Grid::new("rotation_offsets").num_columns(3).show(ui, |ui| {
+                ui.label("Footprint pattern");
+                ui.label("Offset");
+                ui.end_row();
+                for (index, offset) in table.iter_mut().enumerate() {
+                    ui.text_edit_singleline(&mut offset.pattern);
+                    ui.add(egui::DragValue::new(&mut offset.degrees).speed(90.0).range(-360.0..=360.0).suffix("°"));
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = remove {
+                table.remove(index);
+            }
+            ui.horizontal(|ui| {
+                if ui.button("➕ Add").clicked() {
+                    table.push(RotationOffset { pattern: String::new(), degrees: 0.0 });
+                }
+                if ui.button("Reset").clicked() {
+                    *table = format.default_rotation_offsets();
+                }
+            });
+        });
+}
+
+fn export_pick_and_place(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let mut options = app.export_state.placement.clone();
+    options.rotation_offsets = app.export_state.rotation_tables.get(&options.format)
+        .cloned()
+        .unwrap_or_else(|| options.format.default_rotation_offsets());
+    options.rotation_offsets.retain(|offset| !offset.pattern.trim().is_empty());
+
+    let stem = app.project_manager.get_pcb_path()
+        .and_then(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "board".to_string());
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter("Placement file", &[options.format.extension()])
+        .set_file_name(format!("{}-pos.{}", stem, options.format.extension()))
+        .save_file()
+    else {
+        return;
+    };
+
+    match export_placement(app, &output_path, &options) {
+        Ok(count) => logger.log_info(&format!(
+            "📍 Exported {} placements ({}) to {}",
+            count,
+            options.format.display_name(),
+            output_path.display()
+        )),
+        Err(e) => logger.log_error(&format!("Pick & place export failed: {}", e)),
+    }
+}
+
+fn show_package_options(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let options = &mut app.export_state.package;
+
+    ui.horizontal(|ui| {
+        ui.label("Fab:");
+        for fab in FabHouse::all() {
+            ui.selectable_value(&mut options.fab, fab, fab.display_name());
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Revision:");
+        ui.add(egui::TextEdit::singleline(&mut options.revision)
+            .hint_text("project version")
+            .desired_width(80.0));
+    });
+
+    ui.checkbox(&mut options.include_assembly, "Include centroid and BOM");
+    ui.checkbox(&mut options.include_drc_report, "Include DRC report");
+
+    ui.label("Readme notes:");
+    ui.add(egui::TextEdit::multiline(&mut options.notes).desired_rows(3));
+}
+
+fn export_package(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.package.clone();
+    let Some(output_dir) = rfd::FileDialog::new().pick_folder() else {
+        return;
+    };
+
+    match build_fab_package(app, &output_dir, &options) {
+        Ok(report) => {
+            logger.log_info(&format!(
+                "📦 Built {} package with {} files: {}",
+                options.fab.display_name(),
+                report.files.len(),
+                report.archive_path.display()
+            ));
+            for file in &report.files {
+                logger.log_info(&format!("  • {}", file));
+            }
+            for warning in &report.warnings {
+                logger.log_warning(&format!("⚠️  {}", warning));
+            }
+        }
+        Err(e) => logger.log_error(&format!("Fab package failed: {}", e)),
+    }
+}
+
+fn show_realistic_options(ui: &mut egui::Ui, app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = &mut app.export_state.realistic;
+
+    ui.horizontal(|ui| {
+        ui.label("Side:");
+        ui.selectable_value(&mut options.side, Side::Top, "Top");
+        ui.selectable_value(&mut options.side, Side::Bottom, "Bottom");
+        ui.add_enabled(options.side == Side::Bottom, egui::Checkbox::new(&mut options.mirror_bottom, "Mirror"));
+    });
+
+    show_raster_options(ui, &mut app.export_state.raster);
+
+    ui.label("Appearance:");
+    if show_appearance_controls(ui, &mut app.export_state.realistic.appearance) {
+        save_project_appearance(app, logger);
+    }
+}
+
+fn export_realistic(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.realistic;
+    let raster = app.export_state.raster;
+    let side_name = match options.side {
+        Side::Top => "top",
+        Side::Bottom => "bottom",
+    };
+
+    let stem = app.project_manager.get_pcb_path()
+        .and_then(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "board".to_string());
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter("PNG image", &["png"])
+        .set_file_name(format!("{}-{}.png", stem, side_name))
+        .save_file()
+    else {
+        return;
+    };
+
+    match export_realistic_png(app, &output_path, options.side, &options.appearance, &raster, options.mirror_bottom) {
+        Ok(()) => logger.log_info(&format!(
+            "🎨 Exported realistic {} view ({}) at {:.0} DPI to {}",
+            side_name,
+            options.appearance.finish.display_name(),
+            raster.dpi,
+            output_path.display()
+        )),
+        Err(e) => logger.log_error(&format!("Realistic export failed: {}", e)),
+    }
+}
+
+fn show_dxf_options(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let options = &mut app.export_state.dxf;
+
+    ui.label("The mechanical outline is always included.");
+    ui.label("Extra layers:");
+    ui.horizontal_wrapped(|ui| {
+        for layer_type in LayerType::all().into_iter().filter(|t| *t != LayerType::MechanicalOutline) {
+            let mut selected = options.layers.contains(&layer_type);
+            if ui.checkbox(&mut selected, layer_type.display_name()).changed() {
+                if selected {
+                    options.layers.push(layer_type);
+                } else {
+                    options.layers.retain(|t| *t != layer_type);
+                }
+            }
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Features as:");
+        ui.selectable_value(&mut options.mode, DxfFeatureMode::Outline, "Outlines");
+        ui.selectable_value(&mut options.mode, DxfFeatureMode::Centerline, "Centre lines");
+    });
+
+    ui.checkbox(&mut options.include_drills, "Include drills (PTH and NPTH layers)");
+    ui.checkbox(&mut options.mirror, "Mirror for bottom view");
+}
+
+fn export_mechanical_dxf(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.dxf.clone();
+
+    let stem = app.project_manager.get_pcb_path()
+        .and_then(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "board".to_string());
+    let suffix = if options.mirror { "-bottom" } else { "" };
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter("DXF drawing", &["dxf"])
+        .set_file_name(format!("{}{}.dxf", stem, suffix))
+        .save_file()
+    else {
+        return;
+    };
+
+    match export_dxf(app, &output_path, &options) {
+        Ok(report) => {
+            logger.log_info(&format!("📏 Exported {} DXF entities to {}", report.entities, output_path.display()));
+            if report.open_contours > 0 {
+                logger.log_warning(&format!("⚠️  Board outline has {} open contour(s)", report.open_contours));
+            }
+        }
+        Err(e) => logger.log_error(&format!("DXF export failed: {}", e)),
+    }
+}
+
+fn show_gerber_options(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let loaded: Vec<LayerType> = LayerType::all().into_iter()
+        .filter(|layer_type| crate::ecs::get_layer_by_type_readonly(&mut app.ecs_world, *layer_type).is_some())
+        .collect();
+    let options = &mut app.export_state.gerber;
+
+    if loaded.is_empty() {
+        ui.label("No layers loaded.");
+    }
+    ui.horizontal_wrapped(|ui| {
+        for layer_type in loaded {
+            let mut selected = options.layers.contains(&layer_type);
+            if ui.checkbox(&mut selected, layer_type.display_name()).changed() {
+                if selected {
+                    options.layers.push(layer_type);
+                } else {
+                    options.layers.retain(|t| *t != layer_type);
+                }
+            }
+        }
+    });
+
+    ui.checkbox(&mut options.apply_view_transform, "Apply view rotation and mirroring");
+    ui.checkbox(&mut options.relative_to_user_origin, "Move user origin to 0,0");
+    ui.checkbox(&mut options.merge, "Merge into one file");
+}
+
+fn export_transformed_gerbers(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let options = app.export_state.gerber.clone();
+    if options.layers.is_empty() {
+        logger.log_warning("⚠️  Select at least one layer to export");
+        return;
+    }
+
+    let output = if options.merge {
+        rfd::FileDialog::new()
+            .add_filter("Gerber", &["gbr"])
+            .set_file_name("merged.gbr")
+            .save_file()
+    } else {
+        rfd::FileDialog::new().pick_folder()
+    };
+    let Some(output) = output else {
+        return;
+    };
+
+    match export_gerbers(app, &output, &options) {
+        Ok((files, report)) => {
+            logger.log_info(&format!(
+                "🔁 Exported {} features with {} apertures to {} Gerber file(s)",
+                report.features, report.apertures, files.len()
+            ));
+            for file_path in files {
+                if let Some(filename) = file_path.file_name() {
+                    logger.log_info(&format!("  • {}", filename.to_string_lossy()));
+                }
+            }
+            for warning in report.warnings {
+                logger.log_warning(&format!("⚠️  {}", warning));
+            }
+        }
+        Err(e) => logger.log_error(&format!("Gerber export failed: {}", e)),
+    }
+}
+
+fn export_board_ipc2581(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let revision = app.export_state.ipc2581_revision;
+    let stem = app.project_manager.get_pcb_path()
+        .and_then(|path| path.file_stem())
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "board".to_string());
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter("IPC-2581", &["xml", "cvg"])
+        .set_file_name(format!("{}.xml", stem))
+        .save_file()
+    else {
+        return;
+    };
+
+    match export_ipc2581(app, &output_path, revision) {
+        Ok(report) => {
+            logger.log_info(&format!(
+                "🔗 Exported {} layers, {} features, {} holes and {} components to {}",
+                report.layers, report.features, report.holes, report.components, output_path.display()
+            ));
+            for warning in report.warnings {
+                logger.log_warning(&format!("⚠️  {}", warning));
+            }
+        }
+        Err(e) => logger.log_error(&format!("IPC-2581 export failed: {}", e)),
+    }
+}
@@ -0,0 +1,144 @@
+use crate::DemoLensApp;
+use crate::ecs::{mm_to_nm, SelectedFeatureInfo, UnitsResource};
+use crate::geometry::{shapes, FeatureKind, Polarity};
+
+use egui::RichText;
+use nalgebra::Point2;
+
+// Feature inspector
+// Floating window describing the feature picked in the Gerber view.
+// Coordinates are shown as written in the file, before any view transform.
+
+/// Show the inspector while a feature is selected; closing it clears the selection
+pub fn show_feature_inspector(ctx: &egui::Context, app: &mut DemoLensApp) {
+    let Some(selected) = crate::ecs::get_selected_feature(&mut app.ecs_world) else {
+        return;
+    };
+    let units = app.ecs_world.get_resource::<UnitsResource>()
+        .cloned()
+        .unwrap_or_default();
+
+    let mut open = true;
+    egui::Window::new("🔎 Feature Inspector")
+        .id(egui::Id::new("feature_inspector"))
+        .open(&mut open)
+        .collapsible(true)
+        .resizable(false)
+        .default_width(280.0)
+        .show(ctx, |ui| {
+            show_feature_details(ui, &selected, &units);
+        });
+
+    if !open {
+        crate::ecs::select_feature_system(&mut app.ecs_world, None);
+    }
+}
+
+fn show_feature_details(ui: &mut egui::Ui, selected: &SelectedFeatureInfo, units: &UnitsResource) {
+    let feature = &selected.feature;
+    let length = |mm: f64| units.format_value(mm_to_nm(mm as f32));
+    let point = |p: &Point2<f64>| format!("({}, {})", length(p.x), length(p.y));
+
+    egui::Grid::new("feature_inspector_grid")
+        .num_columns(2)
+        .spacing([12.0, 4.0])
+        .striped(true)
+        .show(ui, |ui| {
+            let mut row = |name: &str, value: String| {
+                ui.label(RichText::new(name).strong());
+                ui.label(value);
+                ui.end_row();
+            };
+
+            row("Layer", selected.layer.layer_type.display_name());
+            row("Feature", format!("{} #{}", feature.kind_name(), selected.index));
+            row("Polarity", match feature.polarity {
+                Polarity::Dark => "Dark".to_string(),
+                Polarity::Clear => "Clear".to_string(),
+            });
+
+            match (&feature.aperture, &selected.aperture) {
+                (Some(d_code), Some(aperture)) => {
+                    row("Aperture", format!("D{} ({})", d_code, aperture.template));
+                    row("Shape", aperture.describe());
+                    if let Some((width, height)) = aperture.size() {
+                        row("Size", format!("{} x {}", length(width), length(height)));
+                    }
+                }
+                (Some(d_code), None) => row("Aperture", format!("D{} (undefined)", d_code)),
+                (None, _) => row("Aperture", "None (region)".to_string()),
+            }
+            if !feature.aperture_transform.is_identity() {
+                let transform = &feature.aperture_transform;
+                row("Aperture transform", format!(
+                    "rot {:.1}°, scale {:.3}{}{}",
+                    transform.rotation,
+                    transform.scale,
+                    if transform.mirror_x { ", mirror X" } else { "" },
+                    if transform.mirror_y { ", mirror Y" } else { "" },
+                ));
+            }
+            if let Some(function) = &feature.function {
+                row("Function", function.clone());
+            }
+            if let Some(net) = feature.net() {
+                row("Net", net.to_string());
+            }
+            if let Some(component) = feature.component() {
+                row("Component", component.to_string());
+            }
+            if let Some((reference, pin)) = feature.pin() {
+                row("Pin", format!("{}-{}", reference, pin));
+            }
+
+            match &feature.kind {
+                FeatureKind::Flash { position } => {
+                    row("Position", point(position));
+                }
+                FeatureKind::Line { start, end } => {
+                    row("Start", point(start));
+                    row("End", point(end));
+                    row("Length", length((end - start).norm()));
+                    if let Some(aperture) = &selected.aperture {
+                        row("Width", length(aperture.stroke_width() * feature.aperture_transform.scale));
+                    }
+                }
+                FeatureKind::Arc { start, end, center, clockwise } => {
+                    let radius = (start - center).norm();
+                    let sweep = shapes::arc_sweep(*start, *end, *center, *clockwise);
+                    row("Start", point(start));
+                    row("End", point(end));
+                    row("Center", point(center));
+                    row("Radius", length(radius));
+                    row("Sweep", format!("{:.2}° {}", sweep.abs().to_degrees(), if *clockwise { "CW" } else { "CCW" }));
+                    row("Length", length(radius * sweep.abs()));
+                    if let Some(aperture) = &selected.aperture {
+                        row("Width", length(aperture.stroke_width() * feature.aperture_transform.scale));
+                    }
+                }
+                FeatureKind::Region { points } => {
+                    row("Vertices", points.len().to_string());
+                    if let Some((min, max)) = shapes::points_bounds(points) {
+                        row("Size", format!("{} x {}", length(max.x - min.x), length(max.y - min.y)));
+                    }
+                    let area = shapes::signed_area(points).abs();
+                    let area_text = if units.is_mils() {
+                        format!("{:.1} mils²", area / (0.0254 * 0.0254))
+                    } else {
+                        format!("{:.4} mm²", area)
+                    };
+                    row("Area", area_text);
+                }
+            }
+        });
+
+    ui.add_space(6.0);
+    ui.label(RichText::new("X2 object attributes").strong());
+    if selected.feature.attributes.is_empty() {
+        ui.weak("None");
+    } else {
+        for (name, values) in &selected.feature.attributes {
+            ui.monospace(format!("{} = {}", name, values.join(",")));
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{DemoLensApp, ecs::{LayerType, Side}};
+use crate::project::{shipped_layer_presets, LayerPreset};
 use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
 use eframe::emath::Vec2;
 use egui_mobius_reactive::*; 
@@ -80,8 +81,40 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
             ui.ctx().request_repaint();
         }
     });
+    
+    show_layer_presets(ui, app, &logger);
+    
+    // Layer blending and active layer highlight
+    ui.horizontal(|ui| {
+        let mut render_config = app.ecs_world.get_resource::<crate::ecs::RenderConfig>()
+            .cloned()
+            .unwrap_or_default();
+        let mut changed = false;
+        
+        ui.label("Blend:");
+        egui::ComboBox::from_id_salt("layer_blend_mode")
+            .selected_text(render_config.blend_mode.display_name())
+            .show_ui(ui, |ui| {
+                for mode in crate::ecs::LayerBlendMode::all() {
+                    changed |= ui.selectable_value(&mut render_config.blend_mode, mode, mode.display_name()).changed();
+                }
+            });
+        changed |= ui.checkbox(&mut render_config.highlight_active_layer, "Highlight active layer")
+            .on_hover_text("Draw the active layer in its highlight color")
+            .changed();
+        changed |= ui.checkbox(&mut render_config.cached_meshes, "Cached meshes")
+            .on_hover_text("Draw layers from cached meshes, only submitting features in view")
+            .changed();
+
+        if changed {
+            app.ecs_world.insert_resource(render_config);
+            ui.ctx().request_repaint();
+        }
+    });
     ui.add_space(4.0);
     
+    let active_layer = app.ecs_world.get_resource::<crate::ecs::ActiveLayer>().map(|active| active.0);
+    
     // Track actions to perform after the UI loop
     let mut show_only_layer: Option<LayerType> = None;
     let mut toggle_color_picker: Option<LayerType> = None;
@@ -89,14 +122,18 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
     // Track visibility changes to apply after reading
     let mut visibility_changes = Vec::new();
     let mut color_changes = Vec::new();
+    let mut opacity_changes = Vec::new();
+    let mut highlight_changes = Vec::new();
+    let mut new_active_layer: Option<LayerType> = None;
     
     for layer_type in LayerType::all() {
         // Get layer data from ECS
         if let Some((_entity, _layer_info, _gerber_data, visibility)) = crate::ecs::get_layer_data(&mut app.ecs_world, layer_type) {
             let was_visible = visibility.visible;
-            let current_color = crate::ecs::get_layer_render_properties(&mut app.ecs_world, layer_type)
-                .map(|props| props.color)
-                .unwrap_or(layer_type.color());
+            let opacity = visibility.opacity;
+            let (current_color, highlight_color) = crate::ecs::get_layer_render_properties(&mut app.ecs_world, layer_type)
+                .map(|props| (props.color, props.highlight_color))
+                .unwrap_or((layer_type.color(), None));
             
             // Show ALL layers regardless of top/bottom view
             ui.horizontal(|ui| {
@@ -147,6 +184,18 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
                                 color_changes.push((layer_type, new_color));
                             }
                             
+                            ui.horizontal(|ui| {
+                                ui.label("Highlight:");
+                                let mut highlight = highlight_color
+                                    .unwrap_or_else(|| current_color.lerp_to_gamma(egui::Color32::WHITE, 0.4));
+                                if ui.color_edit_button_srgba(&mut highlight).changed() {
+                                    highlight_changes.push((layer_type, Some(highlight)));
+                                }
+                                if highlight_color.is_some() && ui.small_button("Default").clicked() {
+                                    highlight_changes.push((layer_type, None));
+                                }
+                            });
+                            
                             ui.horizontal(|ui| {
                                 if ui.button("Reset to Default").clicked() {
                                     color_changes.push((layer_type, layer_type.color()));
@@ -160,7 +209,21 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
                         });
                 }
                 
-                ui.label(layer_type.display_name());
+                // Opacity of the layer in the viewer
+                let mut current_opacity = opacity;
+                let slider = ui.add_sized(
+                    [60.0, 16.0],
+                    egui::Slider::new(&mut current_opacity, 0.0..=1.0).show_value(false),
+                ).on_hover_text(format!("Opacity {:.0}%", current_opacity * 100.0));
+                if slider.changed() {
+                    opacity_changes.push((layer_type, current_opacity));
+                }
+                
+                // Clicking the name makes it the active layer
+                let is_active = active_layer == Some(layer_type);
+                if ui.selectable_label(is_active, layer_type.display_name()).clicked() && !is_active {
+                    new_active_layer = Some(layer_type);
+                }
                 
                 if current_visible != was_visible {
                     logger.log_info(&format!("{} layer {}", 
@@ -172,6 +235,11 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
         }
     }
     
+    // Hand edits no longer match the applied preset
+    if !visibility_changes.is_empty() || !color_changes.is_empty() || !opacity_changes.is_empty() || show_only_layer.is_some() {
+        app.active_layer_preset = None;
+    }
+    
     // Apply visibility changes
     for (layer_type, visible) in visibility_changes {
         crate::ecs::set_layer_visibility(&mut app.ecs_world, layer_type, visible);
@@ -182,6 +250,19 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
         crate::ecs::update_layer_render_properties(&mut app.ecs_world, layer_type, color);
     }
     
+    for (layer_type, opacity) in opacity_changes {
+        crate::ecs::set_layer_opacity(&mut app.ecs_world, layer_type, opacity);
+    }
+    
+    for (layer_type, color) in highlight_changes {
+        crate::ecs::set_layer_highlight_color(&mut app.ecs_world, layer_type, color);
+    }
+    
+    if let Some(layer_type) = new_active_layer {
+        app.ecs_world.insert_resource(crate::ecs::ActiveLayer(layer_type));
+        logger.log_info(&format!("Active layer: {}", layer_type.display_name()));
+    }
+    
     // Handle deferred actions after the UI loop
     if let Some(target_layer) = show_only_layer {
         for layer_type_iter in LayerType::all() {
@@ -269,4 +350,110 @@ pub fn show_layers_panel<'a>(    ui: &mut egui::Ui,
         }
     }
     
-}
\ No newline at end of file
+}
+
+/// Shipped presets for the loaded board, followed by the user's saved ones
+pub fn available_layer_presets(app: &mut DemoLensApp) -> Vec<LayerPreset> {
+    let copper_layers = crate::ecs::loaded_copper_layers(&mut app.ecs_world);
+    let mut presets = shipped_layer_presets(&copper_layers);
+    presets.extend(app.layer_presets.iter().cloned());
+    presets
+}
+
+/// Apply a preset to the layers and switch to its view side
+pub fn apply_layer_preset(app: &mut DemoLensApp, preset: &LayerPreset, logger: &ReactiveEventLogger) {
+    let visible = crate::ecs::apply_layer_preset_system(&mut app.ecs_world, preset);
+    if let Some(showing_top) = preset.showing_top {
+        if app.display_manager.showing_top != showing_top {
+            app.display_manager.showing_top = showing_top;
+            crate::ecs::mark_coordinates_dirty_ecs(&mut app.ecs_world);
+        }
+    }
+    app.active_layer_preset = Some(preset.name.clone());
+    logger.log_info(&format!("Layer preset: {} ({} layers shown)", preset.name, visible));
+}
+
+/// Apply the next (or previous) preset after the one last applied
+pub fn cycle_layer_preset(app: &mut DemoLensApp, forward: bool, logger: &ReactiveEventLogger) {
+    let presets = available_layer_presets(app);
+    if presets.is_empty() {
+        return;
+    }
+    let current = app.active_layer_preset.as_ref()
+        .and_then(|name| presets.iter().position(|preset| &preset.name == name));
+    let index = match (current, forward) {
+        (None, true) => 0,
+        (None, false) => presets.len() - 1,
+        (Some(i), true) => (i + 1) % presets.len(),
+        (Some(i), false) => (i + presets.len() - 1) % presets.len(),
+    };
+    apply_layer_preset(app, &presets[index], logger);
+}
+
+fn show_layer_presets(ui: &mut egui::Ui, app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let presets = available_layer_presets(app);
+    let shipped_count = presets.len() - app.layer_presets.len();
+    
+    ui.horizontal(|ui| {
+        ui.label("Preset:");
+        let mut chosen = None;
+        let selected_text = app.active_layer_preset.clone().unwrap_or_else(|| "Custom".to_string());
+        egui::ComboBox::from_id_salt("layer_preset")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                for (index, preset) in presets.iter().enumerate() {
+                    if index == shipped_count && index > 0 {
+                        ui.separator();
+                    }
+                    let selected = app.active_layer_preset.as_deref() == Some(preset.name.as_str());
+                    if ui.selectable_label(selected, &preset.name).clicked() {
+                        chosen = Some(index);
+                    }
+                }
+            })
+            .response
+            .on_hover_text("L / Shift+L to cycle presets");
+        if let Some(index) = chosen {
+            apply_layer_preset(app, &presets[index], logger);
+            ui.ctx().request_repaint();
+        }
+        
+        // Only user presets can be deleted
+        let user_preset = app.active_layer_preset.as_ref()
+            .and_then(|name| app.layer_presets.iter().position(|preset| &preset.name == name));
+        if let Some(index) = user_preset {
+            if ui.small_button("🗑").on_hover_text("Delete this preset").clicked() {
+                let removed = app.layer_presets.remove(index);
+                app.active_layer_preset = None;
+                app.save_settings();
+                logger.log_info(&format!("Deleted layer preset: {}", removed.name));
+            }
+        }
+    });
+    
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut app.layer_preset_name)
+            .hint_text("Preset name")
+            .desired_width(140.0));
+        let name = app.layer_preset_name.trim().to_string();
+        if ui.add_enabled(!name.is_empty(), egui::Button::new("💾 Save Preset"))
+            .on_hover_text("Save visibility, opacity, color and view side of every layer")
+            .clicked()
+        {
+            if presets[..shipped_count].iter().any(|preset| preset.name == name) {
+                logger.log_warning(&format!("\"{}\" is a built-in preset, choose another name", name));
+            } else {
+                let preset = crate::ecs::capture_layer_preset_system(&mut app.ecs_world, &name, app.display_manager.showing_top);
+                match app.layer_presets.iter_mut().find(|existing| existing.name == name) {
+                    Some(existing) => *existing = preset,
+                    None => app.layer_presets.push(preset),
+                }
+                app.active_layer_preset = Some(name.clone());
+                app.layer_preset_name.clear();
+                app.save_settings();
+                logger.log_info(&format!("Saved layer preset: {}", name));
+            }
+        }
+    });
+    ui.add_space(4.0);
+}
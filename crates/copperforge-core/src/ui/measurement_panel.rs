@@ -0,0 +1,115 @@
+use crate::DemoLensApp;
+use crate::ecs::{format_measurement, Measurement, UnitsResource};
+use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
+use egui_mobius_reactive::Dynamic;
+
+// Measurements
+// Tool selection for the ruler and the list of measurements taken this session.
+// The list lives in the Measurements resource and is drawn over the viewer.
+
+/// What a ruler click measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MeasureTool {
+    #[default]
+    Distance,
+    /// Edge-to-edge distance between two picked features
+    Clearance,
+    /// Angle at the second of three points
+    Angle,
+    /// Area of a polygon, closed by clicking its first point or double-clicking
+    Area,
+}
+
+impl MeasureTool {
+    pub fn all() -> [MeasureTool; 4] {
+        [MeasureTool::Distance, MeasureTool::Clearance, MeasureTool::Angle, MeasureTool::Area]
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            MeasureTool::Distance => "Distance",
+            MeasureTool::Clearance => "Clearance",
+            MeasureTool::Angle => "Angle",
+            MeasureTool::Area => "Area",
+        }
+    }
+
+    /// What to click next, given the points placed so far
+    pub fn hint(&self, points: usize) -> &'static str {
+        match (self, points) {
+            (MeasureTool::Distance, 0) => "Click to start measurement",
+            (MeasureTool::Distance, _) => "Click second point to complete measurement",
+            (MeasureTool::Clearance, 0) => "Click the first feature",
+            (MeasureTool::Clearance, _) => "Click the second feature",
+            (MeasureTool::Angle, 0) => "Click a point on the first ray",
+            (MeasureTool::Angle, 1) => "Click the vertex",
+            (MeasureTool::Angle, _) => "Click a point on the second ray",
+            (MeasureTool::Area, 0..=2) => "Click the polygon vertices",
+            (MeasureTool::Area, _) => "Click the first vertex or double-click to close",
+        }
+    }
+}
+
+pub fn show_measurement_panel(
+    ui: &mut egui::Ui,
+    app: &mut DemoLensApp,
+    logger_state: &Dynamic<ReactiveEventLoggerState>,
+    log_colors: &Dynamic<LogColors>,
+) {
+    let logger = ReactiveEventLogger::with_colors(logger_state, log_colors);
+
+    egui::CollapsingHeader::new("📐 Measurements")
+        .default_open(false)
+        .show(ui, |ui| {
+            let measurements = crate::ecs::get_measurements(&app.ecs_world);
+            let units = app.ecs_world.get_resource::<UnitsResource>()
+                .cloned()
+                .unwrap_or_default();
+
+            if measurements.is_empty() {
+                ui.weak("No measurements yet. Press M and pick a tool in the ruler controls.");
+                return;
+            }
+
+            let lines: Vec<String> = measurements.iter().enumerate()
+                .map(|(index, measurement)| {
+                    let mut line = format!("#{} {}: {}", index + 1, measurement.kind_name(), format_measurement(measurement, &units));
+                    if let Measurement::Clearance { description, .. } = measurement {
+                        if !description.is_empty() {
+                            line.push_str(&format!(" ({})", description));
+                        }
+                    }
+                    line
+                })
+                .collect();
+
+            ui.horizontal(|ui| {
+                if ui.button("📋 Copy").on_hover_text("Copy the list as text").clicked() {
+                    ui.ctx().copy_text(lines.join("\n"));
+                    logger.log_info(&format!("📐 Copied {} measurements", lines.len()));
+                }
+                if ui.button("🗑 Clear All").clicked() {
+                    crate::ecs::clear_measurements_system(&mut app.ecs_world);
+                    logger.log_info("📐 Cleared measurements");
+                }
+            });
+
+            let mut remove = None;
+            egui::ScrollArea::vertical()
+                .id_salt("measurement_list")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for (index, line) in lines.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if ui.small_button("✖").on_hover_text("Remove measurement").clicked() {
+                                remove = Some(index);
+                            }
+                            ui.monospace(line);
+                        });
+                    }
+                });
+            if let Some(index) = remove {
+                crate::ecs::remove_measurement_system(&mut app.ecs_world, index);
+            }
+        });
+}
@@ -10,6 +10,14 @@ pub mod tabs;
 pub mod selection;
 pub mod bom_panel_v2;
 pub mod project_manager_panel;
+pub mod export_panel;
+pub mod realistic_view;
+pub mod feature_inspector;
+pub mod net_panel;
+pub mod revision_panel;
+pub mod measurement_panel;
+pub mod review_panel;
+pub mod stackup_panel;
 
 // Re-export the show functions for each panel
 pub use layer_controls::show_layers_panel;
@@ -19,6 +27,13 @@ pub use project_panel::show_project_panel;
 pub use settings_panel::show_settings_panel;
 pub use about_panel::AboutPanel;
 pub use bom_panel_v2::{show_bom_panel, BomPanelState};
+pub use export_panel::{show_export_panel, ExportPanelState};
+pub use feature_inspector::show_feature_inspector;
+pub use net_panel::show_net_panel;
+pub use revision_panel::show_revision_panel;
+pub use measurement_panel::show_measurement_panel;
+pub use review_panel::{show_review_panel, ReviewState};
+pub use stackup_panel::show_stackup_panel;
 
 // Re-export tab-related types
 pub use tabs::{Tab, TabKind, TabViewer};
@@ -0,0 +1,83 @@
+use crate::DemoLensApp;
+use crate::ecs::AttributeHighlight;
+use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
+use egui_mobius_reactive::Dynamic;
+
+// Net search
+// Lists the nets found in the Gerber X2 object attributes of the copper layers
+// and highlights the chosen one in the viewer.
+
+pub fn show_net_panel(
+    ui: &mut egui::Ui,
+    app: &mut DemoLensApp,
+    logger_state: &Dynamic<ReactiveEventLoggerState>,
+    log_colors: &Dynamic<LogColors>,
+) {
+    let logger = ReactiveEventLogger::with_colors(logger_state, log_colors);
+
+    egui::CollapsingHeader::new("🔌 Nets")
+        .default_open(false)
+        .show(ui, |ui| {
+            let highlight = app.ecs_world.get_resource::<AttributeHighlight>()
+                .cloned()
+                .unwrap_or_default();
+
+            ui.horizontal(|ui| {
+                match &highlight.net {
+                    Some(net) => {
+                        ui.label(format!("Net: {}", net));
+                        if ui.small_button("✖").on_hover_text("Clear net highlight").clicked() {
+                            crate::ecs::highlight_net_system(&mut app.ecs_world, None);
+                        }
+                    }
+                    None => {
+                        ui.weak("No net highlighted");
+                    }
+                }
+            });
+            if let Some(component) = &highlight.component {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Component: {}", component));
+                    if ui.small_button("✖").on_hover_text("Clear component highlight").clicked() {
+                        crate::ecs::highlight_component_system(&mut app.ecs_world, None);
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Search:");
+                ui.text_edit_singleline(&mut app.net_search);
+            });
+
+            let nets = crate::ecs::list_nets_system(&mut app.ecs_world);
+            if nets.is_empty() {
+                ui.weak("No net attributes in the loaded copper layers. Plot Gerbers with X2 netlist attributes enabled.");
+                return;
+            }
+
+            let search = app.net_search.to_lowercase();
+            let matching: Vec<_> = nets.iter()
+                .filter(|net| search.is_empty() || net.name.to_lowercase().contains(&search))
+                .collect();
+            ui.label(format!("{} of {} nets", matching.len(), nets.len()));
+
+            egui::ScrollArea::vertical()
+                .id_salt("net_list")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for net in matching {
+                        let selected = highlight.net.as_deref() == Some(net.name.as_str());
+                        let label = format!("{}  ({} pins)", net.name, net.pins.len());
+                        let response = ui.selectable_label(selected, label)
+                            .on_hover_text(net.pins.join(", "));
+                        if response.clicked() {
+                            let net_name = if selected { None } else { Some(net.name.clone()) };
+                            if let Some(name) = &net_name {
+                                logger.log_info(&format!("🔌 Highlighted net {} ({} features)", name, net.features));
+                            }
+                            crate::ecs::highlight_net_system(&mut app.ecs_world, net_name);
+                        }
+                    }
+                });
+        });
+}
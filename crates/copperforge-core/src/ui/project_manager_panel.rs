@@ -193,6 +193,9 @@ pub fn show_project_manager_panel(
             if let Err(e) = manager_state.load_project(&project_id) {
                 manager_state.last_error = Some(format!("Failed to load project: {}", e));
             } else {
+                app.export_state.realistic.appearance = manager_state.load_board_appearance().unwrap_or_default();
+                app.review.annotations = manager_state.load_annotations().unwrap_or_default();
+                app.review.selected = None;
                 logger.log_info(&format!("Loaded project: {}", project_name));
             }
         }
@@ -50,6 +50,13 @@ pub fn show_project_panel<'a>(
 
     ui.add_space(10.0);
 
+    // Boards delivered as IPC-2581 instead of a KiCad project or Gerber set
+    if ui.button("📂 Import IPC-2581...").clicked() {
+        import_ipc2581(app, &logger);
+    }
+
+    ui.add_space(10.0);
+
     // Auto-generation settings
     ui.horizontal(|ui| {
         ui.checkbox(&mut app.project_manager.auto_generate_on_startup, "Auto-generate on startup");
@@ -71,6 +78,8 @@ pub fn show_project_panel<'a>(
                 manager_state.current_project = None;
                 manager_state.selected_project_id = None;
             }
+            app.review.annotations.clear();
+            app.review.selected = None;
             logger.log_info("Cleared PCB file selection and current project");
         }
     });
@@ -374,6 +383,44 @@ fn load_gerbers_into_viewer(app: &mut DemoLensApp, gerber_dir: &Path, logger: &R
     }
 }
 
+fn import_ipc2581(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("IPC-2581", &["xml", "cvg"])
+        .set_title("Select IPC-2581 File")
+        .pick_file()
+    else {
+        return;
+    };
+
+    let board = match std::fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| crate::ipc2581::parse_ipc2581(&content))
+    {
+        Ok(board) => board,
+        Err(e) => {
+            logger.log_error(&format!("Failed to read IPC-2581 file: {}", e));
+            return;
+        }
+    };
+
+    let revision = board.revision.code();
+    match crate::ipc2581::load_ipc2581_system(&mut app.ecs_world, board) {
+        Ok(summary) => {
+            logger.log_info(&format!(
+                "📂 Loaded IPC-2581 rev {}: {} layers, {} drill sets, {} components, {} stackup layers from {}",
+                revision, summary.layers, summary.drills, summary.components, summary.stackup_layers, path.display()
+            ));
+            for warning in summary.warnings {
+                logger.log_warning(&format!("⚠️  {}", warning));
+            }
+            if summary.layers > 0 {
+                app.needs_initial_view = true;
+            }
+        }
+        Err(e) => logger.log_error(&format!("Failed to load IPC-2581 board: {}", e)),
+    }
+}
+
 fn show_project_database_section(ui: &mut egui::Ui, app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
     ui.group(|ui| {
         ui.label("💾 Project Database");
@@ -555,6 +602,10 @@ fn show_project_database_section(ui: &mut egui::Ui, app: &mut DemoLensApp, logge
                     if let Err(e) = manager_state.load_project(&project_id) {
                         manager_state.last_error = Some(format!("Failed to load project: {}", e));
                     } else {
+                        app.export_state.realistic.appearance = manager_state.load_board_appearance().unwrap_or_default();
+                        app.review.annotations = manager_state.load_annotations().unwrap_or_default();
+                        app.review.selected = None;
+                        
                         // Successfully loaded project data, now restore the project state
                         if let Some(ref project) = manager_state.current_project {
                             // 1. Set the PCB file path in the project manager
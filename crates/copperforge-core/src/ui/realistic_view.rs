@@ -0,0 +1,176 @@
+use crate::DemoLensApp;
+use crate::drc_operations::types::Position;
+use crate::ecs::Side;
+use crate::export::realistic::{render_realistic, RealisticSource};
+use crate::export::{RasterOptions, RealisticBoard};
+use crate::geometry::Antialias;
+use crate::project_manager::appearance::{rgb, BoardAppearance, SurfaceFinish, MASK_PRESETS};
+use egui::{Color32, Painter, Pos2};
+use egui_lens::ReactiveEventLogger;
+use nalgebra::Point2;
+
+// Realistic view mode of the gerber viewer
+// The shown side is rendered once into a texture, which is reused until a
+// layer or drill file is loaded, removed or changed, or the side or the
+// appearance change.
+
+/// Longest side of the preview texture (px)
+const PREVIEW_SIZE: f64 = 2048.0;
+
+/// Cached realistic render and what it was made from
+pub struct RealisticPreview {
+    side: Side,
+    appearance: BoardAppearance,
+    source: RealisticSource,
+    /// None when the render failed, so it is not retried every frame
+    texture: Option<egui::TextureHandle>,
+    /// Board area covered by the texture, gerber mm
+    min: Point2<f64>,
+    max: Point2<f64>,
+}
+
+/// Draw the realistic view of the side currently shown
+pub fn render_realistic_view(app: &mut DemoLensApp, painter: &Painter) {
+    let side = if app.display_manager.showing_top { Side::Top } else { Side::Bottom };
+    let appearance = app.export_state.realistic.appearance;
+
+    let stale = app.realistic_preview.as_ref()
+        .is_none_or(|preview| preview.side != side || preview.appearance != appearance || !preview.source.is_current(&mut app.ecs_world));
+    if stale {
+        app.realistic_preview = Some(build_preview(app, painter.ctx(), side, appearance));
+    }
+
+    let Some(preview) = app.realistic_preview.as_ref() else {
+        return;
+    };
+    let Some(texture) = preview.texture.as_ref() else {
+        return;
+    };
+
+    // Image corners clockwise from top left, through the same transform as the overlays
+    let corners = [
+        (preview.min.x, preview.max.y),
+        (preview.max.x, preview.max.y),
+        (preview.max.x, preview.min.y),
+        (preview.min.x, preview.min.y),
+    ];
+    let uvs = [Pos2::new(0.0, 0.0), Pos2::new(1.0, 0.0), Pos2::new(1.0, 1.0), Pos2::new(0.0, 1.0)];
+    let mut mesh = egui::Mesh::with_texture(texture.id());
+    for ((x, y), uv) in corners.into_iter().zip(uvs) {
+        mesh.vertices.push(egui::epaint::Vertex {
+            pos: super::tabs::board_to_screen(app, Position::new(x, y)),
+            uv,
+            color: Color32::WHITE,
+        });
+    }
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(0, 2, 3);
+    painter.add(egui::Shape::mesh(mesh));
+}
+
+fn build_preview(
+    app: &mut DemoLensApp,
+    ctx: &egui::Context,
+    side: Side,
+    appearance: BoardAppearance,
+) -> RealisticPreview {
+    let source = RealisticSource::capture(&mut app.ecs_world);
+    let board = RealisticBoard::from_world(&mut app.ecs_world, side);
+    let mut preview = RealisticPreview {
+        side,
+        appearance,
+        source,
+        texture: None,
+        min: Point2::origin(),
+        max: Point2::origin(),
+    };
+    let Some((min, max)) = board.bounds() else {
+        return preview;
+    };
+
+    let longest = (max.x - min.x).max(max.y - min.y).max(1.0);
+    let options = RasterOptions {
+        dpi: PREVIEW_SIZE / longest * 25.4,
+        antialias: Antialias::Low,
+        background: Color32::TRANSPARENT,
+        margin: 0.0,
+    };
+    match render_realistic(&board, side, &appearance, &options, false) {
+        Ok(img) => {
+            // The image is rounded up to whole pixels, so it reaches a little past the bounds
+            let scale = options.dpi / 25.4;
+            preview.min = Point2::new(min.x, max.y - img.height() as f64 / scale);
+            preview.max = Point2::new(min.x + img.width() as f64 / scale, max.y);
+            let size = [img.width() as usize, img.height() as usize];
+            let image = egui::ColorImage::from_rgba_unmultiplied(size, img.as_raw());
+            preview.texture = Some(ctx.load_texture("realistic_preview", image, egui::TextureOptions::LINEAR));
+        }
+        Err(e) => println!("Realistic preview unavailable: {}", e),
+    }
+    preview
+}
+
+/// Appearance editor, returns true when anything changed
+pub fn show_appearance_controls(ui: &mut egui::Ui, appearance: &mut BoardAppearance) -> bool {
+    let mut changed = false;
+
+    ui.horizontal(|ui| {
+        ui.label("Solder mask:");
+        let current = MASK_PRESETS.iter()
+            .find(|(_, color)| *color == appearance.soldermask)
+            .map(|(name, _)| *name)
+            .unwrap_or("Custom");
+        egui::ComboBox::from_id_salt("mask_color")
+            .selected_text(current)
+            .show_ui(ui, |ui| {
+                for (name, color) in MASK_PRESETS {
+                    let text = egui::RichText::new(format!("■ {}", name)).color(rgb(color));
+                    if ui.selectable_label(appearance.soldermask == color, text).clicked() {
+                        appearance.soldermask = color;
+                        changed = true;
+                    }
+                }
+            });
+        changed |= ui.color_edit_button_srgb(&mut appearance.soldermask).changed();
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Mask opacity:");
+        changed |= ui.add(egui::Slider::new(&mut appearance.mask_opacity, 0.5..=1.0)).changed();
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Finish:");
+        for finish in SurfaceFinish::all() {
+            changed |= ui.selectable_value(&mut appearance.finish, finish, finish.display_name()).changed();
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label("Silkscreen:");
+        changed |= ui.color_edit_button_srgb(&mut appearance.silkscreen).changed();
+        ui.label("Substrate:");
+        changed |= ui.color_edit_button_srgb(&mut appearance.substrate).changed();
+    });
+
+    changed |= ui.checkbox(&mut appearance.clip_silkscreen, "Clip silkscreen at mask openings").changed();
+
+    if ui.button("Reset").clicked() {
+        *appearance = BoardAppearance::default();
+        changed = true;
+    }
+    changed
+}
+
+/// Store the appearance with the current project, if one is open
+pub fn save_project_appearance(app: &DemoLensApp, logger: &ReactiveEventLogger) {
+    let Some(manager_state) = app.project_manager_state.as_ref() else {
+        return;
+    };
+    if manager_state.current_project.is_none() {
+        return;
+    }
+    if let Err(e) = manager_state.save_board_appearance(&app.export_state.realistic.appearance) {
+        logger.log_error(&format!("Failed to save board appearance: {}", e));
+    }
+}
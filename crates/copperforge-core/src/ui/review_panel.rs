@@ -0,0 +1,434 @@
+use crate::DemoLensApp;
+use crate::ecs::{ActiveLayer, LayerType};
+use crate::export::{export_review_html, TitleBlock};
+use crate::project_manager::annotations::{next_annotation_id, step_open_annotation, Annotation, AnnotationKind, AnnotationStatus};
+use egui::{Color32, Painter, Pos2, Rect, RichText, Stroke, Vec2};
+use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
+use egui_mobius_reactive::Dynamic;
+use nalgebra::Point2;
+
+// Design review annotations
+// Places markers, arrows, rectangles and text comments on the board, lists them
+// with their status and steps through the open ones. Annotations are saved with
+// the current project and go into the HTML report and the PDF review sheet.
+
+const OPEN_COLOR: Color32 = Color32::from_rgb(255, 90, 60);
+const RESOLVED_COLOR: Color32 = Color32::from_rgb(80, 200, 100);
+const SELECTED_COLOR: Color32 = Color32::YELLOW;
+
+/// Which annotations the list shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReviewFilter {
+    #[default]
+    All,
+    Open,
+    Resolved,
+}
+
+impl ReviewFilter {
+    fn matches(&self, annotation: &Annotation) -> bool {
+        match self {
+            ReviewFilter::All => true,
+            ReviewFilter::Open => annotation.status == AnnotationStatus::Open,
+            ReviewFilter::Resolved => annotation.status == AnnotationStatus::Resolved,
+        }
+    }
+}
+
+/// Review annotations of the session and the placement tool state
+pub struct ReviewState {
+    pub annotations: Vec<Annotation>,
+    /// Annotation kind being placed by clicking on the board
+    pub placing: Option<AnnotationKind>,
+    /// First point of an arrow or rectangle being placed
+    pub pending_start: Option<(f64, f64)>,
+    pub author: String,
+    /// Layer new annotations go on, the active layer when unset
+    pub layer: Option<LayerType>,
+    /// Comment for the next annotation
+    pub draft_text: String,
+    pub selected: Option<u32>,
+    pub filter: ReviewFilter,
+    /// Draw annotations of hidden layers too
+    pub show_all_layers: bool,
+}
+
+impl Default for ReviewState {
+    fn default() -> Self {
+        Self {
+            annotations: Vec::new(),
+            placing: None,
+            pending_start: None,
+            author: std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default(),
+            layer: None,
+            draft_text: String::new(),
+            selected: None,
+            filter: ReviewFilter::default(),
+            show_all_layers: false,
+        }
+    }
+}
+
+pub fn show_review_panel(
+    ui: &mut egui::Ui,
+    app: &mut DemoLensApp,
+    logger_state: &Dynamic<ReactiveEventLoggerState>,
+    log_colors: &Dynamic<LogColors>,
+) {
+    let logger = ReactiveEventLogger::with_colors(logger_state, log_colors);
+
+    egui::CollapsingHeader::new("📝 Review Annotations")
+        .default_open(false)
+        .show(ui, |ui| {
+            let project_open = app.project_manager_state.as_ref()
+                .is_some_and(|state| state.current_project.is_some());
+            if !project_open {
+                ui.weak("Open a project to save annotations with it.");
+            }
+
+            ui.horizontal(|ui| {
+                ui.label("Author:");
+                ui.text_edit_singleline(&mut app.review.author);
+            });
+
+            let loaded: Vec<LayerType> = LayerType::all().into_iter()
+                .filter(|layer_type| crate::ecs::get_layer_by_type(&mut app.ecs_world, *layer_type).is_some())
+                .collect();
+            let active_layer = app.ecs_world.get_resource::<ActiveLayer>().map(|active| active.0);
+            let target_layer = app.review.layer.or(active_layer).unwrap_or(LayerType::Copper(1));
+            ui.horizontal(|ui| {
+                ui.label("Layer:");
+                egui::ComboBox::from_id_salt("review_layer")
+                    .selected_text(target_layer.display_name())
+                    .show_ui(ui, |ui| {
+                        if ui.selectable_label(app.review.layer.is_none(), "Active layer").clicked() {
+                            app.review.layer = None;
+                        }
+                        for layer_type in &loaded {
+                            if ui.selectable_label(app.review.layer == Some(*layer_type), layer_type.display_name()).clicked() {
+                                app.review.layer = Some(*layer_type);
+                            }
+                        }
+                    });
+            });
+
+            ui.label("Comment:");
+            ui.add(egui::TextEdit::multiline(&mut app.review.draft_text).desired_rows(2).hint_text("Comment for the next annotation"));
+
+            ui.horizontal(|ui| {
+                for kind in AnnotationKind::all() {
+                    let selected = app.review.placing == Some(kind);
+                    if ui.selectable_label(selected, format!("{} {}", kind.icon(), kind.display_name())).clicked() {
+                        app.review.placing = if selected { None } else { Some(kind) };
+                        app.review.pending_start = None;
+                    }
+                }
+            });
+            if let Some(kind) = app.review.placing {
+                let hint = if app.review.pending_start.is_some() {
+                    "Click the second point, Esc to cancel"
+                } else if kind.has_end() {
+                    "Click the first point on the board"
+                } else {
+                    "Click on the board to place it"
+                };
+                ui.weak(hint);
+            }
+
+            ui.separator();
+
+            let open = app.review.annotations.iter().filter(|annotation| annotation.is_open()).count();
+            ui.label(format!("{} open, {} resolved", open, app.review.annotations.len() - open));
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut app.review.filter, ReviewFilter::All, "All");
+                ui.selectable_value(&mut app.review.filter, ReviewFilter::Open, "Open");
+                ui.selectable_value(&mut app.review.filter, ReviewFilter::Resolved, "Resolved");
+                ui.separator();
+                let mut step = None;
+                if ui.button("◀").on_hover_text("Previous open annotation").clicked() {
+                    step = Some(false);
+                }
+                if ui.button("▶").on_hover_text("Next open annotation").clicked() {
+                    step = Some(true);
+                }
+                if let Some(forward) = step {
+                    match step_open_annotation(&app.review.annotations, app.review.selected, forward) {
+                        Some(id) => focus_annotation(app, id),
+                        None => logger.log_info("📝 No open annotations"),
+                    }
+                }
+            });
+            ui.checkbox(&mut app.review.show_all_layers, "Show annotations on hidden layers");
+
+            let mut focus = None;
+            let mut toggle = None;
+            let mut remove = None;
+            let mut edited = false;
+            egui::ScrollArea::vertical()
+                .id_salt("review_annotation_list")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    let filter = app.review.filter;
+                    let selected_id = app.review.selected;
+                    for annotation in app.review.annotations.iter_mut().filter(|annotation| filter.matches(annotation)) {
+                        let selected = selected_id == Some(annotation.id);
+                        ui.horizontal(|ui| {
+                            let label = format!("#{} {} {}", annotation.id, annotation.kind.icon(), first_line(&annotation.text));
+                            if ui.selectable_label(selected, label).clicked() {
+                                focus = Some(annotation.id);
+                            }
+                            let color = if annotation.is_open() { OPEN_COLOR } else { RESOLVED_COLOR };
+                            if ui.small_button(RichText::new(annotation.status.display_name()).color(color))
+                                .on_hover_text("Toggle open / resolved")
+                                .clicked()
+                            {
+                                toggle = Some(annotation.id);
+                            }
+                            if ui.small_button("✖").on_hover_text("Delete annotation").clicked() {
+                                remove = Some(annotation.id);
+                            }
+                        });
+                        ui.weak(format!(
+                            "{} · {} · {}",
+                            annotation.layer.display_name(),
+                            if annotation.author.is_empty() { "unknown" } else { annotation.author.as_str() },
+                            annotation.created_at.format("%Y-%m-%d %H:%M"),
+                        ));
+                        if selected {
+                            let response = ui.add(egui::TextEdit::multiline(&mut annotation.text).desired_rows(2));
+                            edited |= response.lost_focus();
+                        }
+                    }
+                });
+
+            if let Some(id) = focus {
+                focus_annotation(app, id);
+            }
+            if let Some(id) = toggle {
+                if let Some(annotation) = app.review.annotations.iter_mut().find(|annotation| annotation.id == id) {
+                    annotation.status = annotation.status.toggled();
+                    logger.log_info(&format!("📝 Annotation #{} marked {}", id, annotation.status.display_name().to_lowercase()));
+                }
+                edited = true;
+            }
+            if let Some(id) = remove {
+                app.review.annotations.retain(|annotation| annotation.id != id);
+                if app.review.selected == Some(id) {
+                    app.review.selected = None;
+                }
+                logger.log_info(&format!("📝 Deleted annotation #{}", id));
+                edited = true;
+            }
+            if edited {
+                save_project_annotations(app, &logger);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let enabled = !app.review.annotations.is_empty();
+                if ui.add_enabled(enabled, egui::Button::new("🌐 Export HTML...")).clicked() {
+                    export_review(app, &logger);
+                }
+                ui.weak("The PDF review sheet is under Export > PDF Drawings");
+            });
+        });
+}
+
+fn first_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default();
+    if line.chars().count() > 40 {
+        format!("{}…", line.chars().take(40).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Select an annotation, show its layer and centre the view on it
+pub fn focus_annotation(app: &mut DemoLensApp, id: u32) {
+    let Some(annotation) = app.review.annotations.iter().find(|annotation| annotation.id == id) else {
+        return;
+    };
+    let (x, y) = annotation.center();
+    let layer = annotation.layer;
+    app.review.selected = Some(id);
+    app.ecs_world.insert_resource(ActiveLayer(layer));
+    crate::ecs::set_layer_visibility(&mut app.ecs_world, layer, true);
+    app.pending_view_center = Some(Point2::new(x, y));
+}
+
+/// Store the annotations with the current project, if one is open
+pub fn save_project_annotations(app: &DemoLensApp, logger: &ReactiveEventLogger) {
+    let Some(manager_state) = app.project_manager_state.as_ref() else {
+        return;
+    };
+    if manager_state.current_project.is_none() {
+        return;
+    }
+    if let Err(e) = manager_state.save_annotations(&app.review.annotations) {
+        logger.log_error(&format!("Failed to save review annotations: {}", e));
+    }
+}
+
+fn export_review(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let mut title = match app.project_manager_state.as_ref().and_then(|state| state.current_project.as_ref()) {
+        Some(project) => TitleBlock::from_metadata(&project.metadata),
+        None => TitleBlock::default(),
+    };
+    if title.project.is_empty() {
+        title.project = app.project_manager.get_pcb_path()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+    }
+    title.company = app.export_state.drawings.title.company.clone();
+
+    let file_name = if title.project.is_empty() {
+        "review.html".to_string()
+    } else {
+        format!("{}-review.html", title.project)
+    };
+    let Some(output_path) = rfd::FileDialog::new()
+        .add_filter("HTML page", &["html"])
+        .set_file_name(file_name)
+        .save_file()
+    else {
+        return;
+    };
+
+    match export_review_html(app, &output_path, &title) {
+        Ok(count) => logger.log_info(&format!("📝 Exported {} annotations to {}", count, output_path.display())),
+        Err(e) => logger.log_error(&format!("Review export failed: {}", e)),
+    }
+}
+
+/// Place annotations by clicking on the board while a placement tool is selected
+pub fn handle_annotation_click(ui: &egui::Ui, app: &mut DemoLensApp, response: &egui::Response) {
+    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+        app.review.placing = None;
+        app.review.pending_start = None;
+        return;
+    }
+    let Some(kind) = app.review.placing else {
+        return;
+    };
+    if !response.clicked() {
+        return;
+    }
+    let Some(gerber_coords) = app.ui_state.cursor_gerber_coords else {
+        return;
+    };
+    let point = (gerber_coords.x, gerber_coords.y);
+
+    let (position, end) = match (kind.has_end(), app.review.pending_start.take()) {
+        (true, None) => {
+            app.review.pending_start = Some(point);
+            return;
+        }
+        (true, Some(start)) => (start, Some(point)),
+        (false, _) => (point, None),
+    };
+
+    let active_layer = app.ecs_world.get_resource::<ActiveLayer>().map(|active| active.0);
+    let annotation = Annotation {
+        id: next_annotation_id(&app.review.annotations),
+        kind,
+        layer: app.review.layer.or(active_layer).unwrap_or(LayerType::Copper(1)),
+        position,
+        end,
+        text: std::mem::take(&mut app.review.draft_text),
+        author: app.review.author.clone(),
+        status: AnnotationStatus::Open,
+        created_at: chrono::Utc::now(),
+    };
+    let logger = ReactiveEventLogger::with_colors(&app.logger_state, &app.log_colors);
+    logger.log_info(&format!(
+        "📝 Added {} #{} on {} at ({:.2}, {:.2})",
+        kind.display_name().to_lowercase(), annotation.id, annotation.layer.display_name(), position.0, position.1,
+    ));
+    app.review.selected = Some(annotation.id);
+    app.review.annotations.push(annotation);
+    save_project_annotations(app, &logger);
+}
+
+/// Draw the annotations of visible layers over the board
+pub fn render_annotations(app: &mut DemoLensApp, painter: &Painter) {
+    let to_screen = |view_state: &gerber_viewer::ViewState, (x, y): (f64, f64)| view_state.gerber_to_screen_coords(Point2::new(x, y));
+
+    for annotation in &app.review.annotations {
+        let selected = app.review.selected == Some(annotation.id);
+        if !selected && !app.review.show_all_layers && !crate::ecs::get_layer_visibility(&mut app.ecs_world, annotation.layer) {
+            continue;
+        }
+        let color = if selected {
+            SELECTED_COLOR
+        } else if annotation.is_open() {
+            OPEN_COLOR
+        } else {
+            RESOLVED_COLOR
+        };
+        let stroke = Stroke::new(if selected { 2.5 } else { 1.5 }, color);
+        let start = to_screen(&app.view_state, annotation.position);
+        let end = annotation.end.map(|point| to_screen(&app.view_state, point));
+
+        let label_pos = match (annotation.kind, end) {
+            (AnnotationKind::Rectangle, Some(end)) => {
+                painter.rect_stroke(Rect::from_two_pos(start, end), 0.0, stroke, egui::StrokeKind::Middle);
+                Rect::from_two_pos(start, end).left_top()
+            }
+            (AnnotationKind::Arrow, Some(end)) => {
+                draw_arrow(painter, start, end, stroke);
+                start
+            }
+            (AnnotationKind::Text, _) => {
+                painter.circle_filled(start, 3.0, color);
+                painter.text(
+                    start + Vec2::new(6.0, 0.0),
+                    egui::Align2::LEFT_CENTER,
+                    first_line(&annotation.text),
+                    egui::FontId::proportional(13.0),
+                    color,
+                );
+                start
+            }
+            _ => {
+                painter.circle_stroke(start, 6.0, stroke);
+                painter.circle_filled(start, 2.0, color);
+                start
+            }
+        };
+        painter.text(
+            label_pos + Vec2::new(4.0, -4.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("#{}", annotation.id),
+            egui::FontId::monospace(12.0),
+            color,
+        );
+    }
+
+    // Rubber band while placing an arrow or rectangle
+    if let (Some(kind), Some(start)) = (app.review.placing, app.review.pending_start) {
+        let Some(mouse_pos) = painter.ctx().input(|i| i.pointer.hover_pos()) else {
+            return;
+        };
+        let start = to_screen(&app.view_state, start);
+        let stroke = Stroke::new(1.5, Color32::LIGHT_BLUE);
+        match kind {
+            AnnotationKind::Rectangle => {
+                painter.rect_stroke(Rect::from_two_pos(start, mouse_pos), 0.0, stroke, egui::StrokeKind::Middle);
+            }
+            _ => draw_arrow(painter, start, mouse_pos, stroke),
+        }
+    }
+}
+
+fn draw_arrow(painter: &Painter, start: Pos2, end: Pos2, stroke: Stroke) {
+    painter.line_segment([start, end], stroke);
+    let direction = (end - start).normalized();
+    if direction.length() == 0.0 || !direction.x.is_finite() {
+        return;
+    }
+    let back = end - direction * 10.0;
+    let side = direction.rot90() * 5.0;
+    painter.line_segment([end, back + side], stroke);
+    painter.line_segment([end, back - side], stroke);
+}
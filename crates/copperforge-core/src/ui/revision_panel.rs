@@ -0,0 +1,216 @@
+use crate::DemoLensApp;
+use crate::ecs::{ActiveLayer, LayerInfo, ReferenceRevision, RevisionDiff, RevisionLayerStatus};
+use crate::geometry::DiffClass;
+use egui::{Color32, Painter, Pos2, RichText};
+use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
+use egui_mobius_reactive::Dynamic;
+use nalgebra::Point2;
+
+// Revision comparison
+// Loads an earlier Gerber set as the reference revision, lists what changed per
+// layer and steps through the changed regions. The diff view replaces the layers
+// with the active layer's comparison image.
+
+const ADDED_COLOR: Color32 = Color32::from_rgb(60, 200, 80);
+const REMOVED_COLOR: Color32 = Color32::from_rgb(230, 60, 60);
+const UNCHANGED_COLOR: Color32 = Color32::from_rgba_premultiplied(70, 70, 70, 140);
+
+/// Texture of a layer's comparison and what it was made from
+pub struct RevisionDiffPreview {
+    entity: bevy_ecs::entity::Entity,
+    /// Address of the diff pixels, which change when the layer is compared again
+    fingerprint: usize,
+    texture: egui::TextureHandle,
+    min: Point2<f64>,
+    max: Point2<f64>,
+}
+
+pub fn show_revision_panel(
+    ui: &mut egui::Ui,
+    app: &mut DemoLensApp,
+    logger_state: &Dynamic<ReactiveEventLoggerState>,
+    log_colors: &Dynamic<LogColors>,
+) {
+    let logger = ReactiveEventLogger::with_colors(logger_state, log_colors);
+
+    egui::CollapsingHeader::new("🆚 Revision Compare")
+        .default_open(false)
+        .show(ui, |ui| {
+            // Layers loaded since the last comparison
+            crate::ecs::compare_revisions_system(&mut app.ecs_world);
+
+            let source = app.ecs_world.get_resource::<ReferenceRevision>()
+                .and_then(|reference| reference.source.clone());
+
+            ui.horizontal(|ui| {
+                if ui.button("📂 Load Reference...").on_hover_text("Gerber directory of the earlier revision").clicked() {
+                    if let Some(dir) = rfd::FileDialog::new().set_title("Select Reference Gerber Directory").pick_folder() {
+                        match crate::ecs::load_reference_revision_system(&mut app.ecs_world, &dir) {
+                            Ok(count) => {
+                                logger.log_info(&format!("🆚 Loaded {} reference layers from {}", count, dir.display()));
+                                app.diff_focus = None;
+                            }
+                            Err(e) => logger.log_error(&format!("Failed to load reference revision: {}", e)),
+                        }
+                    }
+                }
+                if source.is_some() && ui.button("✖ Clear").clicked() {
+                    crate::ecs::clear_reference_revision_system(&mut app.ecs_world);
+                    app.revision_diff_view = false;
+                    app.revision_diff_preview = None;
+                    app.diff_focus = None;
+                    logger.log_info("🆚 Cleared reference revision");
+                }
+            });
+
+            let Some(source) = source else {
+                ui.weak("Load the Gerber directory of an earlier revision to compare it with the loaded layers.");
+                return;
+            };
+            ui.label(format!("Reference: {}", source.display()));
+
+            ui.checkbox(&mut app.revision_diff_view, "Show diff of the active layer")
+                .on_hover_text("Replace the layers with the comparison of the active layer");
+            ui.horizontal(|ui| {
+                ui.colored_label(ADDED_COLOR, "■ Added");
+                ui.colored_label(REMOVED_COLOR, "■ Removed");
+                ui.colored_label(Color32::GRAY, "■ Unchanged");
+            });
+
+            ui.add_space(4.0);
+            let summaries = crate::ecs::revision_summary_system(&mut app.ecs_world);
+            let active_layer = app.ecs_world.get_resource::<ActiveLayer>().map(|active| active.0);
+            egui::Grid::new("revision_summary_grid")
+                .num_columns(4)
+                .spacing([10.0, 2.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label(RichText::new("Layer").strong());
+                    ui.label(RichText::new("Status").strong());
+                    ui.label(RichText::new("+/- mm²").strong());
+                    ui.label(RichText::new("Regions").strong());
+                    ui.end_row();
+
+                    for summary in &summaries {
+                        let selected = active_layer == Some(summary.layer_type);
+                        if ui.selectable_label(selected, summary.layer_type.display_name()).clicked() {
+                            app.ecs_world.insert_resource(ActiveLayer(summary.layer_type));
+                        }
+                        let status_color = match summary.status {
+                            RevisionLayerStatus::Unchanged => Color32::GRAY,
+                            RevisionLayerStatus::Changed => Color32::YELLOW,
+                            RevisionLayerStatus::Added => ADDED_COLOR,
+                            RevisionLayerStatus::Removed => REMOVED_COLOR,
+                        };
+                        ui.colored_label(status_color, summary.status.display_name());
+                        ui.label(format!("+{:.3} / -{:.3}", summary.added_area, summary.removed_area));
+                        ui.label(summary.regions.len().to_string());
+                        ui.end_row();
+                    }
+                });
+
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                let mut step = None;
+                if ui.button("◀ Previous").clicked() {
+                    step = Some(false);
+                }
+                if ui.button("Next ▶").on_hover_text("Centre the view on the next difference").clicked() {
+                    step = Some(true);
+                }
+                if let Some(forward) = step {
+                    app.diff_focus = crate::ecs::step_difference_system(&mut app.ecs_world, forward, &app.display_manager);
+                    match &app.diff_focus {
+                        Some(focus) => app.pending_view_center = Some(focus.center),
+                        None => logger.log_info("🆚 No differences between the revisions"),
+                    }
+                }
+            });
+
+            if let Some(focus) = &app.diff_focus {
+                let center = focus.region.center();
+                ui.label(format!(
+                    "{} of {}: {} on {}",
+                    focus.position + 1,
+                    focus.count,
+                    focus.region.describe(),
+                    focus.layer_type.display_name(),
+                ));
+                ui.weak(format!(
+                    "+{:.3} / -{:.3} mm² at ({:.3}, {:.3}) mm, {:.2} x {:.2} mm",
+                    focus.region.added_area,
+                    focus.region.removed_area,
+                    center.x,
+                    center.y,
+                    focus.region.max.x - focus.region.min.x,
+                    focus.region.max.y - focus.region.min.y,
+                ));
+            }
+        });
+}
+
+/// Draw the comparison image of the active layer in place of the layers
+pub fn render_revision_diff(app: &mut DemoLensApp, painter: &Painter) {
+    crate::ecs::compare_revisions_system(&mut app.ecs_world);
+    let Some(active_layer) = app.ecs_world.get_resource::<ActiveLayer>().map(|active| active.0) else {
+        return;
+    };
+
+    let mut query = app.ecs_world.query::<(bevy_ecs::entity::Entity, &LayerInfo, &RevisionDiff, &crate::ecs::Transform, &crate::ecs::ImageTransform)>();
+    let Some((entity, info, diff, transform, image_transform)) = query.iter(&app.ecs_world)
+        .find(|(_, info, ..)| info.layer_type == active_layer)
+    else {
+        return;
+    };
+    let Some(diff) = diff.0.as_ref() else {
+        return;
+    };
+    let view_transform = crate::ecs::layer_view_transform(transform, image_transform, &info.layer_type, &app.display_manager);
+
+    let fingerprint = diff.classes.as_ptr() as usize;
+    let stale = app.revision_diff_preview.as_ref()
+        .is_none_or(|preview| preview.entity != entity || preview.fingerprint != fingerprint);
+    if stale {
+        let size = [diff.view.width as usize, diff.view.height as usize];
+        let pixels: Vec<Color32> = diff.classes.iter()
+            .map(|class| match class {
+                DiffClass::Empty => Color32::TRANSPARENT,
+                DiffClass::Unchanged => UNCHANGED_COLOR,
+                DiffClass::Added => ADDED_COLOR,
+                DiffClass::Removed => REMOVED_COLOR,
+            })
+            .collect();
+        let image = egui::ColorImage::new(size, pixels);
+        let (min, max) = diff.extent();
+        app.revision_diff_preview = Some(RevisionDiffPreview {
+            entity,
+            fingerprint,
+            texture: painter.ctx().load_texture("revision_diff", image, egui::TextureOptions::NEAREST),
+            min,
+            max,
+        });
+    }
+    let Some(preview) = app.revision_diff_preview.as_ref() else {
+        return;
+    };
+
+    // Image corners clockwise from top left, through the layer's transform
+    let corners = [
+        (preview.min.x, preview.max.y),
+        (preview.max.x, preview.max.y),
+        (preview.max.x, preview.min.y),
+        (preview.min.x, preview.min.y),
+    ];
+    let uvs = [Pos2::new(0.0, 0.0), Pos2::new(1.0, 0.0), Pos2::new(1.0, 1.0), Pos2::new(0.0, 1.0)];
+    let mut mesh = egui::Mesh::with_texture(preview.texture.id());
+    for ((x, y), uv) in corners.into_iter().zip(uvs) {
+        mesh.vertices.push(egui::epaint::Vertex {
+            pos: app.view_state.gerber_to_screen_coords(view_transform.apply(Point2::new(x, y))),
+            uv,
+            color: Color32::WHITE,
+        });
+    }
+    mesh.add_triangle(0, 1, 2);
+    mesh.add_triangle(0, 2, 3);
+    painter.add(egui::Shape::mesh(mesh));
+}
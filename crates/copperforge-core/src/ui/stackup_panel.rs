@@ -0,0 +1,192 @@
+use crate::DemoLensApp;
+use crate::ecs::{ActiveLayer, BoardStackup, LayerType, StackupLayer, StackupLayerKind};
+use egui::{Align2, Color32, FontId, RichText, Sense, Stroke, StrokeKind, Vec2};
+use egui_lens::{ReactiveEventLogger, ReactiveEventLoggerState, LogColors};
+use egui_mobius_reactive::Dynamic;
+use kicad_ecs::client::KiCadClient;
+
+// Stackup cross-section
+// Draws the board's layer stack top to bottom with thickness and material of
+// each layer. Band heights follow the thickness within limits so thin copper
+// stays clickable next to a thick core; clicking a copper band makes it the
+// active layer.
+
+/// Band height per mm of layer thickness
+const BAND_SCALE: f32 = 40.0;
+const MIN_BAND_HEIGHT: f32 = 14.0;
+const MAX_BAND_HEIGHT: f32 = 48.0;
+/// Share of the panel width used by the cross-section, the rest holds the labels
+const SECTION_WIDTH: f32 = 0.4;
+
+const ACTIVE_COLOR: Color32 = Color32::from_rgb(255, 220, 0);
+
+fn band_color(kind: StackupLayerKind) -> Color32 {
+    match kind {
+        StackupLayerKind::Copper => Color32::from_rgb(200, 120, 50),
+        StackupLayerKind::Dielectric => Color32::from_rgb(170, 160, 90),
+        StackupLayerKind::Soldermask => Color32::from_rgb(30, 120, 60),
+        StackupLayerKind::Silkscreen => Color32::from_rgb(235, 235, 235),
+        StackupLayerKind::Paste => Color32::from_rgb(150, 150, 160),
+    }
+}
+
+/// Thickness in µm below 0.1 mm, else in mm
+fn format_thickness(thickness: f64) -> String {
+    if thickness <= 0.0 {
+        "-".to_string()
+    } else if thickness < 0.1 {
+        format!("{:.0} µm", thickness * 1000.0)
+    } else {
+        format!("{:.3} mm", thickness)
+    }
+}
+
+fn layer_details(layer: &StackupLayer) -> String {
+    let mut details = vec![format_thickness(layer.thickness)];
+    if let Some(material) = &layer.material {
+        details.push(material.clone());
+    }
+    if let Some(dielectric_constant) = layer.dielectric_constant {
+        details.push(format!("εr {:.2}", dielectric_constant));
+    }
+    details.join(" · ")
+}
+
+pub fn show_stackup_panel(
+    ui: &mut egui::Ui,
+    app: &mut DemoLensApp,
+    logger_state: &Dynamic<ReactiveEventLoggerState>,
+    log_colors: &Dynamic<LogColors>,
+) {
+    let logger = ReactiveEventLogger::with_colors(logger_state, log_colors);
+
+    egui::CollapsingHeader::new("🥞 Stackup")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("📂 Load Job File...").on_hover_text("Gerber job file (.gbrjob)").clicked() {
+                    load_job_file(app, &logger);
+                }
+                if ui.button("🔌 From KiCad").on_hover_text("Stackup of the board open in KiCad").clicked() {
+                    load_from_kicad(app, &logger);
+                }
+            });
+
+            let stackup = app.ecs_world.get_resource::<BoardStackup>()
+                .map(|stackup| stackup.0.clone())
+                .unwrap_or_default();
+            if stackup.is_empty() {
+                ui.label(RichText::new("No stackup loaded. Load a job file, an IPC-2581 board or the board open in KiCad.").weak());
+                return;
+            }
+
+            let copper = stackup.iter().filter(|layer| layer.kind == StackupLayerKind::Copper).count();
+            ui.label(format!(
+                "{} layers, {} copper, {} total",
+                stackup.len(),
+                copper,
+                format_thickness(crate::ecs::stackup_thickness(&stackup))
+            ));
+            ui.add_space(4.0);
+
+            let active_layer = app.ecs_world.get_resource::<ActiveLayer>().map(|active| active.0);
+            if let Some(index) = draw_cross_section(ui, &stackup, active_layer)
+                && let Some(layer_type) = crate::ecs::select_stackup_layer_system(&mut app.ecs_world, index)
+            {
+                logger.log_info(&format!("Active layer: {}", layer_type.display_name()));
+            }
+        });
+}
+
+/// Draw the layer bands, returning the index of a clicked copper layer
+fn draw_cross_section(ui: &mut egui::Ui, stackup: &[StackupLayer], active_layer: Option<LayerType>) -> Option<usize> {
+    let mut clicked = None;
+    let width = ui.available_width().max(200.0);
+    let section_width = width * SECTION_WIDTH;
+
+    ui.scope(|ui| {
+        ui.spacing_mut().item_spacing.y = 0.0;
+        for (index, layer) in stackup.iter().enumerate() {
+            let height = (layer.thickness as f32 * BAND_SCALE).clamp(MIN_BAND_HEIGHT, MAX_BAND_HEIGHT);
+            let copper = layer.kind == StackupLayerKind::Copper;
+            let sense = if copper { Sense::click() } else { Sense::hover() };
+            let (rect, response) = ui.allocate_exact_size(Vec2::new(width, height), sense);
+
+            let band = egui::Rect::from_min_size(rect.min, Vec2::new(section_width, height));
+            let painter = ui.painter();
+            painter.rect_filled(band, 0.0, band_color(layer.kind));
+
+            let active = copper && layer.layer_type.is_some() && layer.layer_type == active_layer;
+            if active {
+                painter.rect_stroke(band.shrink(1.0), 0.0, Stroke::new(2.0, ACTIVE_COLOR), StrokeKind::Inside);
+            } else if copper && response.hovered() {
+                painter.rect_stroke(band.shrink(1.0), 0.0, Stroke::new(1.0, Color32::WHITE), StrokeKind::Inside);
+            }
+
+            let text_color = if active { ACTIVE_COLOR } else { ui.visuals().text_color() };
+            let font = FontId::proportional((height - 3.0).clamp(10.0, 13.0));
+            let label_x = band.right() + 8.0;
+            painter.text(
+                egui::pos2(label_x, rect.center().y),
+                Align2::LEFT_CENTER,
+                &layer.name,
+                font.clone(),
+                text_color,
+            );
+            painter.text(
+                egui::pos2(rect.right() - 4.0, rect.center().y),
+                Align2::RIGHT_CENTER,
+                layer_details(layer),
+                font,
+                ui.visuals().weak_text_color(),
+            );
+
+            if copper {
+                let response = response.on_hover_cursor(egui::CursorIcon::PointingHand);
+                let response = match layer.layer_type {
+                    Some(layer_type) => response.on_hover_text(format!("Click to make {} the active layer", layer_type.display_name())),
+                    None => response,
+                };
+                if response.clicked() {
+                    clicked = Some(index);
+                }
+            }
+        }
+    });
+    clicked
+}
+
+fn load_job_file(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let Some(path) = rfd::FileDialog::new()
+        .add_filter("Gerber job file", &["gbrjob"])
+        .pick_file()
+    else {
+        return;
+    };
+    let result = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        .and_then(|text| crate::ecs::parse_gbrjob_stackup(&text));
+    match result {
+        Ok(layers) => {
+            let count = layers.len();
+            let copper = crate::ecs::load_stackup_system(&mut app.ecs_world, layers);
+            logger.log_info(&format!("🥞 Loaded stackup of {} layers ({} copper) from {}", count, copper, path.display()));
+        }
+        Err(e) => logger.log_error(&format!("Stackup load failed: {}", e)),
+    }
+}
+
+fn load_from_kicad(app: &mut DemoLensApp, logger: &ReactiveEventLogger) {
+    let result = KiCadClient::connect()
+        .and_then(|mut client| futures::executor::block_on(client.get_board_stackup()));
+    match result {
+        Ok(layers) if layers.is_empty() => logger.log_warning("KiCad reported an empty stackup"),
+        Ok(layers) => {
+            let layers = crate::ecs::stackup_from_kicad(&layers);
+            let count = layers.len();
+            let copper = crate::ecs::load_stackup_system(&mut app.ecs_world, layers);
+            logger.log_info(&format!("🥞 Loaded stackup of {} layers ({} copper) from KiCad", count, copper));
+        }
+        Err(e) => logger.log_error(&format!("Failed to get the stackup from KiCad: {}", e)),
+    }
+}
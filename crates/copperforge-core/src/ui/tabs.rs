@@ -1,6 +1,7 @@
 use crate::DemoLensApp;
 use crate::ui;
-use crate::ecs::{UnitsResource, mm_to_nm, nm_to_mm, mils_to_nm, nm_to_mils};
+use crate::ecs::{UnitsResource, format_measurement, mm_to_nm, nm_to_mm, mils_to_nm, nm_to_mils};
+use crate::ui::measurement_panel::MeasureTool;
 
 use eframe::emath::{Rect, Vec2};
 use eframe::epaint::Color32;
@@ -18,6 +19,10 @@ use crate::display::manager::ToPosition;
 use nalgebra::Vector2;
 
 const MARKER_RADIUS: f32 = 6.0;
+/// Screen distance (px) within which a click still picks a feature
+const PICK_RADIUS: f32 = 3.0;
+/// Screen distance (px) within which measurement points snap to feature geometry
+const SNAP_RADIUS: f32 = 8.0;
 
 /// Define the tabs for the DockArea
 #[derive(Clone, Serialize, Deserialize)]
@@ -84,6 +89,24 @@ impl Tab {
                     ui.heading("Layer Controls");
                     ui.separator();
                     ui::show_layers_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
+                    
+                    ui.add_space(8.0);
+                    ui::show_stackup_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
+                    
+                    ui.add_space(8.0);
+                    ui::show_net_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
+
+                    ui.add_space(8.0);
+                    ui::show_measurement_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
+
+                    ui.add_space(8.0);
+                    ui::show_review_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
+
+                    ui.add_space(8.0);
+                    ui::show_revision_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
+                    
+                    ui.add_space(8.0);
+                    ui::show_export_panel(ui, params.app, &logger_state_clone, &log_colors_clone);
                 });
             }
             TabKind::DRC => {
@@ -127,6 +150,9 @@ impl Tab {
         
         // Render the gerber layers and overlays
         render_gerber_content(ui, app, &viewport);
+        
+        // Inspector for the feature picked in the view
+        ui::show_feature_inspector(ui.ctx(), app);
     }
 }
 
@@ -146,6 +172,7 @@ fn render_controls(ui: &mut egui::Ui, app: &mut DemoLensApp) {
         // Second row: Measurement and grid tools
         ui.horizontal(|ui| {
             render_zoom_display(ui, app);
+            render_frame_stats(ui, app);
             ui.separator();
             render_ruler_controls(ui, app);
             ui.separator();
@@ -231,6 +258,10 @@ fn render_layer_controls(ui: &mut egui::Ui, app: &mut DemoLensApp) {
         
         crate::ecs::mark_coordinates_dirty_ecs(&mut app.ecs_world);
     }
+    
+    ui.add_enabled(!app.display_manager.quadrant_view_enabled, egui::Checkbox::new(&mut app.realistic_view, "Realistic"))
+        .on_hover_text("Show the board as manufactured, with mask, finish and silkscreen")
+        .on_disabled_hover_text("Not available in quadrant view");
 }
 
 fn render_transform_controls(ui: &mut egui::Ui, app: &mut DemoLensApp) {
@@ -393,11 +424,46 @@ fn render_ruler_controls(ui: &mut egui::Ui, app: &mut DemoLensApp) {
             // Clear ruler when deactivated
             app.ruler_start = None;
             app.ruler_end = None;
+            app.measure_points.clear();
+            app.clearance_start = None;
         }
     }
     
+    let previous_tool = app.measure_tool;
+    egui::ComboBox::from_id_salt("measure_tool")
+        .selected_text(app.measure_tool.display_name())
+        .show_ui(ui, |ui| {
+            for tool in MeasureTool::all() {
+                ui.selectable_value(&mut app.measure_tool, tool, tool.display_name());
+            }
+        });
+    if app.measure_tool != previous_tool {
+        app.ruler_start = None;
+        app.ruler_end = None;
+        app.ruler_dragging = false;
+        app.measure_points.clear();
+        app.clearance_start = None;
+    }
+    ui.checkbox(&mut app.snap_to_features, "🧲 Snap to Features")
+        .on_hover_text("Snap to pad and drill centres, track ends, centrelines, vertices and edges");
+    
+    // Angle, area and clearance show their progress; results go to the measurement list
+    if app.ruler_active && app.measure_tool != MeasureTool::Distance {
+        let placed = match app.measure_tool {
+            MeasureTool::Clearance => usize::from(app.clearance_start.is_some()),
+            _ => app.measure_points.len(),
+        };
+        if let Some(first) = &app.clearance_start {
+            ui.label(format!("First: {} #{}", first.layer_type.display_name(), first.index));
+        }
+        ui.label(app.measure_tool.hint(placed));
+        if let Some(last) = crate::ecs::get_measurements(&app.ecs_world).last() {
+            let units_resource = Tab::get_units(app);
+            ui.label(format!("Last {}: {}", last.kind_name().to_lowercase(), format_measurement(last, units_resource)));
+        }
+    }
     // Show ruler measurement if active and both points set
-    if app.ruler_active {
+    else if app.ruler_active {
         if let (Some(start), Some(end)) = (app.ruler_start, app.ruler_end) {
             let dx = end.x - start.x;
             let dy = end.y - start.y;
@@ -534,11 +600,12 @@ fn handle_viewport_interactions(ui: &mut egui::Ui, app: &mut DemoLensApp, viewpo
                 let painter = ui.painter();
                 
                 // Draw preview text
-                let text = if app.ruler_start.is_some() && app.ruler_end.is_none() {
-                    "Click second point to complete measurement"
-                } else {
-                    "Click to start measurement"
+                let placed = match app.measure_tool {
+                    MeasureTool::Distance => usize::from(app.ruler_start.is_some() && app.ruler_end.is_none()),
+                    MeasureTool::Clearance => usize::from(app.clearance_start.is_some()),
+                    MeasureTool::Angle | MeasureTool::Area => app.measure_points.len(),
                 };
+                let text = app.measure_tool.hint(placed);
                 painter.text(
                     mouse_pos + Vec2::new(20.0, -20.0),
                     egui::Align2::LEFT_BOTTOM,
@@ -554,6 +621,25 @@ fn handle_viewport_interactions(ui: &mut egui::Ui, app: &mut DemoLensApp, viewpo
             handle_ruler_interaction(ui, app, response);
         }
         
+        // Place review annotations while a placement tool is selected
+        if app.review.placing.is_some() && !app.ruler_active && !app.setting_origin_mode {
+            ui::review_panel::handle_annotation_click(ui, app, response);
+        }
+        
+        // Click-to-inspect: select the topmost feature under the cursor
+        if !app.ruler_active && !app.setting_origin_mode && app.review.placing.is_none() && response.clicked() {
+            if let Some(gerber_coords) = app.ui_state.cursor_gerber_coords {
+                let radius = (PICK_RADIUS / app.view_state.scale) as f64;
+                let hit = crate::ecs::pick_feature_system(&mut app.ecs_world, gerber_coords, radius, &app.display_manager);
+                crate::ecs::select_feature_system(&mut app.ecs_world, hit);
+                
+                // Highlight the whole net of the picked feature (X2 .N attribute)
+                let net = crate::ecs::get_selected_feature(&mut app.ecs_world)
+                    .and_then(|selected| selected.feature.net().map(str::to_string));
+                crate::ecs::highlight_net_system(&mut app.ecs_world, net);
+            }
+        }
+        
         // Handle origin setting
         if app.setting_origin_mode && response.clicked() {
             if let Some(gerber_coords) = app.ui_state.cursor_gerber_coords {
@@ -692,6 +778,19 @@ fn render_gerber_content(ui: &mut egui::Ui, app: &mut DemoLensApp, viewport: &Re
     if app.needs_initial_view {
         app.reset_view(*viewport);
     }
+    app.viewer_rect = Some(*viewport);
+    
+    // Centre on a point requested by a panel (e.g. the revision difference navigator)
+    if let Some(center) = app.pending_view_center.take() {
+        let viewport_center = viewport.center();
+        app.view_state.translation = Vec2::new(
+            viewport_center.x - (center.x as f32 * app.view_state.scale),
+            viewport_center.y + (center.y as f32 * app.view_state.scale),
+        );
+        if let Some(mut view_state_resource) = app.ecs_world.get_resource_mut::<crate::ecs::ViewStateResource>() {
+            view_state_resource.view_state = app.view_state.clone();
+        }
+    }
     
     let painter = ui.painter().with_clip_rect(*viewport);
     
@@ -741,15 +840,30 @@ fn render_overlays(app: &mut DemoLensApp, painter: &Painter, viewport: &Rect) {
     // Corner overlay shapes
     render_corner_overlays(app, painter);
     
+    // Dead copper islands
+    render_dead_copper_overlay(app, painter);
+    
     // DRC violations
     render_drc_violations(app, painter);
     
+    // IPC-D-356 test points
+    render_netlist_overlay(app, painter);
+    
+    // Net and component highlighted from X2 attributes
+    render_highlighted_features(app, painter);
+    
+    // Feature picked for inspection
+    render_selected_feature(app, painter);
+    
     // Board dimensions
     render_board_dimensions(app, painter, viewport);
     
     // Enterprise feature: Ruler visualization
     render_ruler(app, painter);
     
+    // Design review annotations
+    ui::review_panel::render_annotations(app, painter);
+    
     // Custom measurement crosshair
     render_measurement_crosshair(app, painter);
     
@@ -840,6 +954,91 @@ fn render_drc_violations(app: &mut DemoLensApp, painter: &Painter) {
     }
 }
 
+/// Convert a board position (mm) to screen space, applying rotation, mirroring and offsets
+pub(crate) fn board_to_screen(app: &DemoLensApp, position: Position) -> Pos2 {
+    app.view_state.gerber_to_screen_coords(app.display_manager.board_to_viewer(position, app.rotation_degrees))
+}
+
+fn render_dead_copper_overlay(app: &mut DemoLensApp, painter: &Painter) {
+    for island in &app.drc_manager.dead_copper {
+        // Islands below the minimum area are violations, larger ones are warnings
+        let color = if island.below_threshold {
+            Color32::RED
+        } else {
+            Color32::from_rgb(230, 126, 34)
+        };
+        
+        for outline in &island.outlines {
+            let points: Vec<Pos2> = outline.iter()
+                .map(|position| board_to_screen(app, *position))
+                .collect();
+            if points.len() >= 3 {
+                painter.add(egui::Shape::closed_line(points, Stroke::new(2.0, color)));
+            }
+        }
+    }
+}
+
+fn render_netlist_overlay(app: &mut DemoLensApp, painter: &Painter) {
+    if !app.show_netlist_overlay {
+        return;
+    }
+    
+    let points = crate::netlist::get_netlist_overlay_points(&mut app.ecs_world);
+    for (position, size, problem) in points {
+        let screen_pos = board_to_screen(app, Position::new(position.x, position.y));
+        let radius = ((size / 2.0) as f32 * app.view_state.scale).max(2.0);
+        
+        if problem {
+            painter.circle_stroke(screen_pos, radius, Stroke::new(2.0, Color32::RED));
+            draw_violation_marker(painter, screen_pos, radius, Color32::RED);
+        } else {
+            painter.circle_stroke(screen_pos, radius, Stroke::new(1.0, Color32::from_rgb(0, 200, 255)));
+        }
+    }
+}
+
+fn render_highlighted_features(app: &mut DemoLensApp, painter: &Painter) {
+    let (net, component) = crate::ecs::highlighted_feature_outlines(&mut app.ecs_world, &app.display_manager);
+    let styles = [
+        (net, Stroke::new(1.5, Color32::from_rgb(0, 220, 255))),
+        (component, Stroke::new(2.0, Color32::from_rgb(255, 80, 220))),
+    ];
+    for (outlines, stroke) in styles {
+        for polygon in outlines.iter().filter(|polygon| polygon.dark) {
+            let points: Vec<Pos2> = polygon.points.iter()
+                .map(|point| app.view_state.gerber_to_screen_coords(*point))
+                .collect();
+            if points.len() >= 3 {
+                painter.add(egui::Shape::closed_line(points, stroke));
+            } else if points.len() == 2 {
+                painter.line_segment([points[0], points[1]], stroke);
+            }
+        }
+    }
+}
+
+fn render_selected_feature(app: &mut DemoLensApp, painter: &Painter) {
+    let outlines = crate::ecs::selected_feature_outlines(&mut app.ecs_world, &app.display_manager);
+    for polygon in outlines {
+        let points: Vec<Pos2> = polygon.points.iter()
+            .map(|point| app.view_state.gerber_to_screen_coords(*point))
+            .collect();
+        // Aperture holes are drawn thinner than the feature outline
+        let stroke = if polygon.dark {
+            Stroke::new(2.0, Color32::from_rgb(255, 230, 0))
+        } else {
+            Stroke::new(1.0, Color32::from_rgb(255, 230, 0))
+        };
+        
+        if points.len() >= 3 {
+            painter.add(egui::Shape::closed_line(points, stroke));
+        } else if points.len() == 2 {
+            painter.line_segment([points[0], points[1]], stroke);
+        }
+    }
+}
+
 fn render_board_dimensions(app: &mut DemoLensApp, painter: &Painter, viewport: &Rect) {
     if let Some((_entity, _layer_info, gerber_data, _visibility)) = crate::ecs::get_layer_data(&mut app.ecs_world, crate::ecs::LayerType::MechanicalOutline) {
         let bbox = gerber_data.0.bounding_box();
@@ -902,9 +1101,15 @@ fn render_zoom_window(app: &mut DemoLensApp, painter: &Painter) {
 }
 
 fn render_ruler(app: &mut DemoLensApp, painter: &Painter) {
+    // Measurements kept in the session list
+    render_measurement_list(app, painter);
+    
     // Render active ruler if active
     if app.ruler_active {
-        render_ruler_measurement(app, painter, app.ruler_start, app.ruler_end, true);
+        if app.measure_tool == MeasureTool::Distance {
+            render_ruler_measurement(app, painter, app.ruler_start, app.ruler_end, true);
+        }
+        render_measurement_in_progress(app, painter);
     }
     // Render latched ruler if not active but latched measurement exists
     else if app.latched_measurement_start.is_some() && app.latched_measurement_end.is_some() {
@@ -999,6 +1204,97 @@ fn render_ruler_measurement(app: &mut DemoLensApp, painter: &Painter, start_opt:
     }
 }
 
+fn render_measurement_list(app: &mut DemoLensApp, painter: &Painter) {
+    let measurements = crate::ecs::get_measurements(&app.ecs_world);
+    if measurements.is_empty() {
+        return;
+    }
+    let units_resource = Tab::get_units(app).clone();
+    let color = Color32::from_rgb(255, 190, 70);
+    let stroke = Stroke::new(1.5, color);
+    
+    for (index, measurement) in measurements.iter().enumerate() {
+        let points: Vec<Pos2> = measurement.points().iter()
+            .map(|point| app.view_state.gerber_to_screen_coords(*point))
+            .collect();
+        if points.is_empty() {
+            continue;
+        }
+        match measurement {
+            crate::ecs::Measurement::Area { .. } => {
+                painter.add(egui::Shape::closed_line(points.clone(), stroke));
+            }
+            _ => {
+                painter.add(egui::Shape::line(points.clone(), stroke));
+            }
+        }
+        for point in &points {
+            painter.circle_filled(*point, 2.5, color);
+        }
+        
+        // Label at the vertex of an angle, the centroid of an area, else the midpoint
+        let anchor = match measurement {
+            crate::ecs::Measurement::Angle { .. } => points[1],
+            _ => {
+                let sum = points.iter().fold(Vec2::ZERO, |sum, point| sum + point.to_vec2());
+                (sum / points.len() as f32).to_pos2()
+            }
+        };
+        painter.text(
+            anchor + Vec2::new(8.0, -8.0),
+            egui::Align2::LEFT_BOTTOM,
+            format!("#{} {}", index + 1, format_measurement(measurement, &units_resource)),
+            egui::FontId::monospace(13.0),
+            color,
+        );
+    }
+}
+
+fn render_measurement_in_progress(app: &mut DemoLensApp, painter: &Painter) {
+    let Some(mouse_pos) = painter.ctx().input(|i| i.pointer.hover_pos()) else {
+        return;
+    };
+    if !painter.clip_rect().contains(mouse_pos) {
+        return;
+    }
+    
+    // Snap indicator
+    let snap = feature_snap(app, mouse_pos);
+    if let Some(snap) = &snap {
+        let screen = app.view_state.gerber_to_screen_coords(snap.point);
+        painter.rect_stroke(
+            Rect::from_center_size(screen, Vec2::splat(10.0)),
+            0.0,
+            Stroke::new(1.5, Color32::GREEN),
+            egui::StrokeKind::Middle,
+        );
+        painter.text(
+            screen + Vec2::new(8.0, 8.0),
+            egui::Align2::LEFT_TOP,
+            snap.label(),
+            egui::FontId::proportional(12.0),
+            Color32::GREEN,
+        );
+    }
+    
+    if matches!(app.measure_tool, MeasureTool::Angle | MeasureTool::Area) && !app.measure_points.is_empty() {
+        let cursor = snap.map(|snap| app.view_state.gerber_to_screen_coords(snap.point)).unwrap_or(mouse_pos);
+        let mut points: Vec<Pos2> = app.measure_points.iter()
+            .map(|point| app.view_state.gerber_to_screen_coords(*point))
+            .collect();
+        points.push(cursor);
+        let stroke = Stroke::new(2.0, Color32::LIGHT_BLUE);
+        if app.measure_tool == MeasureTool::Area && points.len() >= 3 {
+            painter.add(egui::Shape::closed_line(points.clone(), stroke));
+        } else {
+            painter.add(egui::Shape::line(points.clone(), stroke));
+        }
+        for point in &points[..points.len() - 1] {
+            painter.circle_filled(*point, 3.0, Color32::LIGHT_BLUE);
+        }
+    }
+}
+
 fn handle_ruler_interaction(ui: &mut egui::Ui, app: &mut DemoLensApp, response: &egui::Response) {
     if !app.ruler_active {
         return;
@@ -1006,28 +1302,35 @@ fn handle_ruler_interaction(ui: &mut egui::Ui, app: &mut DemoLensApp, response:
     
     let mouse_pos = ui.input(|i| i.pointer.hover_pos());
     
+    match app.measure_tool {
+        MeasureTool::Distance => {}
+        MeasureTool::Clearance => {
+            handle_clearance_click(app, response);
+            return;
+        }
+        MeasureTool::Angle | MeasureTool::Area => {
+            handle_polyline_click(app, response, mouse_pos);
+            return;
+        }
+    }
+    
     // In ruler mode, left-click to set measurement points
     if response.clicked() {
         if let Some(mouse_screen_pos) = mouse_pos {
-            let gerber_coords = app.view_state.screen_to_gerber_coords(mouse_screen_pos);
-            
-            // Apply snap to grid if enabled
-            let final_coords = if app.grid_settings.snap_enabled {
-                let point = nalgebra::Point2::new(gerber_coords.x, gerber_coords.y);
-                crate::display::snap_to_grid(point, &app.grid_settings)
-            } else {
-                nalgebra::Point2::new(gerber_coords.x, gerber_coords.y)
-            };
+            let final_coords = measure_point(app, mouse_screen_pos);
             
             if app.ruler_start.is_none() {
                 // First click - set start point
                 app.ruler_start = Some(final_coords);
                 app.ruler_end = None;
                 app.ruler_dragging = true; // Enable live preview
-            } else if app.ruler_end.is_none() {
+            } else if app.ruler_end.is_none() || app.ruler_dragging {
                 // Second click - set end point and complete measurement
                 app.ruler_end = Some(final_coords);
                 app.ruler_dragging = false;
+                if let Some(start) = app.ruler_start {
+                    crate::ecs::add_measurement_system(&mut app.ecs_world, crate::ecs::Measurement::Distance { start, end: final_coords });
+                }
             } else {
                 // Third click - start new measurement
                 app.ruler_start = Some(final_coords);
@@ -1038,20 +1341,106 @@ fn handle_ruler_interaction(ui: &mut egui::Ui, app: &mut DemoLensApp, response:
     }
     
     // Show live preview when dragging (after first click, before second click)
-    if app.ruler_dragging && app.ruler_start.is_some() && mouse_pos.is_some() {
-        let mouse_screen_pos = mouse_pos.unwrap();
-        let gerber_coords = app.view_state.screen_to_gerber_coords(mouse_screen_pos);
-        
-        // Apply snap to grid if enabled
-        let final_coords = if app.grid_settings.snap_enabled {
-            let point = nalgebra::Point2::new(gerber_coords.x, gerber_coords.y);
-            crate::display::snap_to_grid(point, &app.grid_settings)
-        } else {
-            nalgebra::Point2::new(gerber_coords.x, gerber_coords.y)
-        };
-        
-        // Update live preview end point
-        app.ruler_end = Some(final_coords);
+    if app.ruler_dragging && app.ruler_start.is_some() {
+        if let Some(mouse_screen_pos) = mouse_pos {
+            // Update live preview end point
+            app.ruler_end = Some(measure_point(app, mouse_screen_pos));
+        }
+    }
+}
+
+/// Feature point near the cursor, when snapping to features is on
+fn feature_snap(app: &mut DemoLensApp, screen_pos: Pos2) -> Option<crate::ecs::SnapTarget> {
+    if !app.snap_to_features {
+        return None;
+    }
+    let point = app.view_state.screen_to_gerber_coords(screen_pos);
+    let radius = (SNAP_RADIUS / app.view_state.scale) as f64;
+    crate::ecs::snap_point_system(&mut app.ecs_world, point, radius, &app.display_manager)
+}
+
+/// Measurement point under the cursor: snapped to feature geometry, else to the grid if enabled
+fn measure_point(app: &mut DemoLensApp, screen_pos: Pos2) -> nalgebra::Point2<f64> {
+    if let Some(snap) = feature_snap(app, screen_pos) {
+        return snap.point;
+    }
+    let gerber_coords = app.view_state.screen_to_gerber_coords(screen_pos);
+    let point = nalgebra::Point2::new(gerber_coords.x, gerber_coords.y);
+    if app.grid_settings.snap_enabled {
+        crate::display::snap_to_grid(point, &app.grid_settings)
+    } else {
+        point
+    }
+}
+
+/// Clearance tool: the first click picks a feature, the second measures to it
+fn handle_clearance_click(app: &mut DemoLensApp, response: &egui::Response) {
+    if !response.clicked() {
+        return;
+    }
+    let Some(gerber_coords) = app.ui_state.cursor_gerber_coords else {
+        return;
+    };
+    let radius = (PICK_RADIUS / app.view_state.scale) as f64;
+    let Some(hit) = crate::ecs::pick_feature_system(&mut app.ecs_world, gerber_coords, radius, &app.display_manager) else {
+        return;
+    };
+    
+    match app.clearance_start.take() {
+        None => app.clearance_start = Some(hit),
+        Some(first) => {
+            if let Some(clearance) = crate::ecs::feature_clearance_system(&mut app.ecs_world, &first, &hit, &app.display_manager) {
+                let description = format!(
+                    "{} #{} to {} #{}",
+                    first.layer_type.display_name(), first.index,
+                    hit.layer_type.display_name(), hit.index,
+                );
+                crate::ecs::add_measurement_system(&mut app.ecs_world, crate::ecs::Measurement::Clearance { clearance, description });
+            }
+        }
+    }
+}
+
+/// Angle and area tools: clicks add points until the measurement is complete
+fn handle_polyline_click(app: &mut DemoLensApp, response: &egui::Response, mouse_pos: Option<Pos2>) {
+    let Some(screen_pos) = mouse_pos else {
+        return;
+    };
+    if app.measure_tool == MeasureTool::Area && response.double_clicked() {
+        finish_area(app);
+        return;
+    }
+    if !response.clicked() {
+        return;
+    }
+    
+    let point = measure_point(app, screen_pos);
+    match app.measure_tool {
+        MeasureTool::Angle => {
+            app.measure_points.push(point);
+            if let [first, vertex, second] = app.measure_points[..] {
+                crate::ecs::add_measurement_system(&mut app.ecs_world, crate::ecs::Measurement::Angle { first, vertex, second });
+                app.measure_points.clear();
+            }
+        }
+        MeasureTool::Area => {
+            // Clicking the first vertex again closes the polygon
+            let closes = app.measure_points.len() >= 3
+                && (app.view_state.gerber_to_screen_coords(app.measure_points[0]) - screen_pos).length() <= SNAP_RADIUS;
+            if closes {
+                finish_area(app);
+            } else {
+                app.measure_points.push(point);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn finish_area(app: &mut DemoLensApp) {
+    let points = std::mem::take(&mut app.measure_points);
+    if points.len() >= 3 {
+        crate::ecs::add_measurement_system(&mut app.ecs_world, crate::ecs::Measurement::Area { points });
     }
 }
 
@@ -1252,6 +1641,26 @@ fn render_zoom_display(ui: &mut egui::Ui, app: &mut DemoLensApp) {
         ));
 }
 
+fn render_frame_stats(ui: &mut egui::Ui, app: &mut DemoLensApp) {
+    let Some(stats) = app.ecs_world.get_resource::<crate::ecs::RenderStats>() else {
+        return;
+    };
+    let cached = app.ecs_world.get_resource::<crate::ecs::RenderConfig>()
+        .is_some_and(|config| config.cached_meshes);
+    
+    ui.label(egui::RichText::new(format!("⏱ {:.1} ms", stats.render_ms)).weak().monospace())
+        .on_hover_text(format!(
+            "Layer render: {:.2} ms\nFrame: {:.1} ms\nFeatures drawn: {} of {}\nTriangles: {}\nMesh rebuilds: {}\nCached meshes: {}",
+            stats.render_ms,
+            stats.frame_ms,
+            stats.features_drawn,
+            stats.features_total,
+            stats.triangles,
+            stats.mesh_rebuilds,
+            if cached { "on" } else { "off" },
+        ));
+}
+
 /// Draw a red X marker for DRC violations
 fn draw_violation_marker(painter: &Painter, center: Pos2, size: f32, color: Color32) {
     let half_size = size / 2.0;
@@ -7,6 +7,7 @@ use nng::{Protocol, Socket};
 use protobuf::{EnumOrUnknown, Message, MessageFull};
 use protobuf::well_known_types::any::Any;
 use rand::distributions::{Alphanumeric, DistString};
+use std::collections::BTreeMap;
 use std::env;
 use thiserror::Error;
 use tracing::{debug, error, info, instrument, warn};
@@ -239,6 +240,69 @@ impl KiCadClient {
         Ok(footprints)
     }
     
+    /// Get the physical stackup of the current board, top to bottom
+    /// Dielectric slots made of several plies return one layer per ply.
+    #[instrument(skip(self))]
+    pub async fn get_board_stackup(&mut self) -> Result<Vec<StackupLayerData>> {
+        info!("Requesting board stackup");
+        
+        let docs = self.get_open_documents().await?;
+        let doc = docs.first().ok_or(KiCadError::NoBoardOpen)?;
+        
+        let mut request = protos::board_commands::GetBoardStackup::new();
+        request.board = Some(doc.clone()).into();
+        
+        let response: protos::board_commands::BoardStackupResponse = self.send_request(request)?;
+        
+        let mut layers = Vec::new();
+        let mut dielectrics = 0;
+        for layer in response.stackup.get_or_default().layers.iter().filter(|layer| layer.enabled) {
+            let layer_type = StackupLayerType::from(layer.type_.enum_value_or_default());
+            let material = Some(layer.material_name.clone()).filter(|m| !m.is_empty());
+            let thickness = to_mm(layer.thickness.get_or_default().value_nm);
+            
+            if layer_type == StackupLayerType::Dielectric {
+                dielectrics += 1;
+                let plies = &layer.dielectric.get_or_default().layer;
+                if plies.is_empty() {
+                    layers.push(StackupLayerData {
+                        name: format!("Dielectric {}", dielectrics),
+                        layer_type,
+                        thickness,
+                        material,
+                        epsilon_r: None,
+                    });
+                    continue;
+                }
+                for (index, ply) in plies.iter().enumerate() {
+                    let name = if plies.len() > 1 {
+                        format!("Dielectric {}.{}", dielectrics, index + 1)
+                    } else {
+                        format!("Dielectric {}", dielectrics)
+                    };
+                    layers.push(StackupLayerData {
+                        name,
+                        layer_type,
+                        thickness: to_mm(ply.thickness.get_or_default().value_nm),
+                        material: Some(ply.material_name.clone()).filter(|m| !m.is_empty()).or(material.clone()),
+                        epsilon_r: Some(ply.epsilon_r).filter(|e| *e > 0.0),
+                    });
+                }
+                continue;
+            }
+            
+            let name = if layer.user_name.is_empty() {
+                self.layer_to_string(layer.layer.enum_value_or_default())
+            } else {
+                layer.user_name.clone()
+            };
+            layers.push(StackupLayerData { name, layer_type, thickness, material, epsilon_r: None });
+        }
+        
+        info!("Retrieved {} stackup layers", layers.len());
+        Ok(layers)
+    }
+    
     /// Unpack a footprint from an Any message
     fn unpack_footprint(&self, any: &Any) -> Result<FootprintData> {
         let footprint_instance: FootprintInstance = Any::unpack(any)?
@@ -269,6 +333,27 @@ impl KiCadClient {
             .map(|text| text.text.clone())
             .filter(|s| !s.is_empty());
 
+        // User fields such as MPN or Manufacturer come as Field items of the definition
+        let mut fields = BTreeMap::new();
+        for item in &footprint_instance.definition.get_or_default().items {
+            let Some(field) = Any::unpack::<Field>(item)? else { continue };
+            let text = field.text.as_ref()
+                .and_then(|board_text| board_text.text.as_ref())
+                .map(|text| text.text.clone())
+                .unwrap_or_default();
+            if !field.name.is_empty() && !text.is_empty() {
+                fields.insert(field.name.clone(), text);
+            }
+        }
+        if let Some(datasheet) = footprint_instance.datasheet_field.as_ref()
+            .and_then(|field| field.text.as_ref())
+            .and_then(|board_text| board_text.text.as_ref())
+            .map(|text| text.text.clone())
+            .filter(|s| !s.is_empty() && s != "~")
+        {
+            fields.insert("Datasheet".to_string(), datasheet);
+        }
+
         Ok(FootprintData {
             id: footprint_instance.id.get_or_default().value.clone(),
             reference,
@@ -283,6 +368,7 @@ impl KiCadClient {
             description,
             exclude_from_bom: attributes.exclude_from_bill_of_materials,
             do_not_populate: attributes.do_not_populate,
+            fields,
             locked: footprint_instance.locked.enum_value_or_default() == LockedState::LS_LOCKED,
         })
     }
@@ -338,9 +424,48 @@ pub struct FootprintData {
     pub description: Option<String>,
     pub exclude_from_bom: bool,
     pub do_not_populate: bool,
+    /// Footprint fields beyond reference, value and description, by name
+    pub fields: BTreeMap<String, String>,
     pub locked: bool,
 }
 
+/// Material class of a stackup layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StackupLayerType {
+    Copper,
+    Dielectric,
+    Silkscreen,
+    Soldermask,
+    Solderpaste,
+    Unknown,
+}
+
+impl From<protos::board::BoardStackupLayerType> for StackupLayerType {
+    fn from(layer_type: protos::board::BoardStackupLayerType) -> Self {
+        use protos::board::BoardStackupLayerType as Bslt;
+        match layer_type {
+            Bslt::BSLT_COPPER => Self::Copper,
+            Bslt::BSLT_DIELECTRIC => Self::Dielectric,
+            Bslt::BSLT_SILKSCREEN => Self::Silkscreen,
+            Bslt::BSLT_SOLDERMASK => Self::Soldermask,
+            Bslt::BSLT_SOLDERPASTE => Self::Solderpaste,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Stackup layer data from KiCad, listed top to bottom
+#[derive(Debug, Clone)]
+pub struct StackupLayerData {
+    /// Layer name as shown in KiCad, e.g. "F.Cu"
+    pub name: String,
+    pub layer_type: StackupLayerType,
+    pub thickness: f64,  // millimeters
+    pub material: Option<String>,
+    /// Relative permittivity of dielectric plies
+    pub epsilon_r: Option<f64>,
+}
+
 // Coordinate conversion utilities (KiCad uses nanometers internally)
 pub fn to_mm(nanometers: i64) -> f64 {
     nanometers as f64 / 1_000_000.0